@@ -0,0 +1,4 @@
+
+# dynamic script id completions, from the script list cached by fumo
+complete -c fumo -n '__fish_seen_subcommand_from pull; and test (count (commandline -opc)) -eq 2' -f -a '(fumo __complete script-ids 2>/dev/null)'
+complete -c fumo -n '__fish_seen_subcommand_from generate' -l id -x -a '(fumo __complete script-ids 2>/dev/null)'
@@ -0,0 +1,21 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+	// the timestamp only changes along with the sources, and reproducible builds can pin it
+	println!("cargo:rerun-if-changed=src");
+	println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+	let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+		.ok()
+		.and_then(|epoch| epoch.parse::<u64>().ok())
+		.unwrap_or_else(|| {
+			SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map_or(0, |elapsed| elapsed.as_secs())
+		});
+	println!("cargo:rustc-env=FUMO_BUILD_TIMESTAMP={timestamp}");
+	println!(
+		"cargo:rustc-env=FUMO_TARGET={}",
+		std::env::var("TARGET").unwrap_or_default()
+	);
+}
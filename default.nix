@@ -3,6 +3,11 @@
   rustPlatform,
   openssl,
   pkg-config,
+  # self-update is left out; nix manages updates
+  features ? [
+    "browser-login"
+    "cookie-import"
+  ],
   ...
 }:
 rustPlatform.buildRustPackage {
@@ -16,7 +21,8 @@ rustPlatform.buildRustPackage {
     allowBuiltinFetchGit = true;
   };
 
-  buildFeatures = []; # cargo build features
+  buildNoDefaultFeatures = true;
+  buildFeatures = features; # cargo build features
 
   nativeBuildInputs = [
     pkg-config
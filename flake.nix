@@ -50,6 +50,12 @@
           inherit inputs;
           inherit pkgs;
         };
+        # only token logins; keeps the minimal feature set compiling
+        packages.minimal = pkgs.callPackage ./. {
+          inherit inputs;
+          inherit pkgs;
+          features = [];
+        };
       }
     );
 }
@@ -0,0 +1,384 @@
+use crate::{
+	client::PROGRAM_VERSION,
+	error::Error,
+	project::{
+		DESCRIPTION_FILE, MAIN_SCRIPT_FILE, ModuleFilter, PACKAGE_DIRECTORY, ProjectType,
+		SYNC_CONFIGURATION_FILE, SYNC_CONFIGURATION_TOML_FILE, find_configuration_file,
+		list_module_files, read_configuration, write_file,
+	},
+	types::{self, TYPES_FILE},
+};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	io::Read,
+	path::{Component, Path, PathBuf},
+};
+
+/// Describes the archive; always the first entry
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Version of the archive layout written by this version of fumo
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// manifest.json
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+	pub format_version: u32,
+	pub fumo_version: String,
+	pub script_id: String,
+	/// Paths of every other entry, relative to the project directory and separated by `/`
+	pub files: Vec<String>,
+}
+
+/// Converts a path relative to the project directory into an archive path.
+fn archive_path(relative_path: &Path) -> Option<String> {
+	let mut components = Vec::new();
+	for component in relative_path.components() {
+		match component {
+			Component::Normal(name) => components.push(name.to_str()?),
+			Component::CurDir => {}
+			_ => return None,
+		}
+	}
+
+	(!components.is_empty()).then(|| components.join("/"))
+}
+
+/// Archives the files a project syncs, along with its configuration, as a tar.gz.
+///
+/// Everything else, such as the local state, git and type definitions, is left out. Symlinked
+/// modules are archived as regular files. Returns the number of files archived.
+pub async fn export(project_directory: &Path, output: &Path) -> Result<usize, Error> {
+	let configuration = read_configuration(project_directory).await?;
+
+	let mut paths = vec![find_configuration_file(project_directory)?];
+	paths.push(project_directory.join(DESCRIPTION_FILE));
+	if configuration.project_type == ProjectType::Script {
+		paths.push(project_directory.join(MAIN_SCRIPT_FILE));
+	}
+	paths.extend(
+		list_module_files(
+			&project_directory.join(PACKAGE_DIRECTORY),
+			&configuration.module_filter(),
+		)
+		.await?,
+	);
+
+	let mut files: Vec<(String, Vec<u8>)> = Vec::with_capacity(paths.len());
+	for path in paths {
+		if !path.try_exists()? {
+			continue;
+		}
+
+		let relative_path = path.strip_prefix(project_directory).unwrap_or(&path);
+		let name = archive_path(relative_path).ok_or_else(|| {
+			Error::InvalidArchive(
+				output.to_path_buf(),
+				format!("{} can't be archived", relative_path.display()),
+			)
+		})?;
+		let contents = tokio::fs::read(&path)
+			.await
+			.map_err(|io_error| Error::ReadFile(path.clone(), io_error))?;
+		files.push((name, contents));
+	}
+
+	let manifest = Manifest {
+		format_version: ARCHIVE_FORMAT_VERSION,
+		fumo_version: PROGRAM_VERSION.to_owned(),
+		script_id: configuration.script_id,
+		files: files.iter().map(|(name, _)| name.clone()).collect(),
+	};
+
+	let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+	let manifest = serde_json::to_vec_pretty(&manifest)?;
+	for (name, contents) in std::iter::once((MANIFEST_FILE, manifest.as_slice())).chain(
+		files
+			.iter()
+			.map(|(name, contents)| (name.as_str(), contents.as_slice())),
+	) {
+		let mut header = tar::Header::new_gnu();
+		header.set_size(contents.len() as u64);
+		header.set_mode(0o644);
+		header.set_cksum();
+		builder.append_data(&mut header, name, contents)?;
+	}
+	let archive = builder.into_inner()?.finish()?;
+
+	tokio::fs::write(output, archive)
+		.await
+		.map_err(|io_error| Error::CreateFile(output.to_path_buf(), io_error))?;
+	Ok(files.len())
+}
+
+/// Lists the files of an existing project which an archive replaces: its configuration,
+/// description, main script and modules. Symlinked directories aren't followed, so nothing
+/// outside of the project is listed.
+async fn managed_files(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+	let mut paths: Vec<PathBuf> = [
+		SYNC_CONFIGURATION_FILE,
+		SYNC_CONFIGURATION_TOML_FILE,
+		DESCRIPTION_FILE,
+		MAIN_SCRIPT_FILE,
+	]
+	.into_iter()
+	.map(|name| directory.join(name))
+	.collect();
+
+	let package_directory = directory.join(PACKAGE_DIRECTORY);
+	if package_directory.is_dir() {
+		paths.extend(list_module_files(&package_directory, &ModuleFilter::default()).await?);
+	}
+
+	Ok(paths)
+}
+
+/// Removes symlinks at a path inside of a directory, or at any directory between them, so the
+/// path is written inside of the directory rather than wherever the symlinks lead.
+async fn remove_symlinks(directory: &Path, path: &Path) -> Result<(), Error> {
+	let Ok(relative_path) = path.strip_prefix(directory) else {
+		return Ok(());
+	};
+
+	let mut current = directory.to_path_buf();
+	for component in relative_path.components() {
+		current.push(component);
+		match tokio::fs::symlink_metadata(&current).await {
+			Ok(metadata) if metadata.is_symlink() => tokio::fs::remove_file(&current).await?,
+			Ok(..) => {}
+			// nothing below a missing path exists either
+			Err(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => break,
+			Err(io_error) => return Err(io_error.into()),
+		}
+	}
+
+	Ok(())
+}
+
+/// Unpacks an archive written by [`export`] into a project directory.
+///
+/// Only regular files listed by the manifest are accepted, and none of them may leave the
+/// directory. The whole archive is checked before anything is written. When `force` replaces an
+/// existing project, its configuration, description, main script and modules missing from the
+/// archive are deleted, and symlinks are replaced by the archived files rather than written
+/// through; other files, such as .git, .fumo/ and symlinked modules, are kept. Type definitions are written if the
+/// archive didn't bring them.
+///
+/// # Errors
+/// - [`Error::DirectoryAlreadyExists`] unless `force` is set
+/// - [`Error::InvalidArchive`]
+pub async fn import(archive: &Path, directory: &Path, force: bool) -> Result<Manifest, Error> {
+	if directory.exists() && !force {
+		return Err(Error::DirectoryAlreadyExists(directory.to_path_buf()));
+	}
+
+	let invalid = |reason: String| Error::InvalidArchive(archive.to_path_buf(), reason);
+	let bytes = tokio::fs::read(archive)
+		.await
+		.map_err(|io_error| Error::ReadFile(archive.to_path_buf(), io_error))?;
+
+	let mut entries: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+	let mut reader = tar::Archive::new(GzDecoder::new(bytes.as_slice()));
+	for entry in reader
+		.entries()
+		.map_err(|error| invalid(error.to_string()))?
+	{
+		let mut entry = entry.map_err(|error| invalid(error.to_string()))?;
+		let path = entry
+			.path()
+			.map_err(|error| invalid(error.to_string()))?
+			.into_owned();
+		if !entry.header().entry_type().is_file() {
+			return Err(invalid(format!("{} isn't a regular file", path.display())));
+		}
+
+		let name = archive_path(&path)
+			.ok_or_else(|| invalid(format!("{} leaves the project directory", path.display())))?;
+		let mut contents = Vec::new();
+		entry
+			.read_to_end(&mut contents)
+			.map_err(|error| invalid(error.to_string()))?;
+		entries.insert(name, contents);
+	}
+
+	let manifest: Manifest = serde_json::from_slice(
+		&entries
+			.remove(MANIFEST_FILE)
+			.ok_or_else(|| invalid(format!("{MANIFEST_FILE} is missing")))?,
+	)
+	.map_err(|error| invalid(format!("{MANIFEST_FILE} is invalid: {error}")))?;
+	if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+		return Err(invalid(format!(
+			"it was written in format {} by fumo {}, which is newer than this version of fumo",
+			manifest.format_version, manifest.fumo_version
+		)));
+	}
+
+	if let Some(name) = entries.keys().find(|name| !manifest.files.contains(name)) {
+		return Err(invalid(format!("{name} isn't listed in {MANIFEST_FILE}")));
+	}
+	if let Some(name) = manifest
+		.files
+		.iter()
+		.find(|name| !entries.contains_key(*name))
+	{
+		return Err(invalid(format!(
+			"{name} is listed in {MANIFEST_FILE}, but missing"
+		)));
+	}
+
+	let entries: BTreeMap<String, String> = entries
+		.into_iter()
+		.map(|(name, contents)| match String::from_utf8(contents) {
+			Ok(contents) => Ok((name, contents)),
+			Err(_) => Err(invalid(format!("{name} isn't UTF-8"))),
+		})
+		.collect::<Result<_, Error>>()?;
+
+	if directory.exists() {
+		for path in managed_files(directory).await? {
+			let archived = path
+				.strip_prefix(directory)
+				.ok()
+				.and_then(archive_path)
+				.is_some_and(|name| entries.contains_key(&name));
+			let exists = tokio::fs::symlink_metadata(&path).await.is_ok();
+			if exists && !archived {
+				tokio::fs::remove_file(&path).await?;
+			}
+		}
+	}
+
+	for (name, contents) in entries {
+		let path = directory.join(&name);
+		remove_symlinks(directory, &path).await?;
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent)
+				.await
+				.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+		}
+
+		write_file(path, &contents).await?;
+	}
+
+	let types_path = directory.join(TYPES_FILE);
+	if !types_path.try_exists()? {
+		write_file(types_path, &types::current()).await?;
+	}
+
+	Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SCRIPT_ID: &str = "675e3d3e59d07c8ba779b988";
+
+	fn write_project(directory: &Path, files: &[(&str, &str)]) {
+		let configuration = format!(
+			r#"{{"scriptName": "fumo", "scriptId": "{SCRIPT_ID}", "whitelist": [], "isPublic": false}}"#
+		);
+		for (name, contents) in [(SYNC_CONFIGURATION_FILE, configuration.as_str())]
+			.into_iter()
+			.chain(files.iter().copied())
+		{
+			let path = directory.join(name);
+			std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+			std::fs::write(path, contents).unwrap();
+		}
+	}
+
+	#[tokio::test]
+	async fn imports_what_was_exported() {
+		let directory = tempfile::tempdir().unwrap();
+		let project = directory.path().join("project");
+		write_project(
+			&project,
+			&[
+				(DESCRIPTION_FILE, "# fumo"),
+				(MAIN_SCRIPT_FILE, "print(requireM(\"util\"))"),
+				("pkg/util.luau", "return 1"),
+				("pkg/ui/button.luau", "return 2"),
+			],
+		);
+		let archive = directory.path().join("project.tar.gz");
+		assert_eq!(export(&project, &archive).await.unwrap(), 5);
+
+		let imported = directory.path().join("imported");
+		let manifest = import(&archive, &imported, false).await.unwrap();
+		assert_eq!(manifest.script_id, SCRIPT_ID);
+		assert_eq!(manifest.files.len(), 5);
+		for name in &manifest.files {
+			assert_eq!(
+				std::fs::read(imported.join(name)).unwrap(),
+				std::fs::read(project.join(name)).unwrap(),
+				"{name} changed"
+			);
+		}
+		assert!(imported.join(TYPES_FILE).exists());
+	}
+
+	#[tokio::test]
+	async fn existing_projects_need_force() {
+		let directory = tempfile::tempdir().unwrap();
+		let project = directory.path().join("project");
+		write_project(&project, &[]);
+		let archive = directory.path().join("project.tar.gz");
+		export(&project, &archive).await.unwrap();
+
+		let error = import(&archive, &project, false).await.unwrap_err();
+		assert!(matches!(error, Error::DirectoryAlreadyExists(..)));
+	}
+
+	#[tokio::test]
+	async fn forced_imports_replace_managed_files() {
+		let directory = tempfile::tempdir().unwrap();
+		let project = directory.path().join("project");
+		write_project(&project, &[("pkg/util.luau", "return 1")]);
+		let archive = directory.path().join("project.tar.gz");
+		export(&project, &archive).await.unwrap();
+
+		let target = directory.path().join("target");
+		write_project(
+			&target,
+			&[
+				("pkg/stale.luau", "return 0"),
+				("pkg/util.luau", "return 0"),
+				(".git/HEAD", "ref: refs/heads/main"),
+			],
+		);
+		import(&archive, &target, true).await.unwrap();
+
+		assert!(!target.join("pkg/stale.luau").exists());
+		assert!(target.join(".git/HEAD").exists());
+		assert_eq!(
+			std::fs::read_to_string(target.join("pkg/util.luau")).unwrap(),
+			"return 1"
+		);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn forced_imports_dont_write_through_symlinks() {
+		let directory = tempfile::tempdir().unwrap();
+		let project = directory.path().join("project");
+		write_project(&project, &[("pkg/util.luau", "return 1")]);
+		let archive = directory.path().join("project.tar.gz");
+		export(&project, &archive).await.unwrap();
+
+		let target = directory.path().join("target");
+		let outside = directory.path().join("shared.luau");
+		write_project(&target, &[]);
+		std::fs::write(&outside, "return 0").unwrap();
+		std::fs::create_dir_all(target.join(PACKAGE_DIRECTORY)).unwrap();
+		std::os::unix::fs::symlink(&outside, target.join("pkg/util.luau")).unwrap();
+		import(&archive, &target, true).await.unwrap();
+
+		assert_eq!(std::fs::read_to_string(&outside).unwrap(), "return 0");
+		let metadata = std::fs::symlink_metadata(target.join("pkg/util.luau")).unwrap();
+		assert!(!metadata.is_symlink());
+	}
+}
@@ -0,0 +1,130 @@
+use crate::{
+	error::Error,
+	project::{Configuration, read_configuration, write_configuration},
+};
+use serde_json::Value;
+use std::path::Path;
+
+/// First line of the prelude push puts in front of uploaded main scripts
+const PRELUDE_START: &str =
+	"-- fumo:arguments begin; generated from \"arguments\" in fumosync.json, removed again by pull";
+/// Last line of the prelude
+const PRELUDE_END: &str = "-- fumo:arguments end";
+
+/// Writes a JSON value as a Luau expression.
+fn to_luau(value: &Value) -> String {
+	match value {
+		Value::Null => "nil".to_owned(),
+		Value::Bool(boolean) => boolean.to_string(),
+		Value::Number(number) => number.to_string(),
+		// Luau understands every escape of Rust's debug formatting, \u{...} included
+		Value::String(string) => format!("{string:?}"),
+		Value::Array(values) => {
+			let values: Vec<String> = values.iter().map(to_luau).collect();
+			format!("{{ {} }}", values.join(", "))
+		}
+		Value::Object(entries) => {
+			let entries: Vec<String> = entries
+				.iter()
+				.map(|(key, value)| format!("[{key:?}] = {}", to_luau(value)))
+				.collect();
+			format!("{{ {} }}", entries.join(", "))
+		}
+	}
+}
+
+/// Removes the prelude from a main source, if it has one.
+pub fn strip(source: &str) -> &str {
+	let Some(rest) = source.strip_prefix(PRELUDE_START) else {
+		return source;
+	};
+
+	match rest.split_once(&format!("\n{PRELUDE_END}")) {
+		Some((_, after)) => after.strip_prefix('\n').unwrap_or(after),
+		None => source,
+	}
+}
+
+/// Puts a prelude declaring `arguments` in front of a main source, replacing any prelude it
+/// already has.
+pub fn inject(source: &str, arguments: &[Value]) -> String {
+	format!(
+		"{PRELUDE_START}\nlocal arguments = {}\n{PRELUDE_END}\n{}",
+		to_luau(&Value::Array(arguments.to_vec())),
+		strip(source)
+	)
+}
+
+/// Gets the main source as it is uploaded: with the prelude when `"injectArguments": true` is set.
+pub fn prepare(configuration: &Configuration, source: &str) -> String {
+	if configuration.injects_arguments() {
+		inject(source, &configuration.arguments)
+	} else {
+		source.to_owned()
+	}
+}
+
+/// Replaces the `arguments` of fumosync.json; values which aren't valid JSON are stored as strings.
+pub async fn set(project_directory: &Path, values: &[String]) -> Result<Vec<Value>, Error> {
+	let arguments: Vec<Value> = values
+		.iter()
+		.map(|value| serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.clone())))
+		.collect();
+
+	let configuration = Configuration {
+		arguments: arguments.clone(),
+		..read_configuration(project_directory).await?
+	};
+	write_configuration(project_directory, &configuration).await?;
+	Ok(arguments)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	const SOURCE: &str = "print(arguments)\n";
+
+	#[test]
+	fn writes_arguments_as_luau() {
+		let arguments = [
+			json!(1),
+			json!("a\n\"b\""),
+			json!(null),
+			json!(true),
+			json!({ "k": [2.5] }),
+		];
+		assert_eq!(
+			inject(SOURCE, &arguments),
+			format!(
+				"{PRELUDE_START}\nlocal arguments = {{ 1, \"a\\n\\\"b\\\"\", nil, true, {{ [\"k\"] = {{ 2.5 }} }} }}\n{PRELUDE_END}\n{SOURCE}"
+			)
+		);
+	}
+
+	#[test]
+	fn injecting_is_idempotent() {
+		let injected = inject(SOURCE, &[json!("first")]);
+		assert_eq!(inject(&injected, &[json!("first")]), injected);
+		assert_eq!(
+			inject(&injected, &[json!("second")]),
+			inject(SOURCE, &[json!("second")])
+		);
+		assert_eq!(strip(&inject("", &[])), "");
+	}
+
+	#[test]
+	fn stripping_is_idempotent() {
+		let injected = inject(SOURCE, &[json!(1)]);
+		assert_eq!(strip(&injected), SOURCE);
+		assert_eq!(strip(strip(&injected)), SOURCE);
+		assert_eq!(strip(SOURCE), SOURCE);
+	}
+
+	#[test]
+	fn keeps_unterminated_preludes() {
+		let source = format!("{PRELUDE_START}\nlocal arguments = {{}}\n{SOURCE}");
+		assert_eq!(strip(&source), source);
+	}
+}
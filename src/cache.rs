@@ -0,0 +1,162 @@
+use crate::{
+	client::{AccountDetails, Script, ScriptDetails},
+	error::Error,
+	login::get_config_directory,
+	project::{read_file, write_file},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+use tracing::{debug, warn};
+
+/// Directory inside of the config directory holding data which can always be fetched again
+pub const CACHE_DIRECTORY: &str = "cache";
+
+/// cache/scripts.<account id>.json
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedScripts {
+	pub fetched_at: DateTime<Utc>,
+	pub scripts: Vec<Script>,
+	/// Sizes from the last `fumo list --detailed`, by script id
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub details: BTreeMap<String, ScriptDetails>,
+}
+
+fn get_scripts_path(account_id: &str) -> Result<PathBuf, Error> {
+	Ok(
+		get_config_directory()?
+			.join(CACHE_DIRECTORY)
+			.join(format!("scripts.{account_id}.json")),
+	)
+}
+
+/// Reads the cached script list of an account, however old it is.
+///
+/// A missing or unreadable cache is a miss, never an error.
+pub async fn read_scripts(account_id: &str) -> Option<CachedScripts> {
+	let path = get_scripts_path(account_id).ok()?;
+	if !path.try_exists().ok()? {
+		return None;
+	}
+
+	match read_file(&path)
+		.await
+		.and_then(|contents| Ok(serde_json::from_str(&contents)?))
+	{
+		Ok(cached) => Some(cached),
+		Err(error) => {
+			debug!("ignoring the script list cache: {error}");
+			None
+		}
+	}
+}
+
+/// Caches the script list of an account, with the details fetched of its scripts; details cached
+/// earlier are kept for scripts which are still listed. Failures are only warned about.
+pub async fn write_scripts(
+	account_id: &str,
+	scripts: &[Script],
+	details: &BTreeMap<String, ScriptDetails>,
+) {
+	let result = async {
+		let path = get_scripts_path(account_id)?;
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent)
+				.await
+				.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+		}
+
+		let mut cached_details = read_scripts(account_id)
+			.await
+			.map(|cached| cached.details)
+			.unwrap_or_default();
+		cached_details.retain(|id, _| scripts.iter().any(|script| script.id == *id));
+		cached_details.extend(details.iter().map(|(id, details)| (id.clone(), *details)));
+
+		let cached = CachedScripts {
+			fetched_at: Utc::now(),
+			scripts: scripts.to_vec(),
+			details: cached_details,
+		};
+		write_file(path, &serde_json::to_string(&cached)?).await
+	};
+
+	if let Err(error) = result.await {
+		warn!("failed caching the script list: {error}");
+	}
+}
+
+/// cache/account.<account id>.json; holds the public account fields, never secrets
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedDetails {
+	pub fetched_at: DateTime<Utc>,
+	pub details: AccountDetails,
+}
+
+fn get_details_path(account_id: &str) -> Result<PathBuf, Error> {
+	Ok(
+		get_config_directory()?
+			.join(CACHE_DIRECTORY)
+			.join(format!("account.{account_id}.json")),
+	)
+}
+
+/// Reads the cached details of an account, however old they are; a missing or unreadable cache is
+/// a miss.
+pub async fn read_details(account_id: &str) -> Option<CachedDetails> {
+	let path = get_details_path(account_id).ok()?;
+	if !path.try_exists().ok()? {
+		return None;
+	}
+
+	match read_file(&path)
+		.await
+		.and_then(|contents| Ok(serde_json::from_str(&contents)?))
+	{
+		Ok(cached) => Some(cached),
+		Err(error) => {
+			debug!("ignoring the account details cache: {error}");
+			None
+		}
+	}
+}
+
+/// Caches the details of an account; failures are only warned about.
+pub async fn write_details(account_id: &str, details: &AccountDetails) {
+	let result = async {
+		let path = get_details_path(account_id)?;
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent)
+				.await
+				.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+		}
+
+		let cached = CachedDetails {
+			fetched_at: Utc::now(),
+			details: details.clone(),
+		};
+		write_file(path, &serde_json::to_string(&cached)?).await
+	};
+
+	if let Err(error) = result.await {
+		warn!("failed caching the account details: {error}");
+	}
+}
+
+/// Drops the cached details of an account, once it is no longer the one logged in.
+pub async fn invalidate_details(account_id: &str) {
+	if let Ok(path) = get_details_path(account_id) {
+		// a missing cache is already invalid
+		let _ = tokio::fs::remove_file(path).await;
+	}
+}
+
+/// Drops the cached script list of an account, after changes which make it stale.
+pub async fn invalidate_scripts(account_id: &str) {
+	if let Ok(path) = get_scripts_path(account_id) {
+		// a missing cache is already invalid
+		let _ = tokio::fs::remove_file(path).await;
+	}
+}
@@ -1,9 +1,29 @@
-use crate::{error::Error, login::Secrets};
+use crate::{
+	cache,
+	error::Error,
+	login::{SESSION_COOKIE, Secrets, normalize_session_token, save_rotated_session},
+	settings,
+	state::StateFile,
+	timing,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use flate2::{Compression, write::GzEncoder};
 use git_version::git_version;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use serde_repr::Deserialize_repr;
-use std::collections::HashMap;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use sha2::Digest;
+use std::{
+	collections::{BTreeMap, HashMap},
+	io::Write,
+	net::IpAddr,
+	sync::{
+		Mutex, OnceLock, PoisonError,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+use tracing::Instrument;
 
 pub const PROGRAM_VERSION: &str = git_version!(
 	prefix = "git-",
@@ -13,11 +33,172 @@ pub const PROGRAM_VERSION: &str = git_version!(
 pub const BASE_URL: &str = "https://fumosclubv1.vercel.app";
 pub const DOMAIN: &str = "fumosclubv1.vercel.app";
 
+static USER_AGENT: OnceLock<String> = OnceLock::new();
+
+/// Gets the user agent sent with every request: the fumo build, platform, and the suffix from
+/// `--user-agent-suffix`, if any.
 pub fn get_user_agent() -> String {
-	format!("fumo/{PROGRAM_VERSION}; (https://github.com/techs-sus/fumosync)")
+	USER_AGENT
+		.get_or_init(|| {
+			let os_version = sysinfo::System::os_version().unwrap_or_else(|| "unknown".to_owned());
+			let suffix = settings::get()
+				.user_agent_suffix
+				.as_deref()
+				.map(sanitize_header_value)
+				.filter(|suffix| !suffix.is_empty())
+				.map(|suffix| format!(" {suffix}"))
+				.unwrap_or_default();
+
+			format!(
+				"fumo/{PROGRAM_VERSION} ({}-{}; {os_version}){suffix}; (https://github.com/techs-sus/fumosync)",
+				std::env::consts::ARCH,
+				std::env::consts::OS,
+			)
+		})
+		.clone()
+}
+
+/// Keeps the printable ASCII characters of a value, so it is always a valid header.
+fn sanitize_header_value(value: &str) -> String {
+	value
+		.chars()
+		.filter(|character| character.is_ascii_graphic() || *character == ' ')
+		.collect::<String>()
+		.trim()
+		.to_owned()
+}
+
+/// Longest script id accepted; real ids are much shorter, so this only catches pasted junk
+const MAX_SCRIPT_ID_LENGTH: usize = 64;
+/// Longest module name fumosclub accepts
+pub const MAX_MODULE_NAME_LENGTH: usize = 100;
+
+/// Id of a script or package on fumosclub, checked when constructed so a name or module can't be
+/// passed in its place.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct ScriptId(String);
+
+impl ScriptId {
+	/// # Errors
+	/// - [`Error::InvalidScriptId`] unless the id is letters, digits, `-` and `_`
+	pub fn new(id: &str) -> Result<Self, Error> {
+		let valid = !id.is_empty()
+			&& id.len() <= MAX_SCRIPT_ID_LENGTH
+			&& id
+				.bytes()
+				.all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_'));
+		if valid {
+			Ok(Self(id.to_owned()))
+		} else {
+			Err(Error::InvalidScriptId(id.to_owned()))
+		}
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+/// Name of a module as it is uploaded, checked when constructed with the rules push enforces.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct ModuleName(String);
+
+impl ModuleName {
+	/// Trims surrounding whitespace, which fumosclub would otherwise keep.
+	///
+	/// Errors with the reason the name is refused, for [`Error::InvalidModuleName`].
+	pub fn new(name: &str) -> Result<Self, String> {
+		let name = name.trim();
+		if name.is_empty() {
+			Err("the module name is empty".to_owned())
+		} else if name.chars().any(char::is_control) {
+			Err("the module name contains control characters".to_owned())
+		} else if name.chars().count() > MAX_MODULE_NAME_LENGTH {
+			Err(format!(
+				"the module name is longer than {MAX_MODULE_NAME_LENGTH} characters"
+			))
+		} else {
+			Ok(Self(name.to_owned()))
+		}
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+/// Implements the string conversions and comparisons shared by [`ScriptId`] and [`ModuleName`].
+macro_rules! string_newtype {
+	($name:ident, $error:ty, $new:expr) => {
+		impl std::str::FromStr for $name {
+			type Err = String;
+
+			fn from_str(value: &str) -> Result<Self, Self::Err> {
+				$new(value).map_err(|error: $error| error.to_string())
+			}
+		}
+
+		impl TryFrom<String> for $name {
+			type Error = String;
+
+			fn try_from(value: String) -> Result<Self, Self::Error> {
+				value.parse()
+			}
+		}
+
+		impl From<$name> for String {
+			fn from(value: $name) -> Self {
+				value.0
+			}
+		}
+
+		impl std::ops::Deref for $name {
+			type Target = str;
+
+			fn deref(&self) -> &str {
+				&self.0
+			}
+		}
+
+		impl AsRef<str> for $name {
+			fn as_ref(&self) -> &str {
+				&self.0
+			}
+		}
+
+		impl std::fmt::Display for $name {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				f.write_str(&self.0)
+			}
+		}
+
+		// printed like the string it holds, since both end up quoted in messages
+		impl std::fmt::Debug for $name {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				std::fmt::Debug::fmt(&self.0, f)
+			}
+		}
+
+		impl PartialEq<str> for $name {
+			fn eq(&self, other: &str) -> bool {
+				self.0 == other
+			}
+		}
+
+		impl PartialEq<String> for $name {
+			fn eq(&self, other: &String) -> bool {
+				self.0 == *other
+			}
+		}
+	};
 }
 
-#[derive(Deserialize, Debug, Clone)]
+string_newtype!(ScriptId, Error, ScriptId::new);
+string_newtype!(ModuleName, String, ModuleName::new);
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountDetails {
 	pub success: bool,
@@ -29,20 +210,20 @@ pub struct AccountDetails {
 	pub num_sessions: i64,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ScriptList {
 	pub success: bool,
 	pub scripts: Vec<Script>,
 }
 
-#[derive(Deserialize_repr, Debug, Clone)]
+#[derive(Deserialize_repr, Serialize_repr, Debug, Clone)]
 #[repr(u8)]
 pub enum ScriptType {
 	Regular = 0,
 	Package = 1,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Script {
 	pub id: String,
@@ -56,7 +237,7 @@ pub struct Script {
 	pub is_favorite: bool,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct EditorScriptInfo {
 	pub name: String,
@@ -68,6 +249,51 @@ pub struct EditorScriptInfo {
 	pub source: Source,
 }
 
+/// Sizes of a script, for `fumo list --detailed`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptDetails {
+	pub module_count: usize,
+	/// Bytes of the main source and every module
+	pub source_bytes: usize,
+	pub has_description: bool,
+}
+
+impl From<&EditorScriptInfo> for ScriptDetails {
+	fn from(info: &EditorScriptInfo) -> Self {
+		Self {
+			module_count: info.source.modules.len(),
+			source_bytes: info.source.main.len()
+				+ info.source.modules.values().map(String::len).sum::<usize>(),
+			has_description: !info.description.trim().is_empty(),
+		}
+	}
+}
+
+/// A script's editor along with the validators fumosclub sent with it.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedEditor {
+	pub etag: Option<String>,
+	pub last_modified: Option<String>,
+	pub script_info: EditorScriptInfo,
+}
+
+/// .fumo/cache/editor.json; editors are only kept when fumosclub sends an ETag or Last-Modified
+/// header to revalidate them with.
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct EditorCache {
+	pub editors: BTreeMap<String, CachedEditor>,
+}
+
+impl StateFile for EditorCache {
+	const PATH: &'static str = "cache/editor.json";
+	const VERSION: u32 = 1;
+}
+
+/// Editor requests in flight at once for [`Client::script_details`]
+const CONCURRENT_DETAIL_REQUESTS: usize = 5;
+
 #[derive(Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Editor {
@@ -78,7 +304,12 @@ pub struct Editor {
 #[derive(Debug, Clone)]
 pub enum EditorUpdate<'a> {
 	Description(&'a str),
-	Module { name: &'a str, source: &'a str },
+	Module {
+		name: &'a ModuleName,
+		source: &'a str,
+	},
+	/// Deletes a module; sent as a null source
+	RemoveModule(&'a str),
 	MainSource(&'a str),
 	// Vec<Id>; directly writes to database
 	Whitelist(Vec<&'a str>),
@@ -86,30 +317,560 @@ pub enum EditorUpdate<'a> {
 	Publicity(bool),
 }
 
-#[derive(Deserialize, Clone)]
+impl EditorUpdate<'_> {
+	/// Gets the bytes of text the update carries.
+	pub fn size(&self) -> usize {
+		match self {
+			Self::Description(text) | Self::MainSource(text) | Self::Name(text) => text.len(),
+			Self::Module { name, source } => name.len() + source.len(),
+			Self::RemoveModule(name) => name.len(),
+			Self::Whitelist(whitelist) => whitelist.iter().map(|entry| entry.len()).sum(),
+			Self::Publicity(..) => 0,
+		}
+	}
+}
+
+impl std::fmt::Display for EditorUpdate<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Description(description) => write!(f, "description ({} bytes)", description.len()),
+			Self::Module { name, source } => write!(f, "module {name:?} ({} bytes)", source.len()),
+			Self::RemoveModule(name) => write!(f, "removal of module {name:?}"),
+			Self::MainSource(source) => write!(f, "main source ({} bytes)", source.len()),
+			Self::Whitelist(whitelist) => write!(f, "whitelist [{}]", whitelist.join(", ")),
+			Self::Name(name) => write!(f, "name {name:?}"),
+			Self::Publicity(true) => write!(f, "publicity public"),
+			Self::Publicity(false) => write!(f, "publicity private"),
+		}
+	}
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Source {
 	pub main: String,
 	// key -> source
 	pub modules: HashMap<String, String>,
 }
 
+/// Request bodies at least this many bytes long are gzipped, if compression is enabled
+const COMPRESSION_THRESHOLD: usize = 64 * 1024;
+/// Set once fumosclub refuses a compressed body, so later pushes don't probe again
+static COMPRESSION_REFUSED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug)]
+pub struct ClientOptions {
+	/// Base url of the fumosclub API, such as a local mock server's
+	pub base_url: String,
+	/// Gzip request bodies over [`COMPRESSION_THRESHOLD`], until fumosclub refuses one
+	pub compress_uploads: bool,
+}
+
+impl ClientOptions {
+	pub fn from_settings() -> Self {
+		let settings = settings::get();
+		Self {
+			base_url: settings.base_url.clone(),
+			compress_uploads: settings.compress_uploads,
+		}
+	}
+}
+
+/// A session fumosclub rotated to with `Set-Cookie`.
+#[derive(Clone, Debug)]
+struct RotatedSession {
+	session: String,
+	/// From the cookie's Max-Age or Expires; the previous expiry is kept without either
+	expires: Option<DateTime<Utc>>,
+}
+
+/// Reads a `Set-Cookie` header setting the session cookie; cleared cookies are ignored.
+fn parse_session_cookie(header: &str) -> Option<RotatedSession> {
+	let mut attributes = header.split(';').map(str::trim);
+	let (name, value) = attributes.next()?.split_once('=')?;
+	if name != SESSION_COOKIE {
+		return None;
+	}
+
+	let session = normalize_session_token(value).ok()?;
+	let mut expires = None;
+	for (key, value) in attributes.filter_map(|attribute| attribute.split_once('=')) {
+		// Max-Age takes precedence over Expires
+		if key.eq_ignore_ascii_case("max-age") {
+			let seconds: i64 = value.parse().ok()?;
+			if seconds <= 0 {
+				return None;
+			}
+			expires = Utc::now().checked_add_signed(TimeDelta::seconds(seconds));
+		} else if key.eq_ignore_ascii_case("expires") && expires.is_none() {
+			expires = DateTime::parse_from_rfc2822(value)
+				.ok()
+				.map(|expires| expires.with_timezone(&Utc));
+		}
+	}
+
+	Some(RotatedSession { session, expires })
+}
+
 pub struct Client {
+	/// The secrets the client was created with; see [`Client::current_secrets`] for rotations
 	pub secrets: Secrets,
+	rotated_session: Mutex<Option<RotatedSession>>,
+	base_url: String,
 	client: reqwest::Client,
+	options: ClientOptions,
+	/// The script list fumosclub returned, fetched at most once per client
+	live_scripts: tokio::sync::OnceCell<Vec<Script>>,
+	/// The script list cached on disk, if it is younger than the TTL
+	cached_scripts: tokio::sync::OnceCell<Option<Vec<Script>>>,
+}
+
+/// Environment variable which, when set to 1, stops clients from reaching anything but loopback
+/// addresses; guards tests against accidentally using the network
+pub const OFFLINE_VARIABLE: &str = "FUMO_OFFLINE";
+
+fn is_loopback(base_url: &str) -> bool {
+	reqwest::Url::parse(base_url).is_ok_and(|url| match url.host_str() {
+		Some("localhost") => true,
+		Some(host) => host
+			.trim_start_matches('[')
+			.trim_end_matches(']')
+			.parse::<IpAddr>()
+			.is_ok_and(|address| address.is_loopback()),
+		None => false,
+	})
+}
+
+/// Length of the ids fumosclub gives scripts, which are hexadecimal object ids
+const FULL_SCRIPT_ID_LENGTH: usize = 24;
+
+/// Whether a reference is a whole script id, rather than an id prefix or a name.
+fn is_full_script_id(reference: &str) -> bool {
+	reference.len() == FULL_SCRIPT_ID_LENGTH && reference.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Resolves a script id, an id prefix, or a part of a script name to the id of a listed script.
+///
+/// Whole ids are used as they are without listing scripts, and other exact ids always win, so
+/// automation never changes behavior. References matching no listed script are returned as they
+/// are, so scripts missing from the list can still be used by id. Nothing is prompted for; callers
+/// pick from the candidates of an ambiguous reference.
+///
+/// # Errors
+/// - [`Error::AmbiguousScriptReference`] if several scripts match
+/// - [`Error::InvalidScriptId`] if the reference is used as an id, but isn't one
+pub async fn resolve_script_reference(client: &Client, reference: &str) -> Result<ScriptId, Error> {
+	if is_full_script_id(reference) {
+		return ScriptId::new(reference);
+	}
+
+	let lowercase_reference = reference.to_lowercase();
+	let mut scripts = client.cached_scripts().await;
+	let candidates: Vec<&Script> = loop {
+		let listed = match scripts {
+			Ok(scripts) => scripts,
+			Err(error) => {
+				tracing::warn!("using {reference} as a script id; failed listing scripts: {error}");
+				return ScriptId::new(reference);
+			}
+		};
+
+		if listed.iter().any(|script| script.id == reference) {
+			return ScriptId::new(reference);
+		}
+
+		let candidates: Vec<&Script> = listed
+			.iter()
+			.filter(|script| {
+				script.id.starts_with(reference)
+					|| script.name.to_lowercase().contains(&lowercase_reference)
+			})
+			.collect();
+
+		// the cached list may predate the script, so misses are checked against fumosclub
+		if candidates.is_empty() && !client.has_live_scripts() {
+			scripts = client.live_scripts().await;
+			continue;
+		}
+
+		break candidates;
+	};
+
+	match candidates.as_slice() {
+		[] => ScriptId::new(reference),
+		[script] => {
+			tracing::info!("using {} ({})", script.name, script.id);
+			ScriptId::new(&script.id)
+		}
+		_ => Err(Error::AmbiguousScriptReference(
+			reference.to_owned(),
+			candidates.iter().map(|&script| script.clone()).collect(),
+		)),
+	}
+}
+
+/// Delay before the first retry of a request; it doubles with every retry
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Statuses answered while fumosclub, or the proxy in front of it, is down
+const UNAVAILABLE_STATUSES: &[u16] = &[502, 503, 504, 520, 521, 522, 523, 524];
+
+/// Checks that a response isn't an outage or maintenance page.
+///
+/// HTML only counts along with a server error, since login redirects and error pages of the
+/// website are HTML too.
+///
+/// # Errors
+/// - [`Error::ServiceUnavailable`]
+fn check_available(response: &reqwest::Response) -> Result<(), Error> {
+	let header = |name| {
+		response
+			.headers()
+			.get(name)
+			.and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+	};
+
+	let status = response.status();
+	let is_html = header(reqwest::header::CONTENT_TYPE)
+		.is_some_and(|content_type| content_type.starts_with("text/html"));
+	if !UNAVAILABLE_STATUSES.contains(&status.as_u16()) && !(is_html && status.is_server_error()) {
+		return Ok(());
+	}
+
+	Err(Error::ServiceUnavailable {
+		status,
+		// only the delay form is used in practice, rather than a date
+		retry_after: header(reqwest::header::RETRY_AFTER)
+			.and_then(|value| value.trim().parse().ok())
+			.map(Duration::from_secs),
+	})
+}
+
+/// Headers identifying a request with fumosclub's hosting, in order of preference
+const REQUEST_ID_HEADERS: &[&str] = &["x-vercel-id", "x-request-id"];
+/// Characters of an error response's body kept in [`Error::ResponseStatus`]
+const BODY_SNIPPET_LENGTH: usize = 200;
+
+/// Turns error statuses into [`Error::ResponseStatus`], with the endpoint, request id and the
+/// start of the body.
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+	let status = response.status();
+	if !status.is_client_error() && !status.is_server_error() {
+		return Ok(response);
+	}
+
+	let endpoint = response.url().path().to_owned();
+	let request_id = REQUEST_ID_HEADERS.iter().find_map(|name| {
+		response
+			.headers()
+			.get(*name)
+			.and_then(|value| value.to_str().ok())
+			.map(str::to_owned)
+	});
+	let body = response
+		.text()
+		.await
+		.ok()
+		.map(|body| {
+			body
+				.trim()
+				.chars()
+				.take(BODY_SNIPPET_LENGTH)
+				.collect::<String>()
+		})
+		.filter(|body| !body.is_empty());
+
+	Err(Error::ResponseStatus {
+		status,
+		endpoint,
+		request_id,
+		body,
+	})
+}
+
+trait CheckedResponse {
+	/// Reads the body, unless the response is an outage page or a server error.
+	async fn checked_bytes(self) -> Result<Vec<u8>, Error>;
+}
+
+impl CheckedResponse for reqwest::Response {
+	async fn checked_bytes(self) -> Result<Vec<u8>, Error> {
+		check_available(&self)?;
+		// client errors still carry the API's own error bodies, which callers report better
+		let response = if self.status().is_server_error() {
+			check_status(self).await?
+		} else {
+			self
+		};
+		let _section = timing::section("response download");
+		Ok(response.bytes().await?.into())
+	}
+}
+
+/// Errors if `FUMO_OFFLINE=1` and the url isn't a loopback address.
+pub fn ensure_reachable(url: &str) -> Result<(), Error> {
+	if !is_loopback(url) && std::env::var_os(OFFLINE_VARIABLE).is_some_and(|value| value == "1") {
+		return Err(Error::Offline(url.to_owned()));
+	}
+
+	Ok(())
+}
+
+/// Host probed by `fumo doctor` when fumosclub can't be reached, to tell network problems apart
+/// from fumosclub being down
+const KNOWN_GOOD_URL: &str = "https://www.cloudflare.com/cdn-cgi/trace";
+
+/// How fumosclub looks from this machine.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Reachability {
+	Reachable,
+	/// fumosclub answers with outage or maintenance pages
+	ServiceUnavailable,
+	/// fumosclub can't be reached, but other hosts can
+	Unreachable,
+	/// no host can be reached, so the network is likely at fault
+	NetworkDown,
+}
+
+/// Builds the unauthenticated client `fumo doctor` probes with.
+fn probe_client(base_url: &str) -> Result<reqwest::Client, Error> {
+	ensure_reachable(base_url)?;
+	Ok(
+		reqwest::Client::builder()
+			.user_agent(get_user_agent())
+			.timeout(settings::get().timeout)
+			.build()?,
+	)
+}
+
+/// Gets how far `now` is ahead of the `Date` header of a response; `None` without a usable one.
+fn clock_skew_of(response: &reqwest::Response, now: DateTime<Utc>) -> Option<TimeDelta> {
+	response
+		.headers()
+		.get(reqwest::header::DATE)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+		.map(|date| now - date.with_timezone(&Utc))
+}
+
+/// Measures how far the local clock is ahead of fumosclub's without logging in; see
+/// [`Client::clock_skew`].
+///
+/// # Errors
+/// - [`Error::Offline`] if `FUMO_OFFLINE=1` and the base url isn't a loopback address
+/// - [`Error::Reqwest`]
+pub async fn probe_clock_skew(base_url: &str) -> Result<Option<TimeDelta>, Error> {
+	let response = probe_client(base_url)?.head(base_url).send().await?;
+	Ok(clock_skew_of(&response, Utc::now()))
+}
+
+/// Probes fumosclub, and a known-good host if fumosclub doesn't answer.
+///
+/// # Errors
+/// - [`Error::Offline`] if `FUMO_OFFLINE=1` and the base url isn't a loopback address
+pub async fn probe_reachability(base_url: &str) -> Result<Reachability, Error> {
+	let client = probe_client(base_url)?;
+	let reachability = match client.get(base_url).send().await {
+		Ok(response) => match check_available(&response) {
+			Ok(()) => Reachability::Reachable,
+			Err(_) => Reachability::ServiceUnavailable,
+		},
+		Err(error) => {
+			tracing::debug!("fumosclub didn't answer: {error}");
+			match client.head(KNOWN_GOOD_URL).send().await {
+				Ok(..) => Reachability::Unreachable,
+				Err(..) => Reachability::NetworkDown,
+			}
+		}
+	};
+
+	Ok(reachability)
 }
 
 impl Client {
-	pub fn new(secrets: Secrets) -> Self {
-		Self {
+	/// Creates a client authenticated with the given secrets, with options from the settings.
+	///
+	/// Plain http is only allowed for loopback base urls, such as a local mock server.
+	///
+	/// # Errors
+	/// - [`Error::Offline`] if `FUMO_OFFLINE=1` and the base url isn't a loopback address
+	/// - [`Error::Reqwest`]
+	pub fn new(secrets: Secrets) -> Result<Self, Error> {
+		Self::with_options(secrets, ClientOptions::from_settings())
+	}
+
+	/// Creates a client authenticated with the given secrets; see [`Client::new`].
+	pub fn with_options(secrets: Secrets, options: ClientOptions) -> Result<Self, Error> {
+		let settings = settings::get();
+		let base_url = options.base_url.trim_end_matches('/').to_owned();
+		let loopback = is_loopback(&base_url);
+		ensure_reachable(&base_url)?;
+		tracing::debug!("user agent: {}", get_user_agent());
+
+		Ok(Self {
 			secrets,
+			rotated_session: Mutex::new(None),
+			base_url,
 			client: reqwest::Client::builder()
 				.user_agent(get_user_agent())
-				.https_only(true)
-				.build()
-				.expect("failed building inner reqwest client"),
+				.https_only(!loopback)
+				.timeout(settings.timeout)
+				.build()?,
+			options,
+			live_scripts: tokio::sync::OnceCell::new(),
+			cached_scripts: tokio::sync::OnceCell::new(),
+		})
+	}
+
+	fn url(&self, path: &str) -> String {
+		format!("{}{path}", self.base_url)
+	}
+
+	/// Gets the secrets, with the session fumosclub rotated to if it did.
+	pub fn current_secrets(&self) -> Secrets {
+		let rotated = self
+			.rotated_session
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clone();
+
+		match rotated {
+			Some(rotated) => Secrets {
+				session: rotated.session,
+				expires: rotated.expires.unwrap_or(self.secrets.expires),
+				..self.secrets.clone()
+			},
+			None => self.secrets.clone(),
+		}
+	}
+
+	/// Sends a request with the session cookie, adopting the session if fumosclub rotates it.
+	async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+		let session = self.current_secrets().session;
+		let mut request = request
+			.header("Cookie", format!("{SESSION_COOKIE}={session}"))
+			.build()?;
+		// only POSTs create something on fumosclub, so the rest can be repeated after timeouts
+		let idempotent = request.method() != reqwest::Method::POST;
+		let retries = settings::get().retries;
+		let mut attempt = 0;
+		let section = timing::section("http request");
+		let response = loop {
+			let retry = if attempt < retries {
+				request.try_clone()
+			} else {
+				None
+			};
+			// paths never hold script ids, so they are fine to export
+			let span = tracing::info_span!(
+				"request",
+				method = %request.method(),
+				path = request.url().path(),
+				bytes = request
+					.body()
+					.and_then(reqwest::Body::as_bytes)
+					.map_or(0, <[u8]>::len),
+				attempt,
+				status = tracing::field::Empty,
+			);
+			let result = self.client.execute(request).instrument(span.clone()).await;
+			if let Ok(response) = &result {
+				span.record("status", response.status().as_u16());
+			}
+			let Some(retry) = retry else {
+				break result?;
+			};
+			match result {
+				Ok(response)
+					if idempotent && UNAVAILABLE_STATUSES.contains(&response.status().as_u16()) =>
+				{
+					tracing::debug!("fumosclub answered {}; retrying", response.status());
+				}
+				Err(error) if error.is_connect() || (idempotent && error.is_timeout()) => {
+					tracing::debug!("request failed: {error}; retrying");
+				}
+				result => break result?,
+			}
+
+			request = retry;
+			attempt += 1;
+			tokio::time::sleep(RETRY_DELAY * 2u32.pow((attempt - 1).min(5))).await;
+		};
+		drop(section);
+
+		let rotated = response
+			.headers()
+			.get_all(reqwest::header::SET_COOKIE)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.find_map(parse_session_cookie)
+			.filter(|rotated| rotated.session != session);
+		if let Some(rotated) = rotated {
+			*self
+				.rotated_session
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner) = Some(rotated);
+			tracing::info!("fumosclub refreshed the session");
+			if settings::get().session_refresh {
+				save_rotated_session(&session, self.current_secrets()).await;
+			}
+		}
+
+		Ok(response)
+	}
+
+	/// Lists all scripts under the logged in account, like [`Client::list_scripts`], but only asks
+	/// fumosclub the first time; the list is cached on disk for later invocations.
+	pub async fn live_scripts(&self) -> Result<&[Script], Error> {
+		self
+			.live_scripts
+			.get_or_try_init(|| async {
+				let scripts = self.list_scripts().await?.scripts;
+				if let Some(account_id) = &self.secrets.account_id {
+					cache::write_scripts(account_id, &scripts, &BTreeMap::new()).await;
+				}
+
+				Ok::<_, Error>(scripts)
+			})
+			.await
+			.map(Vec::as_slice)
+	}
+
+	/// Whether [`Client::live_scripts`] already asked fumosclub.
+	pub fn has_live_scripts(&self) -> bool {
+		self.live_scripts.initialized()
+	}
+
+	/// Lists all scripts under the logged in account, preferring the disk cache while it is younger
+	/// than `scriptListTtl`.
+	pub async fn cached_scripts(&self) -> Result<&[Script], Error> {
+		if let Some(scripts) = self.live_scripts.get() {
+			return Ok(scripts);
+		}
+
+		let cached = self
+			.cached_scripts
+			.get_or_init(|| async {
+				let cached = cache::read_scripts(self.secrets.account_id.as_deref()?).await?;
+				let age = (Utc::now() - cached.fetched_at).to_std().ok()?;
+				(age < settings::get().script_list_ttl).then_some(cached.scripts)
+			})
+			.await;
+
+		match cached {
+			Some(scripts) => Ok(scripts),
+			None => self.live_scripts().await,
 		}
 	}
 
+	/// Measures how far the local clock is ahead of fumosclub's, from the `Date` header of a response.
+	///
+	/// Returns `None` if fumosclub didn't send a usable `Date` header.
+	pub async fn clock_skew(&self) -> Result<Option<TimeDelta>, Error> {
+		let response = self.client.head(self.url("/")).send().await?;
+		Ok(clock_skew_of(&response, Utc::now()))
+	}
+
 	/// Returns `Ok(())` if the user is authenticated.
 	///
 	/// # Errors
@@ -129,15 +890,9 @@ impl Client {
 
 		let value: InitialResponse = serde_json::from_slice(
 			&self
-				.client
-				.get(format!("{BASE_URL}/api/auth/auth"))
-				.header(
-					"Cookie",
-					format!("session={}", self.secrets.session.clone()),
-				)
-				.send()
+				.send(self.client.get(self.url("/api/auth/auth")))
 				.await?
-				.bytes()
+				.checked_bytes()
 				.await?,
 		)?;
 
@@ -160,15 +915,9 @@ impl Client {
 
 				let ban_data: BanData = serde_json::from_slice(
 					&self
-						.client
-						.get(format!("{BASE_URL}/api/auth/getbandata"))
-						.header(
-							"Cookie",
-							format!("session={}", self.secrets.session.clone()),
-						)
-						.send()
+						.send(self.client.get(self.url("/api/auth/getbandata")))
 						.await?
-						.bytes()
+						.checked_bytes()
 						.await?,
 				)?;
 
@@ -197,19 +946,57 @@ impl Client {
 	/// - [`Error::Reqwest`]
 	/// - [`Error::Serde`]
 	pub async fn get_details(&self) -> Result<AccountDetails, Error> {
-		Ok(serde_json::from_slice(
+		let details: AccountDetails = serde_json::from_slice(
 			&self
-				.client
-				.get(format!("{BASE_URL}/api/account/getdetails"))
-				.header(
-					"Cookie",
-					format!("session={}", self.secrets.session.clone()),
-				)
-				.send()
+				.send(self.client.get(self.url("/api/account/getdetails")))
 				.await?
-				.bytes()
+				.checked_bytes()
 				.await?,
-		)?)
+		)?;
+
+		// keyed by the id fumosclub reports, which is the one new logins are saved with
+		cache::write_details(&details.id, &details).await;
+		Ok(details)
+	}
+
+	/// Fetches the details of every script, in the same order; failures are `None`.
+	///
+	/// Once fumosclub reports it is unavailable or rate limited, the remaining scripts aren't asked,
+	/// and listed ids which aren't valid script ids are never asked.
+	pub async fn script_details(&self, ids: &[&str]) -> Vec<Option<ScriptDetails>> {
+		use futures::StreamExt;
+
+		let unavailable = AtomicBool::new(false);
+		let mut details: Vec<(usize, Option<ScriptDetails>)> =
+			futures::stream::iter(ids.iter().enumerate())
+				.map(|(index, id)| {
+					let unavailable = &unavailable;
+					async move {
+						let Ok(script_id) = ScriptId::new(id) else {
+							return (index, None);
+						};
+						if unavailable.load(Ordering::Relaxed) {
+							return (index, None);
+						}
+
+						match self.get_editor(&script_id).await {
+							Ok(editor) => (index, Some(ScriptDetails::from(&editor.script_info))),
+							Err(error) => {
+								if matches!(error, Error::ServiceUnavailable { .. }) {
+									unavailable.store(true, Ordering::Relaxed);
+								}
+								tracing::debug!("couldn't fetch the details of {id}: {error}");
+								(index, None)
+							}
+						}
+					}
+				})
+				.buffer_unordered(CONCURRENT_DETAIL_REQUESTS)
+				.collect()
+				.await;
+
+		details.sort_by_key(|(index, _)| *index);
+		details.into_iter().map(|(_, details)| details).collect()
 	}
 
 	/// Generates a key for a fumosclub script.
@@ -219,44 +1006,32 @@ impl Client {
 	/// - [`Error::Reqwest`]
 	/// - [`Error::ResponseStatus`]
 	/// - [`Error::Serde`]
-	pub async fn generate_key(&self, id: &str) -> Result<String, Error> {
+	pub async fn generate_key(&self, id: &ScriptId) -> Result<String, Error> {
 		#[derive(Deserialize)]
 		struct Key {
 			success: bool,
 			require: String,
 		}
 
-		match self
-			.client
-			.post(format!("{BASE_URL}/api/script/generatekey"))
-			.header(
-				"Cookie",
-				format!("session={}", self.secrets.session.clone()),
+		let response = self
+			.send(
+				self
+					.client
+					.post(self.url("/api/script/generatekey"))
+					.header("Content-Type", "application/json")
+					.body(serde_json::to_string(&json!({
+						"scriptId": id
+					}))?),
 			)
-			.header("Content-Type", "application/json")
-			.body(serde_json::to_string(&json!({
-				"scriptId": id
-			}))?)
-			.send()
-			.await?
-			.error_for_status()
-		{
-			Ok(response) => {
-				let value: Key = serde_json::from_slice(&response.bytes().await?)?;
-
-				Ok(value.require)
-			}
-
-			Err(error) => {
-				let status = error.status().unwrap_or_else(|| unreachable!());
+			.await?;
+		check_available(&response)?;
 
-				Err(if status == 400 {
-					Error::InvalidKeyGenerationTarget
-				} else {
-					Error::ResponseStatus(status)
-				})
-			}
+		if response.status() == reqwest::StatusCode::BAD_REQUEST {
+			return Err(Error::InvalidKeyGenerationTarget);
 		}
+
+		let value: Key = serde_json::from_slice(&check_status(response).await?.bytes().await?)?;
+		Ok(value.require)
 	}
 
 	/// Lists all scripts this account can access.
@@ -267,15 +1042,9 @@ impl Client {
 	pub async fn list_scripts(&self) -> Result<ScriptList, Error> {
 		Ok(serde_json::from_slice(
 			&self
-				.client
-				.get(format!("{BASE_URL}/api/script/home/getscripts"))
-				.header(
-					"Cookie",
-					format!("session={}", self.secrets.session.clone()),
-				)
-				.send()
+				.send(self.client.get(self.url("/api/script/home/getscripts")))
 				.await?
-				.bytes()
+				.checked_bytes()
 				.await?,
 		)?)
 	}
@@ -285,110 +1054,397 @@ impl Client {
 	/// # Errors
 	/// - [`Error::Reqwest`]
 	/// - [`Error::Serde`]
-	pub async fn get_editor(&self, id: &str) -> Result<Editor, Error> {
-		Ok(serde_json::from_slice(
-			&self
-				.client
-				.get(format!("{BASE_URL}/api/script/editor"))
-				.query(&[("id", id)])
-				.header(
-					"Cookie",
-					format!("session={}", self.secrets.session.clone()),
-				)
-				.send()
-				.await?
-				.bytes()
-				.await?,
-		)?)
+	pub async fn get_editor(&self, id: &ScriptId) -> Result<Editor, Error> {
+		let body = self
+			.send(
+				self
+					.client
+					.get(self.url("/api/script/editor"))
+					.query(&[("id", id.as_str())]),
+			)
+			.await?
+			.checked_bytes()
+			.await?;
+
+		let _section = timing::section("response handling");
+		Ok(serde_json::from_slice(&body)?)
+	}
+
+	/// Gets the editor like [`Client::get_editor`], but revalidates the cached one with a
+	/// conditional request; fumosclub answering 304 skips downloading every source again.
+	///
+	/// Anything short of a 304 for a cached editor is a full fetch, which replaces the cache entry,
+	/// or drops it if the response has no validators.
+	pub async fn get_editor_cached(
+		&self,
+		id: &ScriptId,
+		cache: &mut EditorCache,
+	) -> Result<Editor, Error> {
+		let cached = cache.editors.get(id.as_str());
+		let mut request = self
+			.client
+			.get(self.url("/api/script/editor"))
+			.query(&[("id", id.as_str())]);
+		if let Some(cached) = cached {
+			if let Some(etag) = &cached.etag {
+				request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+			}
+			if let Some(last_modified) = &cached.last_modified {
+				request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+			}
+		}
+
+		let response = self.send(request).await?;
+		if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+			tracing::debug!("the editor of {id} is unchanged since it was cached");
+			return Ok(Editor {
+				success: true,
+				script_info: cached.script_info.clone(),
+			});
+		}
+
+		let header = |name| {
+			response
+				.headers()
+				.get(name)
+				.and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+				.map(str::to_owned)
+		};
+		let etag = header(reqwest::header::ETAG);
+		let last_modified = header(reqwest::header::LAST_MODIFIED);
+		let body = response.checked_bytes().await?;
+
+		let _section = timing::section("response handling");
+		let editor: Editor = serde_json::from_slice(&body)?;
+		if editor.success && (etag.is_some() || last_modified.is_some()) {
+			cache.editors.insert(
+				id.to_string(),
+				CachedEditor {
+					etag,
+					last_modified,
+					script_info: editor.script_info.clone(),
+				},
+			);
+		} else {
+			cache.editors.remove(id.as_str());
+		}
+
+		Ok(editor)
+	}
+
+	/// Sends a serialized editor update, gzipped if `compress` is set.
+	async fn send_editor_body(
+		&self,
+		body: &[u8],
+		compress: bool,
+	) -> Result<reqwest::Response, Error> {
+		let request = self
+			.client
+			.patch(self.url("/api/script/editor"))
+			.header("Content-Type", "application/json");
+
+		let request = if compress {
+			let _section = timing::section("body compression");
+			let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+			encoder.write_all(body)?;
+			let compressed = encoder.finish()?;
+			tracing::debug!(
+				"compressed the request body from {} to {} bytes",
+				body.len(),
+				compressed.len()
+			);
+
+			request.header("Content-Encoding", "gzip").body(compressed)
+		} else {
+			request.body(body.to_vec())
+		};
+
+		self.send(request).await
+	}
+
+	/// Sends a body fumosclub refused compressed again, and stops compressing bodies from then on.
+	async fn resend_uncompressed(
+		&self,
+		body: &[u8],
+		status: reqwest::StatusCode,
+	) -> Result<reqwest::Response, Error> {
+		tracing::debug!(
+			"fumosclub refused a compressed body with {status}; sending it uncompressed from now on"
+		);
+		COMPRESSION_REFUSED.store(true, Ordering::Relaxed);
+		self.send_editor_body(body, false).await
 	}
 
 	/// Updates a script or package, via the editor API.
 	///
+	/// Large bodies are gzipped unless disabled by the [options](ClientOptions), falling back to
+	/// uncompressed bodies for the rest of the process if fumosclub refuses one.
+	///
 	/// # Errors
 	/// - [`Error::Reqwest`]
 	/// - [`Error::Serde`]
-	pub async fn set_editor(&self, id: &str, updates: &[EditorUpdate<'_>]) -> Result<(), Error> {
-		#[derive(Serialize, Debug, Clone)]
-		#[serde(rename_all = "camelCase")]
-		struct ScriptInfo<'a> {
-			pub source: Source<'a>,
-			#[serde(skip_serializing_if = "Option::is_none")]
-			pub description: Option<&'a str>,
-			#[serde(skip_serializing_if = "Option::is_none")]
-			pub whitelist: Option<Vec<&'a str>>,
-			#[serde(skip_serializing_if = "Option::is_none")]
-			pub name: Option<&'a str>,
-			#[serde(skip_serializing_if = "Option::is_none")]
-			pub is_public: Option<bool>,
-		}
-
-		#[derive(Serialize, Debug, Clone)]
-		#[serde(rename_all = "camelCase")]
-		struct Source<'a> {
-			#[serde(skip_serializing_if = "Option::is_none")]
-			pub modules: Option<HashMap<&'a str, &'a str>>,
-			#[serde(skip_serializing_if = "Option::is_none")]
-			pub main: Option<&'a str>,
-		}
-
-		#[derive(Serialize, Debug, Clone)]
-		#[serde(rename_all = "camelCase")]
-		struct SetEditor<'a> {
-			pub script_id: &'a str,
-			pub script_info: ScriptInfo<'a>,
-		}
-
-		let mut request_body = SetEditor {
-			script_id: id,
-			script_info: ScriptInfo {
-				source: Source {
-					modules: None,
-					main: None,
-				},
-				whitelist: None,
-				description: None,
-				name: None,
-				is_public: None,
+	pub async fn set_editor(&self, id: &ScriptId, updates: &[EditorUpdate<'_>]) -> Result<(), Error> {
+		let body = editor_body(id, updates)?;
+		tracing::debug!("editor body sha256 {:x}", sha2::Sha256::digest(&body));
+		let compress = self.options.compress_uploads
+			&& body.len() >= COMPRESSION_THRESHOLD
+			&& !COMPRESSION_REFUSED.load(Ordering::Relaxed);
+
+		let response = self.send_editor_body(&body, compress).await?;
+		let response = match response.status() {
+			_ if !compress => response,
+			status @ reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE => {
+				self.resend_uncompressed(&body, status).await?
+			}
+			// invalid updates are refused with a 400 too, so only ones blaming the encoding count
+			status @ reqwest::StatusCode::BAD_REQUEST => match check_status(response).await {
+				Err(Error::ResponseStatus {
+					body: Some(reason), ..
+				}) if names_encoding(&reason) => self.resend_uncompressed(&body, status).await?,
+				Err(error) => return Err(error),
+				Ok(response) => response,
 			},
+			_ => response,
 		};
+		check_available(&response)?;
+		check_status(response).await?;
+		Ok(())
+	}
+}
 
-		for update in updates {
-			match update {
-				EditorUpdate::Description(value) => request_body.script_info.description = Some(value),
-				EditorUpdate::Module { name, source } => match request_body.script_info.source.modules {
-					None => {
-						request_body.script_info.source.modules = Some(HashMap::from([(*name, *source)]));
-					}
-					Some(ref mut modules) => {
-						modules.insert(*name, *source);
-					}
-				},
-				EditorUpdate::MainSource(source) => {
-					request_body.script_info.source.main = Some(source);
-				}
-				EditorUpdate::Whitelist(whitelist) => {
-					request_body.script_info.whitelist = Some(whitelist.clone());
-				}
-				EditorUpdate::Name(name) => request_body.script_info.name = Some(name),
-				EditorUpdate::Publicity(public) => request_body.script_info.is_public = Some(*public),
+/// Whether an error response blames the encoding of a compressed body.
+fn names_encoding(body: &str) -> bool {
+	let body = body.to_lowercase();
+	["encoding", "gzip", "compress"]
+		.iter()
+		.any(|word| body.contains(word))
+}
+
+/// Serializes the body of a [`Client::set_editor`] request.
+///
+/// Modules are sorted by name and the whitelist is sorted, so the same updates always give the same
+/// bytes.
+pub fn editor_body(id: &ScriptId, updates: &[EditorUpdate<'_>]) -> Result<Vec<u8>, Error> {
+	#[derive(Serialize, Debug, Clone)]
+	#[serde(rename_all = "camelCase")]
+	struct ScriptInfo<'a> {
+		pub source: Source<'a>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub description: Option<&'a str>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub whitelist: Option<Vec<&'a str>>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub name: Option<&'a str>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub is_public: Option<bool>,
+	}
+
+	#[derive(Serialize, Debug, Clone)]
+	#[serde(rename_all = "camelCase")]
+	struct Source<'a> {
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub modules: Option<BTreeMap<&'a str, Option<&'a str>>>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub main: Option<&'a str>,
+	}
+
+	#[derive(Serialize, Debug, Clone)]
+	#[serde(rename_all = "camelCase")]
+	struct SetEditor<'a> {
+		pub script_id: &'a str,
+		pub script_info: ScriptInfo<'a>,
+	}
+
+	let mut request_body = SetEditor {
+		script_id: id.as_str(),
+		script_info: ScriptInfo {
+			source: Source {
+				modules: None,
+				main: None,
+			},
+			whitelist: None,
+			description: None,
+			name: None,
+			is_public: None,
+		},
+	};
+
+	for update in updates {
+		match update {
+			EditorUpdate::Description(value) => request_body.script_info.description = Some(value),
+			EditorUpdate::Module { name, source } => {
+				request_body
+					.script_info
+					.source
+					.modules
+					.get_or_insert_default()
+					.insert(name.as_str(), Some(*source));
+			}
+			EditorUpdate::RemoveModule(name) => {
+				request_body
+					.script_info
+					.source
+					.modules
+					.get_or_insert_default()
+					.insert(*name, None);
 			}
+			EditorUpdate::MainSource(source) => {
+				request_body.script_info.source.main = Some(source);
+			}
+			EditorUpdate::Whitelist(whitelist) => {
+				let mut whitelist = whitelist.clone();
+				whitelist.sort_unstable();
+				whitelist.dedup();
+				request_body.script_info.whitelist = Some(whitelist);
+			}
+			EditorUpdate::Name(name) => request_body.script_info.name = Some(name),
+			EditorUpdate::Publicity(public) => request_body.script_info.is_public = Some(*public),
 		}
+	}
 
-		let response = self
-			.client
-			.patch(format!("{BASE_URL}/api/script/editor"))
-			.header(
-				"Cookie",
-				format!("session={}", self.secrets.session.clone()),
-			)
-			.header("Content-Type", "application/json")
-			.body(serde_json::to_string(&request_body)?)
-			.send()
-			.await?;
+	let _section = timing::section("body serialization");
+	Ok(serde_json::to_vec(&request_body)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn modules() -> Vec<(ModuleName, &'static str)> {
+		["util", "signal", "maid"]
+			.into_iter()
+			.map(|name| (ModuleName::new(name).unwrap(), "return {}"))
+			.collect()
+	}
+
+	/// Builds the updates of a push of one project, visiting its modules in the given order.
+	fn body(modules: &[&(ModuleName, &str)], whitelist: Vec<&str>) -> Vec<u8> {
+		let mut updates = vec![
+			EditorUpdate::MainSource("print(requireM(\"util\"))"),
+			EditorUpdate::Whitelist(whitelist),
+		];
+		updates.extend(modules.iter().map(|(name, source)| EditorUpdate::Module {
+			name,
+			source: *source,
+		}));
+		editor_body(&ScriptId::new("abc123").unwrap(), &updates).unwrap()
+	}
+
+	#[test]
+	fn bodies_are_byte_identical() {
+		let modules = modules();
+		let forwards: Vec<_> = modules.iter().collect();
+		let backwards: Vec<_> = modules.iter().rev().collect();
+
+		let first = body(&forwards, vec!["2", "1"]);
+		assert_eq!(first, body(&forwards, vec!["2", "1"]));
+		assert_eq!(first, body(&backwards, vec!["1", "2", "1"]));
+		assert_eq!(
+			String::from_utf8(first).unwrap(),
+			r#"{"scriptId":"abc123","scriptInfo":{"source":{"modules":{"maid":"return {}","signal":"return {}","util":"return {}"},"main":"print(requireM(\"util\"))"},"whitelist":["1","2"]}}"#
+		);
+	}
+
+	#[test]
+	fn unchanged_projects_hash_the_same() {
+		let modules = modules();
+		let forwards: Vec<_> = modules.iter().collect();
+		let backwards: Vec<_> = modules.iter().rev().collect();
+		let hash = |body: Vec<u8>| sha2::Sha256::digest(body);
+
+		assert_eq!(
+			hash(body(&forwards, vec!["1"])),
+			hash(body(&backwards, vec!["1"]))
+		);
+		assert_ne!(
+			hash(body(&forwards, vec!["1"])),
+			hash(body(&forwards, vec!["1", "3"]))
+		);
+	}
+
+	#[test]
+	fn validates_script_ids() {
+		for id in ["abc123", "a-b_C", "x".repeat(MAX_SCRIPT_ID_LENGTH).as_str()] {
+			assert_eq!(ScriptId::new(id).unwrap(), *id);
+		}
+		for id in [
+			"",
+			"abc 123",
+			"abc/123",
+			"https://fumosclub.com/abc",
+			"x".repeat(MAX_SCRIPT_ID_LENGTH + 1).as_str(),
+		] {
+			assert!(matches!(ScriptId::new(id), Err(Error::InvalidScriptId(invalid)) if invalid == id));
+		}
+
+		assert!("abc123".parse::<ScriptId>().is_ok());
+		assert!(serde_json::from_str::<ScriptId>("\"abc 123\"").is_err());
+		assert_eq!(
+			serde_json::to_string(&ScriptId::new("abc123").unwrap()).unwrap(),
+			"\"abc123\""
+		);
+	}
+
+	#[test]
+	fn validates_module_names() {
+		assert_eq!(ModuleName::new("  util  ").unwrap(), *"util");
+		assert_eq!(ModuleName::new("my module.v2").unwrap(), *"my module.v2");
+		let longest = "\u{e9}".repeat(MAX_MODULE_NAME_LENGTH);
+		assert_eq!(ModuleName::new(&longest).unwrap(), *longest);
+
+		for name in [
+			"",
+			"   ",
+			"util\n",
+			"a\tb",
+			"x".repeat(MAX_MODULE_NAME_LENGTH + 1).as_str(),
+		] {
+			assert!(ModuleName::new(name).is_err(), "{name:?} was accepted");
+		}
+		assert!(serde_json::from_str::<ModuleName>("\"\"").is_err());
+	}
+
+	#[test]
+	fn parses_rotated_session_cookies() {
+		let rotated = parse_session_cookie("session=abc; Path=/; Max-Age=3600; HttpOnly").unwrap();
+		assert_eq!(rotated.session, "abc");
+		let expires = rotated.expires.unwrap() - Utc::now();
+		assert!((TimeDelta::seconds(3590)..=TimeDelta::seconds(3600)).contains(&expires));
+
+		let rotated =
+			parse_session_cookie("session=\"abc\"; expires=Wed, 21 Oct 2026 07:28:00 GMT").unwrap();
+		assert_eq!(rotated.session, "abc");
+		assert_eq!(
+			rotated.expires,
+			Some("2026-10-21T07:28:00Z".parse().unwrap())
+		);
+
+		let rotated = parse_session_cookie("session=abc").unwrap();
+		assert!(rotated.expires.is_none());
+	}
+
+	#[test]
+	fn max_age_beats_expires() {
+		for header in [
+			"session=abc; Expires=Wed, 21 Oct 2026 07:28:00 GMT; Max-Age=60",
+			"session=abc; Max-Age=60; Expires=Wed, 21 Oct 2026 07:28:00 GMT",
+		] {
+			let expires = parse_session_cookie(header).unwrap().expires.unwrap();
+			assert!(expires - Utc::now() <= TimeDelta::seconds(60), "{header}");
+		}
+	}
 
-		match response.error_for_status() {
-			Ok(..) => Ok(()),
-			Err(error) => Err(Error::ResponseStatus(error.status().expect("must exist"))),
+	#[test]
+	fn ignores_cleared_and_other_cookies() {
+		for header in [
+			"session=abc; Max-Age=0",
+			"session=; Path=/",
+			"session=abc; Max-Age=soon",
+			"theme=dark; Max-Age=3600",
+			"sessions=abc",
+			"session",
+		] {
+			assert!(parse_session_cookie(header).is_none(), "{header}");
 		}
 	}
 }
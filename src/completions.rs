@@ -0,0 +1,241 @@
+use crate::{
+	cache,
+	error::Error,
+	login::{get_config_directory, read_session_secrets},
+	project::{read_file, write_file},
+};
+use clap::ValueEnum;
+use clap_complete::Shell;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Hooks appended to the static completions, completing script ids with `fumo __complete`
+const ZSH_HOOK: &str = include_str!("../assets/completions/fumo.zsh");
+const FISH_HOOK: &str = include_str!("../assets/completions/fumo.fish");
+
+/// Values completed by `fumo __complete`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionTarget {
+	ScriptIds,
+}
+
+/// Comment at the top of installed completions; other files aren't overwritten by [`install`]
+const INSTALL_MARKER: &str =
+	"# written by `fumo completions install`; run it again to update this file";
+/// Lines the marker is looked for in, since it follows the first line when that has to stay first
+const MARKER_LINES: usize = 3;
+
+/// Puts the marker at the top of completions, after the `#compdef` line zsh only autoloads files
+/// starting with, or a shebang.
+fn mark(script: &str) -> String {
+	match script.split_once('\n') {
+		Some((first, rest)) if first.starts_with("#compdef") || first.starts_with("#!") => {
+			format!("{first}\n{INSTALL_MARKER}\n{rest}")
+		}
+		_ => format!("{INSTALL_MARKER}\n{script}"),
+	}
+}
+
+/// Checks whether completions were written by [`install`].
+fn is_marked(contents: &str) -> bool {
+	contents
+		.lines()
+		.take(MARKER_LINES)
+		.any(|line| line == INSTALL_MARKER)
+}
+
+/// Gets the completions for the shell, with the dynamic hooks if `dynamic` is set.
+///
+/// The flag is `false` if the shell has no dynamic hooks.
+fn render(shell: Shell, command: &mut clap::Command, dynamic: bool) -> (String, bool) {
+	let mut script = Vec::new();
+	clap_complete::generate(shell, command, "fumo", &mut script);
+	let mut script = String::from_utf8_lossy(&script).into_owned();
+	if !dynamic {
+		return (script, true);
+	}
+
+	match shell {
+		Shell::Zsh => script.push_str(ZSH_HOOK),
+		Shell::Fish => script.push_str(FISH_HOOK),
+		_ => return (script, false),
+	}
+
+	(script, true)
+}
+
+/// Writes completions for the shell to stdout, with the dynamic hooks if `dynamic` is set.
+///
+/// Returns `false` if the shell has no dynamic hooks.
+pub fn generate(shell: Shell, command: &mut clap::Command, dynamic: bool) -> bool {
+	let (script, hooked) = render(shell, command, dynamic);
+	print!("{script}");
+	hooked
+}
+
+/// Gets the shell named by `$SHELL`, unless one is given.
+///
+/// # Errors
+/// - [`Error::ShellNotDetected`]
+pub fn detect_shell(shell: Option<Shell>) -> Result<Shell, Error> {
+	shell
+		.or_else(Shell::from_env)
+		.ok_or(Error::ShellNotDetected)
+}
+
+/// Completions written, or to be written, by [`install`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Installation {
+	pub shell: String,
+	pub path: PathBuf,
+	pub dry_run: bool,
+	/// What is left to do by hand for the shell to load the completions
+	pub manual_step: Option<String>,
+	#[serde(skip)]
+	pub contents: String,
+}
+
+/// Gets `$<variable>`, or `<home>/<fallback>` without it, for XDG base directories.
+fn xdg_directory(variable: &str, fallback: &str) -> Result<PathBuf, Error> {
+	if let Some(directory) = std::env::var_os(variable).filter(|value| !value.is_empty()) {
+		return Ok(PathBuf::from(directory));
+	}
+
+	let home = directories::BaseDirs::new().ok_or(Error::HomeDirectoryNotFound)?;
+	Ok(home.home_dir().join(fallback))
+}
+
+/// Gets where a shell loads per-user completions from, and what has to be done by hand first.
+async fn install_target(shell: Shell) -> Result<(PathBuf, Option<String>), Error> {
+	match shell {
+		Shell::Bash => Ok((
+			xdg_directory("XDG_DATA_HOME", ".local/share")?.join("bash-completion/completions/fumo"),
+			None,
+		)),
+		Shell::Zsh => {
+			let home = directories::BaseDirs::new().ok_or(Error::HomeDirectoryNotFound)?;
+			let home = home.home_dir();
+			let zshrc = read_file(home.join(".zshrc")).await.unwrap_or_default();
+			let step = (!zshrc.contains(".zsh/completions"))
+				.then(|| "add `fpath=(~/.zsh/completions $fpath)` above `compinit` in ~/.zshrc".to_owned());
+			Ok((home.join(".zsh/completions/_fumo"), step))
+		}
+		Shell::Fish => Ok((
+			xdg_directory("XDG_CONFIG_HOME", ".config")?.join("fish/completions/fumo.fish"),
+			None,
+		)),
+		Shell::PowerShell => {
+			// PowerShell only loads completions from the profile, which is left for the user to edit
+			let path = get_config_directory()?.join("completions").join("fumo.ps1");
+			let step = format!(
+				"add `. \"{}\"` to your PowerShell profile, which is at $PROFILE",
+				path.display()
+			);
+			Ok((path, Some(step)))
+		}
+		other => Err(Error::UnsupportedShell(other.to_string())),
+	}
+}
+
+/// Writes completions where the shell loads them from, creating directories as needed.
+///
+/// Existing files are only overwritten if they were written by this function; with `dry_run`,
+/// nothing is written.
+///
+/// # Errors
+/// - [`Error::ShellNotDetected`] if no shell is given and `$SHELL` isn't a known one
+/// - [`Error::UnsupportedShell`]
+/// - [`Error::ForeignCompletions`] if another file is in the way
+pub async fn install(
+	shell: Option<Shell>,
+	command: &mut clap::Command,
+	dynamic: bool,
+	dry_run: bool,
+) -> Result<Installation, Error> {
+	let shell = detect_shell(shell)?;
+	let (path, manual_step) = install_target(shell).await?;
+	let (script, hooked) = render(shell, command, dynamic);
+	if !hooked {
+		tracing::warn!("{shell} has no dynamic completions; only static ones are installed");
+	}
+	let contents = mark(&script);
+
+	if path.try_exists()? {
+		let existing = read_file(&path).await?;
+		if !is_marked(&existing) {
+			return Err(Error::ForeignCompletions(path));
+		}
+	}
+
+	if !dry_run {
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent)
+				.await
+				.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+		}
+		write_file(&path, &contents).await?;
+	}
+
+	Ok(Installation {
+		shell: shell.to_string(),
+		path,
+		dry_run,
+		manual_step,
+		contents,
+	})
+}
+
+/// Prints `id<TAB>name` for every script in the cached script list.
+///
+/// Completion must stay fast and quiet, so fumosclub is never asked, and nothing is printed when
+/// there is no login or cache.
+pub async fn complete(target: CompletionTarget) {
+	match target {
+		CompletionTarget::ScriptIds => {
+			let Some(account_id) = read_session_secrets()
+				.await
+				.ok()
+				.and_then(|secrets| secrets.account_id)
+			else {
+				return;
+			};
+
+			if let Some(cached) = cache::read_scripts(&account_id).await {
+				for script in cached.scripts {
+					println!("{}\t{}", script.id, script.name);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn zsh_completions_start_with_compdef() {
+		let (script, _) = render(Shell::Zsh, &mut clap::Command::new("fumo"), false);
+		let marked = mark(&script);
+		let mut lines = marked.lines();
+		assert!(lines.next().unwrap().starts_with("#compdef fumo"));
+		assert_eq!(lines.next(), Some(INSTALL_MARKER));
+		assert!(is_marked(&marked));
+	}
+
+	#[test]
+	fn marks_other_completions_first() {
+		let (script, _) = render(Shell::Bash, &mut clap::Command::new("fumo"), false);
+		let marked = mark(&script);
+		assert!(marked.starts_with(&format!("{INSTALL_MARKER}\n")));
+		assert!(marked.ends_with(&script));
+		assert!(is_marked(&marked));
+	}
+
+	#[test]
+	fn leaves_foreign_completions_alone() {
+		assert!(!is_marked("#compdef fumo\n_fumo() {}\n"));
+		assert!(!is_marked(&format!("one\ntwo\nthree\n{INSTALL_MARKER}\n")));
+	}
+}
@@ -0,0 +1,121 @@
+use crate::{
+	client::{Client, EditorUpdate},
+	error::Error,
+	login::get_session_secrets,
+	project::{DESCRIPTION_FILE, open_in_editor, read_configuration, read_file},
+	settings,
+};
+use std::path::Path;
+use tracing::warn;
+
+/// Constructs which fumosclub doesn't render, paired with what is wrong with them
+const BADLY_RENDERED: &[(fn(&str) -> bool, &str)] = &[
+	(
+		|line| line.trim_start().starts_with('|'),
+		"tables are shown as plain text",
+	),
+	(
+		|line| line.contains("[^"),
+		"footnotes are shown as plain text",
+	),
+	(
+		|line| {
+			let line = line.trim_start();
+			line.starts_with("- [ ]") || line.starts_with("- [x]")
+		},
+		"task lists are shown as plain text",
+	),
+	(
+		|line| {
+			line.split('<').skip(1).any(|rest| {
+				rest.starts_with(|character: char| character.is_ascii_alphabetic() || character == '/')
+			})
+		},
+		"HTML tags are shown as plain text",
+	),
+];
+
+/// Checks a description before it is uploaded, warning about constructs which render badly and
+/// lengths past `descriptionWarnLength`.
+///
+/// # Errors
+/// - [`Error::DescriptionTooLong`] past `maxDescriptionLength`
+pub fn check(description: &str) -> Result<(), Error> {
+	let settings = settings::get();
+	let length = description.chars().count();
+	if length > settings.max_description_length {
+		return Err(Error::DescriptionTooLong {
+			length,
+			limit: settings.max_description_length,
+		});
+	}
+
+	if length > settings.description_warn_length {
+		warn!(
+			"the description is {length} characters long; fumosclub may truncate descriptions past {} characters",
+			settings.description_warn_length
+		);
+	}
+
+	// the fence which opened the code block the line is in
+	let mut fence: Option<&str> = None;
+	for (number, line) in description.lines().enumerate() {
+		let trimmed = line.trim_start();
+		let line_fence = ["```", "~~~"]
+			.into_iter()
+			.find(|opener| trimmed.starts_with(opener));
+		match (fence, line_fence) {
+			(None, Some(opener)) => fence = Some(opener),
+			(Some(open), Some(opener)) if open == opener => fence = None,
+			_ => {}
+		}
+
+		if line
+			.chars()
+			.any(|character| character.is_control() && character != '\t')
+		{
+			warn!(
+				"{DESCRIPTION_FILE}:{}: control characters aren't shown",
+				number + 1
+			);
+		}
+
+		// code blocks are shown verbatim, so nothing inside of them renders badly
+		if fence.is_some() || line_fence.is_some() {
+			continue;
+		}
+
+		for (renders_badly, problem) in BADLY_RENDERED {
+			if renders_badly(line) {
+				warn!("{DESCRIPTION_FILE}:{}: {problem}", number + 1);
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Opens `$VISUAL` or `$EDITOR` on the description, then pushes only the description if the editor
+/// exits successfully.
+pub async fn edit(project_directory: &Path) -> Result<(), Error> {
+	if !settings::get().interactive {
+		return Err(Error::InteractionRequired(
+			"--edit opens an editor, which needs a terminal".to_owned(),
+		));
+	}
+
+	let configuration = read_configuration(project_directory).await?;
+	let path = project_directory.join(DESCRIPTION_FILE);
+	open_in_editor(&path).await?;
+
+	let description = read_file(&path).await?;
+	check(&description)?;
+
+	let client = Client::new(get_session_secrets().await?)?;
+	client
+		.set_editor(
+			&configuration.linked_script_id()?,
+			&[EditorUpdate::Description(&description)],
+		)
+		.await
+}
@@ -1,6 +1,7 @@
+use crate::{client::Script, project::SYNC_CONFIGURATION_FILE};
 use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
-use std::path::PathBuf;
+use std::{ffi::OsStr, path::PathBuf, time::Duration};
 use thiserror::Error;
 use tokio::io;
 
@@ -24,26 +25,484 @@ pub enum Error {
 	Serde(#[from] serde_json::Error),
 	#[error("generic io error: {0}")]
 	Io(#[from] io::Error),
-	#[error("got error response status: {0}")]
-	ResponseStatus(StatusCode),
+	#[error(
+		"fumosclub answered {endpoint} with {status}{}{}",
+		.request_id.as_ref().map(|id| format!(" (request id {id})")).unwrap_or_default(),
+		.body.as_ref().map(|body| format!(": {body}")).unwrap_or_default()
+	)]
+	ResponseStatus {
+		status: StatusCode,
+		/// Path of the url which was requested
+		endpoint: String,
+		/// The x-vercel-id or x-request-id header, which fumosclub's maintainers can look requests up by
+		request_id: Option<String>,
+		/// Start of the response body
+		body: Option<String>,
+	},
+	#[error(
+		"fumosclub appears to be down (status {status}){}",
+		.retry_after.map(|delay| format!("; retry in {}s", delay.as_secs())).unwrap_or_default()
+	)]
+	ServiceUnavailable {
+		status: StatusCode,
+		retry_after: Option<Duration>,
+	},
 	#[error("secrets expired at {0}")]
 	SecretsExpired(DateTime<Utc>),
+	#[error("{}", .0.map_or_else(
+		|| "the secrets were never validated with fumosclub".to_owned(),
+		|time| format!("the secrets were last validated with fumosclub at {time}"),
+	))]
+	UnvalidatedSecrets(Option<DateTime<Utc>>),
+	#[error(
+		"secrets.json has format version {0}, which is newer than this version of fumo supports; please upgrade fumo"
+	)]
+	SecretsVersionTooNew(u32),
 	#[error("failed finding config directory")]
 	ConfigDirectoryNotFound,
+	#[error("can't write to the config directory {}: {}", .0.display(), describe_io(.1))]
+	ConfigDirectoryUnwritable(PathBuf, io::Error),
+	#[error("failed initializing file watcher: {0}")]
+	WatcherInit(String),
+	#[error("failed watching {0}: {1}")]
+	WatchPath(PathBuf, String),
 	#[error("failed diffing paths")]
 	PathDiffFailed,
+	#[error("the project at {0} is inside of the fumo config directory")]
+	ProjectInsideConfigDir(PathBuf),
+	#[error("the fumo config directory at {0} is inside of the project")]
+	ConfigDirInsideProject(PathBuf),
 	#[error("secrets do not have a high enough role")]
 	InsufficentAuthorization,
 	#[error("invalid secrets; authentication required")]
 	InvalidSecrets,
+	#[error("malformed session token: {0}")]
+	MalformedSessionToken(String),
 	#[error("the id specified for key generation is either invalid or designated for a package")]
 	InvalidKeyGenerationTarget,
-	#[error("invalid secrets; API returned a \"not logged in\" response")]
+	#[error("{0:?} isn't a script id; ids are up to 64 letters, digits, - and _")]
+	InvalidScriptId(String),
+	#[error("the project isn't linked to a script yet")]
+	PlaceholderScriptId,
+	#[error("{} has no project configuration to take the script id from", .0.display())]
+	NoConfiguredScript(PathBuf),
+	#[error("not logged in; secrets are missing or were rejected by fumosclub")]
 	NotLoggedIn,
 	#[error("the user is banned for {:?}", .reason.as_ref().map_or_else(|| "(no reason provided)", String::as_str))]
 	UserIsBanned { reason: Option<String> },
 	#[error("fumosclub api error: {0}")]
 	FumosclubAPI(String),
+	#[error("failed reading global configuration at {0}: {1}")]
+	GlobalConfiguration(PathBuf, String),
+	#[error("{0} has both fumosync.json and fumosync.toml; remove one of them")]
+	ConflictingConfigurationFiles(PathBuf),
+	#[error("invalid project configuration at {0}: {1}")]
+	InvalidProjectConfiguration(PathBuf, String),
+	#[error("{0} isn't a fumosync.json key")]
+	UnknownConfigurationKey(String),
+	#[error("invalid module alias {0}: {1}")]
+	InvalidModuleAlias(String, String),
+	#[error("invalid module at {path}: {reason}")]
+	InvalidModuleName { path: PathBuf, reason: String },
+	#[error("refusing to reach {0} because FUMO_OFFLINE is set")]
+	Offline(String),
+	#[error("failed importing browser cookies: {0}")]
+	BrowserCookies(String),
+	#[error("no browser session belongs to an account named or with the id {0}")]
+	AccountNotFound(String),
+	#[error("{0:?} isn't a profile name; profile names are letters, digits, - and _")]
+	InvalidProfile(String),
+	#[error("multiple browser sessions match {0}: {}", .1.join(", "))]
+	AmbiguousAccount(String, Vec<String>),
+	#[error("multiple browser sessions were found, pass --account to pick one: {}", .0.join(", "))]
+	AccountSelectionRequired(Vec<String>),
+	#[error(
+		"failed finding a chrome/chromium executable ({0}); pass --chrome-path or set FUMO_CHROME"
+	)]
+	ChromeNotFound(String),
+	#[error("failed launching chrome/chromium at {0}: {1}")]
+	ChromeExecutable(PathBuf, String),
+	#[error("chrome/chromium login failed while {0}: {1}")]
+	BrowserLogin(&'static str, String),
+	#[error("can't wait for input while running non-interactively: {0}")]
+	InteractionRequired(String),
+	#[error("fumo was built without the {0} feature")]
+	FeatureDisabled(&'static str),
+	#[cfg(feature = "otel")]
+	#[error("failed setting up OpenTelemetry export: {0}")]
+	Telemetry(String),
+	#[error("prompt failed: {0}")]
+	Prompt(#[from] inquire::InquireError),
+	#[error("self update failed: {0}")]
+	SelfUpdate(String),
+	#[error("release {0} has no {1} asset")]
+	NoReleaseAsset(String, String),
+	#[error("checksum of {0} doesn't match the published checksum")]
+	ChecksumMismatch(String),
+	#[error("{0} {} from fumosclub", if *.0 == 1 { "file differs" } else { "files differ" })]
+	VerificationFailed(usize),
+	#[error("{} is {size} bytes, which is over the {limit} byte limit", .path.display())]
+	FileTooLarge {
+		path: PathBuf,
+		size: u64,
+		limit: u64,
+	},
+	#[error("the project is {size} bytes, which is over the {limit} byte limit")]
+	ProjectTooLarge { size: u64, limit: u64 },
+	#[error("no template is named {0}; available templates: {}", .1.join(", "))]
+	UnknownTemplate(String, Vec<String>),
+	#[error("invalid workspace member {0}: {1}")]
+	InvalidWorkspaceMember(PathBuf, String),
+	#[error("workspace members {} all link to script {0}", .1.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", "))]
+	DuplicateScriptId(String, Vec<PathBuf>),
+	#[error("{0} of {1} workspace members failed")]
+	WorkspaceFailed(usize, usize),
+	#[error("git error: {0}")]
+	Git(String),
+	#[error("{0} has uncommitted changes which pulling would overwrite")]
+	DirtyWorkingTree(PathBuf),
+	#[error("{0} isn't writable by the current user")]
+	UpdateNotPermitted(PathBuf),
+	#[error("{0} is a file, not a directory")]
+	TargetIsFile(PathBuf),
+	#[error("the project directory {0} doesn't exist")]
+	ProjectNotFound(PathBuf),
+	#[error("the parent directory {0} doesn't exist")]
+	MissingParentDirectory(PathBuf),
+	#[error("{0} already exists")]
+	FileAlreadyExists(PathBuf),
+	#[error("the script has no module named {0}; modules: {}", .1.join(", "))]
+	UnknownModule(String, Vec<String>),
+	#[error("{0} is a package, which has no main source")]
+	NoMainSource(String),
+	#[error("{path} last pushed the script {script_id}")]
+	ScriptPushedElsewhere { script_id: String, path: PathBuf },
+	#[error("{0} is a workspace, but --main-stdin and --description-stdin push a single project")]
+	WorkspaceStdin(PathBuf),
+	#[error("the description is {length} characters long, which is over the {limit} character limit")]
+	DescriptionTooLong { length: usize, limit: usize },
+	#[error("editor error: {0}")]
+	Editor(String),
+	#[error("no key is remembered for script {0}")]
+	NoStoredKey(String),
+	#[error(
+		"{0} matches several scripts: {}",
+		.1.iter().map(|script| format!("{} ({})", script.name, script.id)).collect::<Vec<_>>().join(", ")
+	)]
+	AmbiguousScriptReference(String, Vec<Script>),
+	#[error("no script list is cached for the logged in account")]
+	NoCachedScripts,
+	#[error("no account details are cached for the logged in account")]
+	NoCachedDetails,
+	#[error("invalid archive at {0}: {1}")]
+	InvalidArchive(PathBuf, String),
+	#[error("{1} (credentials from {0})")]
+	Credentials(String, Box<Error>),
+	#[error("the project is linked to {1}, not {0}")]
+	ScriptMismatch(String, String),
+	#[error("no snapshot was taken at {0}; snapshots: {}", .1.join(", "))]
+	UnknownSnapshot(String, Vec<String>),
+	#[error("whitelist entries {} aren't user ids", .0.iter().map(|entry| format!("{entry:?}")).collect::<Vec<_>>().join(", "))]
+	InvalidWhitelistEntries(Vec<String>),
+	#[error("whitelist entries {} aren't Roblox users", .0.iter().map(|entry| format!("{entry:?}")).collect::<Vec<_>>().join(", "))]
+	UnresolvedWhitelistEntries(Vec<String>),
+	#[error("refusing to upload the empty {}", .0.join(", "))]
+	EmptyFiles(Vec<String>),
+	#[error("{} contains NUL bytes, which stop it from loading in-game", .0.display())]
+	BinaryContent(PathBuf),
+	#[error("refusing to push with broken requireM calls:\n{}", .0.join("\n"))]
+	BrokenRequires(Vec<String>),
+	#[error("couldn't tell the shell from $SHELL")]
+	ShellNotDetected,
+	#[error("completions can't be installed for {0}")]
+	UnsupportedShell(String),
+	#[error("{} wasn't written by fumo, so it isn't overwritten", .0.display())]
+	ForeignCompletions(PathBuf),
+	#[error("couldn't find the home directory")]
+	HomeDirectoryNotFound,
+	#[error("pkg/ has no modules, but the script has {0}; refusing to prune all of them")]
+	PruneEverything(usize),
+	#[error("a watcher (pid {1}) is running in {}", .0.display())]
+	WatchRunning(PathBuf, u32),
+	#[error("fumosclub accepted the prune, but still has these modules: {}", .0.join(", "))]
+	ModulesNotPruned(Vec<String>),
+}
+
+impl Error {
+	/// Gets the name of the error variant, for machine-readable output.
+	pub fn kind_str(&self) -> &'static str {
+		match self {
+			Self::DirectoryAlreadyExists(..) => "DirectoryAlreadyExists",
+			Self::CreateFile(..) => "CreateFile",
+			Self::CreateDirectory(..) => "CreateDirectory",
+			Self::ReadFile(..) => "ReadFile",
+			Self::ReadDirectory(..) => "ReadDirectory",
+			Self::ProjectDidntInitialize(..) => "ProjectDidntInitialize",
+			Self::Reqwest(..) => "Reqwest",
+			Self::Serde(..) => "Serde",
+			Self::Io(..) => "Io",
+			Self::ResponseStatus { .. } => "ResponseStatus",
+			Self::ServiceUnavailable { .. } => "ServiceUnavailable",
+			Self::SecretsExpired(..) => "SecretsExpired",
+			Self::UnvalidatedSecrets(..) => "UnvalidatedSecrets",
+			Self::SecretsVersionTooNew(..) => "SecretsVersionTooNew",
+			Self::ConfigDirectoryNotFound => "ConfigDirectoryNotFound",
+			Self::ConfigDirectoryUnwritable(..) => "ConfigDirectoryUnwritable",
+			Self::WatcherInit(..) => "WatcherInit",
+			Self::WatchPath(..) => "WatchPath",
+			Self::PathDiffFailed => "PathDiffFailed",
+			Self::ProjectInsideConfigDir(..) => "ProjectInsideConfigDir",
+			Self::ConfigDirInsideProject(..) => "ConfigDirInsideProject",
+			Self::InsufficentAuthorization => "InsufficentAuthorization",
+			Self::InvalidSecrets => "InvalidSecrets",
+			Self::InvalidKeyGenerationTarget => "InvalidKeyGenerationTarget",
+			Self::InvalidScriptId(..) => "InvalidScriptId",
+			Self::PlaceholderScriptId => "PlaceholderScriptId",
+			Self::NoConfiguredScript(..) => "NoConfiguredScript",
+			Self::NotLoggedIn => "NotLoggedIn",
+			Self::UserIsBanned { .. } => "UserIsBanned",
+			Self::FumosclubAPI(..) => "FumosclubAPI",
+			Self::GlobalConfiguration(..) => "GlobalConfiguration",
+			Self::ConflictingConfigurationFiles(..) => "ConflictingConfigurationFiles",
+			Self::InvalidProjectConfiguration(..) => "InvalidProjectConfiguration",
+			Self::UnknownConfigurationKey(..) => "UnknownConfigurationKey",
+			Self::InvalidModuleAlias(..) => "InvalidModuleAlias",
+			Self::InvalidModuleName { .. } => "InvalidModuleName",
+			Self::InvalidWhitelistEntries(..) => "InvalidWhitelistEntries",
+			Self::FileAlreadyExists(..) => "FileAlreadyExists",
+			Self::UnknownModule(..) => "UnknownModule",
+			Self::NoMainSource(..) => "NoMainSource",
+			Self::WorkspaceStdin(..) => "WorkspaceStdin",
+			Self::ScriptPushedElsewhere { .. } => "ScriptPushedElsewhere",
+			Self::DescriptionTooLong { .. } => "DescriptionTooLong",
+			Self::Editor(..) => "Editor",
+			Self::NoStoredKey(..) => "NoStoredKey",
+			Self::AmbiguousScriptReference(..) => "AmbiguousScriptReference",
+			Self::NoCachedScripts => "NoCachedScripts",
+			Self::NoCachedDetails => "NoCachedDetails",
+			Self::InvalidArchive(..) => "InvalidArchive",
+			Self::UnknownSnapshot(..) => "UnknownSnapshot",
+			Self::ScriptMismatch(..) => "ScriptMismatch",
+			Self::Credentials(_, error) => error.kind_str(),
+			Self::UnresolvedWhitelistEntries(..) => "UnresolvedWhitelistEntries",
+			Self::EmptyFiles(..) => "EmptyFiles",
+			Self::BinaryContent(..) => "BinaryContent",
+			Self::BrokenRequires(..) => "BrokenRequires",
+			Self::ShellNotDetected => "ShellNotDetected",
+			Self::UnsupportedShell(..) => "UnsupportedShell",
+			Self::ForeignCompletions(..) => "ForeignCompletions",
+			Self::HomeDirectoryNotFound => "HomeDirectoryNotFound",
+			Self::PruneEverything(..) => "PruneEverything",
+			Self::WatchRunning(..) => "WatchRunning",
+			Self::ModulesNotPruned(..) => "ModulesNotPruned",
+			Self::Offline(..) => "Offline",
+			Self::BrowserCookies(..) => "BrowserCookies",
+			Self::AccountNotFound(..) => "AccountNotFound",
+			Self::BrowserLogin(..) => "BrowserLogin",
+			Self::InvalidProfile(..) => "InvalidProfile",
+			Self::AmbiguousAccount(..) => "AmbiguousAccount",
+			Self::AccountSelectionRequired(..) => "AccountSelectionRequired",
+			Self::ChromeNotFound(..) => "ChromeNotFound",
+			Self::ChromeExecutable(..) => "ChromeExecutable",
+			Self::InteractionRequired(..) => "InteractionRequired",
+			Self::FeatureDisabled(..) => "FeatureDisabled",
+			#[cfg(feature = "otel")]
+			Self::Telemetry(..) => "Telemetry",
+			Self::Prompt(..) => "Prompt",
+			Self::SelfUpdate(..) => "SelfUpdate",
+			Self::NoReleaseAsset(..) => "NoReleaseAsset",
+			Self::ChecksumMismatch(..) => "ChecksumMismatch",
+			Self::UpdateNotPermitted(..) => "UpdateNotPermitted",
+			Self::UnknownTemplate(..) => "UnknownTemplate",
+			Self::InvalidWorkspaceMember(..) => "InvalidWorkspaceMember",
+			Self::DuplicateScriptId(..) => "DuplicateScriptId",
+			Self::WorkspaceFailed(..) => "WorkspaceFailed",
+			Self::Git(..) => "Git",
+			Self::DirtyWorkingTree(..) => "DirtyWorkingTree",
+			Self::TargetIsFile(..) => "TargetIsFile",
+			Self::ProjectNotFound(..) => "ProjectNotFound",
+			Self::MalformedSessionToken(..) => "MalformedSessionToken",
+			Self::MissingParentDirectory(..) => "MissingParentDirectory",
+			Self::VerificationFailed(..) => "VerificationFailed",
+			Self::FileTooLarge { .. } => "FileTooLarge",
+			Self::ProjectTooLarge { .. } => "ProjectTooLarge",
+		}
+	}
+
+	/// Gets the process exit code for the error.
+	///
+	/// - 3: authentication problems; logging in again should fix them
+	/// - 4: network or fumosclub API failures
+	/// - 1: everything else
+	pub fn exit_code(&self) -> i32 {
+		match self {
+			Self::Credentials(_, error) => error.exit_code(),
+			Self::NotLoggedIn
+			| Self::InvalidSecrets
+			| Self::MalformedSessionToken(..)
+			| Self::SecretsExpired(..)
+			| Self::UnvalidatedSecrets(..)
+			| Self::InsufficentAuthorization
+			| Self::UserIsBanned { .. } => 3,
+			Self::Reqwest(..)
+			| Self::ResponseStatus { .. }
+			| Self::ServiceUnavailable { .. }
+			| Self::FumosclubAPI(..)
+			| Self::SelfUpdate(..) => 4,
+			_ => 1,
+		}
+	}
+
+	/// Whether retrying can't help until the user steps in, such as for bad secrets or configuration.
+	pub fn is_fatal(&self) -> bool {
+		self.exit_code() == 3
+			|| matches!(
+				self,
+				Self::PlaceholderScriptId
+					| Self::ConflictingConfigurationFiles(..)
+					| Self::InvalidProjectConfiguration(..)
+					| Self::InvalidModuleAlias(..)
+					| Self::InvalidWhitelistEntries(..)
+					| Self::FeatureDisabled(..)
+					| Self::Offline(..)
+			)
+	}
+
+	/// Gets a line telling the user how to fix the error, if there is an obvious fix.
+	pub fn hint(&self) -> Option<&'static str> {
+		match self {
+			Self::NotLoggedIn | Self::InvalidSecrets => Some("run `fumo login`"),
+			Self::SecretsExpired(..) => Some("run `fumo login` to refresh your secrets"),
+			Self::UnvalidatedSecrets(..) => Some("run `fumo auth status --online` to validate them"),
+			Self::ReadFile(path, _) if path.file_name() == Some(OsStr::new(SYNC_CONFIGURATION_FILE)) => {
+				Some("run `fumo init` or `fumo pull` to create a project")
+			}
+			Self::PlaceholderScriptId => {
+				Some("run `fumo link <script id>` to link the project to a script (see `fumo list`)")
+			}
+			Self::NoConfiguredScript(..) => {
+				Some("pass --id <script id>, or run the command inside of a project")
+			}
+			Self::InvalidKeyGenerationTarget | Self::InvalidScriptId(..) => {
+				Some("script ids are listed by `fumo list`")
+			}
+			Self::NoStoredKey(..) => Some("run `fumo generate` to generate and remember a key"),
+			Self::AmbiguousScriptReference(..) => Some("pass more of the id or name, or the full id"),
+			Self::NoCachedScripts => Some("run `fumo list` while online to cache it"),
+			Self::NoCachedDetails => Some("run `fumo view` while online to cache them"),
+			Self::VerificationFailed(..) => {
+				Some("run `fumo pull` to take the remote changes, or `fumo push` to overwrite them")
+			}
+			Self::FileTooLarge { .. } | Self::ProjectTooLarge { .. } => Some(
+				"move large data out of the project, or raise maxFileSize/maxTotalSize with `fumo config --global set`",
+			),
+			Self::FeatureDisabled("otel") => {
+				Some("rebuild fumo with `--features otel`, or leave out --otel-endpoint")
+			}
+			Self::BrowserLogin(..) => {
+				Some("try again, or log in with `fumo login --token <session>` instead")
+			}
+			Self::FeatureDisabled(..) => {
+				Some("log in with `fumo login --token <session>`, or set FUMO_SESSION")
+			}
+			Self::DirtyWorkingTree(..) => {
+				Some("commit or stash your changes, or pass --allow-dirty to overwrite them")
+			}
+			Self::ProjectDidntInitialize(error)
+				if matches!(**error, Self::DirectoryAlreadyExists(..)) =>
+			{
+				Some("pass --force to pull into an existing project")
+			}
+			Self::ProjectDidntInitialize(error) => error.hint(),
+			Self::ProjectInsideConfigDir(..) | Self::ConfigDirInsideProject(..) => Some(
+				"move the project elsewhere, since secrets.json would be synced; --allow-config-overlap skips this check",
+			),
+			Self::TargetIsFile(..) => Some("pick a path which doesn't exist yet, or move the file away"),
+			Self::MalformedSessionToken(..) => Some(
+				"copy only the value of the session cookie from your browser's devtools (Application > Cookies), without its name, quotes or a trailing ;",
+			),
+			Self::ProjectNotFound(..) => {
+				Some("check the path, or create a project there with `fumo init` or `fumo pull`")
+			}
+			Self::MissingParentDirectory(..) => {
+				Some("create it first, or pass --parents to create it along with the project")
+			}
+			Self::UpdateNotPermitted(..) => {
+				Some("update fumo with the package manager you installed it with instead")
+			}
+			Self::InvalidWhitelistEntries(..) => {
+				Some("run `fumo whitelist resolve` to replace them with user ids")
+			}
+			Self::FileAlreadyExists(..) => Some("pass --force to overwrite it"),
+			Self::NoMainSource(..) => Some("pick one of its modules with --module"),
+			Self::WorkspaceStdin(..) => Some("pass the directory of a member with -p"),
+			Self::ScriptPushedElsewhere { .. } => Some(
+				"link this project to its own script with `fumo link`, or pass --force to push over the other project",
+			),
+			Self::ScriptMismatch(..) => Some("run `fumo link` to link the project to another script"),
+			Self::InvalidProjectConfiguration(..) => {
+				Some("run `fumo schema` to see the fields fumosync.json can have")
+			}
+			Self::Credentials(..) => {
+				Some("check the session passed with --session, --session-file or FUMO_SESSION")
+			}
+			Self::ResponseStatus {
+				request_id: Some(..),
+				..
+			} => Some("include the request id when reporting this to fumosclub"),
+			Self::ServiceUnavailable { .. } => {
+				Some("fumosclub is likely under maintenance; try again later")
+			}
+			Self::PruneEverything(..) => {
+				Some("check that project_directory is the project linked to the script")
+			}
+			Self::WatchRunning(..) => Some("stop the watcher first, then run the command again"),
+			Self::ModulesNotPruned(..) => {
+				Some("delete them on the website; fumosclub may no longer take null sources as removals")
+			}
+			Self::BinaryContent(..) => {
+				Some("encode binary data as text, such as base64 or escape sequences, before uploading it")
+			}
+			Self::ShellNotDetected => Some("pass the shell, such as `fumo completions install zsh`"),
+			Self::UnsupportedShell(..) => {
+				Some("print the completions with `fumo completions <shell>` and load them by hand")
+			}
+			Self::ForeignCompletions(..) => Some(
+				"move the file aside, or print fumo's completions with `fumo completions` and merge them by hand",
+			),
+			Self::BrokenRequires(..) => {
+				Some("require the names modules are uploaded as, or push without --strict to only warn")
+			}
+			Self::EmptyFiles(..) => Some(
+				"pass --allow-empty to upload them anyway, or set \"allowEmptyFiles\": true in fumosync.json",
+			),
+			Self::DescriptionTooLong { .. } => {
+				Some("shorten README.md, or raise maxDescriptionLength with `fumo config --global set`")
+			}
+			Self::ConfigDirectoryUnwritable(_, io_error)
+				if io_error.kind() == io::ErrorKind::StorageFull =>
+			{
+				Some("free up disk space, then run fumo again")
+			}
+			Self::ConfigDirectoryUnwritable(..) => Some(
+				"make the directory writable; commands which only read, such as `fumo view`, still work with FUMO_SESSION set",
+			),
+			Self::CreateFile(_, io_error) if io_error.kind() == io::ErrorKind::StorageFull => {
+				Some("free up disk space; the file was left as it was")
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Words io errors which have an obvious cause plainly, rather than as an os error.
+fn describe_io(io_error: &io::Error) -> String {
+	match io_error.kind() {
+		io::ErrorKind::PermissionDenied => "permission denied".to_owned(),
+		io::ErrorKind::ReadOnlyFilesystem => "the filesystem is read-only".to_owned(),
+		io::ErrorKind::StorageFull => "the disk is full".to_owned(),
+		_ => io_error.to_string(),
+	}
 }
 
 /// Custom context trait to convert a Option to a Result.
@@ -64,3 +523,70 @@ impl<T, E> Context<T, E> for Option<T> {
 		self.map_or_else(|| Err(f()), |value| Ok(value))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn not_found() -> io::Error {
+		io::Error::from(io::ErrorKind::NotFound)
+	}
+
+	#[test]
+	fn hints_at_logging_in() {
+		assert_eq!(Error::NotLoggedIn.hint(), Some("run `fumo login`"));
+		assert_eq!(Error::InvalidSecrets.hint(), Some("run `fumo login`"));
+		assert_eq!(
+			Error::SecretsExpired(Utc::now()).hint(),
+			Some("run `fumo login` to refresh your secrets")
+		);
+	}
+
+	#[test]
+	fn hints_at_creating_a_project() {
+		let missing = Error::ReadFile(
+			PathBuf::from("project").join(SYNC_CONFIGURATION_FILE),
+			not_found(),
+		);
+		assert_eq!(
+			missing.hint(),
+			Some("run `fumo init` or `fumo pull` to create a project")
+		);
+		assert_eq!(
+			Error::ReadFile(PathBuf::from("project/main.luau"), not_found()).hint(),
+			None
+		);
+	}
+
+	#[test]
+	fn hints_at_linking_a_script() {
+		assert!(
+			Error::PlaceholderScriptId
+				.hint()
+				.is_some_and(|hint| hint.contains("fumo link"))
+		);
+		assert_eq!(
+			Error::InvalidKeyGenerationTarget.hint(),
+			Some("script ids are listed by `fumo list`")
+		);
+	}
+
+	#[test]
+	fn hints_through_initialization_failures() {
+		let exists = Error::ProjectDidntInitialize(Box::new(Error::DirectoryAlreadyExists(
+			PathBuf::from("project"),
+		)));
+		assert_eq!(
+			exists.hint(),
+			Some("pass --force to pull into an existing project")
+		);
+		let logged_out = Error::ProjectDidntInitialize(Box::new(Error::NotLoggedIn));
+		assert_eq!(logged_out.hint(), Error::NotLoggedIn.hint());
+	}
+
+	#[test]
+	fn has_no_hint_for_unexplained_errors() {
+		assert_eq!(Error::Io(not_found()).hint(), None);
+		assert_eq!(Error::PathDiffFailed.hint(), None);
+	}
+}
@@ -0,0 +1,112 @@
+use crate::error::Error;
+use std::path::Path;
+
+const INITIAL_COMMIT_MESSAGE: &str = "Initial commit";
+
+/// Creates a repository in the directory and commits everything inside of it.
+#[cfg(feature = "git")]
+pub fn init_repository(directory: &Path) -> Result<(), Error> {
+	let git_error = |error: git2::Error| Error::Git(error.message().to_owned());
+
+	let repository = git2::Repository::init(directory).map_err(git_error)?;
+	let mut index = repository.index().map_err(git_error)?;
+	index
+		.add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+		.map_err(git_error)?;
+	index.write().map_err(git_error)?;
+
+	let tree = repository
+		.find_tree(index.write_tree().map_err(git_error)?)
+		.map_err(git_error)?;
+	let signature = repository.signature().map_err(git_error)?;
+	repository
+		.commit(
+			Some("HEAD"),
+			&signature,
+			&signature,
+			INITIAL_COMMIT_MESSAGE,
+			&tree,
+			&[],
+		)
+		.map_err(git_error)?;
+
+	Ok(())
+}
+
+/// Creates a repository in the directory and commits everything inside of it.
+#[cfg(not(feature = "git"))]
+pub fn init_repository(directory: &Path) -> Result<(), Error> {
+	for args in [
+		&["init", "--quiet"][..],
+		&["add", "--all"],
+		&["commit", "--quiet", "--message", INITIAL_COMMIT_MESSAGE],
+	] {
+		let status = std::process::Command::new("git")
+			.args(args)
+			.current_dir(directory)
+			.status()
+			.map_err(|error| Error::Git(format!("failed running git: {error}")))?;
+
+		if !status.success() {
+			return Err(Error::Git(format!(
+				"`git {}` exited with {status}",
+				args.join(" ")
+			)));
+		}
+	}
+
+	Ok(())
+}
+
+/// Checks whether the directory has uncommitted changes; changes elsewhere in its repository don't
+/// count.
+///
+/// Returns `None` if the directory isn't inside of a repository.
+#[cfg(feature = "git")]
+pub fn is_dirty(directory: &Path) -> Result<Option<bool>, Error> {
+	let Ok(repository) = git2::Repository::discover(directory) else {
+		return Ok(None);
+	};
+
+	let mut options = git2::StatusOptions::new();
+	options.include_untracked(true).include_ignored(false);
+	// pathspecs are relative to the working tree, which is reported canonicalized
+	if let (Some(workdir), Ok(directory)) = (repository.workdir(), directory.canonicalize()) {
+		let workdir = workdir
+			.canonicalize()
+			.unwrap_or_else(|_| workdir.to_path_buf());
+		if let Ok(relative) = directory.strip_prefix(&workdir) {
+			if !relative.as_os_str().is_empty() {
+				options.pathspec(relative);
+			}
+		}
+	}
+
+	let statuses = repository
+		.statuses(Some(&mut options))
+		.map_err(|error| Error::Git(error.message().to_owned()))?;
+
+	Ok(Some(!statuses.is_empty()))
+}
+
+/// Checks whether the directory has uncommitted changes; changes elsewhere in its repository don't
+/// count.
+///
+/// Returns `None` if the directory isn't inside of a repository, or if git isn't installed.
+#[cfg(not(feature = "git"))]
+pub fn is_dirty(directory: &Path) -> Result<Option<bool>, Error> {
+	let output = match std::process::Command::new("git")
+		.args(["status", "--porcelain", "--", "."])
+		.current_dir(directory)
+		.output()
+	{
+		Ok(output) => output,
+		Err(error) => {
+			tracing::warn!("skipping the uncommitted changes check; failed running git: {error}");
+			return Ok(None);
+		}
+	};
+
+	// git exits with an error outside of repositories
+	Ok(output.status.success().then(|| !output.stdout.is_empty()))
+}
@@ -0,0 +1,47 @@
+use crate::{
+	error::Error,
+	login::get_config_directory,
+	project::{read_file, write_private_file},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Keys remembered by `fumo generate`, inside of the config directory
+pub const KEYS_FILE: &str = "keys.json";
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredKey {
+	pub key: String,
+	pub generated_at: DateTime<Utc>,
+}
+
+fn get_keys_path() -> Result<PathBuf, Error> {
+	Ok(get_config_directory()?.join(KEYS_FILE))
+}
+
+/// Reads the remembered keys by script id; empty if none were remembered yet.
+pub async fn read_keys() -> Result<BTreeMap<String, StoredKey>, Error> {
+	let path = get_keys_path()?;
+	if !path.try_exists()? {
+		return Ok(BTreeMap::new());
+	}
+
+	Ok(serde_json::from_str(&read_file(&path).await?)?)
+}
+
+/// Remembers the latest key of a script, returning the key it replaces.
+pub async fn store_key(script_id: &str, key: &str) -> Result<Option<StoredKey>, Error> {
+	let mut keys = read_keys().await?;
+	let previous = keys.insert(
+		script_id.to_owned(),
+		StoredKey {
+			key: key.to_owned(),
+			generated_at: Utc::now(),
+		},
+	);
+
+	write_private_file(get_keys_path()?, &serde_json::to_string_pretty(&keys)?).await?;
+	Ok(previous)
+}
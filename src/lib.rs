@@ -0,0 +1,38 @@
+//! The fumosclub cli; the `fumo` binary is a thin command line layer over these modules.
+
+#![forbid(unsafe_code)]
+
+pub mod archive;
+pub mod arguments;
+pub mod cache;
+pub mod client;
+pub mod completions;
+pub mod description;
+pub mod error;
+pub mod git;
+pub mod keys;
+pub mod limits;
+pub mod login;
+pub mod output;
+pub mod porcelain;
+pub mod progress;
+pub mod project;
+pub mod registry;
+pub mod report;
+pub mod requires;
+pub mod roblox;
+pub mod schema;
+pub mod settings;
+pub mod snapshot;
+pub mod state;
+pub mod telemetry;
+pub mod template;
+pub mod timing;
+pub mod types;
+#[cfg(feature = "self-update")]
+pub mod update;
+pub mod verification;
+pub mod version;
+pub mod vscode;
+pub mod whitelist;
+pub mod workspace;
@@ -0,0 +1,99 @@
+use crate::{error::Error, settings};
+use std::path::PathBuf;
+
+/// Largest file, in bytes, which is read for uploading
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+/// Largest combined size, in bytes, of the files uploaded by a push
+pub const DEFAULT_MAX_TOTAL_SIZE: u64 = 20 * 1024 * 1024;
+pub const DEFAULT_DESCRIPTION_WARN_LENGTH: usize = 2000;
+pub const DEFAULT_MAX_DESCRIPTION_LENGTH: usize = 5000;
+/// Lines longer than this many characters are likely minified blobs
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 20_000;
+/// Main scripts over this many bytes or lines are known to load slowly or not at all in-game
+pub const DEFAULT_MAIN_SCRIPT_WARN_BYTES: usize = 1024 * 1024;
+pub const DEFAULT_MAIN_SCRIPT_WARN_LINES: usize = 50_000;
+
+/// Checks a source for content which uploads fine but breaks in-game, returning warnings about it.
+///
+/// `path` is relative to the project directory, and `main` is set for the main script.
+///
+/// # Errors
+/// - [`Error::BinaryContent`] if the source contains NUL bytes
+pub fn check_content(path: &str, contents: &str, main: bool) -> Result<Vec<String>, Error> {
+	if contents.contains('\0') {
+		return Err(Error::BinaryContent(PathBuf::from(path)));
+	}
+
+	let settings = settings::get();
+	let mut warnings = Vec::new();
+	if let Some((number, line)) = contents
+		.lines()
+		.enumerate()
+		.find(|(_, line)| line.chars().count() > settings.max_line_length)
+	{
+		warnings.push(format!(
+			"line {} of {path} is {} characters long; minified code may fail to load in-game",
+			number + 1,
+			line.chars().count()
+		));
+	}
+
+	if main {
+		let lines = contents.lines().count();
+		if contents.len() > settings.main_script_warn_bytes || lines > settings.main_script_warn_lines {
+			warnings.push(format!(
+				"{path} is {} bytes over {lines} lines; scripts this large may fail to load in-game",
+				contents.len()
+			));
+		}
+	}
+
+	Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rejects_nul_bytes() {
+		let error = check_content("pkg/blob.luau", "local s = [[\0]]", false).unwrap_err();
+		assert!(matches!(error, Error::BinaryContent(path) if path == PathBuf::from("pkg/blob.luau")));
+	}
+
+	#[test]
+	fn warns_about_long_lines() {
+		let source = format!("print(1)\n{}\n", "x".repeat(DEFAULT_MAX_LINE_LENGTH + 1));
+		let warnings = check_content("pkg/minified.luau", &source, false).unwrap();
+		assert_eq!(warnings.len(), 1);
+		assert!(warnings[0].starts_with("line 2 of pkg/minified.luau"));
+
+		let source = "x".repeat(DEFAULT_MAX_LINE_LENGTH);
+		assert!(
+			check_content("pkg/util.luau", &source, false)
+				.unwrap()
+				.is_empty()
+		);
+	}
+
+	#[test]
+	fn only_warns_about_large_main_scripts() {
+		let source = "print(1)\n".repeat(DEFAULT_MAIN_SCRIPT_WARN_LINES + 1);
+		assert_eq!(
+			check_content("init.server.luau", &source, true)
+				.unwrap()
+				.len(),
+			1
+		);
+		assert!(
+			check_content("pkg/large.luau", &source, false)
+				.unwrap()
+				.is_empty()
+		);
+		assert!(
+			check_content("init.server.luau", "print(1)\n", true)
+				.unwrap()
+				.is_empty()
+		);
+	}
+}
@@ -1,16 +1,98 @@
+#[cfg(feature = "browser-login")]
+mod chrome;
+#[cfg(feature = "cookie-import")]
+mod cookies;
+
 use crate::{
-	client::{BASE_URL, Client, DOMAIN},
+	cache,
+	client::{AccountDetails, Client},
 	error::{Context, Error},
-	project::{read_file, write_file},
+	project::{read_file, write_private_file},
+	settings,
 };
-use chrono::{DateTime, Utc};
-use chrono::{Months, serde::ts_seconds};
-use directories::ProjectDirs;
-use headless_chrome::{
-	Browser, LaunchOptionsBuilder, browser::default_executable, protocol::cdp::Target::CreateTarget,
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono::{
+	Months,
+	serde::{ts_seconds, ts_seconds_option},
 };
+use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+	io::ErrorKind,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+#[cfg(feature = "browser-login")]
+pub use chrome::use_headful_chrome;
+#[cfg(feature = "cookie-import")]
+use cookies::refresh_session_secrets;
+#[cfg(feature = "cookie-import")]
+pub use cookies::use_browser_token;
+
+/// Environment variable holding a session to use instead of secrets.json, for CI
+pub const SESSION_VARIABLE: &str = "FUMO_SESSION";
+
+/// Where the credentials of an invocation come from, in order of precedence.
+#[derive(Clone)]
+pub enum SessionSource {
+	/// `--session`
+	Argument(String),
+	/// `--session-file`
+	File(PathBuf),
+	/// `FUMO_SESSION`
+	Variable(String),
+	/// The saved login in secrets.json
+	SecretsFile,
+}
+
+impl std::fmt::Display for SessionSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Argument(..) => write!(f, "--session"),
+			Self::File(path) => write!(f, "--session-file {}", path.display()),
+			Self::Variable(..) => write!(f, "{SESSION_VARIABLE}"),
+			Self::SecretsFile => write!(f, "secrets.json"),
+		}
+	}
+}
+
+impl SessionSource {
+	pub fn get() -> Self {
+		let settings = settings::get();
+		if let Some(session) = &settings.session {
+			Self::Argument(session.clone())
+		} else if let Some(path) = &settings.session_file {
+			Self::File(path.clone())
+		} else if let Some(session) = std::env::var(SESSION_VARIABLE)
+			.ok()
+			.filter(|session| !session.is_empty())
+		{
+			Self::Variable(session)
+		} else {
+			Self::SecretsFile
+		}
+	}
+
+	/// Names the kind of source for machine readable output.
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Argument(..) => "argument",
+			Self::File(..) | Self::SecretsFile => "file",
+			Self::Variable(..) => "env",
+		}
+	}
+
+	/// Names the source in errors caused by its credentials; the saved login goes unnamed.
+	fn attribute(&self, error: Error) -> Error {
+		match self {
+			Self::SecretsFile => error,
+			source => Error::Credentials(source.to_string(), Box::new(error)),
+		}
+	}
+}
 
 pub fn get_config_directory() -> Result<PathBuf, Error> {
 	Ok(
@@ -21,145 +103,760 @@ pub fn get_config_directory() -> Result<PathBuf, Error> {
 	)
 }
 
+/// Creates, writes and removes a probe file, syncing it so a full disk shows up.
+async fn write_probe(directory: &Path, probe: &Path) -> std::io::Result<()> {
+	use tokio::io::AsyncWriteExt;
+
+	tokio::fs::create_dir_all(directory).await?;
+	let mut file = tokio::fs::OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(probe)
+		.await?;
+	file.write_all(b"fumo").await?;
+	file.sync_all().await?;
+	drop(file);
+	tokio::fs::remove_file(probe).await
+}
+
+/// Checks that files can be written to the config directory, with a uniquely named probe file
+/// which is removed again, for `fumo doctor`.
+///
+/// # Errors
+/// - [`Error::ConfigDirectoryUnwritable`]
+pub async fn probe_config_directory() -> Result<PathBuf, Error> {
+	let directory = get_config_directory()?;
+	let probe = directory.join(format!(
+		".fumo-probe.{}.{}",
+		std::process::id(),
+		Utc::now().timestamp_nanos_opt().unwrap_or_default()
+	));
+
+	match write_probe(&directory, &probe).await {
+		Ok(()) => Ok(directory),
+		Err(io_error) => {
+			// the name is unique, so this never removes somebody else's file
+			let _ = tokio::fs::remove_file(&probe).await;
+			Err(Error::ConfigDirectoryUnwritable(directory, io_error))
+		}
+	}
+}
+
+/// Gets the path of the saved login; profiles other than the default one are saved in
+/// `secrets.<profile>.json`.
+///
+/// # Errors
+/// - [`Error::InvalidProfile`]
+pub fn get_secrets_path() -> Result<PathBuf, Error> {
+	let directory = get_config_directory()?;
+	match &settings::get().profile {
+		None => Ok(directory.join("secrets.json")),
+		Some(profile)
+			if !profile.is_empty()
+				&& profile
+					.chars()
+					.all(|character| character.is_ascii_alphanumeric() || matches!(character, '-' | '_')) =>
+		{
+			Ok(directory.join(format!("secrets.{profile}.json")))
+		}
+		Some(profile) => Err(Error::InvalidProfile(profile.clone())),
+	}
+}
+
+/// Current format version of secrets.json
+pub const SECRETS_VERSION: u32 = 2;
+
+/// Where the session token is stored.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsStorage {
+	#[default]
+	File,
+}
+
 /// secrets.json
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Secrets {
+	pub version: u32,
 	pub session: String,
 	#[serde(with = "ts_seconds")]
 	pub expires: DateTime<Utc>,
+	/// Id of the account the session belongs to; used to match refreshed sessions
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub account_id: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub profile: Option<String>,
+	#[serde(default)]
+	pub storage: SecretsStorage,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub base_url: Option<String>,
+	#[serde(
+		default,
+		with = "ts_seconds_option",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub last_validated: Option<DateTime<Utc>>,
+}
+
+/// Name of the fumosclub session cookie
+pub const SESSION_COOKIE: &str = "session";
+
+/// Checks that a token can be sent as the session cookie.
+fn check_session_token(token: &str) -> Result<(), String> {
+	if token.is_empty() {
+		Err("the token is empty".to_owned())
+	} else if token.contains(';') {
+		Err("the token contains ;, so more than the cookie value was copied".to_owned())
+	} else if token
+		.chars()
+		.any(|character| character.is_whitespace() || character.is_control())
+	{
+		Err("the token contains whitespace or line breaks".to_owned())
+	} else if !token.is_ascii() {
+		Err("the token contains characters which aren't ASCII".to_owned())
+	} else {
+		Ok(())
+	}
+}
+
+/// Decodes `%XX` escapes; `None` if the value has none, or an escape is invalid.
+fn percent_decode(value: &str) -> Option<String> {
+	if !value.contains('%') {
+		return None;
+	}
+
+	let bytes = value.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut index = 0;
+	while index < bytes.len() {
+		if bytes[index] == b'%' {
+			let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+			decoded.push(u8::from_str_radix(hex, 16).ok()?);
+			index += 3;
+		} else {
+			decoded.push(bytes[index]);
+			index += 1;
+		}
+	}
+
+	String::from_utf8(decoded).ok()
+}
+
+/// Removes surrounding whitespace, quotes and trailing `;`.
+fn unquote(token: &str) -> &str {
+	let mut token = token.trim().trim_end_matches(';').trim();
+	for quote in ['"', '\''] {
+		if let Some(unquoted) = token
+			.strip_prefix(quote)
+			.and_then(|token| token.strip_suffix(quote))
+		{
+			token = unquoted.trim();
+		}
+	}
+
+	token
+}
+
+/// Normalizes a session token however it was copied: surrounding whitespace, quotes and trailing
+/// `;` are removed, as is a `session=` prefix, and percent-encoded tokens are decoded.
+///
+/// Every way of logging in goes through this, so malformed tokens fail here instead of with 401s.
+///
+/// # Errors
+/// - [`Error::MalformedSessionToken`]
+pub fn normalize_session_token(token: &str) -> Result<String, Error> {
+	let mut token = unquote(token);
+	// the value of a pasted `session="..."` pair may be quoted too
+	if let Some(value) = token
+		.strip_prefix(SESSION_COOKIE)
+		.and_then(|token| token.trim_start().strip_prefix('='))
+	{
+		token = unquote(value);
+	}
+
+	// only decoded if that gives a usable token, since tokens may contain % themselves
+	if let Some(decoded) =
+		percent_decode(token).filter(|decoded| check_session_token(decoded).is_ok())
+	{
+		return Ok(decoded);
+	}
+
+	check_session_token(token).map_err(Error::MalformedSessionToken)?;
+	Ok(token.to_owned())
+}
+
+impl Secrets {
+	/// Creates secrets from a bare session token, normalizing it; the expiry isn't known, so the same
+	/// default as session cookies is used.
+	///
+	/// # Errors
+	/// - [`Error::MalformedSessionToken`]
+	pub fn from_token(session: &str) -> Result<Self, Error> {
+		let expires = Utc::now().checked_add_months(Months::new(3)).unwrap();
+		Ok(Self::new(normalize_session_token(session)?, expires))
+	}
+
+	pub fn new(session: String, expires: DateTime<Utc>) -> Self {
+		Self {
+			version: SECRETS_VERSION,
+			session,
+			expires,
+			account_id: None,
+			profile: None,
+			storage: SecretsStorage::default(),
+			base_url: None,
+			last_validated: None,
+		}
+	}
+}
+
+/// Parses secrets.json, upgrading older formats in memory.
+///
+/// # Errors
+/// - [`Error::SecretsVersionTooNew`]
+/// - [`Error::Serde`]
+pub fn parse_session_secrets(contents: &str) -> Result<Secrets, Error> {
+	/// secrets.json before versioning was introduced
+	#[derive(Deserialize)]
+	struct SecretsV1 {
+		session: String,
+		#[serde(with = "ts_seconds")]
+		expires: DateTime<Utc>,
+		#[serde(default)]
+		account_id: Option<String>,
+	}
+
+	#[derive(Deserialize)]
+	struct Versioned {
+		version: Option<u32>,
+	}
+
+	match serde_json::from_str::<Versioned>(contents)?.version {
+		None | Some(1) => {
+			let v1: SecretsV1 = serde_json::from_str(contents)?;
+			Ok(Secrets {
+				account_id: v1.account_id,
+				..Secrets::new(v1.session, v1.expires)
+			})
+		}
+		Some(SECRETS_VERSION) => Ok(serde_json::from_str(contents)?),
+		Some(version) if version > SECRETS_VERSION => Err(Error::SecretsVersionTooNew(version)),
+		Some(version) => Err(Error::Serde(serde::de::Error::custom(format!(
+			"invalid secrets version {version}"
+		)))),
+	}
 }
 
 /// Forcefully saves session secrets.
 pub async fn save_session_secrets(secrets: Secrets) -> Result<(), Error> {
-	write_file(
-		get_config_directory()?.join("secrets.json"),
+	// cached account details belong to the previous login, unless it is the same account
+	if let Ok(Secrets {
+		account_id: Some(previous_id),
+		..
+	}) = read_secrets_from(&SessionSource::SecretsFile).await
+	{
+		if secrets.account_id.as_ref() != Some(&previous_id) {
+			cache::invalidate_details(&previous_id).await;
+		}
+	}
+
+	let secrets = Secrets {
+		profile: settings::get().profile.clone(),
+		..secrets
+	};
+	write_private_file(
+		get_secrets_path()?,
 		&serde_json::to_string_pretty(&secrets)?,
 	)
 	.await
 }
 
-/// Gets session secrets, errors if secrets are expired.
+/// Saves a session fumosclub rotated to, if the session it replaces is the saved login; sessions
+/// given for a single invocation, or for other accounts, are left alone.
+pub async fn save_rotated_session(previous: &str, secrets: Secrets) {
+	if !matches!(SessionSource::get(), SessionSource::SecretsFile) {
+		return;
+	}
+
+	match read_secrets_from(&SessionSource::SecretsFile).await {
+		Ok(saved) if saved.session == previous => {
+			if let Err(error) = save_session_secrets(secrets).await {
+				warn!("failed saving the refreshed session: {error}");
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Gets session secrets validated with fumosclub, errors if secrets are expired.
+///
+/// If auto refresh is enabled, expired or logged out secrets are replaced with a browser cookie
+/// belonging to the same account when one can be found. Only the saved login is refreshed, since
+/// refreshed secrets are saved.
 pub async fn get_session_secrets() -> Result<Secrets, Error> {
-	let secrets_string = read_file(get_config_directory()?.join("secrets.json")).await?;
-	let secrets: Secrets = serde_json::from_str(&secrets_string)?;
-	if secrets.expires <= Utc::now() {
+	let secrets = fetch_session_secrets().await?;
+	warn_if_expiring(&secrets);
+	Ok(secrets)
+}
+
+/// Does what [`get_session_secrets`] does, without warning about expiring secrets.
+async fn fetch_session_secrets() -> Result<Secrets, Error> {
+	let source = SessionSource::get();
+	let secrets = read_secrets_from(&source)
+		.await
+		.map_err(|error| source.attribute(error))?;
+	check_session_secrets(&source, secrets, SessionCheck::Refresh).await
+}
+
+/// How long a validation with fumosclub vouches for unexpired secrets in offline checks
+const VALIDATION_FRESHNESS: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How [`check_session_secrets`] makes sure secrets are usable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SessionCheck {
+	/// Trust unexpired secrets validated within [`VALIDATION_FRESHNESS`], without asking fumosclub
+	Offline,
+	/// Ask fumosclub
+	Online,
+	/// Ask fumosclub, replacing expired or logged out secrets if auto refresh is enabled
+	Refresh,
+}
+
+/// Checks secrets read from a source; the one check behind [`get_session_secrets`] and
+/// [`auth_status`]. Validating with fumosclub saves the validation time for later offline checks.
+///
+/// # Errors
+/// - [`Error::SecretsExpired`]
+/// - [`Error::UnvalidatedSecrets`] offline, if the secrets weren't validated recently enough
+/// - anything [`validate_session_secrets`] errors with
+async fn check_session_secrets(
+	source: &SessionSource,
+	secrets: Secrets,
+	check: SessionCheck,
+) -> Result<Secrets, Error> {
+	let result = match check {
+		SessionCheck::Offline => check_validation_freshness(secrets),
+		SessionCheck::Online | SessionCheck::Refresh => {
+			match validate_session_secrets(secrets.clone()).await {
+				Ok(validated) => {
+					save_rotated_session(&secrets.session, validated.clone()).await;
+					Ok(validated)
+				}
+				Err(error @ (Error::SecretsExpired(..) | Error::NotLoggedIn))
+					if check == SessionCheck::Refresh
+						&& settings::get().auto_refresh
+						&& matches!(source, SessionSource::SecretsFile) =>
+				{
+					refresh_session_secrets(&secrets).await.ok_or(error)
+				}
+				result => result,
+			}
+		}
+	};
+
+	result.map_err(|error| source.attribute(error))
+}
+
+/// Errors unless the secrets are unexpired and were validated within [`VALIDATION_FRESHNESS`].
+fn check_validation_freshness(secrets: Secrets) -> Result<Secrets, Error> {
+	let now = Utc::now();
+	if secrets.expires <= now {
 		return Err(Error::SecretsExpired(secrets.expires));
 	}
 
-	let client = crate::client::Client::new(secrets);
-	client.ensure_user_authenticated().await?;
-
-	Ok(client.secrets)
-}
-
-pub async fn use_browser_token() -> Secrets {
-	let secrets = rookie::load(Some(vec![DOMAIN.to_string()]))
-		.unwrap()
-		.into_iter()
-		.filter(|cookie| cookie.name == "session")
-		.map(|cookie| Secrets {
-			session: cookie.value,
-			expires: DateTime::from_timestamp(
-				cookie.expires.map_or_else(
-					|| {
-						Utc::now()
-							.checked_add_months(Months::new(3))
-							.unwrap()
-							.timestamp()
-					},
-					|expiry| expiry as i64,
-				),
-				0,
-			)
-			.unwrap(),
-		})
-		.collect::<Vec<Secrets>>();
-
-	if secrets.is_empty() {
-		panic!("no session cookies were found in any browser supported by rookie");
+	let freshness = TimeDelta::from_std(VALIDATION_FRESHNESS).unwrap_or(TimeDelta::MAX);
+	match secrets.last_validated {
+		Some(validated) if now - validated <= freshness => Ok(secrets),
+		last_validated => Err(Error::UnvalidatedSecrets(last_validated)),
 	}
+}
 
-	let option_to_session = futures::future::join_all(secrets.into_iter().map(|secret| async move {
-		let client = Client::new(secret.clone());
-		(secret, client.get_details().await)
-	}))
-	.await
-	.into_iter()
-	.filter_map(|(secret, result)| result.map(|details| (secret, details)).ok())
-	.map(|(secret, details)| {
-		(
-			format!(
-				"{} ({}, roblox user {})",
-				details.name, details.id, details.roblox_user
-			),
-			secret,
-		)
-	})
-	.collect::<HashMap<String, Secrets>>();
-
-	let select = inquire::Select::new("Pick a session to use.", option_to_session.keys().collect());
-	let selected_option = select.prompt().unwrap();
-	option_to_session[selected_option].to_owned()
-}
-
-pub fn use_headful_chrome() -> Secrets {
-	let browser = Browser::new(
-		LaunchOptionsBuilder::default()
-			.headless(false)
-			.path(Some(
-				default_executable().expect("failed finding chrome/chromium executable"),
-			))
-			.build()
-			.expect("failed building launch options"),
-	)
-	.expect("failed creating browser");
-
-	let tab = browser
-		.new_tab_with_options(CreateTarget {
-			url: BASE_URL.to_string(),
-			width: None,
-			height: None,
-			browser_context_id: None,
-			enable_begin_frame_control: None,
-			new_window: None,
-			background: None,
-			for_tab: None,
-		})
-		.expect("failed creating new tab");
-
-	tab
-		.wait_until_navigated()
-		.expect("failed waiting for new tab to navigate");
-
-	// cleans up tabs which Magically existed
-	let id = tab.get_target_id();
-	browser
-		.get_tabs()
-		.lock()
-		.expect("to lock browser")
-		.iter()
-		.for_each(|tab| {
-			if tab.get_target_id() != id {
-				tab.close(false).ok();
+/// How the last keepalive of [`keep_session_alive`] went.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeepaliveState {
+	Alive,
+	/// The session expires before the next keepalive
+	Expiring,
+	/// Kind of the error the keepalive failed with
+	Failed(&'static str),
+}
+
+/// Validates the session every interval, so fumosclub doesn't expire it for inactivity while
+/// watching. Validating saves the secrets, keeping their validation time current.
+///
+/// Only changes between a healthy session, an expiring one and failures are logged, and delays
+/// asked for by fumosclub are waited out before the next keepalive.
+pub async fn keep_session_alive(interval: Duration) {
+	let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+	ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+	let interval_delta = TimeDelta::from_std(interval).unwrap_or(TimeDelta::MAX);
+	let mut last_state = KeepaliveState::Alive;
+
+	loop {
+		ticker.tick().await;
+		let (state, retry_after) = match fetch_session_secrets().await {
+			Ok(secrets) => {
+				let expires = secrets.expires;
+				if expires - Utc::now() <= interval_delta {
+					if last_state != KeepaliveState::Expiring {
+						warn!(
+							"the session expires at {expires}, before the next keepalive; run `fumo login` to keep syncing"
+						);
+					}
+					(KeepaliveState::Expiring, None)
+				} else {
+					if last_state != KeepaliveState::Alive {
+						info!("session keepalive succeeded; the session is valid again");
+					}
+					(KeepaliveState::Alive, None)
+				}
 			}
-		});
-
-	let session = loop {
-		if let Some(session) = tab
-			.get_cookies()
-			.expect("failed getting cookies")
-			.into_iter()
-			.find(|cookie| cookie.name == "session")
-		{
-			break session;
+			Err(error) => {
+				let state = KeepaliveState::Failed(error.kind_str());
+				if last_state == state {
+					debug!("session keepalive failed again: {error}");
+				} else {
+					warn!("session keepalive failed: {error}; later saves may not sync");
+				}
+				let retry_after = match error {
+					Error::ServiceUnavailable { retry_after, .. } => retry_after,
+					_ => None,
+				};
+				(state, retry_after)
+			}
+		};
+
+		last_state = state;
+		if let Some(delay) = retry_after {
+			tokio::time::sleep(delay).await;
+			ticker.reset();
 		}
+	}
+}
+
+/// Reads session secrets without validating them, from the [source](SessionSource) with the
+/// highest precedence.
+///
+/// # Errors
+/// - [`Error::NotLoggedIn`] if secrets.json doesn't exist
+pub async fn read_session_secrets() -> Result<Secrets, Error> {
+	let source = SessionSource::get();
+	read_secrets_from(&source)
+		.await
+		.map_err(|error| source.attribute(error))
+}
 
-		std::thread::yield_now();
+async fn read_secrets_from(source: &SessionSource) -> Result<Secrets, Error> {
+	let path = match source {
+		SessionSource::Argument(session) | SessionSource::Variable(session) => {
+			return Secrets::from_token(session);
+		}
+		SessionSource::File(path) => return parse_session_secrets(&read_file(path).await?),
+		SessionSource::SecretsFile => get_secrets_path()?,
+	};
+
+	match read_file(path).await {
+		Ok(contents) => parse_session_secrets(&contents),
+		Err(Error::ReadFile(_, io_error)) if io_error.kind() == ErrorKind::NotFound => {
+			Err(Error::NotLoggedIn)
+		}
+		Err(error) => Err(error),
+	}
+}
+
+/// Whether there are usable secrets, and for whom; see [`auth_status`].
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthStatus {
+	pub authenticated: bool,
+	/// The account details, from the cache unless fumosclub was asked
+	pub account: Option<AccountDetails>,
+	pub expires: Option<DateTime<Utc>>,
+	/// `file`, `env` or `argument`; see [`SessionSource::kind`]
+	pub source: &'static str,
+	pub last_validated: Option<DateTime<Utc>>,
+	/// Why the secrets aren't usable
+	#[serde(skip)]
+	pub failure: Option<Error>,
+}
+
+/// Checks whether there are usable secrets, and for whom, without refreshing them.
+///
+/// Without `online`, nothing is asked of fumosclub: unexpired secrets are trusted if they were
+/// validated within the last day, and the account comes from the cache, so secrets logged out on
+/// the website since are only noticed with `online`.
+///
+/// # Errors
+/// Only for failures other than missing, expired and invalid secrets, such as network errors.
+pub async fn auth_status(online: bool) -> Result<AuthStatus, Error> {
+	let source = SessionSource::get();
+	let mut status = AuthStatus {
+		authenticated: false,
+		account: None,
+		expires: None,
+		source: source.kind(),
+		last_validated: None,
+		failure: None,
+	};
+
+	let secrets = match read_secrets_from(&source).await {
+		Ok(secrets) => secrets,
+		Err(error) if error.exit_code() == 3 => {
+			status.failure = Some(source.attribute(error));
+			return Ok(status);
+		}
+		Err(error) => return Err(source.attribute(error)),
 	};
+	status.expires = Some(secrets.expires);
+	status.last_validated = secrets.last_validated;
+	if let (false, Some(account_id)) = (online, &secrets.account_id) {
+		status.account = cache::read_details(account_id)
+			.await
+			.map(|cached| cached.details);
+	}
+
+	let check = if online {
+		SessionCheck::Online
+	} else {
+		SessionCheck::Offline
+	};
+	match check_session_secrets(&source, secrets, check).await {
+		Ok(secrets) => {
+			status.last_validated = secrets.last_validated;
+			if online {
+				status.account = Some(Client::new(secrets)?.get_details().await?);
+			}
+			status.authenticated = true;
+		}
+		Err(error) if error.exit_code() == 3 => status.failure = Some(error),
+		Err(error) => return Err(error),
+	}
+
+	Ok(status)
+}
+
+/// Warns if the secrets expire within the configured warning window.
+pub fn warn_if_expiring(secrets: &Secrets) {
+	let remaining = secrets.expires - Utc::now();
+	if remaining > TimeDelta::zero() && remaining <= settings::get().expiry_warning {
+		warn!(
+			"secrets expire in {} day(s) at {}; run `fumo login` to renew them",
+			remaining.num_days(),
+			secrets.expires
+		);
+	}
+}
 
-	Secrets {
-		session: session.value,
-		expires: DateTime::from_timestamp(session.expires as i64, 0u32)
-			.expect("failed creating DateTime<Utc> for session expiry"),
+/// Errors if the secrets are expired, or if fumosclub doesn't consider them authenticated.
+///
+/// The local clock may be ahead, so secrets which expired within the clock skew window are still
+/// checked with fumosclub, and only refused if fumosclub refuses them too.
+pub async fn validate_session_secrets(secrets: Secrets) -> Result<Secrets, Error> {
+	let expires = secrets.expires;
+	let expired_for = Utc::now() - expires;
+	if expired_for > settings::get().clock_skew {
+		return Err(Error::SecretsExpired(expires));
+	}
+
+	let client = Client::new(secrets)?;
+	match client.ensure_user_authenticated().await {
+		Err(Error::NotLoggedIn) if expired_for >= TimeDelta::zero() => {
+			return Err(Error::SecretsExpired(expires));
+		}
+		result => result?,
+	}
+	if expired_for >= TimeDelta::zero() {
+		warn!(
+			"secrets expired at {expires} by this machine's clock, but fumosclub still accepts them; check that the system clock is right"
+		);
+	}
+
+	Ok(Secrets {
+		last_validated: Some(Utc::now()),
+		..client.current_secrets()
+	})
+}
+
+/// Stands in for cookie importing when fumo is built without the cookie-import feature.
+#[cfg(not(feature = "cookie-import"))]
+pub async fn use_browser_token(
+	_account: Option<&str>,
+	_choose: Option<impl Fn(&[String]) -> Result<usize, Error>>,
+) -> Result<Secrets, Error> {
+	Err(Error::FeatureDisabled("cookie-import"))
+}
+
+/// Auto refresh relies on cookie importing, so without it stale secrets are never replaced.
+#[cfg(not(feature = "cookie-import"))]
+async fn refresh_session_secrets(_stale: &Secrets) -> Option<Secrets> {
+	warn!("auto refresh is unavailable; fumo was built without the cookie-import feature");
+	None
+}
+
+/// Stands in for Chrome logins when fumo is built without the browser-login feature.
+#[cfg(not(feature = "browser-login"))]
+pub fn use_headful_chrome(
+	_executable: Option<PathBuf>,
+	_profile: Option<PathBuf>,
+) -> Result<Secrets, Error> {
+	Err(Error::FeatureDisabled("browser-login"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_unversioned_secrets_as_v1() {
+		let secrets =
+			parse_session_secrets(r#"{"session":"abc","expires":1700000000,"account_id":"42"}"#).unwrap();
+		assert_eq!(secrets.version, SECRETS_VERSION);
+		assert_eq!(secrets.session, "abc");
+		assert_eq!(secrets.expires.timestamp(), 1_700_000_000);
+		assert_eq!(secrets.account_id.as_deref(), Some("42"));
+		assert_eq!(secrets.storage, SecretsStorage::File);
+		assert!(secrets.last_validated.is_none());
+	}
+
+	#[test]
+	fn parses_v1_secrets() {
+		let secrets =
+			parse_session_secrets(r#"{"version":1,"session":"abc","expires":1700000000}"#).unwrap();
+		assert_eq!(secrets.version, SECRETS_VERSION);
+		assert!(secrets.account_id.is_none());
+	}
+
+	#[test]
+	fn parses_v2_secrets() {
+		let secrets = parse_session_secrets(
+			r#"{"version":2,"session":"abc","expires":1700000000,"accountId":"42","profile":"alt","storage":"file","baseUrl":"http://localhost:8080","lastValidated":1690000000}"#,
+		)
+		.unwrap();
+		assert_eq!(secrets.account_id.as_deref(), Some("42"));
+		assert_eq!(secrets.profile.as_deref(), Some("alt"));
+		assert_eq!(secrets.base_url.as_deref(), Some("http://localhost:8080"));
+		assert_eq!(
+			secrets.last_validated.map(|time| time.timestamp()),
+			Some(1_690_000_000)
+		);
+	}
+
+	#[test]
+	fn v1_secrets_are_saved_as_v2() {
+		let secrets = parse_session_secrets(r#"{"session":"abc","expires":1700000000}"#).unwrap();
+		let saved = serde_json::to_string(&secrets).unwrap();
+		assert!(saved.contains(r#""version":2"#));
+		assert_eq!(parse_session_secrets(&saved).unwrap().session, "abc");
+	}
+
+	#[test]
+	fn rejects_corrupted_secrets() {
+		assert!(matches!(
+			parse_session_secrets(r#"{"session":"abc","#),
+			Err(Error::Serde(..))
+		));
+		assert!(matches!(
+			parse_session_secrets(r#"{"version":2,"session":"abc"}"#),
+			Err(Error::Serde(..))
+		));
+		assert!(matches!(
+			parse_session_secrets(r#"{"version":0,"session":"abc","expires":1700000000}"#),
+			Err(Error::Serde(..))
+		));
+	}
+
+	#[test]
+	fn rejects_future_secrets() {
+		assert!(matches!(
+			parse_session_secrets(
+				r#"{"version":3,"session":"abc","expires":1700000000,"newField":true}"#
+			),
+			Err(Error::SecretsVersionTooNew(3))
+		));
+	}
+
+	fn secrets(expires: TimeDelta, last_validated: Option<TimeDelta>) -> Secrets {
+		let now = Utc::now();
+		let mut secrets = Secrets::new("abc".to_owned(), now + expires);
+		secrets.last_validated = last_validated.map(|ago| now - ago);
+		secrets
+	}
+
+	#[test]
+	fn trusts_recently_validated_secrets_offline() {
+		let fresh = secrets(TimeDelta::days(30), Some(TimeDelta::hours(1)));
+		assert!(check_validation_freshness(fresh).is_ok());
+	}
+
+	#[test]
+	fn refuses_stale_or_unvalidated_secrets_offline() {
+		let stale = secrets(TimeDelta::days(30), Some(TimeDelta::days(2)));
+		assert!(matches!(
+			check_validation_freshness(stale),
+			Err(Error::UnvalidatedSecrets(Some(..)))
+		));
+
+		let unvalidated = secrets(TimeDelta::days(30), None);
+		assert!(matches!(
+			check_validation_freshness(unvalidated),
+			Err(Error::UnvalidatedSecrets(None))
+		));
+	}
+
+	#[test]
+	fn refuses_expired_secrets_offline() {
+		let expired = secrets(-TimeDelta::hours(1), Some(TimeDelta::minutes(1)));
+		let error = check_validation_freshness(expired).unwrap_err();
+		assert!(matches!(error, Error::SecretsExpired(..)));
+		assert_eq!(error.exit_code(), 3);
+	}
+
+	#[test]
+	fn normalizes_copied_tokens() {
+		for copied in [
+			"abc.def-123",
+			"  abc.def-123\n",
+			"abc.def-123;",
+			"\"abc.def-123\"",
+			"'abc.def-123';",
+			"session=abc.def-123",
+			"session = \"abc.def-123\"; ",
+			"abc%2Edef-123",
+		] {
+			assert_eq!(
+				normalize_session_token(copied).unwrap(),
+				"abc.def-123",
+				"{copied:?}"
+			);
+		}
+	}
+
+	#[test]
+	fn keeps_percent_signs_which_dont_decode() {
+		assert_eq!(normalize_session_token("abc%zz").unwrap(), "abc%zz");
+		// decoding to a space would give an unusable token
+		assert_eq!(normalize_session_token("abc%20def").unwrap(), "abc%20def");
+	}
+
+	#[test]
+	fn rejects_malformed_tokens() {
+		for malformed in [
+			"",
+			"\"\"",
+			"session=",
+			"abc; path=/",
+			"abc\ndef",
+			"abc def",
+			"abcdéf",
+		] {
+			assert!(
+				matches!(
+					normalize_session_token(malformed),
+					Err(Error::MalformedSessionToken(..))
+				),
+				"{malformed:?}"
+			);
+		}
 	}
 }
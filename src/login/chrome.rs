@@ -0,0 +1,104 @@
+use super::{SESSION_COOKIE, Secrets, normalize_session_token};
+use crate::{error::Error, settings};
+use chrono::DateTime;
+use headless_chrome::{
+	Browser, LaunchOptionsBuilder, browser::default_executable, protocol::cdp::Target::CreateTarget,
+};
+use std::{path::PathBuf, sync::PoisonError, time::Duration};
+use tracing::info;
+
+/// Launches a visible Chrome/Chromium instance and waits for fumosclub to set a session cookie.
+///
+/// `executable` falls back to the default Chrome/Chromium installation; `profile` is used as the
+/// user data directory, so an existing login inside of it is picked up immediately.
+pub fn use_headful_chrome(
+	executable: Option<PathBuf>,
+	profile: Option<PathBuf>,
+) -> Result<Secrets, Error> {
+	let executable = match executable {
+		Some(executable) if !executable.is_file() => {
+			return Err(Error::ChromeExecutable(
+				executable,
+				"no such file".to_owned(),
+			));
+		}
+		Some(executable) => executable,
+		None => default_executable().map_err(Error::ChromeNotFound)?,
+	};
+
+	let browser = Browser::new(
+		LaunchOptionsBuilder::default()
+			.headless(false)
+			.path(Some(executable.clone()))
+			.user_data_dir(profile)
+			.build()
+			.map_err(|error| Error::BrowserLogin("building launch options", error.to_string()))?,
+	)
+	.map_err(|error| Error::ChromeExecutable(executable, error.to_string()))?;
+
+	let tab = browser
+		.new_tab_with_options(CreateTarget {
+			url: settings::get().base_url.clone(),
+			width: None,
+			height: None,
+			browser_context_id: None,
+			enable_begin_frame_control: None,
+			new_window: None,
+			background: None,
+			for_tab: None,
+		})
+		.map_err(|error| Error::BrowserLogin("opening a tab", error.to_string()))?;
+
+	tab
+		.wait_until_navigated()
+		.map_err(|error| Error::BrowserLogin("loading fumosclub", error.to_string()))?;
+
+	// cleans up tabs which Magically existed
+	let id = tab.get_target_id();
+	browser
+		.get_tabs()
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.iter()
+		.for_each(|tab| {
+			if tab.get_target_id() != id {
+				tab.close(false).ok();
+			}
+		});
+
+	// the profile may already be logged in, so check before waiting on the user
+	let mut first_check = true;
+	let session = loop {
+		if let Some(session) = tab
+			.get_cookies()
+			.map_err(|error| Error::BrowserLogin("reading cookies", error.to_string()))?
+			.into_iter()
+			.find(|cookie| cookie.name == SESSION_COOKIE)
+		{
+			if first_check {
+				info!("found an existing session in the chrome profile");
+			}
+
+			break session;
+		}
+
+		if !settings::get().interactive {
+			return Err(Error::InteractionRequired(
+				"the chrome profile isn't logged in to fumosclub; log in interactively once, or set FUMO_SESSION".to_owned(),
+			));
+		}
+
+		first_check = false;
+		std::thread::sleep(Duration::from_millis(250));
+	};
+
+	Ok(Secrets::new(
+		normalize_session_token(&session.value)?,
+		DateTime::from_timestamp(session.expires as i64, 0u32).ok_or_else(|| {
+			Error::BrowserLogin(
+				"reading the session cookie",
+				format!("its expiry {} is out of range", session.expires),
+			)
+		})?,
+	))
+}
@@ -0,0 +1,212 @@
+use super::{
+	SESSION_COOKIE, Secrets, normalize_session_token, save_session_secrets, validate_session_secrets,
+};
+use crate::{
+	client::{AccountDetails, Client, DOMAIN},
+	error::Error,
+};
+use chrono::{DateTime, Months, Utc};
+use tracing::{info, warn};
+
+/// Attempts to replace stale secrets with a browser cookie for the same account; never prompts.
+pub(super) async fn refresh_session_secrets(stale: &Secrets) -> Option<Secrets> {
+	let account_id = match &stale.account_id {
+		Some(account_id) => account_id.clone(),
+		// older secrets don't record their account, but the server might still tell us
+		None => {
+			Client::new(stale.clone())
+				.ok()?
+				.get_details()
+				.await
+				.ok()?
+				.id
+		}
+	};
+
+	let candidates = match get_browser_candidates().await {
+		Ok(candidates) => candidates,
+		Err(error) => {
+			warn!("failed refreshing secrets: {error}");
+			return None;
+		}
+	};
+
+	let candidate = candidates
+		.into_iter()
+		.find(|candidate| candidate.details.id == account_id)?;
+	let secrets = validate_session_secrets(candidate.secrets).await.ok()?;
+
+	if let Err(error) = save_session_secrets(secrets.clone()).await {
+		warn!("failed saving refreshed secrets: {error}");
+	}
+
+	info!("refreshed secrets from browser cookies");
+	Some(secrets)
+}
+
+/// Cookies expiring within this many seconds are treated as already expired.
+const COOKIE_EXPIRY_GRACE_SECONDS: i64 = 60;
+
+/// A browser session cookie which fumosclub returned account details for.
+struct BrowserSession {
+	secrets: Secrets,
+	details: AccountDetails,
+	/// The cookie had no expiry, so the secrets use a default one
+	expiry_unknown: bool,
+}
+
+impl BrowserSession {
+	fn describe(&self) -> String {
+		let details = &self.details;
+		format!(
+			"{} ({}, roblox user {}){}",
+			details.name,
+			details.id,
+			details.roblox_user,
+			if self.expiry_unknown {
+				" (session cookie, expiry unknown)"
+			} else {
+				""
+			}
+		)
+	}
+}
+
+/// Loads every session cookie rookie can find, keeping the ones fumosclub returns account details for.
+async fn get_browser_candidates() -> Result<Vec<BrowserSession>, Error> {
+	let cookies = rookie::load(Some(vec![DOMAIN.to_string()]))
+		.map_err(|error| Error::BrowserCookies(error.to_string()))?
+		.into_iter()
+		.filter(|cookie| cookie.name == SESSION_COOKIE)
+		.collect::<Vec<_>>();
+
+	if cookies.is_empty() {
+		return Err(Error::BrowserCookies(
+			"no session cookies were found in any browser supported by rookie".to_owned(),
+		));
+	}
+
+	let cutoff = Utc::now().timestamp() + COOKIE_EXPIRY_GRACE_SECONDS;
+	let secrets = cookies
+		.into_iter()
+		.filter_map(|cookie| {
+			let expiry_unknown = cookie.expires.is_none();
+			let expires = cookie.expires.map_or_else(
+				|| {
+					Utc::now()
+						.checked_add_months(Months::new(3))
+						.unwrap()
+						.timestamp()
+				},
+				|expiry| expiry as i64,
+			);
+
+			if expires <= cutoff {
+				info!(
+					"discarding session cookie for {}: expired at {}",
+					cookie.domain,
+					DateTime::from_timestamp(expires, 0)
+						.map_or_else(|| expires.to_string(), |expiry| expiry.to_string())
+				);
+				return None;
+			}
+
+			let session = match normalize_session_token(&cookie.value) {
+				Ok(session) => session,
+				Err(error) => {
+					info!("discarding session cookie for {}: {error}", cookie.domain);
+					return None;
+				}
+			};
+			Some((
+				Secrets::new(session, DateTime::from_timestamp(expires, 0).unwrap()),
+				expiry_unknown,
+			))
+		})
+		.collect::<Vec<_>>();
+
+	Ok(
+		futures::future::join_all(
+			secrets
+				.into_iter()
+				.map(|(secret, expiry_unknown)| async move {
+					let result = async { Client::new(secret.clone())?.get_details().await }.await;
+					(secret, expiry_unknown, result)
+				}),
+		)
+		.await
+		.into_iter()
+		.filter_map(|(secret, expiry_unknown, result)| match result {
+			Ok(details) => Some(BrowserSession {
+				secrets: Secrets {
+					account_id: Some(details.id.clone()),
+					..secret
+				},
+				details,
+				expiry_unknown,
+			}),
+			Err(error) => {
+				info!("discarding session cookie which failed validation: {error}");
+				None
+			}
+		})
+		.collect(),
+	)
+}
+
+/// Picks a session from the browser cookies rookie can find.
+///
+/// When several accounts are available and no account was specified, `choose` picks one of their
+/// descriptions, such as by prompting; they are an error without it.
+pub async fn use_browser_token(
+	account: Option<&str>,
+	choose: Option<impl Fn(&[String]) -> Result<usize, Error>>,
+) -> Result<Secrets, Error> {
+	let mut candidates = get_browser_candidates().await?;
+
+	// several browsers may hold sessions for the same account; keep the longest lived one
+	candidates.sort_by(|a, b| {
+		a.details
+			.id
+			.cmp(&b.details.id)
+			.then(b.secrets.expires.cmp(&a.secrets.expires))
+	});
+	candidates.dedup_by(|a, b| a.details.id == b.details.id);
+
+	if let Some(account) = account {
+		candidates
+			.retain(|candidate| candidate.details.id == account || candidate.details.name == account);
+
+		if candidates.is_empty() {
+			return Err(Error::AccountNotFound(account.to_owned()));
+		} else if candidates.len() > 1 {
+			return Err(Error::AmbiguousAccount(
+				account.to_owned(),
+				candidates.iter().map(BrowserSession::describe).collect(),
+			));
+		}
+	}
+
+	match candidates.len() {
+		0 => Err(Error::BrowserCookies(
+			"none of the session cookies found are logged in".to_owned(),
+		)),
+		1 => {
+			let candidate = candidates.swap_remove(0);
+			info!("using session for {}", candidate.describe());
+			Ok(candidate.secrets)
+		}
+		_ => {
+			let descriptions: Vec<String> = candidates.iter().map(BrowserSession::describe).collect();
+			let Some(choose) = choose else {
+				return Err(Error::AccountSelectionRequired(descriptions));
+			};
+
+			let index = choose(&descriptions)?;
+			match candidates.get(index) {
+				Some(candidate) => Ok(candidate.secrets.clone()),
+				None => Err(Error::AccountSelectionRequired(descriptions)),
+			}
+		}
+	}
+}
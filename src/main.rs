@@ -1,20 +1,69 @@
 #![forbid(unsafe_code)]
 
-mod client;
-mod error;
-mod login;
-mod project;
-
-use clap::{Parser, Subcommand};
-use client::Client;
+use chrono::{TimeDelta, Utc};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use client::{
+	Client, Reachability, Script, ScriptId, ScriptType, get_user_agent, probe_clock_skew,
+	probe_reachability, resolve_script_reference,
+};
+use completions::CompletionTarget;
 use error::Error;
+#[cfg(feature = "self-update")]
+use fumo::update;
+use fumo::{
+	archive, arguments, cache, client, completions, description, error, git, keys, login, output,
+	porcelain, project, report, schema, settings, snapshot, state, telemetry, template, timing,
+	types, verification, version, whitelist, workspace,
+};
 use login::{
-	get_config_directory, get_session_secrets, save_session_secrets, use_browser_token,
-	use_headful_chrome,
+	Secrets, auth_status, get_config_directory, get_session_secrets, probe_config_directory,
+	read_session_secrets, save_session_secrets, use_browser_token, use_headful_chrome,
+	validate_session_secrets, warn_if_expiring,
+};
+use output::ColorChoice;
+use porcelain::PorcelainVersion;
+use project::{
+	ConfirmCallback, DESCRIPTION_FILE, EditorSetup, Fetched, Metadata, MetadataDrift, ProjectType,
+	PushOptions, PushPart, SYNC_CONFIGURATION_FILE, WatchLimits, WatchOptions, WatchTasks,
+	check_name, clone_script, compare_remote, fetch, find_configuration_file, find_content_problems,
+	find_empty_files, find_orphaned_modules, get_configuration_value, init, link, new_module,
+	open_in_editor, prune_modules, pull, pull_configuration, push, push_part, push_project,
+	read_configuration, read_file, read_linked_configuration, read_metadata, resolve_project_root,
+	set_configuration_value, unlink, watch, write_schema,
 };
-use project::{init, pull, push, read_configuration, watch};
-use std::{ffi::OsStr, path::PathBuf};
-use tracing::warn;
+use report::{FileReport, REPORT_VERSION};
+use serde_json::json;
+use settings::{
+	GlobalConfiguration, OutputFormat, Overrides, Settings, get_global_value,
+	read_global_configuration, set_global_value,
+};
+use snapshot::SnapshotSchedule;
+use state::StateKind;
+use std::{
+	ffi::{OsStr, OsString},
+	io::Write,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+use telemetry::TelemetryLayer;
+use template::{DEFAULT_TEMPLATE, list_templates, save_template};
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{
+	Layer, filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt,
+};
+use verification::{RemoteEditPolicy, VerifySchedule};
+use version::BuildInfo;
+use whitelist::{AuditStatus, Presence};
+
+/// Records the last day the alpha software warning was shown
+const ALPHA_WARNING_FILE: &str = "alpha-warning";
+/// Sync metadata older than this suggests checking the project against fumosclub
+const STALE_METADATA_DAYS: i64 = 30;
+/// Clock differences from fumosclub below this are within the precision of the `Date` header
+const NOTABLE_CLOCK_SKEW_SECONDS: i64 = 60;
 
 #[derive(Subcommand, Clone, Debug)]
 enum Command {
@@ -23,131 +72,2705 @@ enum Command {
 		/// Whether or not to spawn an instance of Chrome/Chromium in order to login to fumosclub
 		#[arg(short, long, default_value_t = false)]
 		spawn_chromium: bool,
+		/// Report the account, expiry and validity of the current secrets without overwriting them
+		#[arg(long, default_value_t = false)]
+		check: bool,
+		/// Name or id of the account to pick from the browser sessions, instead of prompting
+		#[arg(long, conflicts_with = "spawn_chromium")]
+		account: Option<String>,
+		/// Value of a fumosclub session cookie to log in with, instead of searching browsers
+		#[arg(long, conflicts_with_all = ["spawn_chromium", "account"])]
+		token: Option<String>,
+		/// Path to the Chrome/Chromium executable used by --spawn-chromium
+		#[arg(long, env = "FUMO_CHROME")]
+		chrome_path: Option<PathBuf>,
+		/// Profile (user data) directory used by --spawn-chromium
+		#[arg(long)]
+		chrome_profile: Option<PathBuf>,
+	},
+	/// Reports whether fumo is logged in, exiting with 3 if it isn't
+	Auth {
+		#[command(subcommand)]
+		action: AuthAction,
 	},
+	/// Checks whether fumosclub can be reached, telling outages apart from network problems, and
+	/// whether the system clock agrees with fumosclub's and the config directory is writable; in
+	/// a project, its files get the content checks of push too
+	Doctor,
 	/// Shows infomation about the logged in account
-	View,
+	View {
+		/// Show the details cached by the last command which fetched them, without asking fumosclub
+		#[arg(long, default_value_t = false)]
+		cached: bool,
+	},
 	/// Initializes a project in the specified directory
 	Init {
 		#[arg(short, long)]
 		project_directory: PathBuf,
+		/// Initialize a package, which has no main script
+		#[arg(long, default_value_t = false)]
+		package: bool,
+		/// Create a git repository with an initial commit
+		#[arg(long, default_value_t = false)]
+		git: bool,
+		/// Template to scaffold the project from (see `fumo template list`)
+		#[arg(long, default_value = DEFAULT_TEMPLATE)]
+		template: String,
+		/// Editor to write settings for; remembered for later pulls
+		#[arg(long, value_enum, default_value_t = EditorSetup::Vscode)]
+		editor: EditorSetup,
+		/// Create missing parent directories of the project directory
+		#[arg(long, default_value_t = false)]
+		parents: bool,
+	},
+	/// Manages the types.d.luau type definitions of a project
+	Types {
+		#[command(subcommand)]
+		action: TypesAction,
+	},
+	/// Creates modules in pkg/
+	Module {
+		#[command(subcommand)]
+		action: ModuleAction,
+	},
+	/// Lists and saves templates used by `init`
+	Template {
+		#[command(subcommand)]
+		action: TemplateAction,
+	},
+	/// Manages the whitelist of a project
+	Whitelist {
+		#[command(subcommand)]
+		action: WhitelistAction,
+	},
+	/// Shows who the whitelist entries of a project are
+	Audit {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Audit the whitelist on fumosclub instead of fumosync.json
+		#[arg(long, default_value_t = false, conflicts_with = "diff")]
+		remote: bool,
+		/// Audit both whitelists, showing which of them each entry is in
+		#[arg(long, default_value_t = false)]
+		diff: bool,
 	},
 	/// Lists all projects under the logged in account
-	List,
+	List {
+		/// Ask fumosclub even if the cached list is recent
+		#[arg(long, default_value_t = false)]
+		refresh: bool,
+		/// Show the cached list however old it is, without using the network
+		#[arg(long, default_value_t = false, conflicts_with = "refresh")]
+		cached: bool,
+		/// Only list scripts you can edit
+		#[arg(long, default_value_t = false)]
+		editable: bool,
+		/// Fetch the module count, source size and description of every listed script; with
+		/// --cached, show the ones the last detailed listing fetched
+		#[arg(long, default_value_t = false)]
+		detailed: bool,
+	},
+	/// Pulls a script into a new directory named after it, and prints the directory
+	Clone {
+		/// Id, id prefix, or part of the name of the script
+		script_id: String,
+		/// Directory to pull into, instead of one named after the script
+		#[arg(long)]
+		dir: Option<PathBuf>,
+		/// Create missing parent directories of --dir
+		#[arg(long, default_value_t = false, requires = "dir")]
+		parents: bool,
+	},
 	/// Pulls down a script via the fumosclub API (the script must be editable)
 	Pull {
+		/// Id, id prefix, or part of the name of the script
 		script_id: String,
 		project_directory: PathBuf,
+		/// Overwrite the files of an existing project
+		#[arg(long, default_value_t = false)]
+		force: bool,
+		/// With --force, overwrite files even if the git working tree has uncommitted changes
+		#[arg(long, default_value_t = false, requires = "force")]
+		allow_dirty: bool,
+		/// With --force, leave types.d.luau as it is
+		#[arg(long, default_value_t = false, requires = "force")]
+		keep_types: bool,
+		/// Only adopt the remote name, publicity and whitelist into an existing project's configuration
+		#[arg(long, default_value_t = false, conflicts_with = "force")]
+		config_only: bool,
+		/// Create missing parent directories of the project directory
+		#[arg(long, default_value_t = false, conflicts_with = "config_only")]
+		parents: bool,
+	},
+	/// Downloads the main source, or a module, of a script into a single file
+	Fetch {
+		/// Id, id prefix, or part of the name of the script
+		id: String,
+		/// File to write to, or - for stdout; defaults to <script name>.luau
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+		/// Name of the module to download instead of the main source
+		#[arg(short, long)]
+		module: Option<String>,
+		/// Overwrite the output file if it exists
+		#[arg(long, default_value_t = false)]
+		force: bool,
 	},
 	/// Pushes the script in the specified directory to fumosclub; data is sourced from project_directory/fumosync.json
 	Push {
 		#[arg(short, long, default_value = OsStr::new("."))]
 		project_directory: PathBuf,
+		/// Push every member of the fumo-workspace.json in project_directory; implied when the
+		/// directory only holds a workspace
+		#[arg(long, default_value_t = false)]
+		workspace: bool,
+		/// Only upload README.md
+		#[arg(long, default_value_t = false, conflicts_with_all = ["workspace", "meta_only"])]
+		description_only: bool,
+		/// Only upload the name, whitelist and publicity in fumosync.json
+		#[arg(long, default_value_t = false, conflicts_with = "workspace")]
+		meta_only: bool,
+		/// Show what would be uploaded without uploading it
+		#[arg(long, default_value_t = false, conflicts_with_all = ["workspace", "description_only", "meta_only"])]
+		dry_run: bool,
+		/// Upload a blank init.server.luau and empty modules without asking
+		#[arg(long, default_value_t = false)]
+		allow_empty: bool,
+		/// Push even if another project directory last pushed the script
+		#[arg(long, default_value_t = false)]
+		force: bool,
+		/// Refuse to push when a requireM call names a module which isn't uploaded under that name,
+		/// because it is aliased or skipped, instead of warning
+		#[arg(long, default_value_t = false)]
+		strict: bool,
+		/// Also delete remote modules which have no file in pkg/, like `fumo prune --yes`
+		#[arg(long, default_value_t = false, conflicts_with_all = ["workspace", "description_only", "meta_only"])]
+		prune: bool,
+		/// Rename module files with whitespace around their names to the trimmed names they are
+		/// uploaded as, after asking
+		#[arg(long, default_value_t = false, conflicts_with_all = ["workspace", "description_only", "meta_only", "dry_run"])]
+		fix_names: bool,
+		/// Neither upload nor prune a module, by the name it is uploaded as; repeatable
+		#[arg(long, value_name = "MODULE", conflicts_with_all = ["workspace", "description_only", "meta_only"])]
+		ignore: Vec<String>,
+		/// Upload stdin as the main source instead of init.server.luau
+		#[arg(long, default_value_t = false, conflicts_with_all = ["workspace", "description_only", "meta_only", "description_stdin"])]
+		main_stdin: bool,
+		/// Upload stdin as the description instead of README.md
+		#[arg(long, default_value_t = false, conflicts_with_all = ["workspace", "meta_only"])]
+		description_stdin: bool,
+	},
+	/// Deletes remote modules which have no file in pkg/, without uploading anything
+	Prune {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Don't ask for confirmation
+		#[arg(short, long, default_value_t = false)]
+		yes: bool,
+		/// List the modules which would be deleted, without deleting them
+		#[arg(long, default_value_t = false)]
+		dry_run: bool,
+	},
+	/// Checks that the project exactly matches the script on fumosclub, without writing anything;
+	/// exits with 1 if any file differs
+	Verify {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Verify every member of the fumo-workspace.json in project_directory; implied when the
+		/// directory only holds a workspace
+		#[arg(long, default_value_t = false)]
+		workspace: bool,
+	},
+	/// Shows unified diffs of the files which differ from the script on fumosclub, without
+	/// writing anything
+	Diff {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Show the lines added and removed per file, like `git diff --stat`; exits with 1 on changes
+		#[arg(long, default_value_t = false, conflicts_with = "name_only")]
+		stat: bool,
+		/// Only print the paths of changed files, sorted; exits with 1 on changes
+		#[arg(long, default_value_t = false)]
+		name_only: bool,
+	},
+	/// Archives the synced files of a project, along with its configuration, as a tar.gz
+	Export {
+		output: PathBuf,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Unpacks an archive written by `fumo export` into a new project directory
+	Import {
+		archive: PathBuf,
+		directory: PathBuf,
+		/// Unpack into an existing directory, overwriting the archived files
+		#[arg(long, default_value_t = false)]
+		force: bool,
+	},
+	/// Links the project to another script; the next push overwrites it with the local files
+	Link {
+		/// Id, id prefix, or part of the name of the script
+		script_id: String,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Unlinks the project from its script, resetting scriptId to the placeholder
+	Unlink {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Previews README.md as fumosclub renders it, or edits it and pushes only the description
+	Describe {
+		/// Print the description with Markdown rendered
+		#[arg(long, required_unless_present = "edit", conflicts_with = "edit")]
+		preview: bool,
+		/// Open $VISUAL or $EDITOR on README.md, then push it once the editor exits successfully
+		#[arg(long)]
+		edit: bool,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Shows the project and when it was last synced, without asking fumosclub; run by a bare
+	/// `fumo`
+	#[command(hide = true)]
+	Summary {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Summarizes the project, including when it was last pulled or pushed
+	Status {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Set scriptName in the configuration to the remote name
+		#[arg(long, default_value_t = false, conflicts_with = "workspace")]
+		fix_name: bool,
+		/// Report the drift of every member of the fumo-workspace.json in project_directory;
+		/// implied when the directory only holds a workspace
+		#[arg(long, default_value_t = false)]
+		workspace: bool,
 	},
 	/// Watches the specified directory for changes, and pushes them to fumosclub
 	Watch {
 		#[arg(short, long, default_value = OsStr::new("."))]
 		project_directory: PathBuf,
+		/// Milliseconds to wait for changes to settle before pushing
+		#[arg(long, env = "FUMO_DEBOUNCE")]
+		debounce: Option<u64>,
+		/// Keep watching if the initial push fails, instead of asking; auth and configuration errors
+		/// still stop the watcher
+		#[arg(long, default_value_t = false)]
+		continue_on_error: bool,
+		/// Snapshot the remote script into .fumo/snapshots this often, such as 30m
+		#[arg(long, value_parser = humantime::parse_duration)]
+		snapshot_interval: Option<Duration>,
+		/// How many snapshots are kept
+		#[arg(long, default_value_t = 10, requires = "snapshot_interval")]
+		snapshot_keep: usize,
+		/// Log what would be uploaded instead of uploading it, skipping the initial push
+		#[arg(long, default_value_t = false)]
+		dry_run: bool,
+		/// Stop watching after this long, such as 2h, flushing pending updates first
+		#[arg(long, value_parser = humantime::parse_duration)]
+		exit_after: Option<Duration>,
+		/// Stop watching after this long without file changes, such as 30m
+		#[arg(long, value_parser = humantime::parse_duration)]
+		exit_on_idle: Option<Duration>,
+		/// Watch even if another project directory last pushed the script
+		#[arg(long, default_value_t = false)]
+		force: bool,
+		/// Check the session every `sessionKeepaliveMinutes` (30 by default), so fumosclub doesn't
+		/// expire it while no files change
+		#[arg(long, default_value_t = false)]
+		session_keepalive: bool,
+		/// Compare the remote script this often, such as 5m, warning about edits made on fumosclub
+		/// since this watcher last synced
+		#[arg(long, value_parser = humantime::parse_duration)]
+		verify_interval: Option<Duration>,
+		/// What to do about files edited on fumosclub
+		#[arg(long, value_enum, default_value_t, requires = "verify_interval")]
+		on_remote_edit: RemoteEditPolicy,
+		/// Sync files whose permissions or timestamps changed, if their contents changed too; like
+		/// "watchEvents": ["metadata"] in fumosync.json
+		#[arg(long, default_value_t = false)]
+		watch_metadata: bool,
+		/// Refuse the initial push when a requireM call names a module which isn't uploaded under
+		/// that name, like `push --strict`
+		#[arg(long, default_value_t = false)]
+		strict: bool,
+	},
+	/// Prints the JSON Schema of fumosync.json
+	Schema {
+		/// Refresh the project's copy in fumosync.schema.json instead
+		#[arg(long, default_value_t = false)]
+		write: bool,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Prints shell completions
+	#[command(args_conflicts_with_subcommands = true)]
+	Completions {
+		/// Defaults to the shell in $SHELL
+		shell: Option<clap_complete::Shell>,
+		/// Also complete script ids from the cached script list; zsh and fish only
+		#[arg(long, default_value_t = false)]
+		dynamic: bool,
+		#[command(subcommand)]
+		action: Option<CompletionsAction>,
+	},
+	/// Prints completion values for the dynamic completions
+	#[command(name = "__complete", hide = true)]
+	Complete { target: CompletionTarget },
+	/// Deletes local state from the project's .fumo directory
+	Clean {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Delete cached remote data
+		#[arg(long, default_value_t = false)]
+		cache: bool,
+		/// Delete backups of overwritten files
+		#[arg(long, default_value_t = false)]
+		backups: bool,
+		/// Delete snapshots taken by `watch --snapshot-interval`
+		#[arg(long, default_value_t = false)]
+		snapshots: bool,
+		/// Delete all of the above
+		#[arg(
+			long,
+			default_value_t = false,
+			required_unless_present_any = ["cache", "backups", "snapshots"],
+			conflicts_with_all = ["cache", "backups", "snapshots"]
+		)]
+		all: bool,
+	},
+	/// Lists and restores snapshots taken by `watch --snapshot-interval`
+	Snapshots {
+		#[command(subcommand)]
+		action: SnapshotsAction,
+	},
+	/// Gets, sets or clears the values of the `arguments` global, kept in fumosync.json
+	Args {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		#[command(subcommand)]
+		action: ArgsAction,
+	},
+	/// Gets or sets values in fumosync.json, or in the global config.toml with --global
+	Config {
+		/// Edit the global configuration instead of the project's fumosync.json
+		#[arg(long, default_value_t = false)]
+		global: bool,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		#[command(subcommand)]
+		action: ConfigAction,
+	},
+	/// Prints which build of fumo this is: its version, git description, target, build time and
+	/// optional features; with --format json, the fields are kept stable for bug reports
+	Version,
+	/// Updates fumo to the latest GitHub release
+	#[cfg(feature = "self-update")]
+	SelfUpdate {
+		/// Only report whether an update is available
+		#[arg(long, default_value_t = false)]
+		check: bool,
 	},
 	/// Generates a key for a script under the logged in fumosclub account
 	Generate {
-		/// Id of the script; defaults to the script id in project_directory/fumosync.json
+		/// Id, id prefix, or part of the name of the script; defaults to the script id in
+		/// project_directory/fumosync.json
 		#[arg(long)]
 		id: Option<String>,
+		/// Don't remember the key in keys.json
+		#[arg(long, default_value_t = false)]
+		no_store: bool,
+	},
+	/// Shows keys remembered by `fumo generate`, without asking fumosclub
+	Key {
+		#[command(subcommand)]
+		action: KeyAction,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum SnapshotsAction {
+	/// Lists the timestamps of snapshots, oldest first
+	List {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+	/// Pushes a snapshot back to fumosclub, overwriting the script
+	Restore {
+		timestamp: String,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum KeyAction {
+	/// Prints the last key generated for a script
+	Show {
+		/// Id of the script; defaults to the script id in ./fumosync.json
+		#[arg(long)]
+		id: Option<ScriptId>,
+	},
+	/// Lists every remembered key, along with the names of their scripts
+	List,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum TypesAction {
+	/// Rewrites types.d.luau with the definitions embedded in fumo
+	Update {
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Download the definitions from this url instead
+		#[arg(long)]
+		url: Option<String>,
+		/// Overwrite local edits without asking
+		#[arg(short, long, default_value_t = false)]
+		yes: bool,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum TemplateAction {
+	/// Lists the built-in and user templates
+	List,
+	/// Saves a project as a user template; fumosync.json is unlinked from its script
+	Save {
+		name: String,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum AuthAction {
+	/// Reports the account and expiry of the current secrets, without refreshing them
+	Status {
+		/// Ask fumosclub whether the secrets are still valid, instead of trusting a validation from
+		/// the last day
+		#[arg(long, default_value_t = false)]
+		online: bool,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum ModuleAction {
+	/// Creates pkg/<name>.luau, checking the name like push does
+	New {
+		/// Module name; `/` separates directories inside of pkg/
+		name: String,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+		/// Template whose module.luau the module starts from
+		#[arg(long, default_value = DEFAULT_TEMPLATE)]
+		template: String,
+		/// Open $VISUAL or $EDITOR on the new module
+		#[arg(long, default_value_t = false)]
+		open: bool,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum WhitelistAction {
+	/// Adds usernames or ids to the whitelist, and replaces every entry with the id of its Roblox user
+	Resolve {
+		entries: Vec<String>,
+		#[arg(short, long, default_value = OsStr::new("."))]
+		project_directory: PathBuf,
+	},
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum ConfigAction {
+	/// Prints the value of a key
+	Get { key: String },
+	/// Sets the value of a key
+	Set { key: String, value: String },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum CompletionsAction {
+	/// Writes the completions where the shell loads them from, and prints what is left to do
+	Install {
+		/// Defaults to the shell in $SHELL
+		shell: Option<clap_complete::Shell>,
+		/// Also complete script ids from the cached script list; zsh and fish only
+		#[arg(long, default_value_t = false)]
+		dynamic: bool,
+		/// Print the path and the completions instead of writing them
+		#[arg(long, default_value_t = false)]
+		dry_run: bool,
 	},
 }
 
+#[derive(Subcommand, Clone, Debug)]
+enum ArgsAction {
+	/// Prints the arguments as a JSON array
+	Get,
+	/// Replaces the arguments; values which aren't valid JSON are stored as strings
+	Set { values: Vec<String> },
+	/// Removes every argument
+	Clear,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormat {
+	Compact,
+	Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogRotation {
+	Never,
+	Daily,
+}
+
 /// fumo is a cli tool built for fumosclub <https://fumosclubv1.vercel.app>
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
 	#[command(subcommand)]
 	command: Command,
+	/// Refresh expired secrets from browser cookies belonging to the same account;
+	/// --auto-refresh=false overrides config.toml
+	#[arg(
+		long,
+		global = true,
+		env = "FUMO_AUTO_REFRESH",
+		num_args = 0..=1,
+		require_equals = true,
+		default_missing_value = "true"
+	)]
+	auto_refresh: Option<bool>,
+	/// Only log warnings and errors; --quiet=false overrides config.toml
+	#[arg(
+		short,
+		long,
+		global = true,
+		env = "FUMO_QUIET",
+		num_args = 0..=1,
+		require_equals = true,
+		default_missing_value = "true"
+	)]
+	quiet: Option<bool>,
+	/// When to color output; NO_COLOR is honored when automatic [default: auto]
+	#[arg(long, global = true, env = "FUMO_COLOR", value_enum)]
+	color: Option<ColorChoice>,
+	/// Base url of the fumosclub API
+	#[arg(long, global = true, env = "FUMO_BASE_URL")]
+	base_url: Option<String>,
+	/// Never prompt; commands use safe defaults or fail with the flag to pass instead (implied when
+	/// stdin isn't a terminal)
+	#[arg(
+		long,
+		global = true,
+		env = "FUMO_NON_INTERACTIVE",
+		default_value_t = false
+	)]
+	non_interactive: bool,
+	/// Request timeout in seconds [default: 30]
+	#[arg(long, global = true, env = "FUMO_TIMEOUT")]
+	timeout: Option<u64>,
+	/// Times requests are retried after connection failures and outages [default: 2]
+	#[arg(long, global = true, env = "FUMO_RETRIES")]
+	retries: Option<u32>,
+	/// Use the saved login of this profile, such as for a second account
+	#[arg(long, global = true, env = "FUMO_PROFILE")]
+	profile: Option<String>,
+	/// Session token to use for this invocation, instead of FUMO_SESSION or the saved login
+	#[arg(long, global = true, conflicts_with = "session_file")]
+	session: Option<String>,
+	/// Secrets file to read instead of the saved login; it is never written to
+	#[arg(long, global = true)]
+	session_file: Option<PathBuf>,
+	/// Appended to the user agent, such as "ci-deploy", to tag automated traffic
+	#[arg(long, global = true, env = "FUMO_UA_SUFFIX")]
+	user_agent_suffix: Option<String>,
+	/// Push and watch projects which are inside of the fumo config directory, or contain it
+	#[arg(long, global = true, default_value_t = false)]
+	allow_config_overlap: bool,
+	/// Don't save sessions fumosclub rotates to, such as for shared read-only credentials
+	#[arg(long, global = true, default_value_t = false)]
+	no_session_refresh: bool,
+	/// Also write logs (with timestamps) to this file
+	#[arg(long, global = true)]
+	log_file: Option<PathBuf>,
+	/// Format of lines written to --log-file
+	#[arg(long, global = true, value_enum, default_value_t = LogFormat::Compact)]
+	log_format: LogFormat,
+	/// How often --log-file is rotated; rotated files get a date suffix
+	#[arg(long, global = true, value_enum, default_value_t = LogRotation::Never)]
+	log_rotate: LogRotation,
+	/// Export spans of syncs and http requests to this OTLP/HTTP collector, such as
+	/// http://localhost:4318; script ids are hashed. Needs the otel feature
+	#[arg(long, global = true)]
+	otel_endpoint: Option<String>,
+	/// Format of command output; with json, failures are also reported on stdout
+	#[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+	format: OutputFormat,
+	/// Stable tab separated output for scripts from list, status and view; versions never change
+	#[arg(
+		long,
+		global = true,
+		value_enum,
+		num_args = 0..=1,
+		require_equals = true,
+		default_missing_value = "v1",
+		conflicts_with = "format"
+	)]
+	porcelain: Option<PorcelainVersion>,
+	/// Print how long each phase took to stderr, such as file reads and http requests
+	#[arg(long, global = true, default_value_t = false)]
+	timings: bool,
 }
 
-async fn ensure_config_directory_exists() {
-	if !get_config_directory()
-		.expect("failed getting config directory")
-		.try_exists()
-		.expect("failed verifying the existance of config directory")
-	{
-		tokio::fs::create_dir_all(get_config_directory().expect("failed getting config directory"))
-			.await
-			.expect("failed creating config directory");
+/// Creates the config directory if it doesn't exist yet.
+///
+/// # Errors
+/// - [`Error::ConfigDirectoryUnwritable`] if it can't be created, such as on a read-only system
+async fn ensure_config_directory_exists() -> Result<(), Error> {
+	let directory = get_config_directory()?;
+	if directory.try_exists()? {
+		return Ok(());
 	}
+
+	tokio::fs::create_dir_all(&directory)
+		.await
+		.map_err(|io_error| Error::ConfigDirectoryUnwritable(directory, io_error))
 }
 
-#[tokio::main]
-async fn main() {
-	if let Err(error) = main_fn().await {
-		tracing::error!("{error}");
+/// Whether a command works without writing to the config directory, given secrets from the
+/// environment; its caches are skipped with warnings.
+fn reads_only(command: &Command) -> bool {
+	matches!(
+		command,
+		Command::View { .. }
+			| Command::List { .. }
+			| Command::Fetch { .. }
+			| Command::Auth { .. }
+			| Command::Doctor
+			| Command::Schema { write: false, .. }
+			| Command::Completions { .. }
+			| Command::Version
+	)
+}
+
+/// Gets a measured clock skew, warning instead of failing when it couldn't be measured.
+fn measured_clock_skew(measured: Result<Option<TimeDelta>, Error>) -> Option<TimeDelta> {
+	measured.unwrap_or_else(|error| {
+		warn!("couldn't compare the system clock against fumosclub: {error}");
+		None
+	})
+}
+
+/// Describes how far the system clock is from fumosclub's, if that is enough to matter.
+fn describe_clock_skew(skew: TimeDelta) -> Option<String> {
+	(skew.num_seconds().abs() >= NOTABLE_CLOCK_SKEW_SECONDS).then(|| {
+		format!(
+			"the system clock is {} minute(s) {} of fumosclub's; secrets may look expired early or late",
+			skew.num_minutes().abs(),
+			if skew > TimeDelta::zero() {
+				"ahead"
+			} else {
+				"behind"
+			}
+		)
+	})
+}
+
+/// Warns that fumo is alpha software, at most once per day.
+async fn warn_alpha_once_per_day() {
+	let Ok(path) = get_config_directory().map(|directory| directory.join(ALPHA_WARNING_FILE)) else {
+		return;
+	};
+
+	let today = Utc::now().date_naive().to_string();
+	if tokio::fs::read_to_string(&path)
+		.await
+		.is_ok_and(|last_warned| last_warned.trim() == today)
+	{
+		return;
 	}
+
+	warn!("fumo is alpha software; please report bugs to https://github.com/techs-sus/fumo",);
+	tokio::fs::write(&path, today).await.ok();
 }
 
-async fn main_fn() -> Result<(), Error> {
-	tracing_subscriber::fmt()
+/// Installs the tracing subscriber; the returned guard must be dropped to flush the log file.
+fn init_logging(
+	args: &Args,
+	settings: &Settings,
+	telemetry_layer: Option<TelemetryLayer>,
+) -> Option<WorkerGuard> {
+	// stdout is reserved for command output, so logs always go to stderr
+	let terminal_layer = tracing_subscriber::fmt::layer()
 		.compact()
 		.with_target(false)
 		.without_time()
 		.with_level(true)
+		.with_writer(std::io::stderr)
+		.with_ansi(settings.color.enabled_for(&std::io::stderr()))
+		.with_filter(if settings.quiet {
+			LevelFilter::WARN
+		} else {
+			LevelFilter::INFO
+		});
+
+	let (file_layer, guard) = match &args.log_file {
+		Some(path) => {
+			let directory = path
+				.parent()
+				.filter(|parent| !parent.as_os_str().is_empty())
+				.unwrap_or_else(|| Path::new("."));
+			let file_name = path.file_name().unwrap_or_else(|| OsStr::new("fumo.log"));
+			let appender = match args.log_rotate {
+				LogRotation::Never => tracing_appender::rolling::never(directory, file_name),
+				LogRotation::Daily => tracing_appender::rolling::daily(directory, file_name),
+			};
+
+			let (writer, guard) = tracing_appender::non_blocking(appender);
+			let layer = match args.log_format {
+				LogFormat::Compact => tracing_subscriber::fmt::layer()
+					.compact()
+					.with_ansi(false)
+					.with_writer(writer)
+					.boxed(),
+				LogFormat::Json => tracing_subscriber::fmt::layer()
+					.json()
+					.with_writer(writer)
+					.boxed(),
+			};
+
+			(Some(layer.with_filter(LevelFilter::INFO)), Some(guard))
+		}
+		None => (None, None),
+	};
+
+	tracing_subscriber::registry()
+		.with(telemetry_layer)
+		.with(terminal_layer)
+		.with(file_layer)
 		.init();
-	warn!("fumo is alpha software; please report bugs to https://github.com/techs-sus/fumo",);
 
-	let args = Args::parse();
+	guard
+}
+
+#[tokio::main]
+async fn main() {
+	// a bare `fumo` summarizes the project it's run in, rather than only erroring
+	let args = match Args::try_parse() {
+		Ok(args) => args,
+		Err(error)
+			if matches!(
+				error.kind(),
+				clap::error::ErrorKind::MissingSubcommand
+					| clap::error::ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+			) =>
+		{
+			Args::parse_from(std::env::args_os().chain([OsString::from("summary")]))
+		}
+		Err(error) => error.exit(),
+	};
+	let format = args.format;
+
+	// settings are needed before logging is set up, so configuration errors are reported afterwards
+	let global_configuration = read_global_configuration().await;
+	let project_configuration = match &args.command {
+		Command::Push {
+			project_directory, ..
+		}
+		| Command::Watch {
+			project_directory, ..
+		} => read_configuration(project_directory).await.ok(),
+		_ => None,
+	};
+
+	let (global, unknown_keys) = match &global_configuration {
+		Ok((global, unknown_keys)) => (global.clone(), unknown_keys.clone()),
+		Err(_) => (GlobalConfiguration::default(), Vec::new()),
+	};
+
+	let settings = Settings::resolve(&overrides(&args), project_configuration.as_ref(), &global);
+	// spans are only ever exported to an endpoint passed on the command line
+	let (telemetry_layer, telemetry) = match args.otel_endpoint.as_deref().map(telemetry::layer) {
+		Some(Ok((layer, telemetry))) => (Some(layer), Ok(Some(telemetry))),
+		Some(Err(error)) => (None, Err(error)),
+		None => (None, Ok(None)),
+	};
+	let log_guard = init_logging(&args, &settings, telemetry_layer);
+	settings::install(settings);
+
+	// completions run on every tab press, so they skip everything else and never fail
+	if let Command::Complete { target } = args.command {
+		completions::complete(target).await;
+		return;
+	}
+
+	for key in unknown_keys {
+		warn!("ignoring unknown key {key} in the global configuration");
+	}
+
+	// verify and the summary modes of diff report their failures on stdout themselves
+	let failures_reported = matches!(
+		args.command,
+		Command::Verify { .. }
+			| Command::Diff { stat: true, .. }
+			| Command::Diff {
+				name_only: true,
+				..
+			}
+	);
+	// as does auth status, in every format, for missing or unusable secrets
+	let auth_status_reported = matches!(args.command, Command::Auth { .. });
+	let timings = args.timings;
+	let result = match (global_configuration, telemetry) {
+		(Err(error), _) | (_, Err(error)) => Err(error),
+		// spans are flushed at the end of the arm, since process::exit below skips destructors
+		(Ok(_), Ok(_telemetry)) => main_fn(args).await,
+	};
+	if timings {
+		timing::print_report();
+	}
+	project::report_unrecognized_files();
+
+	if let Err(error) = result {
+		match format {
+			_ if auth_status_reported && error.exit_code() == 3 => {}
+			// the verification report already describes the failure on stdout
+			OutputFormat::Json
+				if failures_reported
+					&& matches!(
+						error,
+						Error::VerificationFailed(..) | Error::WorkspaceFailed(..)
+					) => {}
+			OutputFormat::Json => println!(
+				"{}",
+				json!({
+					"error": {
+						"kind": error.kind_str(),
+						"message": error.to_string(),
+						"hint": error.hint(),
+						"exit_code": error.exit_code(),
+					}
+				})
+			),
+			OutputFormat::Text => {
+				tracing::error!("{}", output::error(&error.to_string()));
+				if let Some(hint) = error.hint() {
+					// printed directly, so --quiet doesn't hide it along with info logs
+					eprintln!("{}", output::hint(&format!("hint: {hint}")));
+				}
+			}
+		}
+
+		// process::exit skips destructors, so flush the log file first
+		drop(log_guard);
+		std::process::exit(error.exit_code());
+	}
+}
+
+/// Prints the files which don't match fumosclub, with their paths prefixed.
+fn print_mismatches(prefix: &str, files: &[FileReport]) {
+	for file in report::mismatches(files) {
+		println!("{}: {prefix}{}", file.state, file.path);
+	}
+}
+
+/// Collects the settings given on the command line or through environment variables.
+fn overrides(args: &Args) -> Overrides {
+	Overrides {
+		auto_refresh: args.auto_refresh,
+		quiet: args.quiet,
+		color: args.color,
+		format: Some(args.format),
+		base_url: args.base_url.clone(),
+		timeout: args.timeout,
+		non_interactive: args.non_interactive.then_some(true),
+		session: args.session.clone(),
+		session_file: args.session_file.clone(),
+		user_agent_suffix: args.user_agent_suffix.clone(),
+		allow_config_overlap: args.allow_config_overlap.then_some(true),
+		session_refresh: args.no_session_refresh.then_some(false),
+		retries: args.retries,
+		profile: args.profile.clone(),
+		debounce: match args.command {
+			Command::Watch { debounce, .. } => debounce,
+			_ => None,
+		},
+	}
+}
+
+/// Asks before pushing a project configured as public, if `confirmPublicPush` is set.
+///
+/// # Errors
+/// - [`Error::InteractionRequired`] if prompts can't be shown
+async fn confirm_public_push(project_directory: &Path) -> Result<bool, Error> {
+	let settings = settings::get();
+	if !settings.confirm_public_push {
+		return Ok(true);
+	}
+
+	let configuration = read_configuration(project_directory).await?;
+	if !configuration.is_public || !configuration.syncs_metadata() {
+		return Ok(true);
+	}
+	if !settings.interactive {
+		return Err(Error::InteractionRequired(format!(
+			"{} is public; run `fumo config --global set confirmPublicPush false` to push public scripts without asking",
+			configuration.script_name
+		)));
+	}
+
+	Ok(
+		inquire::Confirm::new(&format!(
+			"push {} as a public script?",
+			configuration.script_name
+		))
+		.with_default(false)
+		.prompt()?,
+	)
+}
+
+/// Asks yes-or-no questions for the library when interactive; without it, pushes refuse to do
+/// what they would have asked about.
+fn confirm_callback() -> Option<ConfirmCallback> {
+	settings::get().interactive.then(|| {
+		Arc::new(|message: &str| -> Result<bool, Error> {
+			Ok(
+				inquire::Confirm::new(message)
+					.with_default(false)
+					.prompt()?,
+			)
+		}) as ConfirmCallback
+	})
+}
+
+/// Asks which of the scripts an ambiguous reference matched was meant, returning its id.
+///
+/// # Errors
+/// - [`Error::AmbiguousScriptReference`] if prompts can't be shown
+fn choose_script(reference: String, candidates: Vec<Script>) -> Result<String, Error> {
+	if !settings::get().interactive {
+		return Err(Error::AmbiguousScriptReference(reference, candidates));
+	}
+
+	let labels: Vec<String> = candidates
+		.iter()
+		.map(|script| format!("{} ({})", script.name, script.id))
+		.collect();
+	let choice =
+		inquire::Select::new(&format!("which script is {reference}?"), labels.clone()).prompt()?;
+	let index = labels
+		.iter()
+		.position(|label| *label == choice)
+		.unwrap_or_default();
+
+	Ok(candidates[index].id.clone())
+}
 
-	ensure_config_directory_exists().await;
+/// Asks which of several browser sessions to log in with, returning its index.
+fn choose_session(descriptions: &[String]) -> Result<usize, Error> {
+	let choice = inquire::Select::new("Pick a session to use.", descriptions.to_vec()).prompt()?;
+	Ok(
+		descriptions
+			.iter()
+			.position(|description| *description == choice)
+			.unwrap_or_default(),
+	)
+}
+
+/// Prints the sync metadata of a project, nudging towards a remote comparison when it is stale.
+fn print_metadata(meta: Option<&Metadata>) {
+	let Some(meta) = meta else {
+		println!("no sync metadata yet; it is recorded by `fumo pull` and `fumo push`");
+		return;
+	};
+
+	if let Some(creator) = &meta.creator {
+		println!("owned by {creator}");
+	}
+	println!(
+		"last synced at {} with fumo {}",
+		meta.synced_at, meta.fumo_version
+	);
+
+	let age = Utc::now() - meta.synced_at;
+	if age.num_days() >= STALE_METADATA_DAYS {
+		warn!(
+			"last synced {} days ago; run `fumo verify` to compare the project against fumosclub",
+			age.num_days()
+		);
+	}
+}
+
+/// Prints the drift of a workspace member for `status --workspace`.
+fn print_member_status(member: &workspace::MemberStatus) {
+	let configuration = &member.configuration;
+	println!(
+		"{} ({}, {}){}",
+		member.member.display(),
+		configuration.script_name,
+		configuration.script_id,
+		output::package_marker(configuration.project_type == ProjectType::Package)
+	);
+
+	let Some(report) = &member.comparison else {
+		println!("  couldn't be compared against fumosclub");
+		return;
+	};
+	// fumosync.json is covered by the metadata
+	let files: Vec<_> = report
+		.mismatches()
+		.filter(|file| file.path != SYNC_CONFIGURATION_FILE)
+		.collect();
+	for file in &files {
+		println!("  {}: {}", file.state, file.path);
+	}
+	for field in &report.metadata_drift {
+		println!("  metadata: {field}");
+	}
+	if files.is_empty() && report.metadata_drift.is_empty() {
+		println!("  matches fumosclub");
+	}
+}
+
+/// Reads stdin fully for `push --main-stdin` or `--description-stdin`, before anything is asked of
+/// fumosclub.
+async fn read_stdin_sources(
+	main_stdin: bool,
+	description_stdin: bool,
+) -> Result<(Option<String>, Option<String>), Error> {
+	if !main_stdin && !description_stdin {
+		return Ok((None, None));
+	}
+
+	let mut input = String::new();
+	tokio::io::stdin().read_to_string(&mut input).await?;
+	Ok(if main_stdin {
+		(Some(input), None)
+	} else {
+		(None, Some(input))
+	})
+}
+
+/// Gets the directory of the existing project a command works on, if it has one.
+fn existing_project_directory(command: &mut Command) -> Option<&mut PathBuf> {
+	match command {
+		Command::Audit {
+			project_directory, ..
+		}
+		| Command::Push {
+			project_directory, ..
+		}
+		| Command::Prune {
+			project_directory, ..
+		}
+		| Command::Verify {
+			project_directory, ..
+		}
+		| Command::Diff {
+			project_directory, ..
+		}
+		| Command::Export {
+			project_directory, ..
+		}
+		| Command::Link {
+			project_directory, ..
+		}
+		| Command::Unlink { project_directory }
+		| Command::Describe {
+			project_directory, ..
+		}
+		| Command::Status {
+			project_directory, ..
+		}
+		| Command::Watch {
+			project_directory, ..
+		}
+		| Command::Schema {
+			write: true,
+			project_directory,
+		}
+		| Command::Config {
+			global: false,
+			project_directory,
+			..
+		}
+		| Command::Args {
+			project_directory, ..
+		}
+		| Command::Clean {
+			project_directory, ..
+		}
+		| Command::Snapshots {
+			action:
+				SnapshotsAction::List { project_directory }
+				| SnapshotsAction::Restore {
+					project_directory, ..
+				},
+		}
+		| Command::Types {
+			action: TypesAction::Update {
+				project_directory, ..
+			},
+		}
+		| Command::Template {
+			action: TemplateAction::Save {
+				project_directory, ..
+			},
+		}
+		| Command::Module {
+			action: ModuleAction::New {
+				project_directory, ..
+			},
+		}
+		| Command::Whitelist {
+			action: WhitelistAction::Resolve {
+				project_directory, ..
+			},
+		} => Some(project_directory),
+		_ => None,
+	}
+}
+
+async fn main_fn(mut args: Args) -> Result<(), Error> {
+	let format = args.format;
+	let porcelain = args.porcelain;
+	match ensure_config_directory_exists().await {
+		Err(error) if reads_only(&args.command) => {
+			warn!("{error}; continuing, since this command only reads");
+		}
+		result => result?,
+	}
+	if let Some(project_directory) = existing_project_directory(&mut args.command) {
+		*project_directory = resolve_project_root(std::mem::take(project_directory))?;
+	}
+	if !settings::get().quiet {
+		warn_alpha_once_per_day().await;
+	}
 
 	match args.command {
-		Command::View => {
-			let client = Client::new(get_session_secrets().await?);
+		Command::Doctor => {
+			let base_url = &settings::get().base_url;
+			let reachability = probe_reachability(base_url).await?;
+			let clock_skew = if reachability == Reachability::Reachable {
+				measured_clock_skew(probe_clock_skew(base_url).await)
+			} else {
+				None
+			};
+			let config_directory = probe_config_directory().await;
+			let build = BuildInfo::current();
+			// the same checks push runs, when doctor is run inside of a project
+			let project_directory = std::env::current_dir()?;
+			let content_problems = match read_configuration(&project_directory).await {
+				Ok(configuration) => Some(
+					find_content_problems(&project_directory, &configuration)
+						.await
+						.unwrap_or_else(|error| {
+							warn!("couldn't check the contents of files: {error}");
+							Vec::new()
+						}),
+				),
+				Err(..) => None,
+			};
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({
+						"build": build,
+						"baseUrl": base_url,
+						"fumosclub": reachability,
+						"clockSkewSeconds": clock_skew.map(|skew| skew.num_seconds()),
+						"userAgent": get_user_agent(),
+						"configDirectory": match &config_directory {
+							Ok(directory) => json!({ "path": directory, "writable": true }),
+							Err(error) => json!({
+								"path": get_config_directory().ok(),
+								"writable": false,
+								"error": error.to_string(),
+							}),
+						},
+						"contentProblems": content_problems,
+					})
+				),
+				OutputFormat::Text => {
+					println!("{build}");
+					println!(
+						"{}",
+						match reachability {
+							Reachability::Reachable => format!("fumosclub is reachable at {base_url}"),
+							Reachability::ServiceUnavailable => {
+								"fumosclub appears to be down, likely for maintenance; your network is fine"
+									.to_owned()
+							}
+							Reachability::Unreachable => {
+								"fumosclub can't be reached, but other hosts can; it may be down or blocked"
+									.to_owned()
+							}
+							Reachability::NetworkDown => {
+								"no host can be reached; check your network connection".to_owned()
+							}
+						}
+					);
+					if let Some(skew) = clock_skew {
+						println!(
+							"{}",
+							describe_clock_skew(skew)
+								.unwrap_or_else(|| "the system clock agrees with fumosclub's".to_owned())
+						);
+					}
+					println!("user agent: {}", get_user_agent());
+					match &config_directory {
+						Ok(directory) => println!("the config directory {} is writable", directory.display()),
+						Err(error) => {
+							println!("{error}");
+							if let Some(hint) = error.hint() {
+								println!("hint: {hint}");
+							}
+						}
+					}
+					match &content_problems {
+						None => {}
+						Some(problems) if problems.is_empty() => {
+							println!("no content in the project may break in-game");
+						}
+						Some(problems) => {
+							println!("content which may break in-game:");
+							for problem in problems {
+								println!("  {problem}");
+							}
+						}
+					}
+				}
+			}
+		}
+		Command::Auth {
+			action: AuthAction::Status { online },
+		} => {
+			let status = auth_status(online).await?;
+			match format {
+				OutputFormat::Json => println!("{}", json!(status)),
+				OutputFormat::Text => match (&status.failure, &status.account) {
+					(Some(failure), _) => {
+						println!("not logged in: {failure}");
+						if let Some(hint) = failure.hint() {
+							println!("hint: {hint}");
+						}
+					}
+					(None, Some(account)) => println!(
+						"logged in as {} - {} from {}; secrets expire at {}",
+						account.name,
+						account.id,
+						status.source,
+						status.expires.unwrap_or_default()
+					),
+					(None, None) => println!(
+						"logged in from {}; secrets expire at {}",
+						status.source,
+						status.expires.unwrap_or_default()
+					),
+				},
+			}
+
+			if let Some(failure) = status.failure {
+				return Err(failure);
+			}
+		}
+		Command::View { cached: true } => {
+			let secrets = read_session_secrets().await?;
+			let cached = match &secrets.account_id {
+				Some(account_id) => cache::read_details(account_id).await,
+				None => None,
+			}
+			.ok_or(Error::NoCachedDetails)?;
+
+			let details = cached.details;
+			if porcelain.is_some() {
+				println!("{}", porcelain::account(&details, secrets.expires));
+				return Ok(());
+			}
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({ "account": details, "expires": secrets.expires, "fetchedAt": cached.fetched_at })
+				),
+				OutputFormat::Text => println!(
+					"{} - {} - {}\n{} logged in sessions as of {}; secrets expire at {}",
+					details.name,
+					details.roblox_user,
+					details.id,
+					details.num_sessions,
+					cached.fetched_at,
+					secrets.expires
+				),
+			}
+		}
+		Command::View { cached: false } => {
+			let client = Client::new(get_session_secrets().await?)?;
 			let details = client.get_details().await?;
-			println!(
-				"{} - {} - {}\n{} currently logged in sessions",
-				details.name, details.roblox_user, details.id, details.num_sessions
-			);
+			if porcelain.is_some() {
+				println!("{}", porcelain::account(&details, client.secrets.expires));
+				return Ok(());
+			}
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({ "account": details, "expires": client.secrets.expires })
+				),
+				OutputFormat::Text => println!(
+					"{} - {} - {}\n{} currently logged in sessions; secrets expire at {}",
+					details.name,
+					details.roblox_user,
+					details.id,
+					details.num_sessions,
+					client.secrets.expires
+				),
+			}
 		}
-		Command::Init { project_directory } => init(project_directory).await?,
-		Command::Login { spawn_chromium } => {
-			save_session_secrets(if spawn_chromium {
-				use_headful_chrome()
+		Command::Init {
+			project_directory,
+			package,
+			git,
+			template,
+			editor,
+			parents,
+		} => {
+			let project_type = if package {
+				ProjectType::Package
 			} else {
-				use_browser_token().await
-			})
-			.await?
+				ProjectType::Script
+			};
+			init(
+				project_directory.clone(),
+				project_type,
+				&template,
+				editor,
+				parents,
+			)
+			.await?;
+			if git {
+				git::init_repository(&project_directory)?;
+			}
 		}
-		Command::List => {
-			let client = Client::new(get_session_secrets().await?);
-			for script in client.list_scripts().await?.scripts {
+		Command::Login { check: true, .. } => {
+			let secrets = read_session_secrets().await?;
+			let expires = secrets.expires;
+			if format == OutputFormat::Text {
+				println!("secrets expire at {expires}");
+			}
+			warn_if_expiring(&secrets);
+
+			let client = Client::new(validate_session_secrets(secrets).await?)?;
+			let details = client.get_details().await?;
+			let clock_skew = measured_clock_skew(client.clock_skew().await);
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({
+						"account": details,
+						"expires": expires,
+						"valid": true,
+						"clockSkewSeconds": clock_skew.map(|skew| skew.num_seconds()),
+						"userAgent": get_user_agent(),
+					})
+				),
+				OutputFormat::Text => {
+					println!(
+						"{} - {} - {}\nsecrets are valid\nuser agent: {}",
+						details.name,
+						details.roblox_user,
+						details.id,
+						get_user_agent()
+					);
+					if let Some(skew) = clock_skew.and_then(describe_clock_skew) {
+						println!("{skew}");
+					}
+				}
+			}
+		}
+		Command::Login {
+			spawn_chromium,
+			account,
+			token,
+			chrome_path,
+			chrome_profile,
+			..
+		} => {
+			let mut secrets = if let Some(token) = token {
+				validate_session_secrets(Secrets::from_token(&token)?).await?
+			} else if spawn_chromium {
+				use_headful_chrome(chrome_path, chrome_profile)?
+			} else {
+				use_browser_token(
+					account.as_deref(),
+					settings::get().interactive.then_some(choose_session),
+				)
+				.await?
+			};
+
+			// remember the account so expired secrets can be refreshed later
+			if secrets.account_id.is_none() {
+				secrets.account_id = Client::new(secrets.clone())?
+					.get_details()
+					.await
+					.ok()
+					.map(|details| details.id);
+			}
+
+			save_session_secrets(secrets).await?
+		}
+		Command::List {
+			refresh,
+			cached,
+			editable,
+			detailed,
+		} => {
+			let mut details = Vec::new();
+			let mut scripts = if cached {
+				let cached = match read_session_secrets().await?.account_id {
+					Some(account_id) => cache::read_scripts(&account_id).await,
+					None => None,
+				}
+				.ok_or(Error::NoCachedScripts)?;
+
+				warn!(
+					"showing the script list cached at {}; it may be out of date",
+					cached.fetched_at
+				);
+				let mut scripts = cached.scripts;
+				if editable {
+					scripts.retain(|script| script.editable);
+				}
+				if detailed {
+					details = scripts
+						.iter()
+						.map(|script| cached.details.get(&script.id).copied())
+						.collect();
+				}
+				scripts
+			} else {
+				let client = Client::new(get_session_secrets().await?)?;
+				let mut scripts = if refresh {
+					client.live_scripts().await?.to_vec()
+				} else {
+					client.cached_scripts().await?.to_vec()
+				};
+
+				if detailed {
+					let listed = scripts.clone();
+					if editable {
+						scripts.retain(|script| script.editable);
+					}
+					let ids: Vec<&str> = scripts.iter().map(|script| script.id.as_str()).collect();
+					details = client.script_details(&ids).await;
+
+					// so a following pull resolves the script without listing again
+					if let Some(account_id) = &client.secrets.account_id {
+						let fetched = scripts
+							.iter()
+							.zip(&details)
+							.filter_map(|(script, details)| Some((script.id.clone(), (*details)?)))
+							.collect();
+						cache::write_scripts(account_id, &listed, &fetched).await;
+					}
+				}
+				scripts
+			};
+			if editable {
+				scripts.retain(|script| script.editable);
+			}
+
+			if porcelain.is_some() {
+				for (index, script) in scripts.iter().enumerate() {
+					let details = detailed.then(|| details.get(index).copied().flatten());
+					println!("{}", porcelain::script(script, details));
+				}
+				return Ok(());
+			}
+
+			if format == OutputFormat::Json {
+				if !detailed {
+					println!("{}", json!({ "scripts": scripts }));
+					return Ok(());
+				}
+
+				let scripts = scripts
+					.iter()
+					.zip(&details)
+					.map(|(script, details)| {
+						let mut value = serde_json::to_value(script)?;
+						value["details"] = json!(details);
+						Ok(value)
+					})
+					.collect::<Result<Vec<_>, Error>>()?;
+				println!("{}", json!({ "scripts": scripts }));
+				return Ok(());
+			}
+
+			for (index, script) in scripts.iter().enumerate() {
+				let details = match (detailed, details.get(index).copied().flatten()) {
+					(false, _) => String::new(),
+					(true, Some(details)) => format!(
+						" [{} modules, {} bytes, {}]",
+						details.module_count,
+						details.source_bytes,
+						if details.has_description {
+							"described"
+						} else {
+							"no description"
+						}
+					),
+					(true, None) => " [? modules, ? bytes, ?]".to_owned(),
+				};
+
 				println!(
-					"{} {} ({}) by {} {}",
-					if script.is_favorite { "★" } else { "☆" },
+					"{} {} ({}){} by {} {}{details}",
+					output::favorite_marker(script.is_favorite),
 					script.name,
 					script.id,
+					output::package_marker(matches!(script.r#type, ScriptType::Package)),
 					script.creator,
-					if script.editable { "🔓" } else { "🔐" }
+					output::editable_marker(script.editable)
 				);
 			}
 		}
 		Command::Pull {
 			script_id,
 			project_directory,
+			force,
+			allow_dirty,
+			keep_types,
+			config_only,
+			parents,
+		} => {
+			if config_only {
+				let drift = match pull_configuration(&script_id, &project_directory).await {
+					Err(Error::AmbiguousScriptReference(reference, candidates)) => {
+						let script_id = choose_script(reference, candidates)?;
+						pull_configuration(&script_id, &project_directory).await?
+					}
+					drift => drift?,
+				};
+				match format {
+					OutputFormat::Json => println!("{}", json!({ "adopted": drift })),
+					OutputFormat::Text if drift.is_empty() => info!("metadata already matches fumosclub"),
+					OutputFormat::Text => drift
+						.iter()
+						.for_each(|drift| info!("adopted remote metadata: {drift}")),
+				}
+				return Ok(());
+			}
+
+			let pulled = pull(
+				script_id,
+				project_directory.clone(),
+				force,
+				allow_dirty,
+				keep_types,
+				parents,
+			)
+			.await;
+			match pulled {
+				// only the empty project directory exists by then, which pulling again reuses
+				Err(Error::AmbiguousScriptReference(reference, candidates)) => {
+					let script_id = choose_script(reference, candidates)?;
+					pull(
+						script_id,
+						project_directory,
+						force,
+						allow_dirty,
+						keep_types,
+						parents,
+					)
+					.await?;
+				}
+				pulled => pulled?,
+			}
+		}
+		Command::Clone {
+			script_id,
+			dir,
+			parents,
+		} => {
+			let directory = match clone_script(&script_id, dir.clone(), parents).await {
+				Err(Error::AmbiguousScriptReference(reference, candidates)) => {
+					clone_script(&choose_script(reference, candidates)?, dir, parents).await?
+				}
+				directory => directory?,
+			};
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "path": directory })),
+				OutputFormat::Text => println!("{}", directory.display()),
+			}
+		}
+		Command::Export {
+			output,
+			project_directory,
+		} => {
+			let files = archive::export(&project_directory, &output).await?;
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "path": output, "files": files })),
+				OutputFormat::Text => info!("archived {files} files to {}", output.display()),
+			}
+		}
+		Command::Import {
+			archive,
+			directory,
+			force,
+		} => {
+			let manifest = archive::import(&archive, &directory, force).await?;
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "manifest": manifest })),
+				OutputFormat::Text => info!(
+					"imported {} files of script {} into {}",
+					manifest.files.len(),
+					manifest.script_id,
+					directory.display()
+				),
+			}
+		}
+		Command::Link {
+			script_id,
+			project_directory,
 		} => {
-			pull(script_id, project_directory).await?;
+			let report = match link(&project_directory, &script_id).await {
+				Err(Error::AmbiguousScriptReference(reference, candidates)) => {
+					link(&project_directory, &choose_script(reference, candidates)?).await?
+				}
+				report => report?,
+			};
+			if format == OutputFormat::Json {
+				println!("{}", json!(report));
+				return Ok(());
+			}
+
+			info!("linked to {}", report.script_id);
+			let mismatches = report.mismatches().count();
+			if mismatches > 0 {
+				warn!(
+					"{mismatches} of {} files differ from the script; `fumo push` will overwrite them, and `fumo pull --force` will take them",
+					report.files.len()
+				);
+				print_mismatches("", &report.files);
+			}
+		}
+		Command::Unlink { project_directory } => {
+			unlink(&project_directory).await?;
+			info!("unlinked {}", project_directory.display());
 		}
+		Command::Describe {
+			edit,
+			project_directory,
+			..
+		} => {
+			if edit {
+				description::edit(&project_directory).await?;
+				info!("pushed the description");
+			} else {
+				let text = read_file(project_directory.join(DESCRIPTION_FILE)).await?;
+				description::check(&text)?;
+				print!("{}", output::markdown(&text));
+			}
+		}
+		Command::Status {
+			project_directory,
+			workspace,
+			..
+		} if workspace || workspace::is_workspace_root(&project_directory)? => {
+			let members = workspace::status_workspace(&project_directory).await?;
+			if porcelain.is_some() {
+				// every member starts with its own project line
+				for member in &members {
+					for line in porcelain::status(
+						&member.configuration,
+						member.meta.as_ref(),
+						member.comparison.as_ref(),
+						&[],
+					) {
+						println!("{line}");
+					}
+				}
+				return Ok(());
+			}
 
-		Command::Push { project_directory } => push(project_directory).await?,
-		Command::Generate { id } => {
-			let client = Client::new(get_session_secrets().await?);
-			let id = match id {
-				Some(id) => id,
-				None => read_configuration(".").await?.script_id,
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({
+						"v": REPORT_VERSION,
+						"members": members
+							.iter()
+							.map(|member| json!({
+								"member": member.member,
+								"scriptName": member.configuration.script_name,
+								"scriptId": member.configuration.script_id,
+								"type": member.configuration.project_type,
+								"meta": member.meta,
+								"comparison": member.comparison,
+							}))
+							.collect::<Vec<_>>(),
+					})
+				),
+				OutputFormat::Text => {
+					for member in &members {
+						print_member_status(member);
+					}
+				}
+			}
+		}
+		Command::Summary { project_directory } => {
+			if !find_configuration_file(&project_directory)?.try_exists()? {
+				Args::command().print_help()?;
+				return Ok(());
+			}
+
+			let configuration = read_configuration(&project_directory).await?;
+			let meta = read_metadata(&project_directory, &configuration).await;
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({
+						"scriptName": configuration.script_name,
+						"scriptId": configuration.script_id,
+						"type": configuration.project_type,
+						"meta": meta,
+					})
+				),
+				OutputFormat::Text => {
+					println!(
+						"{} ({}){}",
+						configuration.script_name,
+						configuration.script_id,
+						output::package_marker(configuration.project_type == ProjectType::Package)
+					);
+					print_metadata(meta.as_ref());
+					println!(
+						"run `fumo status` to compare it against fumosclub, or `fumo help` for commands"
+					);
+				}
+			}
+		}
+		Command::Status {
+			project_directory,
+			fix_name,
+			..
+		} => {
+			let mut configuration = read_configuration(&project_directory).await?;
+			let meta = read_metadata(&project_directory, &configuration).await;
+			// status is still useful offline, so the remote comparison is left out when it fails
+			let mut comparison = match compare_remote(&project_directory, false).await {
+				Ok(report) => Some(report),
+				Err(error) => {
+					warn!("couldn't compare the project against fumosclub: {error}");
+					None
+				}
 			};
 
-			println!("{}", client.generate_key(&id).await?);
+			let remote_name = comparison
+				.iter()
+				.flat_map(|report| &report.metadata_drift)
+				.find_map(MetadataDrift::remote_name)
+				.map(str::to_owned);
+			match (&mut comparison, remote_name) {
+				(Some(report), Some(name)) if fix_name => {
+					set_configuration_value(
+						&project_directory,
+						"scriptName",
+						&serde_json::to_string(&name)?,
+					)
+					.await?;
+					info!("set scriptName to {name:?}");
+					let mut drift = std::mem::take(&mut report.metadata_drift);
+					drift.retain(|field| field.remote_name().is_none());
+					report.set_metadata_drift(drift);
+					configuration.script_name = name;
+				}
+				(Some(..), None) if fix_name => info!("scriptName already matches fumosclub"),
+				(_, remote_name) => check_name(&project_directory, &configuration, remote_name.as_deref()),
+			}
+
+			let empty_files = if configuration.allows_empty_files() {
+				Vec::new()
+			} else {
+				find_empty_files(&project_directory, &configuration)
+					.await
+					.unwrap_or_else(|error| {
+						warn!("couldn't check for empty files: {error}");
+						Vec::new()
+					})
+			};
+
+			let content_problems = find_content_problems(&project_directory, &configuration)
+				.await
+				.unwrap_or_else(|error| {
+					warn!("couldn't check the contents of files: {error}");
+					Vec::new()
+				});
+
+			if porcelain.is_some() {
+				for line in porcelain::status(
+					&configuration,
+					meta.as_ref(),
+					comparison.as_ref(),
+					&empty_files,
+				) {
+					println!("{line}");
+				}
+				return Ok(());
+			}
+
+			if format == OutputFormat::Json {
+				println!(
+					"{}",
+					json!({
+						"v": REPORT_VERSION,
+						"scriptName": configuration.script_name,
+						"scriptId": configuration.script_id,
+						"type": configuration.project_type,
+						"meta": meta,
+						"comparison": comparison,
+						"emptyFiles": empty_files,
+						"contentProblems": content_problems,
+					})
+				);
+				return Ok(());
+			}
+
+			println!(
+				"{} ({}){}",
+				configuration.script_name,
+				configuration.script_id,
+				output::package_marker(configuration.project_type == ProjectType::Package)
+			);
+			print_metadata(meta.as_ref());
+
+			if let Some(report) = &comparison {
+				// fumosync.json is covered by the metadata below
+				let files: Vec<_> = report
+					.mismatches()
+					.filter(|file| file.path != SYNC_CONFIGURATION_FILE)
+					.collect();
+				if !files.is_empty() {
+					println!("\nfiles differing from fumosclub:");
+					for file in files {
+						println!("  {}: {}", file.state, file.path);
+					}
+				}
+			}
+
+			match comparison
+				.as_ref()
+				.map(|report| report.metadata_drift.as_slice())
+			{
+				None | Some([]) => {}
+				Some(drift) => {
+					println!("\nmetadata differs from fumosclub:");
+					for field in drift {
+						println!("  {field}");
+					}
+					if configuration.syncs_metadata() {
+						println!(
+							"`fumo push` overwrites the remote metadata; `fumo pull --config-only` adopts it instead"
+						);
+					}
+				}
+			}
+
+			if !empty_files.is_empty() {
+				println!("\nempty files, which `fumo push` asks about before uploading:");
+				for file in &empty_files {
+					println!("  {file}");
+				}
+			}
+			if !content_problems.is_empty() {
+				println!("\ncontent which may break in-game:");
+				for problem in &content_problems {
+					println!("  {problem}");
+				}
+			}
+		}
+		Command::Fetch {
+			id,
+			output,
+			module,
+			force,
+		} => {
+			let fetched = match fetch(&id, output.clone(), module.as_deref(), force).await {
+				Err(Error::AmbiguousScriptReference(reference, candidates)) => {
+					let id = choose_script(reference, candidates)?;
+					fetch(&id, output, module.as_deref(), force).await?
+				}
+				fetched => fetched?,
+			};
+			match fetched {
+				Fetched::Stdout(source) => std::io::stdout().write_all(source.as_bytes())?,
+				Fetched::File(path) => match format {
+					OutputFormat::Json => println!("{}", json!({ "path": path })),
+					OutputFormat::Text => info!("wrote {}", path.display()),
+				},
+			}
 		}
 
-		Command::Watch { project_directory } => {
-			watch(project_directory).await?;
+		Command::Push {
+			project_directory,
+			dry_run: true,
+			strict,
+			prune,
+			ignore,
+			main_stdin,
+			description_stdin,
+			..
+		} => {
+			let (main_source, description) = read_stdin_sources(main_stdin, description_stdin).await?;
+			let client = Client::new(get_session_secrets().await?)?;
+			let options = PushOptions {
+				dry_run: true,
+				strict,
+				prune,
+				ignore,
+				main_source,
+				description,
+				..PushOptions::default()
+			};
+			let report = push_project(&client, &project_directory, options).await?;
+			match format {
+				OutputFormat::Json => println!("{}", json!(report)),
+				OutputFormat::Text => {
+					for update in report.updates {
+						println!("would upload {}", update.update);
+					}
+				}
+			}
+		}
+		Command::Push {
+			project_directory,
+			description_only: true,
+			description_stdin,
+			..
+		} => {
+			let (_, description) = read_stdin_sources(false, description_stdin).await?;
+			let client = Client::new(get_session_secrets().await?)?;
+			push_part(
+				&client,
+				project_directory,
+				PushPart::Description,
+				description,
+			)
+			.await?;
 		}
+		Command::Push {
+			project_directory,
+			meta_only: true,
+			..
+		} => {
+			let client = Client::new(get_session_secrets().await?)?;
+			push_part(&client, project_directory, PushPart::Metadata, None).await?;
+		}
+		Command::Push {
+			project_directory,
+			workspace,
+			allow_empty,
+			force,
+			strict,
+			prune,
+			fix_names,
+			ignore,
+			main_stdin,
+			description_stdin,
+			..
+		} => {
+			let is_workspace = workspace || workspace::is_workspace_root(&project_directory)?;
+			if is_workspace && (main_stdin || description_stdin) {
+				return Err(Error::WorkspaceStdin(project_directory));
+			}
+
+			if is_workspace {
+				workspace::push_workspace(&project_directory, allow_empty, strict, confirm_callback())
+					.await?;
+			} else if !confirm_public_push(&project_directory).await? {
+				info!("nothing was pushed");
+			} else {
+				let (main_source, description) = read_stdin_sources(main_stdin, description_stdin).await?;
+				let options = PushOptions {
+					allow_empty,
+					force,
+					strict,
+					prune,
+					fix_names,
+					ignore,
+					main_source,
+					description,
+					confirm: confirm_callback(),
+					..PushOptions::default()
+				};
+				push(project_directory, options).await?;
+			}
+		}
+		Command::Prune {
+			project_directory,
+			yes,
+			dry_run,
+		} => {
+			let client = Client::new(get_session_secrets().await?)?;
+			let orphaned = find_orphaned_modules(&client, &project_directory).await?;
+			let confirmed = if orphaned.is_empty() || dry_run || yes {
+				!dry_run
+			} else if !settings::get().interactive {
+				return Err(Error::InteractionRequired(format!(
+					"{} remote modules would be deleted; pass --yes to prune them",
+					orphaned.len()
+				)));
+			} else {
+				if format == OutputFormat::Text {
+					for name in &orphaned {
+						println!("{name}");
+					}
+				}
+				inquire::Confirm::new(&format!("delete {} remote modules?", orphaned.len()))
+					.with_default(false)
+					.prompt()?
+			};
+
+			let pruned = confirmed && !orphaned.is_empty();
+			if pruned {
+				prune_modules(&client, &project_directory, &orphaned).await?;
+			}
+
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({ "modules": orphaned, "pruned": pruned, "dryRun": dry_run })
+				),
+				OutputFormat::Text if orphaned.is_empty() => {
+					info!("every remote module has a file in pkg/")
+				}
+				OutputFormat::Text if dry_run || yes => {
+					for name in &orphaned {
+						println!(
+							"{}{name}",
+							if pruned { "deleted " } else { "would delete " }
+						);
+					}
+				}
+				OutputFormat::Text if pruned => info!("deleted {} remote modules", orphaned.len()),
+				OutputFormat::Text => info!("nothing was deleted"),
+			}
+		}
+		Command::Verify {
+			project_directory,
+			workspace,
+		} => {
+			if workspace || workspace::is_workspace_root(&project_directory)? {
+				let members = workspace::verify_workspace(&project_directory).await?;
+				let failed = members
+					.iter()
+					.filter(|member| member.error.is_some())
+					.count();
+				let mismatches: usize = members
+					.iter()
+					.filter_map(|member| member.files.as_ref())
+					.map(|files| report::mismatches(files).count())
+					.sum();
+
+				match format {
+					OutputFormat::Json => println!(
+						"{}",
+						json!({
+							"v": REPORT_VERSION,
+							"matches": mismatches == 0 && failed == 0,
+							"members": members,
+						})
+					),
+					OutputFormat::Text => {
+						for member in &members {
+							if let Some(files) = &member.files {
+								print_mismatches(&format!("{}/", member.member.display()), files);
+							}
+						}
+					}
+				}
+
+				if failed > 0 {
+					return Err(Error::WorkspaceFailed(failed, members.len()));
+				}
+				if mismatches > 0 {
+					return Err(Error::VerificationFailed(mismatches));
+				}
+			} else {
+				let report = compare_remote(project_directory, false).await?;
+				let mismatches = report.mismatches().count();
+
+				match format {
+					OutputFormat::Json => {
+						let mut document = json!(report);
+						document["matches"] = json!(mismatches == 0);
+						println!("{document}");
+					}
+					OutputFormat::Text => print_mismatches("", &report.files),
+				}
+
+				if mismatches > 0 {
+					return Err(Error::VerificationFailed(mismatches));
+				}
+			}
+		}
+		Command::Diff {
+			project_directory,
+			stat,
+			name_only,
+		} => {
+			let report = compare_remote(project_directory, true).await?;
+			let mismatches = report::sorted_mismatches(&report.files);
+			match format {
+				OutputFormat::Json => println!("{}", json!(report)),
+				OutputFormat::Text if name_only => {
+					for file in &mismatches {
+						println!("{}", file.path);
+					}
+				}
+				OutputFormat::Text if stat => {
+					if !mismatches.is_empty() {
+						print!("{}", report::format_stat(&mismatches));
+					}
+				}
+				OutputFormat::Text => {
+					for file in report.mismatches() {
+						match &file.diff {
+							Some(diff) => print!("{diff}"),
+							// fumosync.json is compared by fields
+							None => println!("{}: {}", file.state, file.path),
+						}
+					}
+					for field in &report.metadata_drift {
+						println!("{field}");
+					}
+				}
+			}
+
+			// like verify, so scripts can gate on the summary modes
+			if (stat || name_only) && !mismatches.is_empty() {
+				return Err(Error::VerificationFailed(mismatches.len()));
+			}
+		}
+		Command::Generate { id, no_store } => {
+			let (client, id) = match id {
+				Some(id) => {
+					let client = Client::new(get_session_secrets().await?)?;
+					let id = match resolve_script_reference(&client, &id).await {
+						Err(Error::AmbiguousScriptReference(reference, candidates)) => {
+							ScriptId::new(&choose_script(reference, candidates)?)?
+						}
+						id => id?,
+					};
+					(client, id)
+				}
+				None => {
+					// checked before logging in, so a missing or unlinked project costs no requests
+					let configuration = read_linked_configuration(".").await?;
+					// keys can't be generated for packages, so don't bother asking fumosclub
+					if configuration.project_type == ProjectType::Package {
+						return Err(Error::InvalidKeyGenerationTarget);
+					}
+
+					let client = Client::new(get_session_secrets().await?)?;
+					(client, configuration.linked_script_id()?)
+				}
+			};
+
+			let key = client.generate_key(&id).await?;
+			if !no_store {
+				match keys::store_key(&id, &key).await {
+					Ok(Some(previous)) if previous.key != key => info!(
+						"replaced the key remembered from {}, which may no longer work",
+						previous.generated_at
+					),
+					Ok(..) => {}
+					Err(error) => warn!("failed remembering the key: {error}"),
+				}
+			}
+
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "id": id, "key": key })),
+				OutputFormat::Text => println!("{key}"),
+			}
+		}
+
+		Command::Key { action } => match action {
+			KeyAction::Show { id } => {
+				let id = match id {
+					Some(id) => id,
+					None => read_linked_configuration(".").await?.linked_script_id()?,
+				};
+
+				let stored = keys::read_keys()
+					.await?
+					.remove(id.as_str())
+					.ok_or_else(|| Error::NoStoredKey(id.to_string()))?;
+				match format {
+					OutputFormat::Json => println!(
+						"{}",
+						json!({ "id": id, "key": stored.key, "generatedAt": stored.generated_at })
+					),
+					OutputFormat::Text => println!("{}", stored.key),
+				}
+			}
+			KeyAction::List => {
+				let keys = keys::read_keys().await?;
+				// names are a nicety, so the keys are still listed without them
+				let scripts = match get_session_secrets().await.and_then(Client::new) {
+					Ok(client) => client.cached_scripts().await.map(<[_]>::to_vec),
+					Err(error) => Err(error),
+				}
+				.unwrap_or_else(|error| {
+					warn!("failed getting script names: {error}");
+					Vec::new()
+				});
+				let name_of = |id: &str| {
+					scripts
+						.iter()
+						.find(|script| script.id == id)
+						.map(|script| script.name.clone())
+				};
+
+				if format == OutputFormat::Json {
+					let keys: Vec<_> = keys
+						.iter()
+						.map(|(id, stored)| {
+							json!({
+								"id": id,
+								"name": name_of(id),
+								"key": stored.key,
+								"generatedAt": stored.generated_at,
+							})
+						})
+						.collect();
+					println!("{}", json!({ "keys": keys }));
+					return Ok(());
+				}
+
+				for (id, stored) in &keys {
+					println!(
+						"{} ({id}) at {}: {}",
+						name_of(id).unwrap_or_else(|| "unknown script".to_owned()),
+						stored.generated_at,
+						stored.key
+					);
+				}
+			}
+		},
+
+		Command::Watch {
+			project_directory,
+			continue_on_error,
+			snapshot_interval,
+			snapshot_keep,
+			dry_run,
+			exit_after,
+			exit_on_idle,
+			force,
+			session_keepalive,
+			verify_interval,
+			on_remote_edit,
+			watch_metadata,
+			strict,
+			..
+		} => {
+			let tasks = WatchTasks {
+				snapshots: snapshot_interval.map(|interval| SnapshotSchedule {
+					interval,
+					keep: snapshot_keep,
+				}),
+				verification: verify_interval.map(|interval| VerifySchedule {
+					interval,
+					on_remote_edit,
+				}),
+				session_keepalive,
+			};
+			let options = WatchOptions {
+				continue_on_error,
+				dry_run,
+				force,
+				strict,
+				watch_metadata,
+				limits: WatchLimits {
+					exit_after,
+					exit_on_idle,
+				},
+				tasks,
+				confirm: confirm_callback(),
+			};
+			watch(project_directory, options).await?;
+		}
+		Command::Schema {
+			write: true,
+			project_directory,
+		} => {
+			let path = write_schema(&project_directory).await?;
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "path": path })),
+				OutputFormat::Text => info!("wrote {}", path.display()),
+			}
+		}
+		Command::Schema { .. } => print!("{}", schema::SCHEMA),
+		Command::Completions {
+			action: Some(CompletionsAction::Install {
+				shell,
+				dynamic,
+				dry_run,
+			}),
+			..
+		} => {
+			let installation =
+				completions::install(shell, &mut Args::command(), dynamic, dry_run).await?;
+			match format {
+				OutputFormat::Json => println!("{}", json!(installation)),
+				OutputFormat::Text if dry_run => {
+					println!("would write {}:", installation.path.display());
+					print!("{}", installation.contents);
+				}
+				OutputFormat::Text => info!(
+					"wrote {} completions to {}",
+					installation.shell,
+					installation.path.display()
+				),
+			}
+			if let Some(step) = &installation.manual_step {
+				info!("to finish, {step}");
+			}
+		}
+		Command::Completions {
+			shell,
+			dynamic,
+			action: None,
+		} => {
+			let shell = completions::detect_shell(shell)?;
+			if !completions::generate(shell, &mut Args::command(), dynamic) {
+				warn!("{shell} has no dynamic completions; only static ones were printed");
+			}
+		}
+		Command::Complete { target } => completions::complete(target).await,
+		Command::Clean {
+			project_directory,
+			cache,
+			backups,
+			snapshots,
+			all,
+		} => {
+			let kinds: Vec<StateKind> = if all {
+				StateKind::ALL.to_vec()
+			} else {
+				StateKind::ALL
+					.into_iter()
+					.zip([cache, backups, snapshots])
+					.filter_map(|(kind, selected)| selected.then_some(kind))
+					.collect()
+			};
+			let cleaned = state::clean(&project_directory, &kinds).await?;
+			let freed: u64 = cleaned.iter().map(|cleaned| cleaned.bytes).sum();
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "cleaned": cleaned, "freedBytes": freed })),
+				OutputFormat::Text if cleaned.is_empty() => info!("nothing to clean"),
+				OutputFormat::Text => {
+					for cleaned in &cleaned {
+						info!(
+							"deleted {} ({} bytes)",
+							cleaned.directory.display(),
+							cleaned.bytes
+						);
+					}
+					info!("freed {freed} bytes");
+				}
+			}
+		}
+		Command::Snapshots { action } => match action {
+			SnapshotsAction::List { project_directory } => {
+				let timestamps = snapshot::list(&project_directory).await?;
+				match format {
+					OutputFormat::Json => println!("{}", json!({ "snapshots": timestamps })),
+					OutputFormat::Text => timestamps
+						.iter()
+						.for_each(|timestamp| println!("{timestamp}")),
+				}
+			}
+			SnapshotsAction::Restore {
+				timestamp,
+				project_directory,
+			} => {
+				snapshot::restore(&project_directory, &timestamp).await?;
+				info!("restored snapshot {timestamp}");
+			}
+		},
+
+		Command::Version => {
+			let build = BuildInfo::current();
+			match format {
+				OutputFormat::Json => println!("{}", json!(build)),
+				OutputFormat::Text => println!("{build}"),
+			}
+		}
+		#[cfg(feature = "self-update")]
+		Command::SelfUpdate { check: true } => {
+			let check = update::check().await?;
+			match format {
+				OutputFormat::Json => println!(
+					"{}",
+					json!({
+						"current": check.current.package_version,
+						"latest": check.latest,
+						"available": check.available,
+						"build": check.current,
+					})
+				),
+				OutputFormat::Text if check.available => println!(
+					"fumo {} is available (currently {}); run `fumo self-update`",
+					check.latest, check.current.package_version
+				),
+				OutputFormat::Text => println!("{} is up to date", check.current),
+			}
+		}
+		#[cfg(feature = "self-update")]
+		Command::SelfUpdate { check: false } => {
+			let installed = update::update().await?;
+			match (format, installed) {
+				(OutputFormat::Json, installed) => println!("{}", json!({ "installed": installed })),
+				(OutputFormat::Text, Some(version)) => println!("updated fumo to {version}"),
+				(OutputFormat::Text, None) => println!("fumo is already up to date"),
+			}
+		}
+
+		Command::Types {
+			action: TypesAction::Update {
+				project_directory,
+				url,
+				yes,
+			},
+		} => {
+			let Some(update) = types::prepare_update(&project_directory, url.as_deref()).await? else {
+				info!("{} is already up to date", types::TYPES_FILE);
+				return Ok(());
+			};
+
+			let confirmed = match &update.local_edits {
+				Some(diff) if !yes => {
+					println!("{diff}");
+					if !settings::get().interactive {
+						return Err(Error::InteractionRequired(format!(
+							"{} has local edits; pass --yes to overwrite them",
+							types::TYPES_FILE
+						)));
+					}
+
+					inquire::Confirm::new(&format!(
+						"{} has local edits; overwrite them?",
+						types::TYPES_FILE
+					))
+					.with_default(false)
+					.prompt()?
+				}
+				_ => true,
+			};
+			if confirmed {
+				update.apply().await?;
+				info!("updated {}", types::TYPES_FILE);
+			} else {
+				info!("{} was left as it was", types::TYPES_FILE);
+			}
+		}
+
+		Command::Template { action } => match action {
+			TemplateAction::List => {
+				let templates = list_templates().await?;
+				match format {
+					OutputFormat::Json => println!("{}", json!({ "templates": templates })),
+					OutputFormat::Text => templates.iter().for_each(|name| println!("{name}")),
+				}
+			}
+			TemplateAction::Save {
+				name,
+				project_directory,
+			} => {
+				let directory = save_template(&project_directory, &name).await?;
+				info!("saved template {name} to {}", directory.display());
+			}
+		},
+
+		Command::Module { action } => match action {
+			ModuleAction::New {
+				name,
+				project_directory,
+				template,
+				open,
+			} => {
+				let module = new_module(&project_directory, &name, &template).await?;
+				let require = format!("requireM(\"{}\")", module.remote_name);
+				match format {
+					OutputFormat::Json => println!(
+						"{}",
+						json!({
+							"path": module.path,
+							"module": module.remote_name,
+							"require": require,
+						})
+					),
+					OutputFormat::Text => {
+						info!("created {}", module.path.display());
+						println!("{require}");
+						info!("a running `fumo watch` uploads it, otherwise run `fumo push`");
+					}
+				}
+
+				if open {
+					if !settings::get().interactive {
+						return Err(Error::InteractionRequired(
+							"--open opens an editor, which needs a terminal".to_owned(),
+						));
+					}
+					open_in_editor(&module.path).await?;
+				}
+			}
+		},
+
+		Command::Whitelist { action } => match action {
+			WhitelistAction::Resolve {
+				entries,
+				project_directory,
+			} => {
+				let users = whitelist::resolve(&project_directory, entries).await?;
+				if format == OutputFormat::Json {
+					println!("{}", json!({ "whitelist": users }));
+					return Ok(());
+				}
+
+				let id_width = users
+					.iter()
+					.map(|user| user.id.to_string().len())
+					.max()
+					.unwrap_or_default();
+				let name_width = users
+					.iter()
+					.map(|user| user.name.len())
+					.max()
+					.unwrap_or_default();
+				for user in users {
+					println!(
+						"{:<id_width$}  {:<name_width$}  {}",
+						user.id, user.name, user.display_name
+					);
+				}
+			}
+		},
+
+		Command::Audit {
+			project_directory,
+			remote,
+			diff,
+		} => {
+			let configuration = read_configuration(&project_directory).await?;
+			let entries = if diff {
+				let remote = whitelist::get_remote_whitelist(&configuration).await?;
+				whitelist::audit_diff(&configuration.whitelist, &remote).await
+			} else if remote {
+				whitelist::audit(&whitelist::get_remote_whitelist(&configuration).await?).await
+			} else {
+				whitelist::audit(&configuration.whitelist).await
+			};
+
+			if format == OutputFormat::Json {
+				println!("{}", json!({ "whitelist": entries }));
+				return Ok(());
+			}
+
+			let rows: Vec<[String; 5]> = entries
+				.iter()
+				.map(|entry| {
+					let (name, display_name) = match (&entry.user, entry.status) {
+						(Some(user), _) => (user.name.clone(), user.display_name.clone()),
+						(None, AuditStatus::Missing) => ("(deleted)".to_owned(), String::new()),
+						(None, _) => ("unknown".to_owned(), String::new()),
+					};
+					let presence = match entry.presence {
+						Some(Presence::Both) => "both",
+						Some(Presence::LocalOnly) => "local only",
+						Some(Presence::RemoteOnly) => "remote only",
+						None => "",
+					};
+
+					[
+						entry.entry.clone(),
+						name,
+						display_name,
+						entry.avatar_url.clone().unwrap_or_default(),
+						presence.to_owned(),
+					]
+				})
+				.collect();
+			let width = |column: usize| {
+				rows
+					.iter()
+					.map(|row| row[column].chars().count())
+					.max()
+					.unwrap_or_default()
+			};
+			let (id_width, name_width, display_name_width) = (width(0), width(1), width(2));
+			for [id, name, display_name, avatar_url, presence] in rows {
+				println!(
+					"{}",
+					format!(
+						"{id:<id_width$}  {name:<name_width$}  {display_name:<display_name_width$}  {presence}  {avatar_url}"
+					)
+					.trim_end()
+				);
+			}
+		}
+		Command::Args {
+			project_directory,
+			action,
+		} => {
+			let arguments = match action {
+				ArgsAction::Get => read_configuration(&project_directory).await?.arguments,
+				ArgsAction::Set { values } => arguments::set(&project_directory, &values).await?,
+				ArgsAction::Clear => arguments::set(&project_directory, &[]).await?,
+			};
+
+			match format {
+				OutputFormat::Json => println!("{}", json!({ "arguments": arguments })),
+				OutputFormat::Text => println!("{}", serde_json::Value::Array(arguments)),
+			}
+		}
+		Command::Config {
+			global,
+			project_directory,
+			action,
+		} => match action {
+			ConfigAction::Get { key } => {
+				let value = if global {
+					get_global_value(&key).await?
+				} else {
+					get_configuration_value(project_directory, &key)
+						.await?
+						.map(|value| match value {
+							serde_json::Value::String(string) => string,
+							value => value.to_string(),
+						})
+				};
+
+				match (format, value) {
+					(OutputFormat::Json, value) => println!("{}", json!({ "key": key, "value": value })),
+					(OutputFormat::Text, Some(value)) => println!("{value}"),
+					(OutputFormat::Text, None) => warn!("{key} is not set"),
+				}
+			}
+			ConfigAction::Set { key, value } => {
+				if global {
+					set_global_value(&key, &value).await?
+				} else {
+					set_configuration_value(project_directory, &key, &value).await?
+				}
+			}
+		},
 	}
 
 	Ok(())
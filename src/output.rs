@@ -0,0 +1,101 @@
+use crate::settings;
+use clap::ValueEnum;
+use owo_colors::OwoColorize;
+use serde::Deserialize;
+use std::io::IsTerminal;
+
+/// When output should be colored.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+	/// Color terminals, unless NO_COLOR is set
+	#[default]
+	Auto,
+	Always,
+	Never,
+}
+
+impl ColorChoice {
+	/// Resolves whether a stream should be colored.
+	pub fn enabled_for(self, stream: &impl IsTerminal) -> bool {
+		match self {
+			Self::Always => true,
+			Self::Never => false,
+			Self::Auto => {
+				std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && stream.is_terminal()
+			}
+		}
+	}
+}
+
+fn stdout_colored() -> bool {
+	settings::get().color.enabled_for(&std::io::stdout())
+}
+
+fn stderr_colored() -> bool {
+	settings::get().color.enabled_for(&std::io::stderr())
+}
+
+/// Formats an error line for stderr.
+pub fn error(text: &str) -> String {
+	if stderr_colored() {
+		text.red().to_string()
+	} else {
+		text.to_owned()
+	}
+}
+
+/// Formats a hint line for stderr.
+pub fn hint(text: &str) -> String {
+	if stderr_colored() {
+		text.yellow().to_string()
+	} else {
+		text.to_owned()
+	}
+}
+
+/// Formats the favorite marker of a script for stdout.
+pub fn favorite_marker(is_favorite: bool) -> String {
+	match (is_favorite, stdout_colored()) {
+		(true, true) => "★".yellow().to_string(),
+		(true, false) => "★".to_owned(),
+		(false, _) => "☆".to_owned(),
+	}
+}
+
+/// Formats the package marker of a script for stdout; regular scripts have none.
+pub fn package_marker(is_package: bool) -> String {
+	match (is_package, stdout_colored()) {
+		(true, true) => format!(" {}", "[package]".cyan()),
+		(true, false) => " [package]".to_owned(),
+		(false, _) => String::new(),
+	}
+}
+
+/// Renders Markdown for stdout.
+pub fn markdown(text: &str) -> String {
+	let skin = if stdout_colored() {
+		termimad::MadSkin::default()
+	} else {
+		termimad::MadSkin::no_style()
+	};
+
+	skin.term_text(text).to_string()
+}
+
+/// Formats the editable marker of a script for stdout.
+pub fn editable_marker(editable: bool) -> String {
+	match (editable, stdout_colored()) {
+		(true, true) => "🔓".green().to_string(),
+		(true, false) => "🔓".to_owned(),
+		(false, true) => "🔐".red().to_string(),
+		(false, false) => "🔐".to_owned(),
+	}
+}
+
+/// Rings the terminal bell on stderr, if notifications are enabled and stderr is a terminal.
+pub fn notify_failure() {
+	if settings::get().notifications && std::io::stderr().is_terminal() {
+		eprint!("\x07");
+	}
+}
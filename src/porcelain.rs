@@ -0,0 +1,285 @@
+use crate::{
+	client::{AccountDetails, Script, ScriptDetails, ScriptType},
+	project::{Configuration, Metadata, ProjectType},
+	report::{FileState, Report, sorted_mismatches},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+
+/// Versions of the output `--porcelain` keeps stable for scripts; unlike the text output, a
+/// released version is never changed, only superseded by a new one.
+///
+/// Every line is tab separated fields, starting with what the line describes. Fields are ASCII:
+/// backslashes, tabs, newlines and anything outside of printable ASCII are escaped as `\\`, `\t`,
+/// `\n` and `\u{hex}`. Flags are `1` or `0`, times are RFC 3339 and unknown values are `?`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PorcelainVersion {
+	/// The line formats documented on the functions of this module
+	#[default]
+	V1,
+}
+
+/// Escapes a field so it stays a single ASCII field.
+fn field(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len());
+	for character in value.chars() {
+		match character {
+			'\\' => escaped.push_str("\\\\"),
+			'\t' => escaped.push_str("\\t"),
+			'\n' => escaped.push_str("\\n"),
+			' '..='~' => escaped.push(character),
+			other => escaped.push_str(&format!("\\u{{{:x}}}", u32::from(other))),
+		}
+	}
+
+	escaped
+}
+
+fn flag(value: bool) -> &'static str {
+	if value { "1" } else { "0" }
+}
+
+fn time(value: DateTime<Utc>) -> String {
+	value.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn script_type(script_type: &ScriptType) -> &'static str {
+	match script_type {
+		ScriptType::Regular => "script",
+		ScriptType::Package => "package",
+	}
+}
+
+/// `script <id> <name> <script|package> <creator> <editable> <favorite>`, followed by
+/// `<modules> <source bytes> <has description>` with `list --detailed`.
+pub fn script(script: &Script, details: Option<Option<ScriptDetails>>) -> String {
+	let mut line = [
+		"script".to_owned(),
+		field(&script.id),
+		field(&script.name),
+		script_type(&script.r#type).to_owned(),
+		field(&script.creator),
+		flag(script.editable).to_owned(),
+		flag(script.is_favorite).to_owned(),
+	]
+	.join("\t");
+
+	match details {
+		None => {}
+		Some(Some(details)) => line.push_str(&format!(
+			"\t{}\t{}\t{}",
+			details.module_count,
+			details.source_bytes,
+			flag(details.has_description)
+		)),
+		Some(None) => line.push_str("\t?\t?\t?"),
+	}
+
+	line
+}
+
+/// `account <id> <name> <roblox user> <sessions> <secrets expiry>`
+pub fn account(details: &AccountDetails, expires: DateTime<Utc>) -> String {
+	[
+		"account".to_owned(),
+		field(&details.id),
+		field(&details.name),
+		field(&details.roblox_user),
+		details.num_sessions.to_string(),
+		time(expires),
+	]
+	.join("\t")
+}
+
+/// Lines of `fumo status`:
+/// - `project <script id> <script name> <script|package> <last synced or ?>`
+/// - `remote unavailable`, when fumosclub couldn't be compared against
+/// - `file <added|removed|modified> <path>` for each differing file, sorted by path
+/// - `metadata <scriptName|isPublic|whitelist>` for each differing metadata field
+/// - `empty <path>` for each empty file push refuses
+pub fn status(
+	configuration: &Configuration,
+	meta: Option<&Metadata>,
+	comparison: Option<&Report>,
+	empty_files: &[String],
+) -> Vec<String> {
+	let mut lines = vec![
+		[
+			"project".to_owned(),
+			field(&configuration.script_id),
+			field(&configuration.script_name),
+			match configuration.project_type {
+				ProjectType::Script => "script",
+				ProjectType::Package => "package",
+			}
+			.to_owned(),
+			meta.map_or_else(|| "?".to_owned(), |meta| time(meta.synced_at)),
+		]
+		.join("\t"),
+	];
+
+	match comparison {
+		None => lines.push("remote\tunavailable".to_owned()),
+		Some(report) => {
+			for file in sorted_mismatches(&report.files) {
+				let state = match file.state {
+					FileState::Added => "added",
+					FileState::Removed => "removed",
+					FileState::Modified => "modified",
+					FileState::Unchanged => "unchanged",
+				};
+				lines.push(format!("file\t{state}\t{}", field(&file.path)));
+			}
+
+			let metadata = report.metadata;
+			for (name, differs) in [
+				("scriptName", metadata.script_name),
+				("isPublic", metadata.is_public),
+				("whitelist", metadata.whitelist),
+			] {
+				if differs {
+					lines.push(format!("metadata\t{name}"));
+				}
+			}
+		}
+	}
+
+	lines.extend(
+		empty_files
+			.iter()
+			.map(|path| format!("empty\t{}", field(path))),
+	);
+	lines
+}
+
+/// The line formats of v1 are a compatibility contract: these tests change only with a new version.
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{project::MetadataDrift, report::FileReport};
+	use chrono::TimeZone;
+
+	fn synced_at() -> DateTime<Utc> {
+		Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap()
+	}
+
+	fn listed_script() -> Script {
+		serde_json::from_value(serde_json::json!({
+			"id": "abc123",
+			"name": "my\tscript \u{2728}",
+			"description": "",
+			"type": 1,
+			"creator": "some\\one",
+			"creatorIcon": "",
+			"editable": true,
+			"isFavorite": false,
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn escapes_fields_to_ascii() {
+		assert_eq!(field("a\tb\nc\\d\u{e9}"), "a\\tb\\nc\\\\d\\u{e9}");
+	}
+
+	#[test]
+	fn script_lines() {
+		let script = listed_script();
+		assert_eq!(
+			super::script(&script, None),
+			"script\tabc123\tmy\\tscript \\u{2728}\tpackage\tsome\\\\one\t1\t0"
+		);
+		assert_eq!(
+			super::script(&script, Some(None)),
+			"script\tabc123\tmy\\tscript \\u{2728}\tpackage\tsome\\\\one\t1\t0\t?\t?\t?"
+		);
+		let details = ScriptDetails {
+			module_count: 2,
+			source_bytes: 1024,
+			has_description: true,
+		};
+		assert_eq!(
+			super::script(&script, Some(Some(details))),
+			"script\tabc123\tmy\\tscript \\u{2728}\tpackage\tsome\\\\one\t1\t0\t2\t1024\t1"
+		);
+	}
+
+	#[test]
+	fn account_lines() {
+		let details: AccountDetails = serde_json::from_value(serde_json::json!({
+			"success": true,
+			"id": "42",
+			"name": "fumo",
+			"icon": "",
+			"robloxUser": "fumo_fan",
+			"discordUserId": "",
+			"numSessions": 3,
+		}))
+		.unwrap();
+		assert_eq!(
+			account(&details, synced_at()),
+			"account\t42\tfumo\tfumo_fan\t3\t2024-05-01T12:30:00Z"
+		);
+	}
+
+	#[test]
+	fn status_lines() {
+		let configuration: Configuration = serde_json::from_value(serde_json::json!({
+			"scriptName": "fumo",
+			"scriptId": "abc123",
+			"whitelist": [],
+			"isPublic": false,
+		}))
+		.unwrap();
+		let meta = Metadata {
+			synced_at: synced_at(),
+			creator: None,
+			fumo_version: "0.0.0".to_owned(),
+			script_type: ProjectType::Script,
+		};
+		let files = vec![
+			FileReport::compare("pkg/b.luau".to_owned(), Some("b"), None, Some("x"), false),
+			FileReport::compare("pkg/a.luau".to_owned(), Some("a"), Some("x"), None, false),
+			FileReport::compare(
+				"pkg/c.luau".to_owned(),
+				Some("c"),
+				Some("x"),
+				Some("x"),
+				false,
+			),
+			FileReport::compare(
+				"init.server.luau".to_owned(),
+				None,
+				Some("x"),
+				Some("y"),
+				false,
+			),
+		];
+		let drift = vec![MetadataDrift::Whitelist {
+			local: Vec::new(),
+			remote: vec!["1".to_owned()],
+		}];
+		let report = Report::new("abc123".to_owned(), files, drift);
+
+		assert_eq!(
+			status(
+				&configuration,
+				Some(&meta),
+				Some(&report),
+				&["pkg/empty.luau".to_owned()]
+			),
+			[
+				"project\tabc123\tfumo\tscript\t2024-05-01T12:30:00Z",
+				"file\tmodified\tinit.server.luau",
+				"file\tadded\tpkg/a.luau",
+				"file\tremoved\tpkg/b.luau",
+				"metadata\twhitelist",
+				"empty\tpkg/empty.luau",
+			]
+		);
+		assert_eq!(
+			status(&configuration, None, None, &[]),
+			["project\tabc123\tfumo\tscript\t?", "remote\tunavailable"]
+		);
+	}
+}
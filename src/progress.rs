@@ -0,0 +1,77 @@
+use crate::settings::{self, OutputFormat};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{
+	io::IsTerminal,
+	time::{Duration, Instant},
+};
+
+/// Progress is only rendered for humans watching a terminal.
+fn enabled() -> bool {
+	let settings = settings::get();
+	!settings.quiet && settings.format == OutputFormat::Text && std::io::stderr().is_terminal()
+}
+
+/// Awaits a fallible future while showing a spinner, followed by a completion line on success.
+pub async fn spinner<T, E>(
+	message: &str,
+	future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+	if !enabled() {
+		return future.await;
+	}
+
+	let bar = ProgressBar::new_spinner()
+		.with_style(
+			ProgressStyle::with_template("{spinner} {msg}").expect("failed parsing spinner template"),
+		)
+		.with_message(message.to_owned());
+	bar.enable_steady_tick(Duration::from_millis(100));
+
+	let start = Instant::now();
+	let result = future.await;
+	match result {
+		Ok(..) => bar.finish_with_message(format!("{message} (done in {:.1?})", start.elapsed())),
+		Err(..) => bar.finish_and_clear(),
+	}
+
+	result
+}
+
+/// A progress bar for phases which handle several items; hidden when progress is disabled.
+pub struct Progress {
+	bar: ProgressBar,
+	message: String,
+	start: Instant,
+}
+
+impl Progress {
+	pub fn new(message: &str, length: u64) -> Self {
+		let bar = if enabled() {
+			ProgressBar::new(length).with_style(
+				ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len}")
+					.expect("failed parsing progress bar template")
+					.progress_chars("=> "),
+			)
+		} else {
+			ProgressBar::hidden()
+		};
+
+		Self {
+			bar: bar.with_message(message.to_owned()),
+			message: message.to_owned(),
+			start: Instant::now(),
+		}
+	}
+
+	pub fn inc(&self) {
+		self.bar.inc(1);
+	}
+
+	pub fn finish(self) {
+		self.bar.finish_with_message(format!(
+			"{} (done in {:.1?})",
+			self.message,
+			self.start.elapsed()
+		));
+	}
+}
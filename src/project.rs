@@ -1,42 +1,385 @@
 use crate::{
-	client::{Client, EditorUpdate},
+	arguments, cache,
+	client::{
+		Client, EditorCache, EditorScriptInfo, EditorUpdate, ModuleName, PROGRAM_VERSION, ScriptId,
+		ScriptType, ensure_reachable, resolve_script_reference,
+	},
+	description,
 	error::{Context, Error},
-	login::get_session_secrets,
+	git, limits,
+	login::{self, get_config_directory, get_session_secrets},
+	output,
+	progress::{self, Progress},
+	registry,
+	report::{FileReport, FileState, Report},
+	requires, schema,
+	settings::{self, OutputFormat},
+	snapshot::{self, SnapshotSchedule},
+	state, telemetry,
+	template::{DEFAULT_TEMPLATE, find_template},
+	timing,
+	types::{self, TYPES_FILE},
+	verification::{RemoteBaseline, RemoteEditPolicy, RemoteFile, VerifySchedule},
+	vscode,
+	whitelist::{self, WhitelistMerge},
 };
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use futures::{StreamExt, TryStreamExt};
 use notify_debouncer_full::{
 	DebounceEventResult, new_debouncer,
 	notify::{EventKind, RecursiveMode, event::ModifyKind},
 };
 use serde::{Deserialize, Serialize};
 use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
 	ffi::OsStr,
+	io::Write,
+	ops::Range,
 	path::{Component, Path, PathBuf},
-	sync::Arc,
-	time::Duration,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
 };
 use tokio::sync::{Mutex, Notify};
 use tracing::{Instrument, info, warn};
 
 pub const SYNC_CONFIGURATION_FILE: &str = "fumosync.json";
+/// Alternative to fumosync.json which can hold comments
+pub const SYNC_CONFIGURATION_TOML_FILE: &str = "fumosync.toml";
 pub const MAIN_SCRIPT_FILE: &str = "init.server.luau";
 pub const PACKAGE_DIRECTORY: &str = "pkg";
 pub const DESCRIPTION_FILE: &str = "README.md";
+/// Script id written by `init` before the project is linked to a script
+pub const PLACEHOLDER_SCRIPT_ID: &str = "???";
+/// Local state kept by fumo inside of projects, such as caches and backups
+pub const LOCAL_STATE_DIRECTORY: &str = ".fumo";
+/// How many module files are read or written at once
+const CONCURRENT_FILE_OPERATIONS: usize = 16;
+
+/// Kind of script a project syncs with.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectType {
+	#[default]
+	Script,
+	Package,
+}
+
+/// Editor a project is set up for.
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorSetup {
+	/// .vscode/settings.json points luau-lsp at the type definitions
+	#[default]
+	Vscode,
+	/// No editor files are written
+	None,
+}
+
+impl From<&ScriptType> for ProjectType {
+	fn from(script_type: &ScriptType) -> Self {
+		match script_type {
+			ScriptType::Regular => Self::Script,
+			ScriptType::Package => Self::Package,
+		}
+	}
+}
 
 /// fumosync.json
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Configuration {
+	/// Schema editors validate fumosync.json against
+	#[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+	pub schema: Option<String>,
 	pub script_name: String,
 	pub script_id: String,
 	pub whitelist: Vec<String>,
 	pub is_public: bool,
+	/// Whether the project is a regular script or a package; packages have no main script
+	#[serde(rename = "type", default)]
+	pub project_type: ProjectType,
+	/// Watcher debounce in milliseconds, overriding the global configuration
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub debounce: Option<u64>,
+	/// Remote module names of local files in pkg/, keyed by file name without the extension
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub module_aliases: BTreeMap<String, String>,
+	/// Editor files written by init and pull; defaults to vscode
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub editor: Option<EditorSetup>,
+	/// Whether symlinks in pkg/ are read through; defaults to true
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub follow_symlinks: Option<bool>,
+	/// Whether the name, publicity and whitelist are uploaded; defaults to true
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sync_metadata: Option<bool>,
+	/// Silences notices about the script name differing from the directory or remote name
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub suppress_name_check: Option<bool>,
+	/// Whether push uploads a blank main script and empty modules without asking
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub allow_empty_files: Option<bool>,
+	/// Extensions of files in pkg/ which aren't modules, and are skipped without notices
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub asset_extensions: Vec<String>,
+	/// How pushes combine the whitelist with the remote one; defaults to replacing it
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub whitelist_merge: Option<WhitelistMerge>,
+	/// Values of the `arguments` global, set with `fumo args set`
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub arguments: Vec<serde_json::Value>,
+	/// Whether push declares `arguments` in a prelude of the uploaded main script
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub inject_arguments: Option<bool>,
+	/// Events the watcher counts as changes on top of content changes
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub watch_events: Vec<WatchEvent>,
+	/// Sync metadata written by older versions, which keep it in .fumo/sync.json now
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub meta: Option<Metadata>,
 }
 
-pub async fn write_file<T: AsRef<Path>>(path: T, contents: &str) -> Result<(), Error> {
-	match tokio::fs::write(path.as_ref(), contents).await {
-		Ok(value) => Ok(value),
-		Err(io_error) => Err(Error::CreateFile(path.as_ref().to_path_buf(), io_error)),
+/// What fumo saw when the project was last pulled or pushed; never uploaded.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+	pub synced_at: DateTime<Utc>,
+	/// Name of the account owning the script, if fumosclub listed it
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub creator: Option<String>,
+	pub fumo_version: String,
+	#[serde(rename = "type")]
+	pub script_type: ProjectType,
+}
+
+impl Metadata {
+	fn now(creator: Option<String>, script_type: ProjectType) -> Self {
+		Self {
+			synced_at: Utc::now(),
+			creator,
+			fumo_version: PROGRAM_VERSION.to_owned(),
+			script_type,
+		}
+	}
+}
+
+impl Configuration {
+	/// Gets the script id, erroring if the project isn't linked to a script yet.
+	///
+	/// The id is kept as a string in fumosync.json so the placeholder round-trips, and is only
+	/// checked here.
+	pub fn linked_script_id(&self) -> Result<ScriptId, Error> {
+		if self.script_id.is_empty() || self.script_id == PLACEHOLDER_SCRIPT_ID {
+			Err(Error::PlaceholderScriptId)
+		} else {
+			ScriptId::new(&self.script_id)
+		}
+	}
+
+	pub fn follows_symlinks(&self) -> bool {
+		self.follow_symlinks.unwrap_or(true)
+	}
+
+	pub fn syncs_metadata(&self) -> bool {
+		self.sync_metadata.unwrap_or(true)
+	}
+
+	pub fn allows_empty_files(&self) -> bool {
+		self.allow_empty_files.unwrap_or(false)
+	}
+
+	pub fn injects_arguments(&self) -> bool {
+		self.inject_arguments.unwrap_or(false)
+	}
+
+	pub fn module_filter(&self) -> ModuleFilter {
+		ModuleFilter {
+			follow_symlinks: self.follows_symlinks(),
+			asset_extensions: self.asset_extensions.clone(),
+		}
+	}
+
+	/// Gets the name a local module is uploaded as.
+	pub fn remote_module_name<'a>(&'a self, local_name: &'a str) -> &'a str {
+		self
+			.module_aliases
+			.get(local_name)
+			.map_or(local_name, String::as_str)
 	}
+
+	/// Gets the file name, without the extension, a remote module is stored as.
+	pub fn local_module_name<'a>(&'a self, remote_name: &'a str) -> &'a str {
+		self
+			.module_aliases
+			.iter()
+			.find(|(_, remote)| *remote == remote_name)
+			.map_or(remote_name, |(local, _)| local.as_str())
+	}
+
+	/// Checks module aliases against the modules in pkg/.
+	///
+	/// # Errors
+	/// - [`Error::InvalidModuleAlias`] for aliases of missing files, several aliases with the same
+	///   remote name, and remote names already used by another file
+	pub fn validate_module_aliases(&self, local_names: &[String]) -> Result<(), Error> {
+		let mut remote_names: HashMap<&str, &str> = HashMap::new();
+		for (local, remote) in &self.module_aliases {
+			if !local_names.contains(local) {
+				return Err(Error::InvalidModuleAlias(
+					local.clone(),
+					format!("{PACKAGE_DIRECTORY}/{local}.luau doesn't exist"),
+				));
+			}
+
+			// local names are trimmed, so an untrimmed key could never match a file
+			if local.trim() != local {
+				return Err(Error::InvalidModuleAlias(
+					local.clone(),
+					format!(
+						"the name has surrounding whitespace; use \"{}\"",
+						local.trim()
+					),
+				));
+			}
+
+			if let Some(other) = remote_names.insert(remote, local) {
+				return Err(Error::InvalidModuleAlias(
+					local.clone(),
+					format!("{other} is also aliased to \"{remote}\""),
+				));
+			}
+
+			if !self.module_aliases.contains_key(remote) && local_names.contains(remote) {
+				return Err(Error::InvalidModuleAlias(
+					local.clone(),
+					format!("\"{remote}\" is already the name of {PACKAGE_DIRECTORY}/{remote}.luau"),
+				));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Counts the temporary files written by this process, so concurrent writes never share one
+static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Gets the directory of a file, and a new temporary file for [`write_file_atomic`] to write it
+/// through, named `.{name}.{pid}.{count}.tmp`.
+fn temporary_path(path: &Path) -> (&Path, PathBuf) {
+	let directory = match path.parent() {
+		Some(parent) if !parent.as_os_str().is_empty() => parent,
+		_ => Path::new("."),
+	};
+	let mut temporary_name = std::ffi::OsString::from(".");
+	temporary_name.push(path.file_name().unwrap_or_default());
+	temporary_name.push(format!(
+		".{}.{}.tmp",
+		std::process::id(),
+		TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+	));
+	(directory, directory.join(temporary_name))
+}
+
+/// Removes the temporary files of a file left behind by interrupted writes of processes which
+/// are no longer running; ones being written by this or another running process are kept.
+fn remove_stale_temporary_files(directory: &Path, path: &Path) {
+	let Some(file_name) = path.file_name().and_then(OsStr::to_str) else {
+		return;
+	};
+	let prefix = format!(".{file_name}.");
+	let Ok(entries) = std::fs::read_dir(directory) else {
+		return;
+	};
+
+	for entry in entries.flatten() {
+		let name = entry.file_name();
+		let Some((pid, count)) = name
+			.to_str()
+			.and_then(|name| name.strip_prefix(&prefix)?.strip_suffix(".tmp"))
+			.and_then(|rest| rest.split_once('.'))
+		else {
+			continue;
+		};
+		// ones of files named `{file_name}.{suffix}` have more parts, so they never parse
+		let (Ok(pid), Ok(_)) = (pid.parse::<u32>(), count.parse::<u64>()) else {
+			continue;
+		};
+		if pid != std::process::id() && !process_running(pid) {
+			let _ = std::fs::remove_file(entry.path());
+		}
+	}
+}
+
+/// Writes a file through a temporary file next to it, synced to disk before it is renamed over
+/// the destination, so an interrupted write never leaves a truncated file behind. On unix, the
+/// directory is synced after the rename too, so the new file survives power loss.
+///
+/// Symlinks are written through and existing permissions are kept; `private` files are only
+/// readable by their owner on unix, from before anything is written to them.
+async fn write_file_atomic(path: PathBuf, contents: Vec<u8>, private: bool) -> std::io::Result<()> {
+	tokio::task::spawn_blocking(move || {
+		// renaming over a symlink would replace the link instead of the file it points to
+		let path = std::fs::canonicalize(&path).unwrap_or(path);
+		let (directory, temporary) = temporary_path(&path);
+
+		remove_stale_temporary_files(directory, &path);
+
+		let result = (|| {
+			let mut options = std::fs::OpenOptions::new();
+			options.write(true).create_new(true);
+			#[cfg(unix)]
+			if private {
+				use std::os::unix::fs::OpenOptionsExt;
+				options.mode(0o600);
+			}
+			let mut file = options.open(&temporary)?;
+			file.write_all(&contents)?;
+			match std::fs::metadata(&path) {
+				Ok(metadata) if !private => file.set_permissions(metadata.permissions())?,
+				Ok(_) => {}
+				Err(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => {}
+				Err(io_error) => return Err(io_error),
+			}
+			file.sync_all()?;
+			drop(file);
+
+			// std replaces existing files on Windows too (MOVEFILE_REPLACE_EXISTING); read-only
+			// destinations are refused there, as plain writes would be
+			std::fs::rename(&temporary, &path)?;
+			#[cfg(unix)]
+			std::fs::File::open(directory)?.sync_all()?;
+			#[cfg(not(unix))]
+			let _ = directory;
+			Ok(())
+		})();
+
+		if result.is_err() {
+			let _ = std::fs::remove_file(&temporary);
+		}
+		result
+	})
+	.await
+	.map_err(std::io::Error::other)?
+}
+
+pub async fn write_file<T: AsRef<Path>>(path: T, contents: &str) -> Result<(), Error> {
+	let path = path.as_ref().to_path_buf();
+	write_file_atomic(path.clone(), contents.as_bytes().to_vec(), false)
+		.await
+		.map_err(|io_error| Error::CreateFile(path, io_error))
+}
+
+/// Writes a file like [`write_file`], which only its owner may read; for secrets.
+pub async fn write_private_file<T: AsRef<Path>>(path: T, contents: &str) -> Result<(), Error> {
+	let path = path.as_ref().to_path_buf();
+	write_file_atomic(path.clone(), contents.as_bytes().to_vec(), true)
+		.await
+		.map_err(|io_error| Error::CreateFile(path, io_error))
 }
 
 async fn create_directory<T: AsRef<Path>>(path: T) -> Result<(), Error> {
@@ -49,336 +392,3537 @@ async fn create_directory<T: AsRef<Path>>(path: T) -> Result<(), Error> {
 	}
 }
 
-pub async fn read_configuration<T: AsRef<Path>>(
-	project_directory: T,
-) -> Result<Configuration, Error> {
-	Ok(serde_json::from_str(
-		&read_file(project_directory.as_ref().join(SYNC_CONFIGURATION_FILE)).await?,
-	)?)
+fn is_toml(path: &Path) -> bool {
+	path.extension() == Some(OsStr::new("toml"))
 }
 
-/// Initializes a project for syncing within fumosclub.
-pub async fn init(directory: PathBuf) -> Result<(), Error> {
-	if directory.exists() {
-		return Err(Error::DirectoryAlreadyExists(directory));
+/// Finds the project's configuration file; fumosync.toml is preferred, and defaults to fumosync.json
+/// when neither exists.
+///
+/// # Errors
+/// - [`Error::ConflictingConfigurationFiles`] if both exist
+pub fn find_configuration_file<T: AsRef<Path>>(project_directory: T) -> Result<PathBuf, Error> {
+	let project_directory = project_directory.as_ref();
+	let json_path = project_directory.join(SYNC_CONFIGURATION_FILE);
+	let toml_path = project_directory.join(SYNC_CONFIGURATION_TOML_FILE);
+
+	match (json_path.try_exists()?, toml_path.try_exists()?) {
+		(true, true) => Err(Error::ConflictingConfigurationFiles(
+			project_directory.to_path_buf(),
+		)),
+		(_, true) => Ok(toml_path),
+		(_, false) => Ok(json_path),
 	}
+}
 
-	create_directory(directory.clone()).await?;
-	create_directory(directory.join(PACKAGE_DIRECTORY)).await?;
-	create_directory(directory.join(".vscode")).await?;
+fn parse_configuration(path: &Path, contents: &str) -> Result<Configuration, Error> {
+	if is_toml(path) {
+		toml::from_str(contents)
+			.map_err(|error| Error::InvalidProjectConfiguration(path.to_path_buf(), error.to_string()))
+	} else {
+		serde_json::from_str(contents)
+			.map_err(|error| Error::InvalidProjectConfiguration(path.to_path_buf(), error.to_string()))
+	}
+}
 
-	write_file(
-		directory.join(".vscode").join("settings.json"),
-		r#"{
-	"luau-lsp.types.robloxSecurityLevel": "None",
-	"luau-lsp.types.definitionFiles": ["types.d.luau"]
-}"#,
-	)
-	.await?;
+pub fn serialize_configuration(
+	path: &Path,
+	configuration: &Configuration,
+) -> Result<String, Error> {
+	if is_toml(path) {
+		toml::to_string_pretty(configuration)
+			.map_err(|error| Error::InvalidProjectConfiguration(path.to_path_buf(), error.to_string()))
+	} else {
+		Ok(serde_json::to_string_pretty(configuration)?)
+	}
+}
 
-	write_file(
-		directory.join("init.server.luau"),
-		r#"-- you can require packages with requireM("path") where path is a file inside of pkg (no extension)"#,
-	)
-	.await?;
+/// Gets the indentation of a JSON file from its first indented line; two spaces without one.
+fn detect_indentation(contents: &str) -> String {
+	contents
+		.lines()
+		.find_map(|line| {
+			let indentation: String = line
+				.chars()
+				.take_while(|character| matches!(character, ' ' | '\t'))
+				.collect();
+			(!indentation.is_empty() && indentation.len() < line.len()).then_some(indentation)
+		})
+		.unwrap_or_else(|| "  ".to_owned())
+}
 
-	write_file(directory.join(DESCRIPTION_FILE), r"# stuff here").await?;
+/// A member of the top-level object of a JSON document, by byte offsets into it.
+struct JsonMember {
+	key: String,
+	/// Offset of the quote opening the key
+	start: usize,
+	value: Range<usize>,
+}
 
-	write_file(
-		directory.join("types.d.luau"),
-		r"declare loadstringEnabled: boolean
-declare owner: Player
-declare arguments: { any }
-
-declare isolatedStorage: {
-  get: (name: string) -> any,
-  set: (name: string, value: any?) -> ()
-}
-
-declare immediateSignals: boolean
-declare NLS: (source: string, parent: Instance?) -> LocalScript
-declare requireM: (moduleName: string) -> any
-
-declare LoadAssets: (assetId: number) -> {
-  Get: (asset: string) -> Instance,
-  Exists: (asset: string) -> boolean,
-  GetNames: () -> { string },
-  GetArray: () -> { Instance },
-  GetDictionary: () -> { [string]: Instance }
-}",
-	)
-	.await?;
+/// Finds the members of the top-level object of a JSON document, in order; `None` if it isn't an
+/// object with at least one member.
+fn json_members(contents: &str) -> Option<Vec<JsonMember>> {
+	let bytes = contents.as_bytes();
+	let skip_whitespace = |mut index: usize| {
+		while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+			index += 1;
+		}
+		index
+	};
+	// gets the offset after the string opening at `index`
+	let skip_string = |mut index: usize| -> Option<usize> {
+		if bytes.get(index) != Some(&b'"') {
+			return None;
+		}
 
-	write_file(
-		directory.join(SYNC_CONFIGURATION_FILE),
-		&serde_json::to_string_pretty(&Configuration {
-			script_name: directory
-				.file_name()
-				.unwrap_or_else(|| OsStr::new("unknown"))
-				.to_string_lossy()
-				.to_string(),
-			script_id: "???".to_owned(),
-			whitelist: Vec::new(),
-			is_public: false,
-		})?,
-	)
-	.await?;
+		index += 1;
+		loop {
+			match bytes.get(index)? {
+				b'\\' => index += 2,
+				b'"' => return Some(index + 1),
+				_ => index += 1,
+			}
+		}
+	};
+	let skip_value = |mut index: usize| -> Option<usize> {
+		match bytes.get(index)? {
+			b'"' => skip_string(index),
+			b'{' | b'[' => {
+				let mut depth = 0_usize;
+				loop {
+					match bytes.get(index)? {
+						b'"' => {
+							index = skip_string(index)?;
+							continue;
+						}
+						b'{' | b'[' => depth += 1,
+						b'}' | b']' => {
+							depth -= 1;
+							if depth == 0 {
+								return Some(index + 1);
+							}
+						}
+						_ => {}
+					}
+					index += 1;
+				}
+			}
+			_ => {
+				while bytes
+					.get(index)
+					.is_some_and(|byte| !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace())
+				{
+					index += 1;
+				}
+				Some(index)
+			}
+		}
+	};
 
-	Ok(())
+	let mut index = skip_whitespace(0);
+	if bytes.get(index) != Some(&b'{') {
+		return None;
+	}
+
+	index = skip_whitespace(index + 1);
+	let mut members = Vec::new();
+	loop {
+		let start = index;
+		let key_end = skip_string(start)?;
+		let key = serde_json::from_str(&contents[start..key_end]).ok()?;
+		index = skip_whitespace(key_end);
+		if bytes.get(index) != Some(&b':') {
+			return None;
+		}
+
+		let value_start = skip_whitespace(index + 1);
+		let value_end = skip_value(value_start)?;
+		members.push(JsonMember {
+			key,
+			start,
+			value: value_start..value_end,
+		});
+
+		index = skip_whitespace(value_end);
+		match bytes.get(index)? {
+			b',' => index = skip_whitespace(index + 1),
+			b'}' => return Some(members),
+			_ => return None,
+		}
+	}
 }
 
-/// Pulls a project from fumosclub and links it via fumosync.json.
-pub async fn pull(script_id: String, project_directory: PathBuf) -> Result<(), Error> {
-	let client = Client::new(get_session_secrets().await?);
+/// Gets the whitespace a line starts with, if nothing else precedes `offset` on it.
+fn line_indentation(contents: &str, offset: usize) -> &str {
+	let line = contents[..offset].rsplit('\n').next().unwrap_or_default();
+	if line
+		.chars()
+		.all(|character| matches!(character, ' ' | '\t'))
+	{
+		line
+	} else {
+		""
+	}
+}
 
-	// setup initial file structure for hydration
-	match init(project_directory.clone()).await {
-		Ok(()) => {}
-		Err(e) => return Err(Error::ProjectDidntInitialize(Box::new(e))),
+/// Serializes a value replacing or following one of a JSON document: a value which was on one line
+/// stays on one line, anything else is pretty printed and indented after `line_indentation`.
+fn format_json_value(
+	value: &serde_json::Value,
+	indentation: &str,
+	line_indentation: &str,
+	inline: bool,
+) -> Result<String, Error> {
+	if inline {
+		return Ok(value.to_string());
+	}
+
+	let mut contents = Vec::new();
+	let formatter = serde_json::ser::PrettyFormatter::with_indent(indentation.as_bytes());
+	value.serialize(&mut serde_json::Serializer::with_formatter(
+		&mut contents,
+		formatter,
+	))?;
+	Ok(String::from_utf8_lossy(&contents).replace('\n', &format!("\n{line_indentation}")))
+}
+
+/// Rewrites fumosync.json with only the keys of a configuration which changed, so git diffs stay
+/// small: the text of changed values is replaced in place, removed keys are cut out with their
+/// separators, and new keys are appended after the last one. Everything else, including the
+/// formatting of untouched keys, is kept byte for byte.
+///
+/// A changed value which was on one line is written compactly on one line; any other is pretty
+/// printed with the indentation of the file.
+///
+/// Files which don't parse are replaced with freshly serialized configurations.
+fn rewrite_json_configuration(
+	original: &str,
+	configuration: &Configuration,
+) -> Result<String, Error> {
+	let (Some(members), Ok(previous)) = (
+		json_members(original),
+		serde_json::from_str::<Configuration>(original),
+	) else {
+		return Ok(serde_json::to_string_pretty(configuration)?);
+	};
+	let (serde_json::Value::Object(previous), serde_json::Value::Object(updated)) = (
+		serde_json::to_value(previous)?,
+		serde_json::to_value(configuration)?,
+	) else {
+		return Err(Error::Serde(serde::ser::Error::custom(
+			"the configuration didn't serialize to an object",
+		)));
 	};
 
-	let script_info = client.get_editor(&script_id).await?.script_info;
+	let indentation = detect_indentation(original);
+	let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+	let mut removed = vec![false; members.len()];
+	for (position, member) in members.iter().enumerate() {
+		if previous.get(&member.key) == updated.get(&member.key) {
+			continue;
+		}
 
-	write_file(
-		project_directory.join(DESCRIPTION_FILE),
-		&script_info.description,
-	)
-	.await?;
+		match updated.get(&member.key) {
+			Some(value) => {
+				let inline = !original[member.value.clone()].contains('\n');
+				let line_indentation = line_indentation(original, member.start);
+				edits.push((
+					member.value.clone(),
+					format_json_value(value, &indentation, line_indentation, inline)?,
+				));
+			}
+			None => removed[position] = true,
+		}
+	}
 
-	write_file(
-		project_directory.join(MAIN_SCRIPT_FILE),
-		&script_info.source.main,
-	)
-	.await?;
+	// cuts each run of removed members with the separator before it, or after it for the first
+	let mut position = 0;
+	while position < members.len() {
+		if !removed[position] {
+			position += 1;
+			continue;
+		}
 
-	write_file(
-		project_directory.join(SYNC_CONFIGURATION_FILE),
-		&serde_json::to_string_pretty(&Configuration {
-			script_name: script_info.name,
-			script_id,
-			whitelist: script_info.whitelist,
-			is_public: script_info.is_public,
-		})?,
-	)
-	.await?;
+		let end = (position..members.len())
+			.find(|&index| !removed[index])
+			.unwrap_or(members.len());
+		let cut = match (position.checked_sub(1), members.get(end)) {
+			(Some(before), _) => members[before].value.end..members[end - 1].value.end,
+			(None, Some(after)) => members[0].start..after.start,
+			(None, None) => return Ok(serde_json::to_string_pretty(configuration)?),
+		};
+		edits.push((cut, String::new()));
+		position = end;
+	}
 
-	for (name, source) in script_info.source.modules {
-		write_file(
-			project_directory
-				.join(PACKAGE_DIRECTORY)
-				.join(format!("{name}.luau")),
-			&source,
-		)
-		.await?;
+	let last = &members[members.len() - 1];
+	let member_indentation = match line_indentation(original, last.start) {
+		"" => indentation.as_str(),
+		member_indentation => member_indentation,
+	};
+	let mut appended = String::new();
+	for (key, value) in &updated {
+		if previous.get(key) == Some(value) || members.iter().any(|member| member.key == *key) {
+			continue;
+		}
+
+		appended.push_str(&format!(
+			",\n{member_indentation}{}: {}",
+			serde_json::Value::from(key.as_str()),
+			format_json_value(value, &indentation, member_indentation, false)?
+		));
+	}
+	if !appended.is_empty() {
+		edits.push((last.value.end..last.value.end, appended));
 	}
 
-	Ok(())
+	// edits never overlap, so applying them from the end keeps the offsets of the others valid
+	edits.sort_by_key(|(range, _)| (range.start, range.end));
+	let mut contents = original.to_owned();
+	for (range, replacement) in edits.into_iter().rev() {
+		contents.replace_range(range, &replacement);
+	}
+	Ok(contents)
 }
 
-pub async fn read_file<T: AsRef<Path>>(path: T) -> Result<String, Error> {
-	match tokio::fs::read_to_string(path.as_ref()).await {
-		Ok(value) => Ok(value),
-		Err(io_error) => Err(Error::ReadFile(path.as_ref().to_path_buf(), io_error)),
+/// Rewrites fumosync.toml with only the keys of a configuration which changed, like
+/// [`rewrite_json_configuration`]; comments, and the formatting of the other keys, are kept.
+///
+/// Files which don't parse are replaced with freshly serialized configurations.
+fn rewrite_toml_configuration(
+	path: &Path,
+	original: &str,
+	configuration: &Configuration,
+) -> Result<String, Error> {
+	let (Ok(mut document), Ok(previous)) = (
+		original.parse::<toml_edit::DocumentMut>(),
+		toml::from_str::<Configuration>(original),
+	) else {
+		return serialize_configuration(path, configuration);
+	};
+	let (serde_json::Value::Object(previous), serde_json::Value::Object(updated)) = (
+		serde_json::to_value(previous)?,
+		serde_json::to_value(configuration)?,
+	) else {
+		return Err(Error::Serde(serde::ser::Error::custom(
+			"the configuration didn't serialize to an object",
+		)));
+	};
+	let serialized: toml_edit::DocumentMut = serialize_configuration(path, configuration)?
+		.parse()
+		.map_err(|error: toml_edit::TomlError| {
+			Error::InvalidProjectConfiguration(path.to_path_buf(), error.to_string())
+		})?;
+
+	let keys: BTreeSet<&String> = previous.keys().chain(updated.keys()).collect();
+	for key in keys {
+		if previous.get(key) == updated.get(key) {
+			continue;
+		}
+
+		match serialized.get(key) {
+			Some(item) => {
+				let mut item = item.clone();
+				// keeps comments trailing the old value
+				if let (Some(old), Some(new)) = (
+					document.get(key).and_then(toml_edit::Item::as_value),
+					item.as_value_mut(),
+				) {
+					*new.decor_mut() = old.decor().clone();
+				}
+				document[key.as_str()] = item;
+			}
+			None => {
+				document.remove(key);
+			}
+		}
 	}
+
+	Ok(document.to_string())
 }
 
-fn get_module_from_path<T: Into<PathBuf>>(file_name: T) -> String {
-	let path_without_extension = file_name.into().with_extension("");
-	path_without_extension.to_string_lossy().to_string()
+/// Reads the project configuration, changes it, and writes back only the keys which changed.
+async fn edit_configuration(
+	project_directory: &Path,
+	edit: impl FnOnce(&mut Configuration),
+) -> Result<(), Error> {
+	let mut configuration = read_configuration(project_directory).await?;
+	edit(&mut configuration);
+	write_configuration(project_directory, &configuration).await
 }
 
-fn get_editor_updates_from_configuration(configuration: &Configuration) -> [EditorUpdate<'_>; 3] {
-	let whitelist = configuration.whitelist.iter().map(String::as_str).collect();
+/// Writes the schema next to the project's configuration, pointing a `$schema` which references the
+/// copy older versions kept in .fumo/ at it.
+pub async fn write_schema(project_directory: &Path) -> Result<PathBuf, Error> {
+	let path = schema::write(project_directory).await?;
+	// the schema helps fixing broken configurations, so they don't stop it from being written
+	let configuration = read_configuration(project_directory).await.ok();
+	if configuration
+		.and_then(|configuration| configuration.schema)
+		.is_some_and(|reference| schema::is_legacy_reference(&reference))
+	{
+		edit_configuration(project_directory, |configuration| {
+			configuration.schema = Some(schema::reference());
+		})
+		.await?;
+	}
 
-	[
-		EditorUpdate::Name(&configuration.script_name),
-		EditorUpdate::Whitelist(whitelist),
-		EditorUpdate::Publicity(configuration.is_public),
-	]
+	Ok(path)
 }
 
-pub async fn push<T: AsRef<Path>>(project_directory: T) -> Result<(), Error> {
-	let project_directory = project_directory.as_ref();
-
-	let configuration = read_configuration(project_directory).await?;
-	let description = &read_file(project_directory.join(DESCRIPTION_FILE)).await?;
-	let main_source = &read_file(project_directory.join(MAIN_SCRIPT_FILE)).await?;
+/// .fumo/sync.json; kept out of the configuration, so syncing leaves nothing to commit.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+struct SyncState {
+	meta: Option<Metadata>,
+	/// `None` until the project is first pushed or pulled by a version recording it
+	synced: Option<SyncedMetadata>,
+}
 
-	let mut actions: Vec<EditorUpdate> = Vec::from([
-		EditorUpdate::Description(description),
-		EditorUpdate::MainSource(main_source),
-	]);
+/// The metadata fields of the configuration as they were last pushed or pulled, so pushes only
+/// upload the fields edited locally since, rather than reverting edits made on the website.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct SyncedMetadata {
+	script_name: String,
+	is_public: bool,
+	whitelist: Vec<String>,
+}
 
-	actions.extend(get_editor_updates_from_configuration(&configuration));
+impl SyncedMetadata {
+	fn of(configuration: &Configuration) -> Self {
+		Self {
+			script_name: configuration.script_name.clone(),
+			is_public: configuration.is_public,
+			whitelist: sorted_entries(&configuration.whitelist),
+		}
+	}
 
-	let mut modules: Vec<(String, String)> = Vec::new();
+	/// Whether a field was edited in the configuration since it was synced.
+	fn edited(&self, configuration: &Configuration, field: &MetadataDrift) -> bool {
+		match field {
+			MetadataDrift::ScriptName { .. } => self.script_name != configuration.script_name,
+			MetadataDrift::IsPublic { .. } => self.is_public != configuration.is_public,
+			MetadataDrift::Whitelist { .. } => self.whitelist != sorted_entries(&configuration.whitelist),
+		}
+	}
 
-	let pkg_path = project_directory.join(PACKAGE_DIRECTORY);
-	let mut stream = match tokio::fs::read_dir(&pkg_path).await {
-		Ok(value) => value,
-		Err(io_error) => return Err(Error::ReadDirectory(pkg_path, io_error)),
-	};
-
-	while let Some(module) = stream.next_entry().await? {
-		if let Ok(file_type) = module.file_type().await {
-			if file_type.is_file()
-				&& module
-					.path()
-					.extension()
-					.unwrap_or_else(|| OsStr::new(""))
-					.to_string_lossy()
-					== "luau"
-			{
-				let source: String = read_file(module.path()).await?;
-				modules.push((get_module_from_path(module.file_name()).to_string(), source));
-			}
-		} else {
-			warn!("failed getting file type for {}", module.path().display());
+	/// Whether a field was edited on the website since it was synced.
+	fn remote_edited(&self, field: &MetadataDrift) -> bool {
+		match field {
+			MetadataDrift::ScriptName { remote, .. } => *remote != self.script_name,
+			MetadataDrift::IsPublic { remote, .. } => *remote != self.is_public,
+			MetadataDrift::Whitelist { remote, .. } => sorted_entries(remote) != self.whitelist,
 		}
 	}
+}
 
-	// use .iter() to force items to have a lifetime bounded by the function
-	for (name, source) in &modules {
-		actions.push(EditorUpdate::Module { name, source });
+impl state::StateFile for SyncState {
+	const PATH: &'static str = "sync.json";
+	const VERSION: u32 = 1;
+}
+
+/// .fumo/watch.json; held by a running watcher, so the project isn't relinked under it.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+struct WatchLock {
+	pid: Option<u32>,
+}
+
+impl state::StateFile for WatchLock {
+	const PATH: &'static str = "watch.json";
+	const VERSION: u32 = 1;
+}
+
+/// Releases the watch lock when a watcher stops, however it stops.
+struct WatchLockGuard(PathBuf);
+
+impl Drop for WatchLockGuard {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_file(
+			state::get_state_directory(&self.0).join(<WatchLock as state::StateFile>::PATH),
+		);
 	}
+}
 
-	let client = Client::new(get_session_secrets().await?);
-	client
-		.set_editor(&configuration.script_id, &actions)
-		.await?;
+/// Takes the watch lock of a project for this process.
+async fn lock_watch(project_directory: &Path) -> Result<WatchLockGuard, Error> {
+	check_watch_lock(project_directory).await?;
+	let lock = WatchLock {
+		pid: Some(std::process::id()),
+	};
+	state::write(project_directory, &lock).await?;
+	Ok(WatchLockGuard(project_directory.to_path_buf()))
+}
+
+/// Checks that no running watcher holds the watch lock of a project; locks left behind by
+/// watchers which were killed are ignored.
+///
+/// # Errors
+/// - [`Error::WatchRunning`]
+async fn check_watch_lock(project_directory: &Path) -> Result<(), Error> {
+	let lock: WatchLock = state::read(project_directory).await;
+	let Some(pid) = lock.pid.filter(|pid| *pid != std::process::id()) else {
+		return Ok(());
+	};
+
+	if process_running(pid) {
+		return Err(Error::WatchRunning(project_directory.to_path_buf(), pid));
+	}
 	Ok(())
 }
 
-#[derive(Debug)]
-enum Update {
-	MainSource,
-	Description,
-	ProjectConfiguration,
-	Module(PathBuf),
+/// Checks whether a process with the pid is running.
+fn process_running(pid: u32) -> bool {
+	let mut system = sysinfo::System::new();
+	let process = sysinfo::Pid::from_u32(pid);
+	system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[process]), true);
+	system.process(process).is_some()
 }
 
-/// Processes all of the updates, uploads them to fumosclub, and clears the vector when done.
-async fn process_updates<T: AsRef<Path>>(
+/// Reads the sync metadata of the project, falling back to the `"meta"` older versions wrote to
+/// the configuration.
+pub async fn read_metadata(
+	project_directory: &Path,
+	configuration: &Configuration,
+) -> Option<Metadata> {
+	let state: SyncState = state::read(project_directory).await;
+	state.meta.or_else(|| configuration.meta.clone())
+}
+
+/// Records sync metadata in .fumo/sync.json, along with the metadata fields of the configuration
+/// as synced, moving it out of configurations written by older versions.
+async fn write_metadata(
+	project_directory: &Path,
+	configuration: &Configuration,
+	metadata: Option<Metadata>,
+) -> Result<(), Error> {
+	let state = SyncState {
+		meta: metadata,
+		synced: Some(SyncedMetadata::of(configuration)),
+	};
+	state::write(project_directory, &state).await?;
+	if configuration.meta.is_some() {
+		edit_configuration(project_directory, |configuration| configuration.meta = None).await?;
+	}
+
+	Ok(())
+}
+
+/// Records the metadata fields of the configuration as synced, keeping the sync metadata.
+async fn record_synced_metadata(
+	project_directory: &Path,
+	configuration: &Configuration,
+) -> Result<(), Error> {
+	let mut state: SyncState = state::read(project_directory).await;
+	state.synced = Some(SyncedMetadata::of(configuration));
+	state::write(project_directory, &state).await
+}
+
+/// Reads fumosync.toml or fumosync.json.
+pub async fn read_configuration<T: AsRef<Path>>(
 	project_directory: T,
-	updates: &mut Vec<Update>,
+) -> Result<Configuration, Error> {
+	let _section = timing::section("config read");
+	let path = find_configuration_file(project_directory)?;
+	parse_configuration(&path, &read_file(&path).await?)
+}
+
+/// Reads the configuration of a project linked to a script, for commands which fall back to the
+/// configured script id; nothing is asked of fumosclub.
+///
+/// # Errors
+/// - [`Error::NoConfiguredScript`] if the directory has no project configuration
+/// - [`Error::PlaceholderScriptId`] if the project isn't linked to a script yet
+pub async fn read_linked_configuration<T: AsRef<Path>>(
+	project_directory: T,
+) -> Result<Configuration, Error> {
+	let project_directory = project_directory.as_ref();
+	if !find_configuration_file(project_directory)?.try_exists()? {
+		return Err(Error::NoConfiguredScript(project_directory.to_path_buf()));
+	}
+
+	let configuration = read_configuration(project_directory).await?;
+	configuration.linked_script_id()?;
+	Ok(configuration)
+}
+
+/// Writes the configuration to the project's existing configuration file, or fumosync.json.
+///
+/// Existing files are rewritten with only the keys which changed, keeping the comments of
+/// fumosync.toml.
+pub async fn write_configuration<T: AsRef<Path>>(
+	project_directory: T,
+	configuration: &Configuration,
+) -> Result<(), Error> {
+	let path = find_configuration_file(project_directory)?;
+	let contents = if !path.try_exists()? {
+		serialize_configuration(&path, configuration)?
+	} else if is_toml(&path) {
+		rewrite_toml_configuration(&path, &read_file(&path).await?, configuration)?
+	} else {
+		rewrite_json_configuration(&read_file(&path).await?, configuration)?
+	};
+
+	write_file(&path, &contents).await
+}
+
+/// Gets a value from fumosync.json, if it is set.
+pub async fn get_configuration_value<T: AsRef<Path>>(
+	project_directory: T,
+	key: &str,
+) -> Result<Option<serde_json::Value>, Error> {
+	let configuration = serde_json::to_value(read_configuration(project_directory).await?)?;
+	Ok(configuration.get(key).cloned())
+}
+
+/// Sets a value in fumosync.json or fumosync.toml; values which aren't valid JSON are stored as
+/// strings. Only the key is rewritten, so comments in fumosync.toml are kept.
+///
+/// # Errors
+/// - [`Error::UnknownConfigurationKey`]
+/// - [`Error::Serde`] or [`Error::InvalidProjectConfiguration`] if the value has the wrong type for
+///   the key
+pub async fn set_configuration_value<T: AsRef<Path>>(
+	project_directory: T,
+	key: &str,
+	value: &str,
 ) -> Result<(), Error> {
 	let project_directory = project_directory.as_ref();
-	/* "why use another vector... very inefficent"
-		1. filesystem I/O -> String (direct ownership, no copies or clones)
-		2. String -> EditorPair (rust move semantics, no clones or clones)
-		3. &EditorPair -> EditorUpdate<'lt> (zero-cost moving abstraction)
-	*/
-	enum UpdatePair {
-		MainSource(String),
-		Description(String),
-		ProjectConfiguration,
-		Module { name: String, source: String },
-	}
-
-	let mut editor_updates: Vec<EditorUpdate<'_>> = Vec::with_capacity(updates.len());
-	// we must read the project configuration eventually because we need the project's id
-	let configuration: Configuration = read_configuration(project_directory).await?;
-	let mut update_pairs: Vec<UpdatePair> = Vec::with_capacity(updates.len());
-
-	for update in updates.iter() {
-		let pair = match update {
-			Update::MainSource => Some(UpdatePair::MainSource(
-				read_file(project_directory.join(MAIN_SCRIPT_FILE)).await?,
-			)),
-			Update::Description => Some(UpdatePair::Description(
-				read_file(project_directory.join(DESCRIPTION_FILE)).await?,
-			)),
-			Update::ProjectConfiguration => Some(UpdatePair::ProjectConfiguration),
-			Update::Module(path_buf) => match path_buf.file_name() {
-				None => {
-					warn!(
-						"module at {} has no file name, skipping...",
+	let path = find_configuration_file(project_directory)?;
+	let mut configuration = serde_json::to_value(read_configuration(project_directory).await?)?;
+	let value =
+		serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+
+	let Some(object) = configuration.as_object_mut() else {
+		return Err(Error::InvalidProjectConfiguration(
+			path,
+			"the configuration didn't serialize to an object".to_owned(),
+		));
+	};
+	object.insert(key.to_owned(), value);
+
+	let configuration: Configuration = serde_json::from_value(configuration)?;
+	// keys which don't survive the round trip aren't part of the configuration
+	if serde_json::to_value(&configuration)?.get(key).is_none() {
+		return Err(Error::UnknownConfigurationKey(key.to_owned()));
+	}
+
+	write_configuration(project_directory, &configuration).await
+}
+
+/// Creates the directory of a new project, checking where it goes first.
+///
+/// An existing empty directory is used as is, so a failed pull can be retried.
+///
+/// # Errors
+/// - [`Error::TargetIsFile`] if the directory, or its parent, is a file
+/// - [`Error::MissingParentDirectory`] unless `parents` is set
+/// - [`Error::DirectoryAlreadyExists`] if the directory isn't empty
+pub async fn create_project_directory(directory: &Path, parents: bool) -> Result<(), Error> {
+	if directory.is_file() {
+		return Err(Error::TargetIsFile(directory.to_path_buf()));
+	}
+
+	if directory.is_dir() {
+		let mut entries = tokio::fs::read_dir(directory)
+			.await
+			.map_err(|io_error| Error::ReadDirectory(directory.to_path_buf(), io_error))?;
+		return match entries.next_entry().await? {
+			Some(_) => Err(Error::DirectoryAlreadyExists(directory.to_path_buf())),
+			None => Ok(()),
+		};
+	}
+
+	// relative paths with a single component have an empty parent
+	if let Some(parent) = directory
+		.parent()
+		.filter(|parent| !parent.as_os_str().is_empty() && !parent.is_dir())
+	{
+		if parent.exists() {
+			return Err(Error::TargetIsFile(parent.to_path_buf()));
+		}
+		if !parents {
+			return Err(Error::MissingParentDirectory(parent.to_path_buf()));
+		}
+
+		tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+	}
+
+	create_directory(directory).await
+}
+
+/// Initializes a project for syncing within fumosclub from a template.
+///
+/// Packages don't get a main script, and missing parent directories are created with `parents`.
+pub async fn init(
+	directory: PathBuf,
+	project_type: ProjectType,
+	template: &str,
+	editor: EditorSetup,
+	parents: bool,
+) -> Result<(), Error> {
+	// look the template up first, so unknown templates don't leave an empty directory behind
+	let template = find_template(template).await?;
+	let script_name = directory
+		.file_name()
+		.unwrap_or_else(|| OsStr::new("unknown"))
+		.to_string_lossy()
+		.to_string();
+
+	create_project_directory(&directory, parents).await?;
+	create_directory(directory.join(PACKAGE_DIRECTORY)).await?;
+	template
+		.apply(&directory, &script_name, project_type, editor)
+		.await?;
+	state::ignore_in_git(&directory).await?;
+	if editor == EditorSetup::Vscode {
+		vscode::merge_settings(&directory).await?;
+	}
+	// templates may bring their own type definitions
+	if !directory.join(TYPES_FILE).try_exists()? {
+		write_file(directory.join(TYPES_FILE), &types::current()).await?;
+	}
+
+	// the template's configuration keeps its format
+	let (file_name, configuration) = template.configuration().await?.unwrap_or_else(|| {
+		(
+			PathBuf::from(SYNC_CONFIGURATION_FILE),
+			Configuration::default(),
+		)
+	});
+	let path = directory.join(file_name);
+	// TOML files have no $schema convention
+	let schema = match configuration.schema {
+		None if !is_toml(&path) => {
+			schema::write(&directory).await?;
+			Some(schema::reference())
+		}
+		schema => schema,
+	};
+	write_file(
+		&path,
+		&serialize_configuration(
+			&path,
+			&Configuration {
+				schema,
+				script_name,
+				script_id: PLACEHOLDER_SCRIPT_ID.to_owned(),
+				project_type,
+				editor: Some(editor),
+				meta: None,
+				..configuration
+			},
+		)?,
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Default)]
+pub struct PullOptions {
+	/// Overwrite the files of an existing project
+	pub force: bool,
+	/// With `force`, overwrite files even if the git working tree has uncommitted changes
+	pub allow_dirty: bool,
+	/// With `force`, leave types.d.luau as it is
+	pub keep_types: bool,
+	/// Create missing parent directories of a new project
+	pub parents: bool,
+	/// Called for every phase; progress is rendered on stderr without it
+	pub progress: Option<ProgressCallback>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PullReport {
+	pub script_id: String,
+	/// Number of modules written
+	pub modules: usize,
+}
+
+/// Pulls a project from fumosclub and links it via fumosync.json.
+///
+/// With `force`, an existing project is overwritten, including its type definitions unless
+/// `keep_types` is set; unless `allow_dirty` is set, this is refused when the project is inside of
+/// a git repository with uncommitted changes.
+pub async fn pull(
+	script_id: String,
+	project_directory: PathBuf,
+	force: bool,
+	allow_dirty: bool,
+	keep_types: bool,
+	parents: bool,
+) -> Result<(), Error> {
+	let client = Client::new(get_session_secrets().await?)?;
+	let options = PullOptions {
+		force,
+		allow_dirty,
+		keep_types,
+		parents,
+		progress: None,
+	};
+	pull_project(&client, &script_id, project_directory, options).await?;
+	Ok(())
+}
+
+/// Characters which can't be in file names on some platform
+const INVALID_FILE_NAME_CHARACTERS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Turns a script or module name into a file or directory name, falling back to `fallback` when
+/// nothing is left.
+fn sanitize_file_name(name: &str, fallback: &str) -> String {
+	let sanitized: String = name
+		.chars()
+		.map(|character| {
+			if character.is_control() || INVALID_FILE_NAME_CHARACTERS.contains(&character) {
+				'-'
+			} else {
+				character
+			}
+		})
+		.collect();
+	// Windows drops trailing dots and spaces
+	let sanitized =
+		sanitized.trim_matches(|character: char| character.is_whitespace() || character == '.');
+
+	if sanitized.chars().all(|character| character == '-') {
+		fallback.to_owned()
+	} else {
+		sanitized.to_owned()
+	}
+}
+
+/// Pulls a script into a new directory named after it, in the current directory unless
+/// `directory` is given; taken names get a numeric suffix. Returns the project directory.
+pub async fn clone_script(
+	script_reference: &str,
+	directory: Option<PathBuf>,
+	parents: bool,
+) -> Result<PathBuf, Error> {
+	let client = Client::new(get_session_secrets().await?)?;
+	let script_id = resolve_script_reference(&client, script_reference).await?;
+
+	let directory = match directory {
+		Some(directory) => directory,
+		None => {
+			// the script list has the name without fetching the whole script twice
+			let listed_name = client.cached_scripts().await.ok().and_then(|scripts| {
+				scripts
+					.iter()
+					.find(|script| script_id == script.id)
+					.map(|script| script.name.clone())
+			});
+			let name = match listed_name {
+				Some(name) => name,
+				None => client.get_editor(&script_id).await?.script_info.name,
+			};
+
+			let base = sanitize_file_name(&name, &script_id);
+			let mut directory = PathBuf::from(&base);
+			let mut suffix = 2;
+			while directory.try_exists()? {
+				directory = PathBuf::from(format!("{base}-{suffix}"));
+				suffix += 1;
+			}
+			directory
+		}
+	};
+
+	let options = PullOptions {
+		parents,
+		..PullOptions::default()
+	};
+	pull_project(&client, &script_id, directory.clone(), options).await?;
+	Ok(directory)
+}
+
+/// Pulls the script `script_reference` resolves to into the project directory; see [`pull`].
+///
+/// ```no_run
+/// use fumo::{
+/// 	client::Client,
+/// 	login::get_session_secrets,
+/// 	project::{ProgressEvent, PullOptions, pull_project},
+/// };
+///
+/// # async fn example() -> Result<(), fumo::error::Error> {
+/// let client = Client::new(get_session_secrets().await?)?;
+/// let options = PullOptions {
+/// 	force: true,
+/// 	progress: Some(Box::new(|event: ProgressEvent| eprintln!("{event:?}"))),
+/// 	..PullOptions::default()
+/// };
+/// let report = pull_project(&client, "my script", "my-script".into(), options).await?;
+/// println!("pulled {}", report.script_id);
+/// # Ok(())
+/// # }
+/// ```
+#[tracing::instrument(name = "pull", skip_all, fields(script = tracing::field::Empty))]
+pub async fn pull_project(
+	client: &Client,
+	script_reference: &str,
+	project_directory: PathBuf,
+	options: PullOptions,
+) -> Result<PullReport, Error> {
+	let PullOptions {
+		force,
+		allow_dirty,
+		keep_types,
+		parents,
+		progress: callback,
+	} = options;
+	let callback = callback.as_ref();
+	let overwrite = force && project_directory.is_dir();
+	if overwrite && !allow_dirty && git::is_dirty(&project_directory)? == Some(true) {
+		return Err(Error::DirtyWorkingTree(project_directory));
+	}
+	// fail before asking fumosclub for anything
+	if !overwrite {
+		create_project_directory(&project_directory, parents)
+			.await
+			.map_err(|error| Error::ProjectDidntInitialize(Box::new(error)))?;
+	}
+
+	let script_id = resolve_script_reference(client, script_reference).await?;
+	tracing::Span::current().record("script", telemetry::hash_id(&script_id));
+	let script_info = phase(
+		callback,
+		ProgressEvent::FetchingScript,
+		"fetching script",
+		client.get_editor(&script_id),
+	)
+	.await?
+	.script_info;
+	let project_type = ProjectType::from(&script_info.script_type);
+	// the editor doesn't say who owns the script, but the script list does
+	let creator = client.cached_scripts().await.ok().and_then(|scripts| {
+		scripts
+			.iter()
+			.find(|script| script_id == script.id)
+			.map(|script| script.creator.clone())
+	});
+
+	// setup initial file structure for hydration
+	if overwrite {
+		let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+		tokio::fs::create_dir_all(&package_directory)
+			.await
+			.map_err(|io_error| Error::CreateDirectory(package_directory, io_error))?;
+
+		if !keep_types {
+			write_file(project_directory.join(TYPES_FILE), &types::current()).await?;
+		}
+	} else {
+		match init(
+			project_directory.clone(),
+			project_type,
+			DEFAULT_TEMPLATE,
+			EditorSetup::default(),
+			parents,
+		)
+		.await
+		{
+			Ok(()) => {}
+			Err(e) => return Err(Error::ProjectDidntInitialize(Box::new(e))),
+		};
+	}
+
+	write_file(
+		project_directory.join(DESCRIPTION_FILE),
+		&script_info.description,
+	)
+	.await?;
+
+	if project_type == ProjectType::Script {
+		write_file(
+			project_directory.join(MAIN_SCRIPT_FILE),
+			arguments::strip(&script_info.source.main),
+		)
+		.await?;
+	}
+
+	// local settings, such as module aliases, outlive overwrites
+	let existing = if overwrite {
+		read_configuration(&project_directory)
+			.await
+			.unwrap_or_default()
+	} else {
+		schema::write(&project_directory).await?;
+		Configuration {
+			schema: Some(schema::reference()),
+			..Configuration::default()
+		}
+	};
+	let configuration = Configuration {
+		script_name: script_info.name,
+		script_id: script_id.into(),
+		whitelist: script_info.whitelist,
+		is_public: script_info.is_public,
+		project_type,
+		meta: None,
+		..existing
+	};
+	write_configuration(&project_directory, &configuration).await?;
+	write_metadata(
+		&project_directory,
+		&configuration,
+		Some(Metadata::now(creator, project_type)),
+	)
+	.await?;
+	// merged rather than written, so customized settings survive forced pulls
+	if configuration.editor.unwrap_or_default() == EditorSetup::Vscode {
+		vscode::merge_settings(&project_directory).await?;
+	}
+
+	let total = script_info.source.modules.len();
+	let bar = match callback {
+		Some(callback) => {
+			callback(ProgressEvent::WritingModules { total });
+			None
+		}
+		None => Some(Progress::new("writing modules", total as u64)),
+	};
+	let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+	let section = timing::section("file writes");
+	// the first failure drops the remaining writes
+	futures::stream::iter(script_info.source.modules)
+		.map(|(name, source)| {
+			let relative_path = PathBuf::from(format!("{}.luau", configuration.local_module_name(&name)));
+			let path = package_directory.join(&relative_path);
+			let bar = &bar;
+			async move {
+				// nested modules are written into directories, but never outside of pkg/
+				if !relative_path
+					.components()
+					.all(|component| matches!(component, Component::Normal(..)))
+				{
+					return Err(Error::InvalidModuleName {
+						path,
+						reason: format!(
+							"the remote module {name:?} can't be written inside of {PACKAGE_DIRECTORY}"
+						),
+					});
+				}
+				if let Some(parent) = path.parent() {
+					tokio::fs::create_dir_all(parent)
+						.await
+						.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+				}
+
+				write_file(path, &source).await?;
+				match (bar, callback) {
+					(Some(bar), _) => bar.inc(),
+					(None, Some(callback)) => callback(ProgressEvent::WroteModule),
+					(None, None) => {}
+				}
+				Ok::<_, Error>(())
+			}
+		})
+		.buffer_unordered(CONCURRENT_FILE_OPERATIONS)
+		.try_collect::<()>()
+		.await?;
+	drop(section);
+	match (bar, callback) {
+		(Some(bar), _) => bar.finish(),
+		(None, Some(callback)) => callback(ProgressEvent::Done),
+		(None, None) => {}
+	}
+
+	Ok(PullReport {
+		script_id: configuration.script_id,
+		modules: total,
+	})
+}
+
+/// Output path which stands for stdout.
+pub const STDOUT_PATH: &str = "-";
+
+/// What [`fetch`] did with the source.
+pub enum Fetched {
+	/// The source was written to this file
+	File(PathBuf),
+	/// The source, for the caller to write to stdout
+	Stdout(String),
+}
+
+/// Writes a single file of a script, without scaffolding a project.
+///
+/// The main source is written unless `module` is given; `output` defaults to `<script name>.luau`
+/// in the current directory, and [`STDOUT_PATH`] returns the source instead.
+///
+/// # Errors
+/// - [`Error::Offline`] before the secrets are read, if `FUMO_OFFLINE=1`
+/// - [`Error::FileAlreadyExists`] if the output file exists and `force` isn't set
+/// - [`Error::UnknownModule`]
+/// - [`Error::NoMainSource`] for packages without `module`
+pub async fn fetch(
+	id: &str,
+	output: Option<PathBuf>,
+	module: Option<&str>,
+	force: bool,
+) -> Result<Fetched, Error> {
+	ensure_reachable(&settings::get().base_url)?;
+	let client = Client::new(get_session_secrets().await?)?;
+	let id = resolve_script_reference(&client, id).await?;
+	let mut script_info = progress::spinner("fetching script", client.get_editor(&id))
+		.await?
+		.script_info;
+
+	let (name, source) = match module {
+		Some(module) => match script_info.source.modules.remove(module) {
+			Some(source) => (module.to_owned(), source),
+			None => {
+				let mut modules: Vec<String> = script_info.source.modules.into_keys().collect();
+				modules.sort();
+				return Err(Error::UnknownModule(module.to_owned(), modules));
+			}
+		},
+		None if matches!(script_info.script_type, ScriptType::Package) => {
+			return Err(Error::NoMainSource(script_info.name));
+		}
+		None => (script_info.name, script_info.source.main),
+	};
+
+	let path = output
+		.unwrap_or_else(|| PathBuf::from(format!("{}.luau", sanitize_file_name(&name, id.as_str()))));
+
+	if path == Path::new(STDOUT_PATH) {
+		return Ok(Fetched::Stdout(source));
+	}
+
+	if !force && path.try_exists()? {
+		return Err(Error::FileAlreadyExists(path));
+	}
+
+	write_file(&path, &source).await?;
+	Ok(Fetched::File(path))
+}
+
+pub async fn read_file<T: AsRef<Path>>(path: T) -> Result<String, Error> {
+	match tokio::fs::read_to_string(path.as_ref()).await {
+		Ok(value) => Ok(value),
+		Err(io_error) => Err(Error::ReadFile(path.as_ref().to_path_buf(), io_error)),
+	}
+}
+
+/// Derives a module name from a path relative to the package directory.
+///
+/// Components are joined with `/` regardless of the platform, so every checkout names modules the
+/// same, and only the final extension is stripped.
+fn get_module_from_path(relative_path: &Path) -> Result<String, String> {
+	let mut components = Vec::new();
+	for component in relative_path.components() {
+		match component {
+			Component::Normal(name) => components.push(name.to_string_lossy()),
+			Component::CurDir => {}
+			_ => return Err(format!("the path leaves {PACKAGE_DIRECTORY}")),
+		}
+	}
+
+	let Some(file_name) = components.pop() else {
+		return Err("the module name is empty".to_owned());
+	};
+	// a file named .luau has no extension according to Path, but is still a module
+	let stem = file_name
+		.rsplit_once('.')
+		.map_or(file_name.as_ref(), |(stem, _)| stem);
+
+	let mut name = String::new();
+	for component in components.iter().map(AsRef::as_ref).chain([stem]) {
+		if !name.is_empty() {
+			name.push('/');
+		}
+		name.push_str(ModuleName::new(component)?.as_str());
+	}
+
+	ModuleName::new(&name).map(String::from)
+}
+
+/// Gets the normalized name of a module file, before aliases are applied.
+///
+/// # Errors
+/// - [`Error::InvalidModuleName`]
+fn local_module_name_of(package_directory: &Path, path: &Path) -> Result<String, Error> {
+	path
+		.strip_prefix(package_directory)
+		.map_err(|_| format!("the path isn't inside of {PACKAGE_DIRECTORY}"))
+		.and_then(get_module_from_path)
+		.map_err(|reason| Error::InvalidModuleName {
+			path: path.to_path_buf(),
+			reason,
+		})
+}
+
+/// Gets the name a module file is uploaded as, with its alias applied.
+///
+/// # Errors
+/// - [`Error::InvalidModuleName`]
+fn remote_module_name_of(
+	configuration: &Configuration,
+	package_directory: &Path,
+	path: &Path,
+) -> Result<ModuleName, Error> {
+	let local_name = local_module_name_of(package_directory, path)?;
+	ModuleName::new(configuration.remote_module_name(&local_name)).map_err(|reason| {
+		Error::InvalidModuleName {
+			path: path.to_path_buf(),
+			reason: format!("the alias is invalid: {reason}"),
+		}
+	})
+}
+
+/// Gets the metadata updates for the fields which differ from the remote script.
+///
+/// Unchanged fields are left out, so edits made on the website to them aren't reverted.
+fn get_editor_updates_from_configuration<'a>(
+	configuration: &'a Configuration,
+	drift: &'a [MetadataDrift],
+) -> Vec<EditorUpdate<'a>> {
+	drift
+		.iter()
+		.map(|field| match field {
+			MetadataDrift::ScriptName { .. } => EditorUpdate::Name(&configuration.script_name),
+			MetadataDrift::IsPublic { .. } => EditorUpdate::Publicity(configuration.is_public),
+			// already merged with the remote whitelist by changed_metadata
+			MetadataDrift::Whitelist { local, .. } => {
+				EditorUpdate::Whitelist(local.iter().map(String::as_str).collect())
+			}
+		})
+		.collect()
+}
+
+/// Finds the metadata fields edited in the configuration since the last push or pull which differ
+/// from the script; there is nothing to compare with `"syncMetadata": false`.
+///
+/// The script is only fetched when a field was edited, and fields only edited on the website are
+/// left alone. Projects without a record of the last sync compare every field.
+async fn changed_metadata(
+	client: &Client,
+	project_directory: &Path,
+	configuration: &Configuration,
+) -> Result<Vec<MetadataDrift>, Error> {
+	if !configuration.syncs_metadata() {
+		return Ok(Vec::new());
+	}
+
+	let synced = state::read::<SyncState>(project_directory).await.synced;
+	if synced
+		.as_ref()
+		.is_some_and(|synced| *synced == SyncedMetadata::of(configuration))
+	{
+		return Ok(Vec::new());
+	}
+
+	let remote = client
+		.get_editor(&configuration.linked_script_id()?)
+		.await?
+		.script_info;
+	Ok(edited_metadata(configuration, &remote, synced.as_ref()))
+}
+
+/// Compares the metadata fields edited since `synced` against the script, merging the whitelist.
+fn edited_metadata(
+	configuration: &Configuration,
+	remote: &EditorScriptInfo,
+	synced: Option<&SyncedMetadata>,
+) -> Vec<MetadataDrift> {
+	let policy = configuration.whitelist_merge.unwrap_or_default();
+	compare_metadata(configuration, remote)
+		.into_iter()
+		.filter(|field| synced.is_none_or(|synced| synced.edited(configuration, field)))
+		.filter_map(|field| match field {
+			MetadataDrift::Whitelist { local, remote } => {
+				let merged = whitelist::merge(policy, &local, &remote);
+				// a union may leave nothing to upload
+				(sorted_entries(&merged) != sorted_entries(&remote)).then_some(MetadataDrift::Whitelist {
+					local: merged,
+					remote,
+				})
+			}
+			field => Some(field),
+		})
+		.collect()
+}
+
+/// Logs how the whitelist of the metadata drift changes the remote one, right before it is sent.
+fn log_whitelist_changes(configuration: &Configuration, drift: &[MetadataDrift]) {
+	for field in drift {
+		if let MetadataDrift::Whitelist { local, remote } = field {
+			whitelist::log_changes(
+				configuration.whitelist_merge.unwrap_or_default(),
+				local,
+				remote,
+			);
+		}
+	}
+}
+
+fn sorted_entries(entries: &[String]) -> Vec<String> {
+	let mut entries = entries.to_vec();
+	entries.sort();
+	entries.dedup();
+	entries
+}
+
+/// A metadata field which differs between the configuration and fumosclub.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "field", rename_all = "camelCase")]
+pub enum MetadataDrift {
+	ScriptName {
+		local: String,
+		remote: String,
+	},
+	IsPublic {
+		local: bool,
+		remote: bool,
+	},
+	Whitelist {
+		local: Vec<String>,
+		remote: Vec<String>,
+	},
+}
+
+impl std::fmt::Display for MetadataDrift {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let publicity = |is_public: bool| if is_public { "public" } else { "private" };
+		let whitelist = |entries: &[String]| match entries {
+			[] => "nobody".to_owned(),
+			entries => entries.join(", "),
+		};
+
+		match self {
+			Self::ScriptName { local, remote } => {
+				write!(f, "remote is named {remote:?}, local config says {local:?}")
+			}
+			Self::IsPublic { local, remote } => write!(
+				f,
+				"remote is {}, local config says {}",
+				publicity(*remote),
+				publicity(*local)
+			),
+			Self::Whitelist { local, remote } => write!(
+				f,
+				"remote whitelists {}, local config whitelists {}",
+				whitelist(remote),
+				whitelist(local)
+			),
+		}
+	}
+}
+
+impl MetadataDrift {
+	/// Gets the remote name, if it is the script name which differs.
+	pub fn remote_name(&self) -> Option<&str> {
+		match self {
+			Self::ScriptName { remote, .. } => Some(remote),
+			_ => None,
+		}
+	}
+}
+
+/// Whether the name check already ran in this invocation
+static NAME_CHECKED: AtomicBool = AtomicBool::new(false);
+
+/// Compares names ignoring case, spaces and punctuation, so `my-script` matches `My Script`.
+fn names_match(a: &str, b: &str) -> bool {
+	let normalize = |name: &str| {
+		name
+			.chars()
+			.filter(|character| character.is_alphanumeric())
+			.flat_map(char::to_lowercase)
+			.collect::<String>()
+	};
+
+	normalize(a) == normalize(b)
+}
+
+/// Notes when the script name differs from the project directory's name or the remote name.
+///
+/// Runs once per invocation, and is silent with `--quiet`, JSON output, or
+/// `"suppressNameCheck": true`.
+pub fn check_name(
+	project_directory: &Path,
+	configuration: &Configuration,
+	remote_name: Option<&str>,
+) {
+	let settings = settings::get();
+	if configuration.suppress_name_check.unwrap_or(false)
+		|| settings.quiet
+		|| settings.format == OutputFormat::Json
+		|| NAME_CHECKED.swap(true, Ordering::Relaxed)
+	{
+		return;
+	}
+
+	let directory_name = std::fs::canonicalize(project_directory)
+		.ok()
+		.and_then(|path| Some(path.file_name()?.to_string_lossy().to_string()));
+	let directory_differs = directory_name
+		.as_deref()
+		.is_some_and(|name| !names_match(name, &configuration.script_name));
+	if !directory_differs && remote_name.is_none_or(|name| name == configuration.script_name) {
+		return;
+	}
+
+	info!(
+		"scriptName is {:?}, the directory is named {:?}, and fumosclub has {}",
+		configuration.script_name,
+		directory_name.unwrap_or_default(),
+		remote_name.map_or_else(|| "the same name".to_owned(), |name| format!("{name:?}"))
+	);
+	info!(
+		"run `fumo config set scriptName <name>`, or `fumo status --fix-name` to adopt the remote name; \"suppressNameCheck\": true silences this"
+	);
+}
+
+/// Compares the metadata push uploads against a script's remote state; whitelist order is ignored.
+pub fn compare_metadata(
+	configuration: &Configuration,
+	remote: &EditorScriptInfo,
+) -> Vec<MetadataDrift> {
+	let mut drift = Vec::new();
+	if configuration.script_name != remote.name {
+		drift.push(MetadataDrift::ScriptName {
+			local: configuration.script_name.clone(),
+			remote: remote.name.clone(),
+		});
+	}
+	if configuration.is_public != remote.is_public {
+		drift.push(MetadataDrift::IsPublic {
+			local: configuration.is_public,
+			remote: remote.is_public,
+		});
+	}
+
+	if sorted_entries(&configuration.whitelist) != sorted_entries(&remote.whitelist) {
+		drift.push(MetadataDrift::Whitelist {
+			local: configuration.whitelist.clone(),
+			remote: remote.whitelist.clone(),
+		});
+	}
+
+	drift
+}
+
+/// Gets the size of a file, failing if it is over the configured per-file limit.
+///
+/// # Errors
+/// - [`Error::FileTooLarge`]
+/// - [`Error::ReadFile`]
+async fn checked_file_size(path: &Path) -> Result<u64, Error> {
+	let size = tokio::fs::metadata(path)
+		.await
+		.map_err(|io_error| Error::ReadFile(path.to_path_buf(), io_error))?
+		.len();
+	let limit = settings::get().max_file_size;
+
+	if size > limit {
+		return Err(Error::FileTooLarge {
+			path: path.to_path_buf(),
+			size,
+			limit,
+		});
+	}
+
+	Ok(size)
+}
+
+fn is_module_file(path: &Path) -> bool {
+	// a file named .luau has no extension according to Path, but is still a module
+	path.extension() == Some(OsStr::new("luau")) || path.file_name() == Some(OsStr::new(".luau"))
+}
+
+/// Files in pkg/ skipped for their extension during this run, so they are only reported once
+static UNRECOGNIZED_FILES: std::sync::Mutex<BTreeSet<PathBuf>> =
+	std::sync::Mutex::new(BTreeSet::new());
+
+/// Which files in pkg/ are modules, and how the others are skipped.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleFilter {
+	pub follow_symlinks: bool,
+	/// Extensions of files which are skipped silently
+	pub asset_extensions: Vec<String>,
+}
+
+impl ModuleFilter {
+	pub fn is_asset(&self, path: &Path) -> bool {
+		path
+			.extension()
+			.and_then(OsStr::to_str)
+			.is_some_and(|extension| {
+				self.asset_extensions.iter().any(|asset| {
+					asset
+						.trim_start_matches('.')
+						.eq_ignore_ascii_case(extension)
+				})
+			})
+	}
+
+	/// Skips a file which isn't a module, returning whether it is an unrecognized file which wasn't
+	/// skipped before during this run.
+	fn skip(&self, path: &Path) -> bool {
+		if self.is_asset(path) {
+			tracing::debug!("skipping asset {}", path.display());
+			return false;
+		}
+
+		tracing::debug!("skipping {}; it isn't a module or an asset", path.display());
+		UNRECOGNIZED_FILES
+			.lock()
+			.is_ok_and(|mut files| files.insert(path.to_path_buf()))
+	}
+}
+
+/// Logs a single notice for the files in pkg/ which were skipped for their extension during this
+/// run; the files themselves are only logged at debug level.
+pub fn report_unrecognized_files() {
+	let count = UNRECOGNIZED_FILES
+		.lock()
+		.map(|files| files.len())
+		.unwrap_or_default();
+	if count > 0 {
+		info!(
+			"skipped {count} file(s) in {PACKAGE_DIRECTORY}/ with unrecognized extensions; list them under assetExtensions in fumosync.json to silence this"
+		);
+	}
+}
+
+/// Lists the module files in the package directory and its subdirectories; other files are
+/// skipped, as described by the [filter](ModuleFilter).
+///
+/// When following symlinks, file symlinks are listed like files, and directory symlinks have the
+/// modules inside of them listed, one level deep and only once per target. Otherwise, symlinks are
+/// skipped. Broken symlinks are warned about and skipped.
+pub async fn list_module_files(
+	package_directory: &Path,
+	filter: &ModuleFilter,
+) -> Result<Vec<PathBuf>, Error> {
+	let follow_symlinks = filter.follow_symlinks;
+	let canonicalize = |path: PathBuf| async move {
+		tokio::fs::canonicalize(&path)
+			.await
+			.map_err(|io_error| Error::ReadDirectory(path, io_error))
+	};
+
+	let mut files = Vec::new();
+	// targets of directories which were listed already, so links to them can't cycle
+	let mut listed = vec![canonicalize(package_directory.to_path_buf()).await?];
+	let mut directories = vec![(package_directory.to_path_buf(), true)];
+
+	while let Some((directory, top_level)) = directories.pop() {
+		let mut stream = match tokio::fs::read_dir(&directory).await {
+			Ok(value) => value,
+			Err(io_error) => return Err(Error::ReadDirectory(directory, io_error)),
+		};
+
+		while let Some(entry) = stream.next_entry().await? {
+			let path = entry.path();
+			let Ok(file_type) = entry.file_type().await else {
+				warn!("failed getting file type for {}", path.display());
+				continue;
+			};
+
+			if file_type.is_file() {
+				if is_module_file(&path) {
+					files.push(path);
+				} else {
+					filter.skip(&path);
+				}
+			} else if file_type.is_dir() {
+				directories.push((path, false));
+			} else if file_type.is_symlink() && follow_symlinks {
+				match tokio::fs::metadata(&path).await {
+					Err(_) => warn!("skipping broken symlink {}", path.display()),
+					Ok(metadata) if metadata.is_dir() => {
+						let target = canonicalize(path.clone()).await?;
+						if !top_level {
+							warn!(
+								"skipping {}; only directory symlinks directly inside of {PACKAGE_DIRECTORY} are followed",
+								path.display()
+							);
+						} else if listed.contains(&target) {
+							warn!(
+								"skipping {}; it links to a directory which was already read",
+								path.display()
+							);
+						} else {
+							listed.push(target);
+							directories.push((path, false));
+						}
+					}
+					Ok(_) => {
+						if is_module_file(&path) {
+							files.push(path);
+						} else {
+							filter.skip(&path);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	Ok(files)
+}
+
+/// A symlink directly inside of the package directory, as the watcher sees it.
+struct SymlinkTarget {
+	/// Path of the link, relative to the project directory
+	link: PathBuf,
+	target: PathBuf,
+	is_directory: bool,
+}
+
+/// Resolves the symlinks directly inside of the package directory; broken symlinks are left out.
+fn symlink_targets(project_directory: &Path) -> Result<Vec<SymlinkTarget>, Error> {
+	let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+	let entries = std::fs::read_dir(&package_directory)
+		.map_err(|io_error| Error::ReadDirectory(package_directory, io_error))?;
+
+	let mut targets = Vec::new();
+	for entry in entries {
+		let entry = entry?;
+		if !entry
+			.file_type()
+			.is_ok_and(|file_type| file_type.is_symlink())
+		{
+			continue;
+		}
+
+		if let Ok(target) = std::fs::canonicalize(entry.path()) {
+			targets.push(SymlinkTarget {
+				link: Path::new(PACKAGE_DIRECTORY).join(entry.file_name()),
+				is_directory: target.is_dir(),
+				target,
+			});
+		}
+	}
+
+	Ok(targets)
+}
+
+pub struct NewModule {
+	pub path: PathBuf,
+	/// Name the module is uploaded as, and required with
+	pub remote_name: String,
+}
+
+/// Creates a module in pkg/ from a template, checking its name like push does.
+///
+/// # Errors
+/// - [`Error::InvalidModuleName`] for invalid names, and names of existing modules
+/// - [`Error::InvalidModuleAlias`] if an alias already uploads another module under the name
+pub async fn new_module(
+	project_directory: &Path,
+	name: &str,
+	template: &str,
+) -> Result<NewModule, Error> {
+	let configuration = read_configuration(project_directory).await?;
+	let template = find_template(template).await?;
+	let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+	let name = local_module_name_of(
+		&package_directory,
+		&package_directory.join(format!("{name}.luau")),
+	)?;
+	let path = package_directory.join(format!("{name}.luau"));
+
+	let mut local_names = Vec::new();
+	if package_directory.is_dir() {
+		for existing in list_module_files(&package_directory, &configuration.module_filter()).await? {
+			let local_name = local_module_name_of(&package_directory, &existing)?;
+			if local_name == name {
+				return Err(Error::InvalidModuleName {
+					path,
+					reason: format!("{} is already named \"{name}\"", existing.display()),
+				});
+			}
+			local_names.push(local_name);
+		}
+	}
+	local_names.push(name.clone());
+	configuration.validate_module_aliases(&local_names)?;
+	let remote_name = remote_module_name_of(&configuration, &package_directory, &path)?;
+
+	if let Some(parent) = path.parent() {
+		tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+	}
+	write_file(&path, &template.module_source(&remote_name).await?).await?;
+
+	Ok(NewModule { path, remote_name })
+}
+
+/// Opens `$VISUAL` or `$EDITOR` on a file, waiting for it to exit.
+///
+/// # Errors
+/// - [`Error::Editor`] if the editor can't be run, or exits unsuccessfully
+pub async fn open_in_editor(path: &Path) -> Result<(), Error> {
+	let editor = std::env::var("VISUAL")
+		.or_else(|_| std::env::var("EDITOR"))
+		.unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_owned());
+	// editors such as `code --wait` come with arguments, and quoted paths with spaces
+	let words = shell_words::split(&editor)
+		.map_err(|error| Error::Editor(format!("couldn't parse {editor:?}: {error}")))?;
+	let Some((program, arguments)) = words.split_first() else {
+		return Err(Error::Editor("the editor is empty".to_owned()));
+	};
+
+	let status = tokio::process::Command::new(program)
+		.args(arguments)
+		.arg(path)
+		.status()
+		.await
+		.map_err(|error| Error::Editor(format!("failed running {editor}: {error}")))?;
+	if !status.success() {
+		return Err(Error::Editor(format!("{editor} exited with {status}")));
+	}
+
+	Ok(())
+}
+
+/// Renames module files whose names, or the names of the directories they are in, have
+/// surrounding whitespace, to the trimmed names they are uploaded as, after asking. Declined
+/// renames leave the files as they are.
+///
+/// Returns the renamed files, by their old and new paths.
+///
+/// # Errors
+/// - [`Error::InvalidModuleName`] if a trimmed name is already taken by another file
+pub async fn fix_module_names(
+	project_directory: &Path,
+	configuration: &Configuration,
+	confirm: Option<&ConfirmCallback>,
+) -> Result<Vec<(PathBuf, PathBuf)>, Error> {
+	let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+	let mut renames = Vec::new();
+	for path in list_module_files(&package_directory, &configuration.module_filter()).await? {
+		let Ok(relative_path) = path.strip_prefix(&package_directory) else {
+			continue;
+		};
+		let mut trimmed: Vec<String> = relative_path
+			.components()
+			.map(|component| component.as_os_str().to_string_lossy().trim().to_owned())
+			.collect();
+		// the name is uploaded without its extension, so whitespace before it is trimmed too
+		if let Some(file_name) = trimmed.pop() {
+			trimmed.push(match file_name.rsplit_once('.') {
+				Some((stem, extension)) => format!("{}.{extension}", stem.trim()),
+				None => file_name,
+			});
+		}
+		// empty names can't be fixed by trimming, and are refused when the modules are read
+		if trimmed
+			.iter()
+			.any(|component| component.is_empty() || component.starts_with('.'))
+		{
+			continue;
+		}
+		let trimmed: PathBuf = trimmed.into_iter().collect();
+		if trimmed.as_path() != relative_path {
+			renames.push((path.clone(), package_directory.join(trimmed)));
+		}
+	}
+
+	if renames.is_empty() {
+		return Ok(renames);
+	}
+
+	let listed: Vec<String> = renames
+		.iter()
+		.map(|(from, to)| format!("{} to {}", from.display(), to.display()))
+		.collect();
+	match confirm {
+		Some(confirm) if confirm(&format!("rename {}?", listed.join(", ")))? => {}
+		_ => return Ok(Vec::new()),
+	}
+
+	for (from, to) in &renames {
+		if to.try_exists()? {
+			return Err(Error::InvalidModuleName {
+				path: from.clone(),
+				reason: format!("{} already exists", to.display()),
+			});
+		}
+		if let Some(parent) = to.parent() {
+			tokio::fs::create_dir_all(parent)
+				.await
+				.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+		}
+		tokio::fs::rename(from, to)
+			.await
+			.map_err(|io_error| Error::CreateFile(to.clone(), io_error))?;
+		info!("renamed {} to {}", from.display(), to.display());
+	}
+
+	Ok(renames)
+}
+
+/// Reads every module in the package directory, exactly as they are uploaded.
+///
+/// Modules are read concurrently and sorted by their remote name, so request bodies are stable.
+/// Sizes are checked before anything is read; `total_size` is the size of other files uploaded
+/// alongside the modules.
+async fn read_modules(
+	project_directory: &Path,
+	configuration: &Configuration,
+	mut total_size: u64,
+) -> Result<Vec<(ModuleName, String)>, Error> {
+	let start = Instant::now();
+	let mut module_paths: Vec<(String, PathBuf)> = Vec::new();
+
+	let section = timing::section("module enumeration");
+	let pkg_path = project_directory.join(PACKAGE_DIRECTORY);
+	for path in list_module_files(&pkg_path, &configuration.module_filter()).await? {
+		total_size += checked_file_size(&path).await?;
+		module_paths.push((local_module_name_of(&pkg_path, &path)?, path));
+	}
+	drop(section);
+
+	let limit = settings::get().max_total_size;
+	if total_size > limit {
+		return Err(Error::ProjectTooLarge {
+			size: total_size,
+			limit,
+		});
+	}
+
+	// names only collide once whitespace is trimmed, since file names are unique
+	module_paths.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+	if let Some([(_, first), (_, second)]) =
+		module_paths.windows(2).find(|pair| pair[0].0 == pair[1].0)
+	{
+		return Err(Error::InvalidModuleName {
+			path: second.clone(),
+			reason: format!(
+				"the module name is the same as {} once whitespace is trimmed",
+				first.display()
+			),
+		});
+	}
+
+	let local_names: Vec<String> = module_paths.iter().map(|(name, _)| name.clone()).collect();
+	configuration.validate_module_aliases(&local_names)?;
+	let module_paths: Vec<(ModuleName, PathBuf)> = module_paths
+		.into_iter()
+		.map(|(_, path)| {
+			Ok((
+				remote_module_name_of(configuration, &pkg_path, &path)?,
+				path,
+			))
+		})
+		.collect::<Result<_, Error>>()?;
+
+	let _section = timing::section("file reads");
+	// the first failure drops the remaining reads
+	let mut modules: Vec<(ModuleName, String)> = futures::stream::iter(module_paths)
+		.map(|(name, path)| async move { Ok::<_, Error>((name, read_file(path).await?)) })
+		.buffer_unordered(CONCURRENT_FILE_OPERATIONS)
+		.try_collect()
+		.await?;
+	modules.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+	info!("read {} modules in {:.2?}", modules.len(), start.elapsed());
+	Ok(modules)
+}
+
+/// Local files uploaded by a push.
+struct ProjectSources {
+	description: String,
+	/// `None` for packages, which have no main script
+	main_source: Option<String>,
+	modules: Vec<(ModuleName, String)>,
+}
+
+/// Checks the size of contents uploaded in place of a file.
+fn checked_size(path: &Path, contents: &str) -> Result<u64, Error> {
+	let size = contents.len() as u64;
+	let limit = settings::get().max_file_size;
+	if size > limit {
+		return Err(Error::FileTooLarge {
+			path: path.to_path_buf(),
+			size,
+			limit,
+		});
+	}
+
+	Ok(size)
+}
+
+/// Reads every file a push uploads, checking sizes before anything is read.
+///
+/// `main_source` and `description` are uploaded in place of their files when given.
+///
+/// # Errors
+/// - [`Error::NoMainSource`] if `main_source` is given for a package
+async fn read_project_sources(
+	project_directory: &Path,
+	configuration: &Configuration,
+	main_source: Option<String>,
+	description: Option<String>,
+) -> Result<ProjectSources, Error> {
+	let description_path = project_directory.join(DESCRIPTION_FILE);
+	let main_source_path = (configuration.project_type == ProjectType::Script)
+		.then(|| project_directory.join(MAIN_SCRIPT_FILE));
+	if main_source.is_some() && main_source_path.is_none() {
+		return Err(Error::NoMainSource(configuration.script_name.clone()));
+	}
+
+	let mut size = match &description {
+		Some(description) => checked_size(&description_path, description)?,
+		None => checked_file_size(&description_path).await?,
+	};
+	if let Some(path) = &main_source_path {
+		size += match &main_source {
+			Some(source) => checked_size(path, source)?,
+			None => checked_file_size(path).await?,
+		};
+	}
+
+	let modules = read_modules(project_directory, configuration, size).await?;
+	let _section = timing::section("file reads");
+	let main_source = match (main_source, main_source_path) {
+		(Some(source), _) => Some(source),
+		(None, Some(path)) => Some(read_file(path).await?),
+		(None, None) => None,
+	};
+	let description = match description {
+		Some(description) => description,
+		None => read_file(description_path).await?,
+	};
+
+	Ok(ProjectSources {
+		description,
+		main_source,
+		modules,
+	})
+}
+
+/// Lists the sources which are likely empty by accident: a main script which is blank, and modules
+/// without a single byte.
+fn empty_sources(main_source: Option<&str>, modules: &[(ModuleName, String)]) -> Vec<String> {
+	let mut empty = Vec::new();
+	if main_source.is_some_and(|source| source.trim().is_empty()) {
+		empty.push(MAIN_SCRIPT_FILE.to_owned());
+	}
+	empty.extend(
+		modules
+			.iter()
+			.filter(|(_, source)| source.is_empty())
+			.map(|(name, _)| format!("module {name:?}")),
+	);
+
+	empty
+}
+
+/// Checks the contents of every source with [`limits::check_content`], returning the warnings.
+fn check_sources(
+	configuration: &Configuration,
+	sources: &ProjectSources,
+) -> Result<Vec<String>, Error> {
+	let mut warnings = limits::check_content(DESCRIPTION_FILE, &sources.description, false)?;
+	if let Some(main_source) = &sources.main_source {
+		warnings.extend(limits::check_content(MAIN_SCRIPT_FILE, main_source, true)?);
+	}
+	for (name, source) in &sources.modules {
+		let path = format!(
+			"{PACKAGE_DIRECTORY}/{}.luau",
+			configuration.local_module_name(name)
+		);
+		warnings.extend(limits::check_content(&path, source, false)?);
+	}
+
+	Ok(warnings)
+}
+
+/// Checks the `requireM` calls of every uploaded source; see [`requires::check`].
+async fn check_requires(
+	project_directory: &Path,
+	configuration: &Configuration,
+	sources: &ProjectSources,
+) -> Vec<String> {
+	let uploaded: Vec<&str> = sources
+		.modules
+		.iter()
+		.map(|(name, _)| name.as_str())
+		.collect();
+	let main = sources
+		.main_source
+		.as_deref()
+		.map(|source| (MAIN_SCRIPT_FILE.to_owned(), source));
+	let modules = sources.modules.iter().map(|(name, source)| {
+		(
+			format!(
+				"{PACKAGE_DIRECTORY}/{}.luau",
+				configuration.local_module_name(name)
+			),
+			source.as_str(),
+		)
+	});
+
+	requires::check(
+		project_directory,
+		configuration,
+		main.into_iter().chain(modules),
+		&uploaded,
+	)
+	.await
+}
+
+/// Lists content problems a push would refuse or warn about, such as NUL bytes or minified lines.
+pub async fn find_content_problems(
+	project_directory: &Path,
+	configuration: &Configuration,
+) -> Result<Vec<String>, Error> {
+	let sources = read_project_sources(project_directory, configuration, None, None).await?;
+	match check_sources(configuration, &sources) {
+		Ok(warnings) => Ok(warnings),
+		Err(error @ Error::BinaryContent(..)) => Ok(vec![error.to_string()]),
+		Err(error) => Err(error),
+	}
+}
+
+/// Lists the empty files a push asks about before uploading.
+pub async fn find_empty_files(
+	project_directory: &Path,
+	configuration: &Configuration,
+) -> Result<Vec<String>, Error> {
+	let sources = read_project_sources(project_directory, configuration, None, None).await?;
+	Ok(empty_sources(
+		sources.main_source.as_deref(),
+		&sources.modules,
+	))
+}
+
+/// Asks whether empty files should be uploaded.
+///
+/// # Errors
+/// - [`Error::EmptyFiles`] if they shouldn't be, or there is nothing to ask with
+fn confirm_empty_files(empty: Vec<String>, confirm: Option<&ConfirmCallback>) -> Result<(), Error> {
+	match confirm {
+		Some(confirm) if confirm(&format!("upload the empty {}?", empty.join(", ")))? => Ok(()),
+		_ => Err(Error::EmptyFiles(empty)),
+	}
+}
+
+/// A phase of a push or pull, reported to the progress callback of [`PushOptions`] or
+/// [`PullOptions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressEvent {
+	ReadingSources,
+	CheckingMetadata,
+	Uploading,
+	FetchingScript,
+	WritingModules { total: usize },
+	WroteModule,
+	Done,
+}
+
+pub type ProgressCallback = Box<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// Answers a yes-or-no question, such as by prompting; shared, since a workspace or watcher asks
+/// for several pushes.
+pub type ConfirmCallback = Arc<dyn Fn(&str) -> Result<bool, Error> + Send + Sync>;
+
+/// Reports a phase to the callback, or shows a spinner for it without one.
+async fn phase<T>(
+	callback: Option<&ProgressCallback>,
+	event: ProgressEvent,
+	message: &str,
+	future: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+	match callback {
+		Some(callback) => {
+			callback(event);
+			future.await
+		}
+		None => progress::spinner(message, future).await,
+	}
+}
+
+#[derive(Default)]
+pub struct PushOptions {
+	/// Build the updates without uploading them or recording anything
+	pub dry_run: bool,
+	/// Upload empty files without asking
+	pub allow_empty: bool,
+	/// Push even if another project last pushed the script; see [`registry::check`]
+	pub force: bool,
+	/// Also delete remote modules with no file in pkg/, like `fumo prune`
+	pub prune: bool,
+	/// Remote names of modules which are neither uploaded nor pruned
+	pub ignore: Vec<String>,
+	/// Rename module files with surrounding whitespace in their names first, after asking through
+	/// `confirm`; see [`fix_module_names`]
+	pub fix_names: bool,
+	/// Refuse to push when `requireM` calls break once pushed, instead of warning; see
+	/// [`requires::check`]
+	pub strict: bool,
+	/// Project recorded as pushing the script, when pushing a copy of it such as a snapshot
+	pub registered_directory: Option<PathBuf>,
+	/// Uploaded instead of init.server.luau, such as a main source read from stdin
+	pub main_source: Option<String>,
+	/// Uploaded instead of README.md
+	pub description: Option<String>,
+	/// Called for every phase; progress is rendered on stderr without it
+	pub progress: Option<ProgressCallback>,
+	/// Asked before uploading empty files or pushing over another project, which are refused
+	/// without it
+	pub confirm: Option<ConfirmCallback>,
+}
+
+/// An update which was uploaded, or would have been for dry runs.
+#[derive(Serialize, Clone, Debug)]
+pub struct SentUpdate {
+	pub update: String,
+	/// Bytes of text in the update
+	pub size: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PushReport {
+	pub script_id: ScriptId,
+	pub dry_run: bool,
+	pub updates: Vec<SentUpdate>,
+}
+
+/// Pushes the project with the saved session.
+pub async fn push<T: AsRef<Path>>(
+	project_directory: T,
+	options: PushOptions,
+) -> Result<PushReport, Error> {
+	let client = Client::new(get_session_secrets().await?)?;
+	push_project(&client, project_directory.as_ref(), options).await
+}
+
+/// Uploads every source, the description, and the metadata which differs from the remote script.
+///
+/// Nothing is printed or prompted for; warnings are logged to stderr. Empty files, and scripts last
+/// pushed by another project, are only uploaded once the confirm callback agrees, unless allowed
+/// by the options or the configuration.
+///
+/// ```no_run
+/// use fumo::{
+/// 	client::Client,
+/// 	login::get_session_secrets,
+/// 	project::{ProgressEvent, PushOptions, push_project},
+/// };
+/// use std::path::Path;
+///
+/// # async fn example() -> Result<(), fumo::error::Error> {
+/// let client = Client::new(get_session_secrets().await?)?;
+/// let options = PushOptions {
+/// 	prune: true,
+/// 	ignore: vec!["scratch".to_owned()],
+/// 	progress: Some(Box::new(|event: ProgressEvent| eprintln!("{event:?}"))),
+/// 	..PushOptions::default()
+/// };
+/// let report = push_project(&client, Path::new("my-script"), options).await?;
+/// for update in report.updates {
+/// 	println!("sent {} ({} bytes)", update.update, update.size);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[tracing::instrument(
+	name = "push",
+	skip_all,
+	fields(script = tracing::field::Empty, bytes = tracing::field::Empty)
+)]
+pub async fn push_project(
+	client: &Client,
+	project_directory: &Path,
+	mut options: PushOptions,
+) -> Result<PushReport, Error> {
+	check_config_overlap(project_directory)?;
+	let configuration = read_configuration(project_directory).await?;
+	tracing::Span::current().record("script", telemetry::hash_id(&configuration.script_id));
+	let registered_directory = options
+		.registered_directory
+		.take()
+		.unwrap_or_else(|| project_directory.to_path_buf());
+	if !options.dry_run {
+		registry::check(
+			&configuration.linked_script_id()?,
+			&registered_directory,
+			options.force,
+			options.confirm.as_ref(),
+		)
+		.await?;
+	}
+	let callback = options.progress.as_ref();
+	if configuration.syncs_metadata() {
+		whitelist::check(&configuration)?;
+	}
+	if options.fix_names && !options.dry_run {
+		fix_module_names(project_directory, &configuration, options.confirm.as_ref()).await?;
+	}
+	if let Some(callback) = callback {
+		callback(ProgressEvent::ReadingSources);
+	}
+	let mut sources = read_project_sources(
+		project_directory,
+		&configuration,
+		options.main_source.take(),
+		options.description.take(),
+	)
+	.await?;
+	sources.modules.retain(|(name, _)| {
+		!options
+			.ignore
+			.iter()
+			.any(|ignored| ignored == name.as_str())
+	});
+	tracing::Span::current().record(
+		"bytes",
+		sources.description.len()
+			+ sources.main_source.as_ref().map_or(0, String::len)
+			+ sources
+				.modules
+				.iter()
+				.map(|(_, source)| source.len())
+				.sum::<usize>(),
+	);
+	description::check(&sources.description)?;
+	for warning in check_sources(&configuration, &sources)? {
+		warn!("{warning}");
+	}
+	let broken_requires = check_requires(project_directory, &configuration, &sources).await;
+	if options.strict && !broken_requires.is_empty() {
+		return Err(Error::BrokenRequires(broken_requires));
+	}
+	for problem in broken_requires {
+		warn!("{problem}");
+	}
+
+	let empty = empty_sources(sources.main_source.as_deref(), &sources.modules);
+	if !empty.is_empty() && !options.allow_empty && !configuration.allows_empty_files() {
+		if options.dry_run {
+			warn!("{}", Error::EmptyFiles(empty));
+		} else {
+			confirm_empty_files(empty, options.confirm.as_ref())?;
+		}
+	}
+
+	let orphaned = if options.prune {
+		let remote = phase(
+			callback,
+			ProgressEvent::FetchingScript,
+			"fetching script",
+			client.get_editor(&configuration.linked_script_id()?),
+		)
+		.await?
+		.script_info
+		.source
+		.modules;
+		orphaned_modules(&sources.modules, remote.into_keys(), &options.ignore)?
+	} else {
+		Vec::new()
+	};
+
+	let main_source = sources
+		.main_source
+		.as_deref()
+		.map(|source| arguments::prepare(&configuration, source));
+	let mut actions: Vec<EditorUpdate> = Vec::from([EditorUpdate::Description(&sources.description)]);
+	if let Some(main_source) = &main_source {
+		actions.push(EditorUpdate::MainSource(main_source));
+	}
+
+	let drift = phase(
+		callback,
+		ProgressEvent::CheckingMetadata,
+		"checking metadata",
+		changed_metadata(client, project_directory, &configuration),
+	)
+	.await?;
+	// collaborators may have changed these on the website since the last sync too
+	let synced = state::read::<SyncState>(project_directory).await.synced;
+	for field in drift.iter().filter(|field| {
+		synced
+			.as_ref()
+			.is_none_or(|synced| synced.remote_edited(field))
+	}) {
+		warn!("overwriting remote metadata: {field}");
+	}
+	check_name(
+		project_directory,
+		&configuration,
+		drift.iter().find_map(MetadataDrift::remote_name),
+	);
+	actions.extend(get_editor_updates_from_configuration(
+		&configuration,
+		&drift,
+	));
+
+	// use .iter() to force items to have a lifetime bounded by the function
+	for (name, source) in &sources.modules {
+		actions.push(EditorUpdate::Module { name, source });
+	}
+	actions.extend(orphaned.iter().map(|name| EditorUpdate::RemoveModule(name)));
+
+	let report = PushReport {
+		script_id: configuration.linked_script_id()?,
+		dry_run: options.dry_run,
+		updates: actions
+			.iter()
+			.map(|update| SentUpdate {
+				update: update.to_string(),
+				size: update.size(),
+			})
+			.collect(),
+	};
+	if options.dry_run {
+		return Ok(report);
+	}
+
+	log_whitelist_changes(&configuration, &drift);
+	phase(
+		callback,
+		ProgressEvent::Uploading,
+		"uploading script",
+		client.set_editor(&report.script_id, &actions),
+	)
+	.await?;
+	registry::register(&report.script_id, &registered_directory).await;
+	// the script may have been renamed
+	if let Some(account_id) = &client.secrets.account_id {
+		cache::invalidate_scripts(account_id).await;
+	}
+
+	let creator = read_metadata(project_directory, &configuration)
+		.await
+		.and_then(|meta| meta.creator);
+	if let Err(error) = write_metadata(
+		project_directory,
+		&configuration,
+		Some(Metadata::now(creator, configuration.project_type)),
+	)
+	.await
+	{
+		warn!("pushed, but failed recording sync metadata: {error}");
+	}
+
+	if let Some(callback) = callback {
+		callback(ProgressEvent::Done);
+	}
+	Ok(report)
+}
+
+/// Remote modules which are pruned even when pkg/ is empty; more than this suggests the wrong
+/// project directory
+const MAX_UNCHECKED_PRUNE: usize = 2;
+
+/// Finds the remote modules with no local counterpart, comparing remote names so aliased modules
+/// are kept.
+///
+/// # Errors
+/// - [`Error::PruneEverything`] if pkg/ is empty while the script has several modules
+pub async fn find_orphaned_modules(
+	client: &Client,
+	project_directory: &Path,
+) -> Result<Vec<String>, Error> {
+	let configuration = read_configuration(project_directory).await?;
+	let local = read_modules(project_directory, &configuration, 0).await?;
+	let remote = progress::spinner(
+		"fetching script",
+		client.get_editor(&configuration.linked_script_id()?),
+	)
+	.await?
+	.script_info
+	.source
+	.modules;
+
+	orphaned_modules(&local, remote.into_keys(), &[])
+}
+
+/// Gets the remote module names which no local module is uploaded as, leaving out ignored ones.
+///
+/// # Errors
+/// - [`Error::PruneEverything`] if there are no local modules, but too many remote ones
+fn orphaned_modules(
+	local: &[(ModuleName, String)],
+	remote: impl ExactSizeIterator<Item = String>,
+	ignore: &[String],
+) -> Result<Vec<String>, Error> {
+	if local.is_empty() && remote.len() > MAX_UNCHECKED_PRUNE {
+		return Err(Error::PruneEverything(remote.len()));
+	}
+
+	let mut orphaned: Vec<String> = remote
+		.filter(|name| {
+			!ignore.contains(name) && !local.iter().any(|(local_name, _)| local_name == name)
+		})
+		.collect();
+	orphaned.sort();
+	Ok(orphaned)
+}
+
+/// Deletes remote modules, leaving everything else on the script alone.
+///
+/// The script is fetched again afterwards, so a removal fumosclub ignored isn't reported as done.
+///
+/// # Errors
+/// - [`Error::ModulesNotPruned`] if any of the modules are still on the script
+pub async fn prune_modules(
+	client: &Client,
+	project_directory: &Path,
+	names: &[String],
+) -> Result<(), Error> {
+	let configuration = read_configuration(project_directory).await?;
+	let updates: Vec<EditorUpdate> = names
+		.iter()
+		.map(|name| EditorUpdate::RemoveModule(name))
+		.collect();
+
+	let script_id = configuration.linked_script_id()?;
+	progress::spinner("pruning modules", client.set_editor(&script_id, &updates)).await?;
+
+	let remaining: Vec<String> =
+		progress::spinner("checking the script", client.get_editor(&script_id))
+			.await?
+			.script_info
+			.source
+			.modules
+			.into_keys()
+			.filter(|name| names.contains(name))
+			.collect();
+	if !remaining.is_empty() {
+		return Err(Error::ModulesNotPruned(remaining));
+	}
+
+	Ok(())
+}
+
+/// A part of the project which can be pushed on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushPart {
+	/// README.md
+	Description,
+	/// The name, whitelist and publicity in fumosync.json
+	Metadata,
+}
+
+/// Uploads a single part of the project, without reading any sources.
+///
+/// Unlike `push`, metadata is uploaded even when it matches the remote script or
+/// `"syncMetadata": false` is set, and no sync metadata is recorded. The description is read from
+/// README.md unless given.
+pub async fn push_part<T: AsRef<Path>>(
+	client: &Client,
+	project_directory: T,
+	part: PushPart,
+	description: Option<String>,
+) -> Result<(), Error> {
+	let project_directory = project_directory.as_ref();
+	let configuration = read_configuration(project_directory).await?;
+
+	let description = match description {
+		Some(description) => description,
+		None if part == PushPart::Description => {
+			read_file(project_directory.join(DESCRIPTION_FILE)).await?
+		}
+		None => String::new(),
+	};
+	let whitelist = match part {
+		PushPart::Description => Vec::new(),
+		PushPart::Metadata => {
+			whitelist::check(&configuration)?;
+			let remote = progress::spinner(
+				"fetching script",
+				client.get_editor(&configuration.linked_script_id()?),
+			)
+			.await?
+			.script_info
+			.whitelist;
+			let policy = configuration.whitelist_merge.unwrap_or_default();
+			let merged = whitelist::merge(policy, &configuration.whitelist, &remote);
+			whitelist::log_changes(policy, &merged, &remote);
+			merged
+		}
+	};
+	let actions = match part {
+		PushPart::Description => {
+			description::check(&description)?;
+			vec![EditorUpdate::Description(&description)]
+		}
+		PushPart::Metadata => vec![
+			EditorUpdate::Name(&configuration.script_name),
+			EditorUpdate::Whitelist(whitelist.iter().map(String::as_str).collect()),
+			EditorUpdate::Publicity(configuration.is_public),
+		],
+	};
+
+	progress::spinner(
+		"uploading script",
+		client.set_editor(&configuration.linked_script_id()?, &actions),
+	)
+	.await?;
+	if let (PushPart::Metadata, Some(account_id)) = (part, &client.secrets.account_id) {
+		cache::invalidate_scripts(account_id).await;
+	}
+
+	Ok(())
+}
+
+/// Compares the project against the script on fumosclub without writing anything, with unified
+/// diffs of the files which differ if `diffs` is set.
+///
+/// Files are read exactly as `push` reads them; fumosync.json is compared by the fields `push`
+/// uploads.
+pub async fn compare_remote<T: AsRef<Path>>(
+	project_directory: T,
+	diffs: bool,
+) -> Result<Report, Error> {
+	let project_directory = project_directory.as_ref();
+
+	let configuration = read_configuration(project_directory).await?;
+	let sources = read_project_sources(project_directory, &configuration, None, None).await?;
+
+	let client = Client::new(get_session_secrets().await?)?;
+	let mut cache: EditorCache = state::read(project_directory).await;
+	let remote = progress::spinner(
+		"fetching script",
+		client.get_editor_cached(&configuration.linked_script_id()?, &mut cache),
+	)
+	.await?
+	.script_info;
+	if let Err(error) = state::write(project_directory, &cache).await {
+		warn!("failed caching the script: {error}");
+	}
+
+	Ok(compare_project(&configuration, &sources, remote, diffs))
+}
+
+/// Links the project to a script, dropping the cached editors and snapshots of the previous one.
+///
+/// # Errors
+/// - [`Error::WatchRunning`]
+async fn set_linked_script(project_directory: &Path, script_id: &str) -> Result<(), Error> {
+	check_watch_lock(project_directory).await?;
+	state::clean(
+		project_directory,
+		&[state::StateKind::Cache, state::StateKind::Snapshots],
+	)
+	.await?;
+	edit_configuration(project_directory, |configuration| {
+		configuration.script_id = script_id.to_owned();
+		configuration.meta = None;
+	})
+	.await?;
+	state::write(project_directory, &SyncState::default()).await
+}
+
+/// Writes the remote name, publicity and whitelist into the project configuration.
+async fn write_remote_metadata(
+	project_directory: &Path,
+	remote: &EditorScriptInfo,
+) -> Result<(), Error> {
+	edit_configuration(project_directory, |configuration| {
+		configuration.script_name = remote.name.clone();
+		configuration.is_public = remote.is_public;
+		configuration.whitelist = remote.whitelist.clone();
+	})
+	.await
+}
+
+/// Adopts the remote metadata of the project's script, leaving every other file alone.
+///
+/// Returns the fields which changed.
+///
+/// # Errors
+/// - [`Error::ScriptMismatch`] if the script isn't the one the project is linked to
+pub async fn pull_configuration(
+	script_id: &str,
+	project_directory: &Path,
+) -> Result<Vec<MetadataDrift>, Error> {
+	let configuration = read_configuration(project_directory).await?;
+	let client = Client::new(get_session_secrets().await?)?;
+	let script_id = resolve_script_reference(&client, script_id).await?;
+	if script_id != configuration.linked_script_id()? {
+		return Err(Error::ScriptMismatch(
+			script_id.into(),
+			configuration.script_id,
+		));
+	}
+
+	let remote = progress::spinner("fetching script", client.get_editor(&script_id))
+		.await?
+		.script_info;
+	let drift = compare_metadata(&configuration, &remote);
+	if !drift.is_empty() {
+		write_remote_metadata(project_directory, &remote).await?;
+	}
+
+	Ok(drift)
+}
+
+/// Links the project to another script, after checking that the script exists.
+///
+/// Returns how the local files compare to the script, so differences can be reported before the
+/// next push overwrites them; the report holds the resolved script id.
+///
+/// # Errors
+/// - [`Error::WatchRunning`] while a watcher runs in the project
+pub async fn link(project_directory: &Path, script_id: &str) -> Result<Report, Error> {
+	check_watch_lock(project_directory).await?;
+	let configuration = read_configuration(project_directory).await?;
+	let sources = read_project_sources(project_directory, &configuration, None, None).await?;
+
+	let client = Client::new(get_session_secrets().await?)?;
+	let script_id = resolve_script_reference(&client, script_id).await?;
+	let remote = progress::spinner("fetching script", client.get_editor(&script_id))
+		.await?
+		.script_info;
+
+	set_linked_script(project_directory, &script_id).await?;
+	let report = compare_project(
+		&Configuration {
+			script_id: script_id.into(),
+			..configuration
+		},
+		&sources,
+		remote,
+		false,
+	);
+	Ok(report)
+}
+
+/// Unlinks the project from its script, leaving a local project which `push` refuses until it is
+/// linked again.
+///
+/// # Errors
+/// - [`Error::WatchRunning`] while a watcher runs in the project
+pub async fn unlink(project_directory: &Path) -> Result<(), Error> {
+	set_linked_script(project_directory, PLACEHOLDER_SCRIPT_ID).await
+}
+
+/// Compares local sources, as `push` reads them, against a script's remote state.
+fn compare_project(
+	configuration: &Configuration,
+	sources: &ProjectSources,
+	mut remote: EditorScriptInfo,
+	diffs: bool,
+) -> Report {
+	let drift = compare_metadata(configuration, &remote);
+	let configuration_matches = configuration.project_type == ProjectType::from(&remote.script_type)
+		&& (!configuration.syncs_metadata() || drift.is_empty());
+
+	let mut files = vec![
+		FileReport {
+			path: SYNC_CONFIGURATION_FILE.to_owned(),
+			remote_name: None,
+			local_hash: None,
+			remote_hash: None,
+			state: if configuration_matches {
+				FileState::Unchanged
+			} else {
+				FileState::Modified
+			},
+			diff: None,
+			stat: None,
+		},
+		FileReport::compare(
+			DESCRIPTION_FILE.to_owned(),
+			None,
+			Some(&sources.description),
+			Some(&remote.description),
+			diffs,
+		),
+	];
+
+	if let Some(main_source) = &sources.main_source {
+		files.push(FileReport::compare(
+			MAIN_SCRIPT_FILE.to_owned(),
+			None,
+			Some(main_source),
+			Some(arguments::strip(&remote.source.main)),
+			diffs,
+		));
+	}
+
+	let module_path = |name: &str| format!("{PACKAGE_DIRECTORY}/{name}.luau");
+	for (name, source) in &sources.modules {
+		let remote_source = remote.source.modules.remove(name.as_str());
+		files.push(FileReport::compare(
+			module_path(configuration.local_module_name(name)),
+			Some(name.as_str()),
+			Some(source),
+			remote_source.as_deref(),
+			diffs,
+		));
+	}
+
+	let mut remote_only: Vec<_> = remote.source.modules.into_iter().collect();
+	remote_only.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+	files.extend(remote_only.iter().map(|(name, source)| {
+		FileReport::compare(
+			module_path(configuration.local_module_name(name)),
+			Some(name),
+			None,
+			Some(source),
+			diffs,
+		)
+	}));
+
+	Report::new(configuration.script_id.clone(), files, drift)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Update {
+	MainSource,
+	Description,
+	ProjectConfiguration,
+	Module(PathBuf),
+}
+
+impl std::fmt::Display for Update {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::MainSource => f.write_str(MAIN_SCRIPT_FILE),
+			Self::Description => f.write_str(DESCRIPTION_FILE),
+			Self::ProjectConfiguration => f.write_str("project configuration"),
+			Self::Module(path) => write!(f, "{}", path.display()),
+		}
+	}
+}
+
+/// Syncs an update whose file can't be read is retried for, before it is dropped
+const MAX_UPDATE_ATTEMPTS: u32 = 3;
+
+/// What a sync uploaded, and which updates it left queued.
+#[derive(Debug, Default)]
+struct SyncOutcome {
+	synced: usize,
+	/// Descriptions of the updates which failed, with how often they will be retried
+	failed: Vec<String>,
+}
+
+/// Delay before syncing again while fumosclub is down, doubling up to [`MAX_SYNC_BACKOFF`]
+const MIN_SYNC_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_SYNC_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Kinds of events the watcher counts as changes, on top of the ones which change contents.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchEvent {
+	/// Permission and timestamp changes; only synced when the contents changed too
+	Metadata,
+}
+
+/// What the watcher needs to know about the project to classify events.
+struct WatchConfig {
+	modules: ModuleFilter,
+	/// Whether metadata events are classified; see [`ContentHashes`]
+	metadata_events: bool,
+}
+
+/// Whether an event can change a file's contents; editors send plenty of events which can't.
+///
+/// Metadata events are dropped unless enabled, since build tools restoring mtimes and editors
+/// touching files send them for unchanged contents. Some network shares send nothing else for real
+/// changes, though, so they can be enabled.
+fn is_relevant_event(kind: &EventKind, config: &WatchConfig) -> bool {
+	match kind {
+		// neovim and other editors send access events every 2 seconds...
+		EventKind::Other | EventKind::Access(..) => false,
+		EventKind::Modify(ModifyKind::Metadata(..)) => config.metadata_events,
+		EventKind::Any | EventKind::Modify(..) | EventKind::Create(..) | EventKind::Remove(..) => true,
+	}
+}
+
+/// Hashes of the files the watcher last queued, so the metadata events let through by
+/// [`is_relevant_event`] only sync files whose contents changed.
+struct ContentHashes {
+	/// Nothing is hashed while metadata events are dropped anyway
+	enabled: bool,
+	hashes: HashMap<PathBuf, [u8; 32]>,
+}
+
+impl ContentHashes {
+	fn new(enabled: bool) -> Self {
+		Self {
+			enabled,
+			hashes: HashMap::new(),
+		}
+	}
+
+	/// Records a file's contents, returning whether they changed since they were last recorded;
+	/// unreadable files, such as removed ones, count as changed so the sync reports them.
+	async fn record(&mut self, path: &Path) -> bool {
+		use sha2::Digest;
+
+		let Ok(contents) = tokio::fs::read(path).await else {
+			self.hashes.remove(path);
+			return true;
+		};
+		let hash: [u8; 32] = sha2::Sha256::digest(&contents).into();
+		self.hashes.insert(path.to_path_buf(), hash) != Some(hash)
+	}
+
+	/// Checks whether an event of a file counts as a change. Every event records the contents, so
+	/// a metadata event after a content event of the same contents is skipped.
+	async fn counts(&mut self, kind: &EventKind, path: &Path) -> bool {
+		if !self.enabled {
+			return true;
+		}
+
+		let changed = self.record(path).await;
+		if !changed && matches!(kind, EventKind::Modify(ModifyKind::Metadata(..))) {
+			tracing::debug!(
+				"ignoring metadata change of {}; its contents are unchanged",
+				path.display()
+			);
+			return false;
+		}
+
+		true
+	}
+}
+
+/// What a path leads to, following symlinks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FileType {
+	/// Removed, moved away, or a broken symlink
+	Missing,
+	File,
+	Directory,
+}
+
+/// What is at the path of an event; looked up before the event is classified, so
+/// [`classify_event`] doesn't touch the file system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileKind {
+	file_type: FileType,
+	is_symlink: bool,
+}
+
+impl FileKind {
+	fn of(path: &Path) -> Self {
+		let file_type = match path.metadata() {
+			Ok(metadata) if metadata.is_dir() => FileType::Directory,
+			Ok(metadata) if metadata.is_file() => FileType::File,
+			_ => FileType::Missing,
+		};
+
+		Self {
+			file_type,
+			is_symlink: path
+				.symlink_metadata()
+				.is_ok_and(|metadata| metadata.is_symlink()),
+		}
+	}
+}
+
+/// Classifies an event at a path relative to the project root, given what is at the path.
+///
+/// Module updates may name directories inside of the package directory, or the package directory
+/// itself, which stand for every module inside of them.
+fn classify_event(
+	relative: &Path,
+	kind: &EventKind,
+	file: FileKind,
+	config: &WatchConfig,
+) -> Option<Update> {
+	if !is_relevant_event(kind, config)
+		|| !relative
+			.components()
+			.all(|component| matches!(component, Component::Normal(..)))
+	{
+		return None;
+	}
+
+	if relative.starts_with(PACKAGE_DIRECTORY) {
+		if file.is_symlink && !config.modules.follow_symlinks {
+			return None;
+		}
+
+		// a removed directory can't be told apart from a removed file, so both are module updates
+		let is_directory = file.file_type == FileType::Directory;
+		if !is_directory && (relative == Path::new(PACKAGE_DIRECTORY) || !is_module_file(relative)) {
+			return None;
+		}
+
+		return Some(Update::Module(relative.to_path_buf()));
+	}
+
+	if file.file_type != FileType::File {
+		None
+	} else if relative == Path::new(MAIN_SCRIPT_FILE) {
+		Some(Update::MainSource)
+	} else if relative == Path::new(DESCRIPTION_FILE) {
+		Some(Update::Description)
+	} else if relative == Path::new(SYNC_CONFIGURATION_FILE)
+		|| relative == Path::new(SYNC_CONFIGURATION_TOML_FILE)
+	{
+		Some(Update::ProjectConfiguration)
+	} else {
+		None
+	}
+}
+
+/// Expands a module update naming a directory into updates for every module inside of it.
+///
+/// A directory can be created or moved into the package directory with a single event.
+async fn expand_update(
+	project_directory: &Path,
+	update: Update,
+	config: &WatchConfig,
+) -> Vec<Update> {
+	let path = match update {
+		Update::Module(path) if project_directory.join(&path).is_dir() => path,
+		Update::Module(ref path) => {
+			info!("got package update at {}", path.display());
+			return vec![update];
+		}
+		Update::MainSource => {
+			info!("got main source update");
+			return vec![update];
+		}
+		Update::Description => {
+			info!("got description update");
+			return vec![update];
+		}
+		Update::ProjectConfiguration => {
+			info!("got project configuration update");
+			return vec![update];
+		}
+	};
+
+	let files = match list_module_files(&project_directory.join(&path), &config.modules).await {
+		Ok(files) => files,
+		Err(error) => {
+			warn!("failed listing modules in {}: {error}", path.display());
+			return Vec::new();
+		}
+	};
+	info!(
+		"got package update for {} modules in {}",
+		files.len(),
+		path.display()
+	);
+	files
+		.into_iter()
+		.filter_map(|file| diff_paths(file, project_directory))
+		.map(Update::Module)
+		.collect()
+}
+
+/// Processes all of the updates, uploads them to fumosclub, and clears the vector when done.
+///
+/// Duplicate updates are collapsed first, so every changed file is read (and held in memory) once.
+///
+/// Updates whose files can't be read are skipped rather than failing the others, and stay queued
+/// for up to [`MAX_UPDATE_ATTEMPTS`] syncs; `attempts` counts their failures across syncs.
+async fn process_updates<T: AsRef<Path>>(
+	project_directory: T,
+	updates: &mut Vec<Update>,
+	attempts: &mut HashMap<Update, u32>,
+	dry_run: bool,
+	baseline: &Mutex<RemoteBaseline>,
+) -> Result<SyncOutcome, Error> {
+	let project_directory = project_directory.as_ref();
+	let mut unique_updates = unique_updates(updates);
+
+	// we must read the project configuration eventually because we need the project's id
+	let configuration: Configuration = read_configuration(project_directory).await?;
+	{
+		let baseline = baseline.lock().await;
+		unique_updates.retain(|update| {
+			let paused =
+				remote_file_of(&configuration, update).is_some_and(|file| baseline.is_paused(&file));
+			if paused {
+				warn!("NOT UPLOADING {update}: it was edited on fumosclub, and syncing it is paused");
+			}
+			!paused
+		});
+	}
+	let read = read_updates(
+		project_directory,
+		&configuration,
+		&unique_updates,
+		read_file,
+	)
+	.await;
+	let project_configuration = read.project_configuration;
+
+	let client = Client::new(get_session_secrets().await?)?;
+	let drift = if project_configuration {
+		changed_metadata(&client, project_directory, &configuration).await?
+	} else {
+		Vec::new()
+	};
+
+	let main_source = read
+		.main_source
+		.as_ref()
+		.map(|source| arguments::prepare(&configuration, source));
+	let editor_updates = editor_updates_of(main_source.as_deref(), &read, &configuration, &drift);
+
+	let synced = unique_updates.len() - read.failed.len();
+	// a configuration change may not have touched anything which is uploaded
+	if !editor_updates.is_empty() {
+		if dry_run {
+			for update in &editor_updates {
+				info!("would upload {update}");
+			}
+		} else {
+			log_whitelist_changes(&configuration, &drift);
+			client
+				.set_editor(&configuration.linked_script_id()?, &editor_updates)
+				.await?;
+			baseline.lock().await.record(&editor_updates);
+			if project_configuration {
+				let recorded = record_synced_metadata(project_directory, &configuration).await;
+				if let Err(error) = recorded {
+					warn!("synced, but failed recording the synced metadata: {error}");
+				}
+			}
+		}
+	}
+
+	updates.clear();
+	Ok(SyncOutcome {
+		synced,
+		failed: requeue_failed(updates, attempts, read.failed),
+	})
+}
+
+/// Collapses duplicate updates, keeping the order they were queued in.
+fn unique_updates(updates: &[Update]) -> Vec<Update> {
+	let mut unique_updates: Vec<Update> = Vec::with_capacity(updates.len());
+	for update in updates {
+		if !unique_updates.contains(update) {
+			unique_updates.push(update.clone());
+		}
+	}
+	unique_updates
+}
+
+/// Gets what to upload for read updates; `main_source` is the read one with its arguments prepared.
+fn editor_updates_of<'a>(
+	main_source: Option<&'a str>,
+	read: &'a ReadUpdates,
+	configuration: &'a Configuration,
+	drift: &'a [MetadataDrift],
+) -> Vec<EditorUpdate<'a>> {
+	let mut editor_updates: Vec<EditorUpdate<'_>> = Vec::with_capacity(read.modules.len() + 5);
+	if let Some(source) = main_source {
+		editor_updates.push(EditorUpdate::MainSource(source));
+	}
+	if let Some(description) = &read.description {
+		editor_updates.push(EditorUpdate::Description(description));
+	}
+	editor_updates.extend(get_editor_updates_from_configuration(configuration, drift));
+	for (name, source) in &read.modules {
+		editor_updates.push(EditorUpdate::Module { name, source });
+	}
+	editor_updates
+}
+
+/// The contents of queued updates, read by [`read_updates`].
+#[derive(Default)]
+struct ReadUpdates {
+	main_source: Option<String>,
+	description: Option<String>,
+	project_configuration: bool,
+	modules: Vec<(ModuleName, String)>,
+	/// Updates whose files couldn't be read, with why
+	failed: Vec<(Update, Error)>,
+}
+
+/// Reads the files of updates with `read_source`, normally [`read_file`]; ones which can't be read are
+/// skipped rather than failing the others.
+async fn read_updates(
+	project_directory: &Path,
+	configuration: &Configuration,
+	updates: &[Update],
+	read_source: impl AsyncFn(PathBuf) -> Result<String, Error>,
+) -> ReadUpdates {
+	let mut read = ReadUpdates::default();
+	let _section = timing::section("file reads");
+	for update in updates {
+		let result = async {
+			match update {
+				// packages have no main script, so the file is never uploaded
+				Update::MainSource if configuration.project_type == ProjectType::Package => {}
+				Update::MainSource => {
+					let contents = read_source(project_directory.join(MAIN_SCRIPT_FILE)).await?;
+					for warning in limits::check_content(MAIN_SCRIPT_FILE, &contents, true)? {
+						warn!("{warning}");
+					}
+					if contents.trim().is_empty() && !configuration.allows_empty_files() {
+						warn!(
+							"NOT UPLOADING {MAIN_SCRIPT_FILE}: it is empty; set \"allowEmptyFiles\": true to upload it anyway"
+						);
+					} else {
+						read.main_source = Some(contents);
+					}
+				}
+				Update::Description => {
+					let contents = read_source(project_directory.join(DESCRIPTION_FILE)).await?;
+					description::check(&contents)?;
+					read.description = Some(contents);
+				}
+				Update::ProjectConfiguration => read.project_configuration = true,
+				Update::Module(path_buf) => match path_buf.file_name() {
+					None => warn!(
+						"module at {} has no file name, skipping...",
 						path_buf.display()
-					);
+					),
+					Some(_) => {
+						let contents = read_source(project_directory.join(path_buf)).await?;
+						for warning in
+							limits::check_content(&path_buf.to_string_lossy(), &contents, false)?
+						{
+							warn!("{warning}");
+						}
+						if contents.is_empty() && !configuration.allows_empty_files() {
+							warn!(
+								"NOT UPLOADING {}: it is empty; set \"allowEmptyFiles\": true to upload it anyway",
+								path_buf.display()
+							);
+							return Ok(());
+						}
+
+						read.modules.push((
+							remote_module_name_of(&configuration, Path::new(PACKAGE_DIRECTORY), path_buf)?,
+							contents,
+						));
+					}
+				},
+			}
+
+			Ok::<_, Error>(())
+		}
+		.await;
+
+		if let Err(error) = result {
+			read.failed.push((update.clone(), error));
+		}
+	}
+
+	read
+}
+
+/// Queues failed updates again until they have failed [`MAX_UPDATE_ATTEMPTS`] syncs, returning a
+/// description of each; `attempts` forgets the updates which didn't fail.
+fn requeue_failed(
+	updates: &mut Vec<Update>,
+	attempts: &mut HashMap<Update, u32>,
+	failed: Vec<(Update, Error)>,
+) -> Vec<String> {
+	attempts.retain(|update, _| failed.iter().any(|(failed, _)| failed == update));
+	let mut descriptions = Vec::with_capacity(failed.len());
+	for (update, error) in failed {
+		let attempt = attempts.entry(update.clone()).or_insert(0);
+		*attempt += 1;
+		let remaining = MAX_UPDATE_ATTEMPTS.saturating_sub(*attempt);
+		if remaining == 0 {
+			warn!("GIVING UP ON {update} after {MAX_UPDATE_ATTEMPTS} failed syncs: {error}");
+			attempts.remove(&update);
+			descriptions.push(format!("{update}: {error}, dropped"));
+			continue;
+		}
+
+		descriptions.push(format!(
+			"{update}: {error}, will retry {remaining} more time{}",
+			if remaining == 1 { "" } else { "s" }
+		));
+		updates.push(update);
+	}
+
+	descriptions
+}
+
+/// Gets the remote file an update uploads, if any.
+fn remote_file_of(configuration: &Configuration, update: &Update) -> Option<RemoteFile> {
+	match update {
+		Update::MainSource => Some(RemoteFile::Main),
+		Update::Description => Some(RemoteFile::Description),
+		Update::ProjectConfiguration => None,
+		Update::Module(path) => {
+			remote_module_name_of(configuration, Path::new(PACKAGE_DIRECTORY), path)
+				.ok()
+				.map(|name| RemoteFile::Module(name.into()))
+		}
+	}
+}
+
+/// Gets the path of a remote file, relative to the project directory.
+fn local_path_of(configuration: &Configuration, file: &RemoteFile) -> PathBuf {
+	match file {
+		RemoteFile::Main => PathBuf::from(MAIN_SCRIPT_FILE),
+		RemoteFile::Description => PathBuf::from(DESCRIPTION_FILE),
+		RemoteFile::Module(name) => {
+			Path::new(PACKAGE_DIRECTORY).join(format!("{}.luau", configuration.local_module_name(name)))
+		}
+	}
+}
+
+/// Gets the update uploading a remote file, unless it has no local file.
+fn update_of(
+	project_directory: &Path,
+	configuration: &Configuration,
+	file: &RemoteFile,
+) -> Option<Update> {
+	match file {
+		RemoteFile::Main => Some(Update::MainSource),
+		RemoteFile::Description => Some(Update::Description),
+		RemoteFile::Module(..) => {
+			let path = local_path_of(configuration, file);
+			project_directory
+				.join(&path)
+				.is_file()
+				.then_some(Update::Module(path))
+		}
+	}
+}
+
+/// Reads a local file as it would be uploaded, or `None` if it can't be read.
+async fn read_uploaded(
+	project_directory: &Path,
+	configuration: &Configuration,
+	file: &RemoteFile,
+) -> Option<String> {
+	let contents = read_file(project_directory.join(local_path_of(configuration, file)))
+		.await
+		.ok()?;
+	Some(match file {
+		RemoteFile::Main => arguments::prepare(configuration, &contents),
+		_ => contents,
+	})
+}
+
+/// Writes a file of the remote script into the project; files removed from fumosclub are left
+/// alone, and `false` is returned for them.
+async fn pull_remote_file(
+	project_directory: &Path,
+	configuration: &Configuration,
+	remote: &EditorScriptInfo,
+	file: &RemoteFile,
+) -> Result<bool, Error> {
+	let contents = match file {
+		RemoteFile::Main => arguments::strip(&remote.source.main),
+		RemoteFile::Description => remote.description.as_str(),
+		RemoteFile::Module(name) => match remote.source.modules.get(name) {
+			Some(source) => source.as_str(),
+			None => return Ok(false),
+		},
+	};
+
+	let path = project_directory.join(local_path_of(configuration, file));
+	if let Some(parent) = path.parent() {
+		tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+	}
+	write_file(path, contents).await?;
+	Ok(true)
+}
+
+/// Compares the remote script against what this watcher last synced, handling files edited on
+/// fumosclub as the schedule says; dry runs only ever pause them. Paused files resume once their
+/// local file matches fumosclub again, such as after pulling the edits by hand.
+///
+/// The editor is revalidated with its cached ETag, so unchanged scripts aren't downloaded again.
+/// Returns whether updates were queued.
+async fn verify_remote(
+	project_directory: &Path,
+	schedule: VerifySchedule,
+	dry_run: bool,
+	baseline: &Mutex<RemoteBaseline>,
+	updates: &mut Vec<Update>,
+) -> Result<bool, Error> {
+	let configuration = read_configuration(project_directory).await?;
+	let client = Client::new(get_session_secrets().await?)?;
+	let mut cache: EditorCache = state::read(project_directory).await;
+	let remote = client
+		.get_editor_cached(&configuration.linked_script_id()?, &mut cache)
+		.await?
+		.script_info;
+	if let Err(error) = state::write(project_directory, &cache).await {
+		warn!("failed caching the script: {error}");
+	}
+
+	let mut baseline = baseline.lock().await;
+	let edited = baseline.compare(&remote);
+	for file in baseline.paused() {
+		let local = read_uploaded(project_directory, &configuration, &file).await;
+		if local.is_some_and(|contents| baseline.matches(&file, &contents)) {
+			info!("{file} matches fumosclub again; syncing it");
+			baseline.resume(&file);
+		}
+	}
+	if edited.is_empty() {
+		tracing::debug!("the remote script is as this watcher last synced it");
+		return Ok(false);
+	}
+
+	let names: Vec<String> = edited.iter().map(ToString::to_string).collect();
+	warn!(
+		"!!! {} changed on fumosclub since this watcher last synced; someone may have edited the script in the web editor !!!",
+		names.join(", ")
+	);
+
+	let policy = if dry_run {
+		RemoteEditPolicy::Pause
+	} else {
+		schedule.on_remote_edit
+	};
+	let mut queued = false;
+	for file in edited {
+		match policy {
+			RemoteEditPolicy::Pause => baseline.pause(file),
+			RemoteEditPolicy::Overwrite => match update_of(project_directory, &configuration, &file) {
+				Some(update) => {
+					updates.push(update);
+					queued = true;
+				}
+				None => info!("{file} only exists on fumosclub; leaving it"),
+			},
+			RemoteEditPolicy::Pull => {
+				if pull_remote_file(project_directory, &configuration, &remote, &file).await? {
+					info!("pulled {file} from fumosclub");
+				} else {
+					warn!("{file} was removed from fumosclub; pausing it instead of deleting it");
+					baseline.pause(file);
+				}
+			}
+		}
+	}
+	if policy == RemoteEditPolicy::Pause {
+		warn!(
+			"paused syncing the changed files; merge the edits into them, or restart with --on-remote-edit overwrite or pull"
+		);
+	}
+
+	Ok(queued)
+}
+
+/// Logs the outcome of a step of starting the watcher.
+fn startup_step<T>(step: &str, result: Result<T, Error>) -> Result<T, Error> {
+	match &result {
+		Ok(..) => info!("{step}: done"),
+		Err(..) => tracing::error!("{step}: failed"),
+	}
+
+	result
+}
+
+/// When the watcher stops by itself; either way, pending updates are flushed first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WatchLimits {
+	/// Stop this long after starting
+	pub exit_after: Option<Duration>,
+	/// Stop after this long without filesystem events
+	pub exit_on_idle: Option<Duration>,
+}
 
-					None
-				}
+/// Work the watcher does alongside syncing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WatchTasks {
+	pub snapshots: Option<SnapshotSchedule>,
+	pub verification: Option<VerifySchedule>,
+	/// Keep the session from expiring; see [`login::keep_session_alive`]
+	pub session_keepalive: bool,
+}
 
-				Some(file_name) => Some(UpdatePair::Module {
-					name: get_module_from_path(file_name),
-					source: read_file(project_directory.join(path_buf)).await?,
-				}),
-			},
-		};
+#[derive(Default)]
+pub struct WatchOptions {
+	/// Keep watching when the initial push fails, instead of asking `confirm`
+	pub continue_on_error: bool,
+	/// Log updates instead of uploading them, skipping the initial push
+	pub dry_run: bool,
+	/// Watch even if another project last pushed the script; see [`registry::check`]
+	pub force: bool,
+	/// Refuse the initial push when `requireM` calls break once pushed; see [`requires::check`]
+	pub strict: bool,
+	/// Sync files whose permissions or timestamps changed, like `"watchEvents": ["metadata"]`
+	pub watch_metadata: bool,
+	pub limits: WatchLimits,
+	pub tasks: WatchTasks,
+	/// Asked whether to keep watching, and whatever pushes ask; see [`PushOptions`]
+	pub confirm: Option<ConfirmCallback>,
+}
 
-		if let Some(pair) = pair {
-			update_pairs.push(pair);
-		}
+/// Sleeps for the duration, or forever without one.
+async fn sleep_for(duration: Option<Duration>) {
+	match duration {
+		Some(duration) => tokio::time::sleep(duration).await,
+		None => std::future::pending().await,
 	}
+}
 
-	for pair in &update_pairs {
-		match pair {
-			UpdatePair::MainSource(source) => editor_updates.push(EditorUpdate::MainSource(source)),
-			UpdatePair::Description(description) => {
-				editor_updates.push(EditorUpdate::Description(description));
-			}
-			UpdatePair::ProjectConfiguration => {
-				editor_updates.extend(get_editor_updates_from_configuration(&configuration));
-			}
-			UpdatePair::Module { name, source } => {
-				editor_updates.push(EditorUpdate::Module { name, source });
-			}
-		}
+/// Pushes the project, then pushes changes to it as they happen.
+///
+/// If the initial push fails, watching carries on with `continue_on_error` or once `confirm`
+/// agrees, unless the error is [fatal](Error::is_fatal); `confirm` is also asked what pushes ask.
+/// With a snapshot schedule, the remote script is also snapshotted periodically, never at the same
+/// time as a sync. With a verification schedule, the remote script is compared like that too; see
+/// [`verify_remote`].
+///
+/// With `dry_run`, there is no initial push, and updates are logged instead of uploaded. Reaching
+/// a limit shuts down like ctrl-c does.
+pub async fn watch(project_directory: PathBuf, options: WatchOptions) -> Result<(), Error> {
+	let WatchOptions {
+		continue_on_error,
+		dry_run,
+		force,
+		strict,
+		watch_metadata,
+		limits,
+		tasks,
+		confirm,
+	} = options;
+	let project_directory = startup_step(
+		"resolving the project directory",
+		resolve_project_root(project_directory),
+	)?;
+	check_config_overlap(&project_directory)?;
+	let configuration = startup_step(
+		"reading the project configuration",
+		read_configuration(&project_directory).await,
+	)?;
+	let _lock = lock_watch(&project_directory).await?;
+	// registered right away, so a second watcher on a copy of the project is warned about
+	if !dry_run {
+		let script_id = configuration.linked_script_id()?;
+		registry::check(&script_id, &project_directory, force, confirm.as_ref()).await?;
+		registry::register(&script_id, &project_directory).await;
 	}
 
-	// push updates
-	let client = Client::new(get_session_secrets().await?);
-	client
-		.set_editor(&configuration.script_id, &editor_updates)
-		.await?;
-
-	updates.clear();
-	Ok(())
-}
+	let initial_push = if dry_run {
+		info!("initial push: skipped for a dry run");
+		Ok(())
+	} else {
+		let options = PushOptions {
+			force,
+			strict,
+			confirm: confirm.clone(),
+			..PushOptions::default()
+		};
+		startup_step(
+			"initial push",
+			push(&project_directory, options).await.map(drop),
+		)
+	};
+	if let Err(error) = initial_push {
+		if error.is_fatal() {
+			return Err(error);
+		}
 
-pub async fn watch(project_directory: PathBuf) -> Result<(), Error> {
-	let project_directory = std::fs::canonicalize(project_directory)?;
-	push(&project_directory).await?;
+		warn!("initial push failed: {error}");
+		let keep_watching = match &confirm {
+			_ if continue_on_error => true,
+			Some(confirm) => confirm("keep watching? later saves may push successfully")?,
+			None => false,
+		};
+		if !keep_watching {
+			return Err(error);
+		}
+	}
 
 	let (sender, mut receiver) = tokio::sync::mpsc::channel(32);
 
-	let mut debouncer = new_debouncer(
-		Duration::from_secs(2),
+	let debouncer = new_debouncer(
+		settings::get().debounce,
 		None,
 		move |result: DebounceEventResult| match result {
-			Ok(events) => sender
-				.blocking_send(events)
-				.expect("failed sending event to async task loop"),
+			Ok(events) => {
+				// the receiver is only closed while shutting down, so the events are no longer wanted
+				if sender.blocking_send(events).is_err() {
+					tracing::debug!("dropping events; the async task loop is closed");
+				}
+			}
 			Err(errors) => errors
 				.iter()
 				.for_each(|error| tracing::error!("got error from debouncer: {error}")),
 		},
 	)
-	.unwrap();
+	.map_err(|error| Error::WatcherInit(error.to_string()));
+	let mut debouncer = startup_step("creating the file watcher", debouncer)?;
 
 	// Add a path to be watched. All files and directories at that path and
 	// below will be monitored for changes.
-	debouncer
-		.watch(&project_directory, RecursiveMode::NonRecursive)
-		.unwrap();
+	startup_step(
+		"watching the project directory",
+		debouncer
+			.watch(&project_directory, RecursiveMode::NonRecursive)
+			.map_err(|error| Error::WatchPath(project_directory.clone(), error.to_string())),
+	)?;
 
 	// Add a path to be watched. All files and directories at that path and
 	// below will be monitored for changes.
-	debouncer
-		.watch(
-			project_directory.join(PACKAGE_DIRECTORY),
-			RecursiveMode::Recursive,
-		)
-		.unwrap();
+	let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+	startup_step(
+		"watching the package directory",
+		debouncer
+			.watch(&package_directory, RecursiveMode::Recursive)
+			.map_err(|error| Error::WatchPath(package_directory.clone(), error.to_string())),
+	)?;
+
+	// changes to symlink targets are reported at the target, so targets outside of the watched
+	// directories need watches of their own
+	let watch_config = WatchConfig {
+		modules: configuration.module_filter(),
+		metadata_events: watch_metadata || configuration.watch_events.contains(&WatchEvent::Metadata),
+	};
+	let mut content_hashes = ContentHashes::new(watch_config.metadata_events);
+	if watch_config.metadata_events {
+		// recorded up front, so the first metadata event of an unchanged file is skipped too
+		let mut files = vec![
+			project_directory.join(MAIN_SCRIPT_FILE),
+			project_directory.join(DESCRIPTION_FILE),
+		];
+		if let Ok(modules) = list_module_files(&package_directory, &watch_config.modules).await {
+			files.extend(modules);
+		}
+		for file in files {
+			content_hashes.record(&file).await;
+		}
+	}
+	let symlinks = if watch_config.modules.follow_symlinks {
+		startup_step(
+			"resolving symlinks in the package directory",
+			symlink_targets(&project_directory),
+		)?
+	} else {
+		Vec::new()
+	};
+	let mut watched = vec![project_directory.clone()];
+	for symlink in &symlinks {
+		let directory = match (symlink.is_directory, symlink.target.parent()) {
+			(true, _) => symlink.target.as_path(),
+			(false, Some(parent)) => parent,
+			(false, None) => continue,
+		};
+
+		if directory.starts_with(&package_directory) || watched.iter().any(|path| path == directory) {
+			continue;
+		}
+
+		debouncer
+			.watch(directory, RecursiveMode::NonRecursive)
+			.map_err(|error| Error::WatchPath(directory.to_path_buf(), error.to_string()))?;
+		watched.push(directory.to_path_buf());
+	}
 
 	let updates: Arc<Mutex<Vec<Update>>> = Arc::new(Mutex::new(Vec::with_capacity(16)));
 	let notify = Arc::new(Notify::new());
+	let baseline: Arc<Mutex<RemoteBaseline>> = Arc::default();
 
 	let updates_arc = updates.clone();
 	let notify_arc = notify.clone();
+	let baseline_arc = baseline.clone();
 
 	let update_project_directory = project_directory.clone();
 	tokio::spawn(async move {
+		let mut backoff = MIN_SYNC_BACKOFF;
+		let mut attempts: HashMap<Update, u32> = HashMap::new();
 		loop {
 			// wait for updates
 			notify_arc.notified().await;
@@ -388,79 +3932,242 @@ pub async fn watch(project_directory: PathBuf) -> Result<(), Error> {
 			if !lock.is_empty() {
 				let sync_span = tracing::info_span!("sync");
 
-				async {
+				let retry_delay = async {
 					info!(
 						"processing {} update{}...",
 						lock.len(),
 						if lock.len() == 1 { "" } else { "s" }
 					);
 
-					match process_updates(&update_project_directory, &mut lock).await {
+					let retry_delay = match process_updates(
+						&update_project_directory,
+						&mut lock,
+						&mut attempts,
+						dry_run,
+						&baseline_arc,
+					)
+					.await
+					{
+						Ok(outcome) if !outcome.failed.is_empty() => {
+							warn!(
+								"synced {} update{}, {} failed ({})",
+								outcome.synced,
+								if outcome.synced == 1 { "" } else { "s" },
+								outcome.failed.len(),
+								outcome.failed.join("; ")
+							);
+							output::notify_failure();
+							// files which are still queued are read again after a backoff
+							let delay = backoff;
+							backoff = (backoff * 2).min(MAX_SYNC_BACKOFF);
+							(!lock.is_empty()).then_some(delay)
+						}
+						Ok(..) if dry_run => {
+							info!("dry run; nothing was uploaded");
+							None
+						}
 						Ok(..) => {
-							info!("synced successfully!");
+							let timings = serde_json::to_string(&timing::take()).unwrap_or_default();
+							info!(timings, "synced successfully!");
+							backoff = MIN_SYNC_BACKOFF;
+							None
+						}
+						// failed updates stay queued, so they are synced again once fumosclub is back
+						Err(e @ Error::ServiceUnavailable { retry_after, .. }) => {
+							let delay = retry_after.unwrap_or(backoff);
+							backoff = (backoff * 2).min(MAX_SYNC_BACKOFF);
+							warn!("{e}; retrying in {}s", delay.as_secs());
+							Some(delay)
+						}
+						Err(e) => {
+							warn!("error whilst processing: {e}");
+							output::notify_failure();
+							None
 						}
-						Err(e) => warn!("error whilst processing: {e}"),
 					};
 
 					// drop lock to prevent deadlocks
 					drop(lock);
+					retry_delay
 				}
 				.instrument(sync_span)
 				.await;
+
+				if let Some(delay) = retry_delay {
+					tokio::time::sleep(delay).await;
+					notify_arc.notify_one();
+				}
 			}
 		}
 	});
 
+	if let Some(schedule) = tasks.snapshots {
+		let updates = updates.clone();
+		let project_directory = project_directory.clone();
+		tokio::spawn(async move {
+			let first = snapshot::next_due(&project_directory, schedule).await;
+			let mut interval =
+				tokio::time::interval_at(tokio::time::Instant::now() + first, schedule.interval);
+			let mut client = None;
+			loop {
+				interval.tick().await;
+				// holding the lock keeps syncs from running until the snapshot is written
+				let _lock = updates.lock().await;
+				snapshot::take_scheduled(&project_directory, &mut client, schedule).await;
+			}
+		});
+	}
+
+	if let Some(schedule) = tasks.verification {
+		let updates = updates.clone();
+		let notify = notify.clone();
+		let baseline = baseline.clone();
+		let project_directory = project_directory.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(schedule.interval);
+			interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+			loop {
+				// the first tick is right away, so the baseline is what the initial push left
+				interval.tick().await;
+				// holding the lock keeps syncs from running while the script is compared
+				let mut updates = updates.lock().await;
+				match verify_remote(
+					&project_directory,
+					schedule,
+					dry_run,
+					&baseline,
+					&mut updates,
+				)
+				.await
+				{
+					Ok(true) => {
+						drop(updates);
+						notify.notify_one();
+					}
+					Ok(false) => {}
+					Err(error) => warn!("failed verifying the remote script: {error}"),
+				}
+			}
+		});
+	}
+
+	if tasks.session_keepalive && !dry_run {
+		tokio::spawn(login::keep_session_alive(settings::get().session_keepalive));
+	}
+
 	info!("watcher is ready to receive events");
 
-	while let Some(events) = receiver.recv().await {
+	let exit_after = sleep_for(limits.exit_after);
+	tokio::pin!(exit_after);
+	loop {
+		let events = tokio::select! {
+			events = receiver.recv() => match events {
+				Some(events) => events,
+				None => break,
+			},
+			_ = tokio::signal::ctrl_c() => {
+				info!("shutting down...");
+				break;
+			}
+			() = &mut exit_after => {
+				let duration = limits.exit_after.unwrap_or_default();
+				info!("shutting down after {}...", humantime::format_duration(duration));
+				break;
+			}
+			// restarted with every batch of events
+			() = sleep_for(limits.exit_on_idle) => {
+				let duration = limits.exit_on_idle.unwrap_or_default();
+				info!(
+					"shutting down after {} without file changes...",
+					humantime::format_duration(duration)
+				);
+				break;
+			}
+		};
+
 		let mut updates = updates.lock().await;
 		let starting_len = updates.len();
 		for event in events {
-			// neovim and other editors send access events every 2 seconds...
-			// so we skip events that are useless
-			match event.kind {
-				EventKind::Other | EventKind::Access(..) | EventKind::Modify(ModifyKind::Metadata(..)) => {
-					continue;
-				}
-
-				EventKind::Any | EventKind::Modify(..) | EventKind::Create(..) | EventKind::Remove(..) => {}
-			};
+			if !is_relevant_event(&event.kind, &watch_config) {
+				continue;
+			}
 
 			for path in &event.paths {
 				let watcher_span = tracing::info_span!("watcher");
+				// a target may also be a file of its own, so it is classified below as well
+				let linked_paths = symlinks
+					.iter()
+					.filter_map(|symlink| match path.file_name() {
+						Some(file_name)
+							if symlink.is_directory
+								&& path.parent() == Some(symlink.target.as_path())
+								&& is_module_file(path) =>
+						{
+							Some(symlink.link.join(file_name))
+						}
+						_ if !symlink.is_directory && *path == symlink.target => Some(symlink.link.clone()),
+						_ => None,
+					});
+				for linked_path in linked_paths.collect::<Vec<_>>() {
+					if !content_hashes
+						.counts(&event.kind, &project_directory.join(&linked_path))
+						.await
+					{
+						continue;
+					}
+					match checked_file_size(&project_directory.join(&linked_path)).await {
+						Err(error @ Error::FileTooLarge { .. }) => warn!("skipping update: {error}"),
+						_ => {
+							info!(
+								"got package update through symlink at {}",
+								linked_path.display()
+							);
+							updates.push(Update::Module(linked_path));
+						}
+					}
+				}
+
 				// diff the paths to get a relative PathBuf
-				let path = diff_paths(path, &project_directory).context(Error::PathDiffFailed)?;
-				let is_package = path.parent().is_some_and(|parent| {
-					parent
-						.file_name()
-						.is_some_and(|name| name == PACKAGE_DIRECTORY)
-				});
+				let path = relative_event_path(path, &project_directory).context(Error::PathDiffFailed)?;
+				let full_path = project_directory.join(&path);
+				let file = FileKind::of(&full_path);
+				let Some(update) = classify_event(&path, &event.kind, file, &watch_config) else {
+					let skipped = file.file_type == FileType::File
+						&& path.starts_with(PACKAGE_DIRECTORY)
+						&& !is_module_file(&path)
+						&& (watch_config.modules.follow_symlinks || !file.is_symlink);
+					if skipped && watch_config.modules.skip(&full_path) {
+						info!(
+							"not syncing {}; its extension isn't a module's or listed under assetExtensions",
+							path.display()
+						);
+					}
+					continue;
+				};
 
 				async {
-					let update = if is_package && !path.is_dir() {
-						// this is a package file
-						info!("got package update at {}", path.display());
-						Some(Update::Module(path))
-					} else if !is_package && path.is_file() {
-						if path == Path::new(MAIN_SCRIPT_FILE) {
-							info!("got main source update");
-							Some(Update::MainSource)
-						} else if path == Path::new(DESCRIPTION_FILE) {
-							info!("got description update");
-							Some(Update::Description)
-						} else if path == Path::new(SYNC_CONFIGURATION_FILE) {
-							info!("got project configuration update");
-							Some(Update::ProjectConfiguration)
-						} else {
-							None
+					for update in expand_update(&project_directory, update, &watch_config).await {
+						// oversized files would fail every sync, so they aren't queued at all
+						let full_path = match &update {
+							Update::ProjectConfiguration => None,
+							Update::MainSource => Some(project_directory.join(MAIN_SCRIPT_FILE)),
+							Update::Description => Some(project_directory.join(DESCRIPTION_FILE)),
+							Update::Module(path) => Some(project_directory.join(path)),
+						};
+						let hashed_path = full_path
+							.clone()
+							.unwrap_or_else(|| project_directory.join(&path));
+						if !content_hashes.counts(&event.kind, &hashed_path).await {
+							continue;
+						}
+						match full_path {
+							Some(full_path) => match checked_file_size(&full_path).await {
+								Err(error @ Error::FileTooLarge { .. }) => warn!("skipping update: {error}"),
+								// anything else, such as a removed file, is reported by the sync
+								_ => updates.push(update),
+							},
+							None => updates.push(update),
 						}
-					} else {
-						None
-					};
-
-					if let Some(update) = update {
-						updates.push(update);
 					}
 				}
 				.instrument(watcher_span)
@@ -476,9 +4183,100 @@ pub async fn watch(project_directory: PathBuf) -> Result<(), Error> {
 		drop(updates); // prevent deadlocks
 	}
 
+	// flush pending updates; locking waits for an in-flight sync to finish first
+	let mut updates = updates.lock().await;
+	if !updates.is_empty() {
+		match process_updates(
+			&project_directory,
+			&mut updates,
+			&mut HashMap::new(),
+			dry_run,
+			&baseline,
+		)
+		.await
+		{
+			Ok(outcome) if !outcome.failed.is_empty() => warn!(
+				"synced pending updates before exiting, except for {}",
+				outcome.failed.join("; ")
+			),
+			Ok(..) => info!("synced pending updates before exiting"),
+			Err(e) => warn!("error whilst processing pending updates: {e}"),
+		}
+	}
+
 	Ok(())
 }
 
+/// Gets the path of a watcher event relative to the canonical project directory.
+///
+/// Events reached through a symlinked path diff to `..`-prefixed paths, so those are diffed again
+/// once canonicalized; removed files no longer exist, so only their parent is.
+fn relative_event_path(path: &Path, project_directory: &Path) -> Option<PathBuf> {
+	let relative = diff_paths(path, project_directory)?;
+	if !relative.starts_with(Component::ParentDir) {
+		return Some(relative);
+	}
+
+	let canonical = std::fs::canonicalize(path).ok().or_else(|| {
+		let parent = std::fs::canonicalize(path.parent()?).ok()?;
+		Some(parent.join(path.file_name()?))
+	});
+	Some(
+		canonical
+			.and_then(|canonical| diff_paths(canonical, project_directory))
+			.unwrap_or(relative),
+	)
+}
+
+/// Canonicalizes the directory of an existing project, so paths are diffed against the same root
+/// however it is reached, such as through symlinks, or /var on macOS.
+///
+/// # Errors
+/// - [`Error::ProjectNotFound`]
+/// - [`Error::TargetIsFile`] if it isn't a directory
+pub fn resolve_project_root(project_directory: PathBuf) -> Result<PathBuf, Error> {
+	let root = match std::fs::canonicalize(&project_directory) {
+		Ok(root) => root,
+		Err(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => {
+			return Err(Error::ProjectNotFound(project_directory));
+		}
+		Err(io_error) => return Err(Error::ReadDirectory(project_directory, io_error)),
+	};
+
+	if !root.is_dir() {
+		return Err(Error::TargetIsFile(root));
+	}
+	Ok(root)
+}
+
+/// Refuses projects inside of the config directory and the other way around, since secrets.json
+/// would be synced, checking canonical paths so overlaps through symlinks are found too.
+///
+/// # Errors
+/// - [`Error::ProjectInsideConfigDir`]
+/// - [`Error::ConfigDirInsideProject`]
+pub fn check_config_overlap(project_directory: &Path) -> Result<(), Error> {
+	if settings::get().allow_config_overlap {
+		return Ok(());
+	}
+
+	// paths which don't exist can't overlap yet, and fail with better errors later
+	let (Ok(project), Ok(config)) = (
+		std::fs::canonicalize(project_directory),
+		std::fs::canonicalize(get_config_directory()?),
+	) else {
+		return Ok(());
+	};
+
+	if project.starts_with(&config) {
+		Err(Error::ProjectInsideConfigDir(project))
+	} else if config.starts_with(&project) {
+		Err(Error::ConfigDirInsideProject(config))
+	} else {
+		Ok(())
+	}
+}
+
 // Licensed under the Apache License, Version 2.0; used under the MIT license as permitted
 // https://docs.rs/pathdiff/latest/src/pathdiff/lib.rs.html#32-74
 pub fn diff_paths<P, B>(path: P, base: B) -> Option<PathBuf>
@@ -525,3 +4323,676 @@ where
 		Some(comps.iter().map(|c| c.as_os_str()).collect())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TOML_CONFIGURATION: &str = r#"# the script this project syncs
+scriptName = "fumo" # shown on fumosclub
+scriptId = "abc123"
+whitelist = []
+isPublic = false
+debounce = 50
+"#;
+
+	fn rewrite_toml(edit: impl FnOnce(&mut Configuration)) -> String {
+		let mut configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+		edit(&mut configuration);
+		rewrite_toml_configuration(
+			Path::new(SYNC_CONFIGURATION_TOML_FILE),
+			TOML_CONFIGURATION,
+			&configuration,
+		)
+		.unwrap()
+	}
+
+	#[test]
+	fn toml_rewrites_keep_comments() {
+		let rewritten = rewrite_toml(|configuration| {
+			configuration.script_name = "fumo v2".to_owned();
+			configuration.is_public = true;
+		});
+		assert_eq!(
+			rewritten,
+			TOML_CONFIGURATION
+				.replace("\"fumo\"", "\"fumo v2\"")
+				.replace("isPublic = false", "isPublic = true")
+		);
+	}
+
+	#[test]
+	fn toml_rewrites_drop_removed_keys() {
+		let rewritten = rewrite_toml(|configuration| configuration.debounce = None);
+		assert_eq!(rewritten, TOML_CONFIGURATION.replace("debounce = 50\n", ""));
+	}
+
+	#[test]
+	fn unchanged_toml_is_left_alone() {
+		assert_eq!(rewrite_toml(|_| {}), TOML_CONFIGURATION);
+	}
+
+	const JSON_FOUR_SPACES: &str = r#"{
+    "$schema": "./fumosync.schema.json",
+    "scriptName": "fumo",
+    "scriptId": "abc123",
+    "whitelist": ["1", "2"],
+    "isPublic": false,
+    "debounce": 50
+}
+"#;
+
+	const JSON_TABS: &str = "{\n\t\"scriptId\": \"abc123\",\n\t\"isPublic\": false,\n\t\"whitelist\": [\n\t\t\"1\"\n\t],\n\t\"scriptName\": \"fumo\"\n}";
+
+	fn rewrite_json(original: &str, edit: impl FnOnce(&mut Configuration)) -> String {
+		let mut configuration: Configuration = serde_json::from_str(original).unwrap();
+		edit(&mut configuration);
+		rewrite_json_configuration(original, &configuration).unwrap()
+	}
+
+	#[test]
+	fn json_rewrites_only_change_edited_values() {
+		let rewritten = rewrite_json(JSON_FOUR_SPACES, |configuration| {
+			configuration.script_name = "fumo v2".to_owned();
+			configuration.whitelist.push("3".to_owned());
+		});
+		assert_eq!(
+			rewritten,
+			JSON_FOUR_SPACES
+				.replace("\"fumo\"", "\"fumo v2\"")
+				.replace(r#"["1", "2"]"#, r#"["1","2","3"]"#)
+		);
+
+		let rewritten = rewrite_json(JSON_TABS, |configuration| {
+			configuration.whitelist.push("3".to_owned());
+		});
+		assert_eq!(
+			rewritten,
+			JSON_TABS.replace("\t\t\"1\"\n", "\t\t\"1\",\n\t\t\"3\"\n")
+		);
+	}
+
+	#[test]
+	fn json_rewrites_cut_removed_keys() {
+		let rewritten = rewrite_json(JSON_FOUR_SPACES, |configuration| {
+			configuration.schema = None;
+			configuration.debounce = None;
+		});
+		assert_eq!(
+			rewritten,
+			JSON_FOUR_SPACES
+				.replace("\"$schema\": \"./fumosync.schema.json\",\n    ", "")
+				.replace(",\n    \"debounce\": 50", "")
+		);
+	}
+
+	#[test]
+	fn json_rewrites_append_new_keys() {
+		let rewritten = rewrite_json(JSON_TABS, |configuration| {
+			configuration.debounce = Some(50);
+		});
+		assert_eq!(
+			rewritten,
+			JSON_TABS.replace("\"fumo\"\n}", "\"fumo\",\n\t\"debounce\": 50\n}")
+		);
+
+		let rewritten = rewrite_json(JSON_FOUR_SPACES, |configuration| {
+			configuration.debounce = None;
+			configuration.follow_symlinks = Some(false);
+		});
+		assert_eq!(
+			rewritten,
+			JSON_FOUR_SPACES.replace("\"debounce\": 50", "\"followSymlinks\": false")
+		);
+	}
+
+	#[test]
+	fn unchanged_json_is_left_alone() {
+		for original in [JSON_FOUR_SPACES, JSON_TABS] {
+			assert_eq!(rewrite_json(original, |_| {}), original);
+		}
+	}
+
+	#[test]
+	fn classifies_events() {
+		use notify_debouncer_full::notify::event::{
+			AccessKind, CreateKind, DataChange, MetadataKind, RemoveKind,
+		};
+
+		const FILE: FileKind = FileKind {
+			file_type: FileType::File,
+			is_symlink: false,
+		};
+		const DIRECTORY: FileKind = FileKind {
+			file_type: FileType::Directory,
+			is_symlink: false,
+		};
+		const MISSING: FileKind = FileKind {
+			file_type: FileType::Missing,
+			is_symlink: false,
+		};
+		const SYMLINK: FileKind = FileKind {
+			file_type: FileType::File,
+			is_symlink: true,
+		};
+
+		let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+		let create = EventKind::Create(CreateKind::File);
+		let remove = EventKind::Remove(RemoveKind::Any);
+		let metadata = EventKind::Modify(ModifyKind::Metadata(MetadataKind::WriteTime));
+		let access = EventKind::Access(AccessKind::Any);
+		let module = |path: &str| Some(Update::Module(PathBuf::from(path)));
+
+		let cases = [
+			(MAIN_SCRIPT_FILE, write, FILE, Some(Update::MainSource)),
+			(DESCRIPTION_FILE, create, FILE, Some(Update::Description)),
+			(
+				SYNC_CONFIGURATION_FILE,
+				write,
+				FILE,
+				Some(Update::ProjectConfiguration),
+			),
+			(
+				SYNC_CONFIGURATION_TOML_FILE,
+				write,
+				FILE,
+				Some(Update::ProjectConfiguration),
+			),
+			// removed top-level files only matter once they are back
+			(MAIN_SCRIPT_FILE, remove, MISSING, None),
+			("notes.txt", write, FILE, None),
+			("pkg/util.luau", write, FILE, module("pkg/util.luau")),
+			(
+				"pkg/ui/button.luau",
+				create,
+				FILE,
+				module("pkg/ui/button.luau"),
+			),
+			("pkg/util.luau", remove, MISSING, module("pkg/util.luau")),
+			("pkg/.luau", write, FILE, module("pkg/.luau")),
+			// directories stand for the modules inside of them
+			("pkg/ui", create, DIRECTORY, module("pkg/ui")),
+			("pkg/ui", remove, MISSING, None),
+			("pkg", create, DIRECTORY, module("pkg")),
+			("pkg", remove, MISSING, None),
+			("pkg/data.json", write, FILE, None),
+			("pkg/shared.luau", write, SYMLINK, None),
+			("pkg/util.luau", access, FILE, None),
+			("pkg/util.luau", metadata, FILE, None),
+			("../outside.luau", write, FILE, None),
+		];
+
+		let config = WatchConfig {
+			modules: ModuleFilter::default(),
+			metadata_events: false,
+		};
+		for (path, kind, file, expected) in cases {
+			assert_eq!(
+				classify_event(Path::new(path), &kind, file, &config),
+				expected,
+				"{kind:?} of {path}"
+			);
+		}
+
+		let config = WatchConfig {
+			modules: ModuleFilter {
+				follow_symlinks: true,
+				..ModuleFilter::default()
+			},
+			metadata_events: true,
+		};
+		assert_eq!(
+			classify_event(Path::new("pkg/shared.luau"), &write, SYMLINK, &config),
+			module("pkg/shared.luau")
+		);
+		assert_eq!(
+			classify_event(Path::new("pkg/util.luau"), &metadata, FILE, &config),
+			module("pkg/util.luau")
+		);
+	}
+
+	#[test]
+	fn classifies_metadata_events_in_both_modes() {
+		use notify_debouncer_full::notify::event::{DataChange, MetadataKind};
+
+		const FILE: FileKind = FileKind {
+			file_type: FileType::File,
+			is_symlink: false,
+		};
+
+		let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+		let paths = [
+			MAIN_SCRIPT_FILE,
+			DESCRIPTION_FILE,
+			SYNC_CONFIGURATION_FILE,
+			"pkg/util.luau",
+			"pkg/ui/button.luau",
+			"notes.txt",
+		];
+		for metadata_events in [false, true] {
+			let config = WatchConfig {
+				modules: ModuleFilter::default(),
+				metadata_events,
+			};
+			for kind in [
+				MetadataKind::Any,
+				MetadataKind::AccessTime,
+				MetadataKind::WriteTime,
+				MetadataKind::Permissions,
+				MetadataKind::Ownership,
+				MetadataKind::Extended,
+			] {
+				let kind = EventKind::Modify(ModifyKind::Metadata(kind));
+				for path in paths {
+					// once let through, metadata events are classified like writes
+					let expected = if metadata_events {
+						classify_event(Path::new(path), &write, FILE, &config)
+					} else {
+						None
+					};
+					assert_eq!(
+						classify_event(Path::new(path), &kind, FILE, &config),
+						expected,
+						"{kind:?} of {path} with metadata events {metadata_events}"
+					);
+				}
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn metadata_events_only_count_with_new_contents() {
+		use notify_debouncer_full::notify::event::{DataChange, MetadataKind};
+
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory.path().join(MAIN_SCRIPT_FILE);
+		std::fs::write(&path, "print(1)").unwrap();
+		let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+		let metadata = EventKind::Modify(ModifyKind::Metadata(MetadataKind::WriteTime));
+
+		let mut hashes = ContentHashes::new(true);
+		assert!(hashes.counts(&write, &path).await);
+		assert!(!hashes.counts(&metadata, &path).await);
+		assert!(hashes.counts(&write, &path).await, "writes always count");
+		std::fs::write(&path, "print(2)").unwrap();
+		assert!(hashes.counts(&metadata, &path).await);
+		assert!(!hashes.counts(&metadata, &path).await);
+
+		let mut hashes = ContentHashes::new(false);
+		assert!(hashes.counts(&metadata, &path).await);
+		assert!(hashes.counts(&metadata, &path).await);
+	}
+
+	fn module_from(path: &str) -> Result<String, String> {
+		get_module_from_path(Path::new(path))
+	}
+
+	#[test]
+	fn nested_modules_are_joined_with_slashes() {
+		assert_eq!(module_from("ui/button.luau").unwrap(), "ui/button");
+		assert_eq!(module_from("./ui/button.luau").unwrap(), "ui/button");
+	}
+
+	#[cfg(windows)]
+	#[test]
+	fn backslashes_name_modules_like_slashes() {
+		assert_eq!(module_from(r"ui\button.luau").unwrap(), "ui/button");
+	}
+
+	#[test]
+	fn only_the_last_extension_is_stripped() {
+		assert_eq!(module_from("config.prod.luau").unwrap(), "config.prod");
+		assert_eq!(module_from("v1.2/semver.luau").unwrap(), "v1.2/semver");
+		assert_eq!(module_from("README").unwrap(), "README");
+	}
+
+	#[test]
+	fn module_names_are_trimmed() {
+		assert_eq!(module_from(" mymodule.luau").unwrap(), "mymodule");
+		assert_eq!(module_from(" ui /button .luau").unwrap(), "ui/button");
+	}
+
+	#[test]
+	fn unnameable_paths_are_refused() {
+		assert!(module_from(".luau").is_err());
+		assert!(module_from(" .luau").is_err());
+		assert!(module_from("").is_err());
+		assert!(module_from("../outside.luau").is_err());
+		assert!(module_from("bell\u{7}.luau").is_err());
+	}
+
+	fn remote_script(name: &str, is_public: bool, whitelist: &[&str]) -> EditorScriptInfo {
+		serde_json::from_value(serde_json::json!({
+			"name": name,
+			"type": 0,
+			"description": "",
+			"isPublic": is_public,
+			"whitelist": whitelist,
+			"source": { "main": "", "modules": {} },
+		}))
+		.unwrap()
+	}
+
+	fn metadata_body(configuration: &Configuration, drift: &[MetadataDrift]) -> serde_json::Value {
+		let updates = get_editor_updates_from_configuration(configuration, drift);
+		let body = crate::client::editor_body(&ScriptId::new("abc123").unwrap(), &updates).unwrap();
+		serde_json::from_slice::<serde_json::Value>(&body).unwrap()["scriptInfo"].clone()
+	}
+
+	#[test]
+	fn uploads_only_locally_edited_metadata() {
+		let mut configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+		let synced = SyncedMetadata::of(&configuration);
+		configuration.script_name = "fumo v2".to_owned();
+		// made public and whitelisted on the website since the last pull
+		let remote = remote_script("fumo", true, &["friend"]);
+
+		let drift = edited_metadata(&configuration, &remote, Some(&synced));
+		assert_eq!(
+			metadata_body(&configuration, &drift),
+			serde_json::json!({ "source": {}, "name": "fumo v2" })
+		);
+	}
+
+	#[test]
+	fn uploads_nothing_without_local_edits() {
+		let configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+		let synced = SyncedMetadata::of(&configuration);
+		let remote = remote_script("renamed on the website", true, &["friend"]);
+
+		assert!(edited_metadata(&configuration, &remote, Some(&synced)).is_empty());
+	}
+
+	#[test]
+	fn uploads_every_differing_field_without_a_sync_record() {
+		let mut configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+		configuration.whitelist = vec!["b".to_owned(), "a".to_owned()];
+		let remote = remote_script("old name", true, &[]);
+
+		let drift = edited_metadata(&configuration, &remote, None);
+		assert_eq!(
+			metadata_body(&configuration, &drift),
+			serde_json::json!({
+				"source": {},
+				"whitelist": ["a", "b"],
+				"name": "fumo",
+				"isPublic": false,
+			})
+		);
+	}
+
+	#[test]
+	fn leaves_edits_matching_the_script_alone() {
+		let mut configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+		let synced = SyncedMetadata::of(&configuration);
+		configuration.is_public = true;
+		let remote = remote_script("fumo", true, &[]);
+
+		assert!(edited_metadata(&configuration, &remote, Some(&synced)).is_empty());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn resolves_symlinked_roots() {
+		let directory = tempfile::tempdir().unwrap();
+		let project = directory.path().join("projects/foo");
+		std::fs::create_dir_all(project.join(PACKAGE_DIRECTORY)).unwrap();
+		let link = directory.path().join("foo");
+		std::os::unix::fs::symlink(&project, &link).unwrap();
+
+		let root = resolve_project_root(link.clone()).unwrap();
+		assert_eq!(root, resolve_project_root(project).unwrap());
+		// events are reported under the canonical root, whichever way the project was reached
+		let event = std::fs::canonicalize(link.join(PACKAGE_DIRECTORY))
+			.unwrap()
+			.join("util.luau");
+		assert_eq!(
+			event.strip_prefix(&root).unwrap(),
+			Path::new("pkg/util.luau")
+		);
+	}
+
+	/// macOS reaches temporary directories through /var, which links to /private/var.
+	#[cfg(unix)]
+	#[test]
+	fn resolves_var_aliases() {
+		let directory = tempfile::tempdir().unwrap();
+		let private = directory.path().join("private/var");
+		std::fs::create_dir_all(private.join("project")).unwrap();
+		std::os::unix::fs::symlink(&private, directory.path().join("var")).unwrap();
+
+		let aliased = resolve_project_root(directory.path().join("var/project")).unwrap();
+		let resolved = resolve_project_root(private.join("project")).unwrap();
+		assert_eq!(aliased, resolved);
+		assert!(aliased.ends_with("private/var/project"));
+	}
+
+	#[test]
+	fn refuses_missing_and_file_roots() {
+		let directory = tempfile::tempdir().unwrap();
+		assert!(matches!(
+			resolve_project_root(directory.path().join("missing")),
+			Err(Error::ProjectNotFound(..))
+		));
+
+		let file = directory.path().join(SYNC_CONFIGURATION_FILE);
+		std::fs::write(&file, "{}").unwrap();
+		assert!(matches!(
+			resolve_project_root(file),
+			Err(Error::TargetIsFile(..))
+		));
+	}
+
+	fn temporary_files(directory: &Path) -> Vec<PathBuf> {
+		std::fs::read_dir(directory)
+			.unwrap()
+			.map(|entry| entry.unwrap().path())
+			.filter(|path| path.extension() == Some(OsStr::new("tmp")))
+			.collect()
+	}
+
+	/// A temporary file of the path, as a write by the process `pid` names it.
+	fn temporary_file_of(path: &Path, pid: u32) -> PathBuf {
+		let name = path.file_name().unwrap().to_str().unwrap();
+		path.with_file_name(format!(".{name}.{pid}.0.tmp"))
+	}
+
+	#[tokio::test]
+	async fn interrupted_writes_keep_the_old_contents() {
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory.path().join(SYNC_CONFIGURATION_FILE);
+		write_file(&path, "old").await.unwrap();
+
+		// a write interrupted before its rename leaves a partial temporary file, and nothing else;
+		// no process has the largest pid
+		std::fs::write(temporary_file_of(&path, u32::MAX), "ne").unwrap();
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+
+		write_file(&path, "new").await.unwrap();
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+		assert!(temporary_files(directory.path()).is_empty());
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn writes_keep_the_temporary_files_of_running_processes() {
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory.path().join(SYNC_CONFIGURATION_FILE);
+		let running = temporary_file_of(&path, std::os::unix::process::parent_id());
+		std::fs::write(&running, "ne").unwrap();
+
+		write_file(&path, "new").await.unwrap();
+		assert_eq!(temporary_files(directory.path()), [running]);
+	}
+
+	#[tokio::test]
+	async fn concurrent_writes_use_their_own_temporary_files() {
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory.path().join(SYNC_CONFIGURATION_FILE);
+		let contents: Vec<String> = (0..16).map(|index| index.to_string()).collect();
+
+		let writes = contents.iter().map(|contents| write_file(&path, contents));
+		for result in futures::future::join_all(writes).await {
+			result.unwrap();
+		}
+		assert!(contents.contains(&std::fs::read_to_string(&path).unwrap()));
+		assert!(temporary_files(directory.path()).is_empty());
+	}
+
+	#[tokio::test]
+	async fn failed_writes_remove_their_temporary_file() {
+		let directory = tempfile::tempdir().unwrap();
+		// files can't be renamed over directories
+		let path = directory.path().join(PACKAGE_DIRECTORY);
+		std::fs::create_dir_all(path.join("nested")).unwrap();
+
+		assert!(write_file(&path, "contents").await.is_err());
+		assert!(temporary_files(directory.path()).is_empty());
+		assert!(path.is_dir());
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn private_files_are_created_private() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory.path().join("secrets.json");
+		std::fs::write(&path, "{}").unwrap();
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+		write_private_file(&path, "secret").await.unwrap();
+		let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+		assert_eq!(mode & 0o777, 0o600);
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), "secret");
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn writes_keep_existing_permissions() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory.path().join(MAIN_SCRIPT_FILE);
+		std::fs::write(&path, "print(1)").unwrap();
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+		write_file(&path, "print(2)").await.unwrap();
+		let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+		assert_eq!(mode & 0o777, 0o640);
+	}
+
+	#[tokio::test]
+	async fn reads_the_rest_of_a_batch_with_a_missing_file() {
+		let directory = tempfile::tempdir().unwrap();
+		let package = directory.path().join(PACKAGE_DIRECTORY);
+		std::fs::create_dir(&package).unwrap();
+		std::fs::write(directory.path().join(MAIN_SCRIPT_FILE), "print(1)").unwrap();
+		std::fs::write(package.join("a.luau"), "return 1").unwrap();
+		std::fs::write(package.join("b.luau"), "return 2").unwrap();
+		let configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+
+		// deleted between its event and the sync
+		let missing = Update::Module(PathBuf::from("pkg/missing.luau"));
+		let updates = [
+			Update::MainSource,
+			Update::Module(PathBuf::from("pkg/a.luau")),
+			missing.clone(),
+			Update::Module(PathBuf::from("pkg/b.luau")),
+		];
+		let read = read_updates(directory.path(), &configuration, &updates, read_file).await;
+
+		assert_eq!(read.main_source.as_deref(), Some("print(1)"));
+		let modules: Vec<(&str, &str)> = read
+			.modules
+			.iter()
+			.map(|(name, source)| (name.as_str(), source.as_str()))
+			.collect();
+		assert_eq!(modules, [("a", "return 1"), ("b", "return 2")]);
+		assert_eq!(read.failed.len(), 1);
+		assert_eq!(read.failed[0].0, missing);
+	}
+
+	#[test]
+	fn sanitizes_file_names() {
+		assert_eq!(
+			sanitize_file_name("my/script: v2. ", "abc123"),
+			"my-script- v2"
+		);
+		assert_eq!(sanitize_file_name("tab\there", "abc123"), "tab-here");
+		assert_eq!(sanitize_file_name("<>", "abc123"), "abc123");
+	}
+
+	#[tokio::test]
+	async fn reads_duplicate_updates_once() {
+		let configuration: Configuration = toml::from_str(TOML_CONFIGURATION).unwrap();
+		let module = Update::Module(PathBuf::from("pkg/a.luau"));
+		let updates = unique_updates(&[
+			Update::MainSource,
+			Update::MainSource,
+			module.clone(),
+			module,
+		]);
+
+		let reads = std::cell::Cell::new(0);
+		let read = read_updates(
+			Path::new("project"),
+			&configuration,
+			&updates,
+			async |_: PathBuf| {
+				reads.set(reads.get() + 1);
+				Ok("return 1".to_owned())
+			},
+		)
+		.await;
+		assert_eq!(reads.get(), 2);
+
+		let editor_updates = editor_updates_of(read.main_source.as_deref(), &read, &configuration, &[]);
+		assert!(matches!(
+			editor_updates.as_slice(),
+			[
+				EditorUpdate::MainSource("return 1"),
+				EditorUpdate::Module { name, source: "return 1" },
+			] if name.as_str() == "a"
+		));
+	}
+
+	#[test]
+	fn retries_failed_updates_until_the_cap() {
+		let missing = Update::Module(PathBuf::from("pkg/missing.luau"));
+		let fail = || vec![(missing.clone(), Error::NoCachedScripts)];
+		let mut updates = Vec::new();
+		let mut attempts = HashMap::new();
+
+		for remaining in (1..MAX_UPDATE_ATTEMPTS).rev() {
+			let failed = requeue_failed(&mut updates, &mut attempts, fail());
+			assert!(failed[0].ends_with(&format!(
+				"will retry {remaining} more time{}",
+				if remaining == 1 { "" } else { "s" }
+			)));
+			assert_eq!(updates, [missing.clone()]);
+			updates.clear();
+		}
+
+		let failed = requeue_failed(&mut updates, &mut attempts, fail());
+		assert!(failed[0].ends_with("dropped"));
+		assert!(updates.is_empty());
+		assert!(attempts.is_empty());
+	}
+
+	#[test]
+	fn forgets_the_attempts_of_updates_which_synced() {
+		let missing = Update::Module(PathBuf::from("pkg/missing.luau"));
+		let mut updates = Vec::new();
+		let mut attempts = HashMap::new();
+		requeue_failed(
+			&mut updates,
+			&mut attempts,
+			vec![(missing.clone(), Error::NoCachedScripts)],
+		);
+		assert_eq!(attempts.get(&missing), Some(&1));
+
+		requeue_failed(&mut updates, &mut attempts, Vec::new());
+		assert!(attempts.is_empty());
+	}
+}
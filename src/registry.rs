@@ -0,0 +1,120 @@
+use crate::{
+	error::Error,
+	login::get_config_directory,
+	project::{ConfirmCallback, read_file, write_file},
+	settings,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+use tracing::warn;
+
+/// Projects which last pushed each script, inside of the config directory
+pub const REGISTRY_FILE: &str = "projects.json";
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredProject {
+	pub path: PathBuf,
+	pub pushed_at: DateTime<Utc>,
+}
+
+fn get_registry_path() -> Result<PathBuf, Error> {
+	Ok(get_config_directory()?.join(REGISTRY_FILE))
+}
+
+/// Reads the registry by script id, dropping projects which no longer exist.
+async fn read_registry() -> Result<BTreeMap<String, RegisteredProject>, Error> {
+	let path = get_registry_path()?;
+	if !path.try_exists()? {
+		return Ok(BTreeMap::new());
+	}
+
+	let mut registry: BTreeMap<String, RegisteredProject> =
+		serde_json::from_str(&read_file(&path).await?)?;
+	registry.retain(|_, project| project.path.is_dir());
+	Ok(registry)
+}
+
+/// Checks that no other project last pushed the script, before pushing or watching a project.
+///
+/// Another project is warned about, and only pushed over with `force` or once `confirm` agrees. An
+/// unreadable registry is warned about rather than blocking pushes.
+///
+/// # Errors
+/// - [`Error::ScriptPushedElsewhere`]
+pub async fn check(
+	script_id: &str,
+	project_directory: &Path,
+	force: bool,
+	confirm: Option<&ConfirmCallback>,
+) -> Result<(), Error> {
+	if !settings::get().project_registry {
+		return Ok(());
+	}
+
+	let registry = match read_registry().await {
+		Ok(registry) => registry,
+		Err(error) => {
+			warn!("failed reading {REGISTRY_FILE}: {error}");
+			return Ok(());
+		}
+	};
+	let Some(other) = registry
+		.get(script_id)
+		.filter(|other| other.path != project_directory)
+	else {
+		return Ok(());
+	};
+
+	warn!(
+		"!!! {} last pushed {script_id} at {}; pushing from {} overwrites it !!!",
+		other.path.display(),
+		other.pushed_at,
+		project_directory.display()
+	);
+	let error = Error::ScriptPushedElsewhere {
+		script_id: script_id.to_owned(),
+		path: other.path.clone(),
+	};
+	if force {
+		return Ok(());
+	}
+
+	match confirm {
+		Some(confirm) if confirm("push anyway? this project becomes the one pushing the script")? => {
+			Ok(())
+		}
+		_ => Err(error),
+	}
+}
+
+/// Records the project as the one which last pushed the script; failures are only warned about.
+pub async fn register(script_id: &str, project_directory: &Path) {
+	if !settings::get().project_registry {
+		return;
+	}
+
+	let result = async {
+		let mut registry = read_registry().await?;
+		registry.insert(
+			script_id.to_owned(),
+			RegisteredProject {
+				path: project_directory.to_path_buf(),
+				pushed_at: Utc::now(),
+			},
+		);
+		write_file(
+			get_registry_path()?,
+			&serde_json::to_string_pretty(&registry)?,
+		)
+		.await
+	};
+
+	if let Err(error) = result.await {
+		warn!("failed updating {REGISTRY_FILE}: {error}");
+	}
+}
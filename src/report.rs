@@ -0,0 +1,334 @@
+use crate::project::MetadataDrift;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Version of the JSON documents printed by status, diff and verify; bumped on breaking changes
+pub const REPORT_VERSION: u32 = 1;
+
+/// How a local file compares to its remote counterpart.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FileState {
+	/// Only exists locally
+	Added,
+	/// Only exists on fumosclub
+	Removed,
+	Modified,
+	Unchanged,
+}
+
+impl std::fmt::Display for FileState {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::Added => "added",
+			Self::Removed => "removed",
+			Self::Modified => "modified",
+			Self::Unchanged => "unchanged",
+		})
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+	/// Path relative to the project directory
+	pub path: String,
+	/// Name of the module on fumosclub; `None` for files which aren't modules
+	pub remote_name: Option<String>,
+	/// SHA-256 of the local contents; `None` if there is no local file, or it is compared by fields
+	pub local_hash: Option<String>,
+	pub remote_hash: Option<String>,
+	pub state: FileState,
+	/// Unified diff from the remote to the local contents, only for `fumo diff`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub diff: Option<String>,
+	/// Lines added and removed by the diff, only for `fumo diff`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stat: Option<FileStat>,
+}
+
+/// Lines a diff adds and removes.
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+	pub insertions: usize,
+	pub deletions: usize,
+}
+
+impl FileReport {
+	/// Compares the contents of a file, with a diff if `diff` is set and they differ.
+	pub fn compare(
+		path: String,
+		remote_name: Option<&str>,
+		local: Option<&str>,
+		remote: Option<&str>,
+		diff: bool,
+	) -> Self {
+		let state = match (local, remote) {
+			(Some(local), Some(remote)) if local.as_bytes() == remote.as_bytes() => FileState::Unchanged,
+			(Some(_), Some(_)) => FileState::Modified,
+			(Some(_), None) => FileState::Added,
+			(None, _) => FileState::Removed,
+		};
+
+		let (diff, stat) = if diff && state != FileState::Unchanged {
+			let text_diff =
+				similar::TextDiff::from_lines(remote.unwrap_or_default(), local.unwrap_or_default());
+			let mut stat = FileStat::default();
+			for change in text_diff.iter_all_changes() {
+				match change.tag() {
+					similar::ChangeTag::Insert => stat.insertions += 1,
+					similar::ChangeTag::Delete => stat.deletions += 1,
+					similar::ChangeTag::Equal => {}
+				}
+			}
+
+			let unified = text_diff
+				.unified_diff()
+				.header(&format!("remote/{path}"), &format!("local/{path}"))
+				.to_string();
+			(Some(unified), Some(stat))
+		} else {
+			(None, None)
+		};
+
+		Self {
+			remote_name: remote_name.map(str::to_owned),
+			local_hash: local.map(hash),
+			remote_hash: remote.map(hash),
+			path,
+			state,
+			diff,
+			stat,
+		}
+	}
+}
+
+fn hash(contents: &str) -> String {
+	format!("{:x}", Sha256::digest(contents))
+}
+
+/// Which metadata fields differ from fumosclub.
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataReport {
+	pub script_name: bool,
+	pub is_public: bool,
+	pub whitelist: bool,
+}
+
+impl From<&[MetadataDrift]> for MetadataReport {
+	fn from(drift: &[MetadataDrift]) -> Self {
+		let mut report = Self::default();
+		for field in drift {
+			match field {
+				MetadataDrift::ScriptName { .. } => report.script_name = true,
+				MetadataDrift::IsPublic { .. } => report.is_public = true,
+				MetadataDrift::Whitelist { .. } => report.whitelist = true,
+			}
+		}
+
+		report
+	}
+}
+
+/// How a project compares to its script on fumosclub.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+	/// Always [`REPORT_VERSION`]
+	pub v: u32,
+	pub script_id: String,
+	pub files: Vec<FileReport>,
+	pub metadata: MetadataReport,
+	pub metadata_drift: Vec<MetadataDrift>,
+}
+
+impl Report {
+	pub fn new(
+		script_id: String,
+		files: Vec<FileReport>,
+		metadata_drift: Vec<MetadataDrift>,
+	) -> Self {
+		Self {
+			v: REPORT_VERSION,
+			script_id,
+			files,
+			metadata: MetadataReport::from(metadata_drift.as_slice()),
+			metadata_drift,
+		}
+	}
+
+	/// Replaces the metadata drift, keeping the summary in sync.
+	pub fn set_metadata_drift(&mut self, metadata_drift: Vec<MetadataDrift>) {
+		self.metadata = MetadataReport::from(metadata_drift.as_slice());
+		self.metadata_drift = metadata_drift;
+	}
+
+	/// Iterates over the files which differ from fumosclub.
+	pub fn mismatches(&self) -> impl Iterator<Item = &FileReport> {
+		mismatches(&self.files)
+	}
+}
+
+/// Sorts the files which differ from fumosclub by path, for output which scripts can rely on.
+pub fn sorted_mismatches(files: &[FileReport]) -> Vec<&FileReport> {
+	let mut mismatches: Vec<&FileReport> = mismatches(files).collect();
+	mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+	mismatches
+}
+
+/// Widest +/- bar drawn by [`format_stat`]
+const MAX_STAT_BAR: usize = 40;
+
+/// Formats a diffstat the way `git diff --stat` does, marking added and removed files; files
+/// compared by fields, such as fumosync.json, show as `metadata`.
+pub fn format_stat(files: &[&FileReport]) -> String {
+	let label = |file: &FileReport| match file.state {
+		FileState::Added => format!("{} (new)", file.path),
+		FileState::Removed => format!("{} (gone)", file.path),
+		FileState::Modified | FileState::Unchanged => file.path.clone(),
+	};
+	let width = files
+		.iter()
+		.map(|file| label(file).len())
+		.max()
+		.unwrap_or(0);
+	let changed = |file: &FileReport| file.stat.map_or(0, |stat| stat.insertions + stat.deletions);
+	let most = files.iter().map(|file| changed(file)).max().unwrap_or(0);
+	let count_width = most.to_string().len();
+
+	let mut output = String::new();
+	let mut total = FileStat::default();
+	for file in files {
+		let Some(stat) = file.stat else {
+			output.push_str(&format!(" {:width$} | metadata\n", label(file)));
+			continue;
+		};
+
+		total.insertions += stat.insertions;
+		total.deletions += stat.deletions;
+		// bars are scaled down once a file changes more lines than fit
+		let scale = |lines: usize| {
+			if most > MAX_STAT_BAR {
+				(lines * MAX_STAT_BAR).div_ceil(most)
+			} else {
+				lines
+			}
+		};
+		output.push_str(&format!(
+			" {:width$} | {:>count_width$} {}{}\n",
+			label(file),
+			changed(file),
+			"+".repeat(scale(stat.insertions)),
+			"-".repeat(scale(stat.deletions)),
+		));
+	}
+
+	output.push_str(&format!(
+		" {} {} changed, {} {}(+), {} {}(-)\n",
+		files.len(),
+		if files.len() == 1 { "file" } else { "files" },
+		total.insertions,
+		if total.insertions == 1 {
+			"insertion"
+		} else {
+			"insertions"
+		},
+		total.deletions,
+		if total.deletions == 1 {
+			"deletion"
+		} else {
+			"deletions"
+		},
+	));
+	output
+}
+
+/// Filters out the files which match fumosclub.
+pub fn mismatches(files: &[FileReport]) -> impl Iterator<Item = &FileReport> {
+	files
+		.iter()
+		.filter(|file| file.state != FileState::Unchanged)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Checks a report against a fixture, so changes to the JSON schema are deliberate.
+	fn assert_matches_fixture(report: &Report, fixture: &str) {
+		let fixture: serde_json::Value = serde_json::from_str(fixture).unwrap();
+		assert_eq!(serde_json::to_value(report).unwrap(), fixture);
+	}
+
+	fn main_script(diff: bool) -> FileReport {
+		FileReport::compare(
+			"init.server.luau".to_owned(),
+			None,
+			Some("print(2)\n"),
+			Some("print(1)\n"),
+			diff,
+		)
+	}
+
+	#[test]
+	fn status_reports_match_the_fixture() {
+		let files = vec![
+			main_script(false),
+			FileReport::compare(
+				"pkg/util.luau".to_owned(),
+				Some("util"),
+				Some("return 1"),
+				Some("return 1"),
+				false,
+			),
+			FileReport::compare(
+				"pkg/new.luau".to_owned(),
+				Some("new"),
+				Some("return 2"),
+				None,
+				false,
+			),
+			FileReport::compare(
+				"pkg/old.luau".to_owned(),
+				Some("old"),
+				None,
+				Some("return 3"),
+				false,
+			),
+		];
+		let drift = vec![MetadataDrift::IsPublic {
+			local: true,
+			remote: false,
+		}];
+
+		let report = Report::new("abc123".to_owned(), files, drift);
+		assert_matches_fixture(
+			&report,
+			include_str!("../tests/fixtures/status-report.json"),
+		);
+		assert_eq!(
+			report
+				.mismatches()
+				.map(|file| file.path.as_str())
+				.collect::<Vec<_>>(),
+			["init.server.luau", "pkg/new.luau", "pkg/old.luau"]
+		);
+	}
+
+	#[test]
+	fn diff_reports_match_the_fixture() {
+		let report = Report::new("abc123".to_owned(), vec![main_script(true)], Vec::new());
+		assert_matches_fixture(&report, include_str!("../tests/fixtures/diff-report.json"));
+	}
+
+	#[test]
+	fn stats_are_formatted_like_git() {
+		let file = main_script(true);
+		assert_eq!(
+			format_stat(&[&file]),
+			" init.server.luau | 2 +-\n 1 file changed, 1 insertion(+), 1 deletion(-)\n"
+		);
+	}
+}
@@ -0,0 +1,257 @@
+use crate::project::{Configuration, PACKAGE_DIRECTORY};
+use std::path::Path;
+
+/// Gets the names passed to `requireM` as string literals, in `requireM("name")` or
+/// `requireM "name"` form; names built at runtime can't be checked.
+fn find_requires(source: &str) -> Vec<&str> {
+	let mut names = Vec::new();
+	let mut rest = source;
+	while let Some(index) = rest.find("requireM") {
+		let preceded_by_identifier = rest[..index]
+			.chars()
+			.next_back()
+			.is_some_and(|character| character.is_alphanumeric() || character == '_');
+		rest = &rest[index + "requireM".len()..];
+		if preceded_by_identifier {
+			continue;
+		}
+
+		let call = rest.trim_start();
+		let call = call.strip_prefix('(').map_or(call, str::trim_start);
+		let Some(quote) = call
+			.chars()
+			.next()
+			.filter(|quote| matches!(quote, '"' | '\''))
+		else {
+			continue;
+		};
+		let literal = &call[1..];
+		let Some(end) = literal.find([quote, '\n']) else {
+			continue;
+		};
+		// escapes would need unescaping to be compared, and are never in module names
+		if literal[end..].starts_with(quote) && !literal[..end].contains('\\') {
+			names.push(&literal[..end]);
+		}
+	}
+
+	names
+}
+
+/// Explains why a module required by its local name isn't uploaded under it, if it is one which
+/// exists in pkg/ but isn't part of the uploaded modules.
+async fn explain_missing(
+	project_directory: &Path,
+	configuration: &Configuration,
+	name: &str,
+) -> Option<String> {
+	if let Some(remote) = configuration.module_aliases.get(name) {
+		return Some(format!(
+			"{PACKAGE_DIRECTORY}/{name}.luau is uploaded as \"{remote}\" by its alias in fumosync.json"
+		));
+	}
+
+	let package_directory = project_directory.join(PACKAGE_DIRECTORY);
+	let path = package_directory.join(format!("{name}.luau"));
+	let is_symlink = tokio::fs::symlink_metadata(&path)
+		.await
+		.is_ok_and(|metadata| metadata.is_symlink());
+	if is_symlink && !configuration.follows_symlinks() {
+		return Some(format!(
+			"{PACKAGE_DIRECTORY}/{name}.luau is a symlink, which isn't uploaded with \"followSymlinks\": false"
+		));
+	}
+
+	for extension in &configuration.asset_extensions {
+		let file_name = format!("{name}.{}", extension.trim_start_matches('.'));
+		if package_directory.join(&file_name).is_file() {
+			return Some(format!(
+				"{PACKAGE_DIRECTORY}/{file_name} is skipped as an asset by \"assetExtensions\""
+			));
+		}
+	}
+
+	None
+}
+
+/// Checks the `requireM` calls of the uploaded sources against the names modules are uploaded as,
+/// after aliases and skipped files, returning a problem for each call resolving locally but not
+/// once pushed.
+///
+/// Names matching no local file at all aren't reported, since they may be modules only on
+/// fumosclub. `sources` are paths relative to the project directory with their contents.
+pub async fn check<'a>(
+	project_directory: &Path,
+	configuration: &Configuration,
+	sources: impl IntoIterator<Item = (String, &'a str)>,
+	uploaded: &[&str],
+) -> Vec<String> {
+	let mut problems = Vec::new();
+	for (path, source) in sources {
+		let mut names = find_requires(source);
+		names.sort_unstable();
+		names.dedup();
+		for name in names {
+			if uploaded.contains(&name) {
+				continue;
+			}
+
+			if let Some(reason) = explain_missing(project_directory, configuration, name).await {
+				problems.push(format!(
+					"requireM(\"{name}\") in {path} breaks once pushed: {reason}"
+				));
+			}
+		}
+	}
+
+	problems
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn finds_literal_requires() {
+		let source = r#"
+local util = requireM("util")
+local signal = requireM 'signal'
+local ui = requireM( "ui/button" )
+myrequireM("not a require")
+requireM(name)
+requireM("escaped\"name")
+requireM("unterminated
+"#;
+		assert_eq!(find_requires(source), ["util", "signal", "ui/button"]);
+	}
+
+	fn configuration(extra: serde_json::Value) -> Configuration {
+		let mut configuration = serde_json::json!({
+			"scriptName": "fumo",
+			"scriptId": "abc123",
+			"whitelist": [],
+			"isPublic": false,
+		});
+		configuration
+			.as_object_mut()
+			.unwrap()
+			.extend(extra.as_object().unwrap().clone());
+		serde_json::from_value(configuration).unwrap()
+	}
+
+	async fn problems(
+		project_directory: &Path,
+		configuration: &Configuration,
+		source: &str,
+		uploaded: &[&str],
+	) -> Vec<String> {
+		check(
+			project_directory,
+			configuration,
+			[("init.server.luau".to_owned(), source)],
+			uploaded,
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn reports_aliased_modules() {
+		let directory = tempfile::tempdir().unwrap();
+		let configuration = configuration(serde_json::json!({
+			"moduleAliases": { "secrets_local": "secrets", "ui/button": "button" },
+		}));
+
+		let found = problems(
+			directory.path(),
+			&configuration,
+			"requireM(\"secrets_local\") requireM(\"secrets\")",
+			&["secrets", "button"],
+		)
+		.await;
+		assert_eq!(
+			found,
+			[
+				"requireM(\"secrets_local\") in init.server.luau breaks once pushed: pkg/secrets_local.luau is uploaded as \"secrets\" by its alias in fumosync.json"
+			]
+		);
+
+		let found = problems(
+			directory.path(),
+			&configuration,
+			"requireM(\"ui/button\")",
+			&["secrets", "button"],
+		)
+		.await;
+		assert_eq!(found.len(), 1);
+		assert!(found[0].contains("pkg/ui/button.luau is uploaded as \"button\""));
+	}
+
+	#[tokio::test]
+	async fn reports_skipped_assets() {
+		let directory = tempfile::tempdir().unwrap();
+		let package_directory = directory.path().join(PACKAGE_DIRECTORY);
+		std::fs::create_dir_all(package_directory.join("ui")).unwrap();
+		std::fs::write(package_directory.join("data.json"), "{}").unwrap();
+		std::fs::write(package_directory.join("ui/icon.png"), "").unwrap();
+		let configuration = configuration(serde_json::json!({ "assetExtensions": [".json", "png"] }));
+
+		let found = problems(
+			directory.path(),
+			&configuration,
+			"requireM(\"data\") requireM(\"ui/icon\")",
+			&[],
+		)
+		.await;
+		assert_eq!(
+			found,
+			[
+				"requireM(\"data\") in init.server.luau breaks once pushed: pkg/data.json is skipped as an asset by \"assetExtensions\"",
+				"requireM(\"ui/icon\") in init.server.luau breaks once pushed: pkg/ui/icon.png is skipped as an asset by \"assetExtensions\"",
+			]
+		);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn reports_skipped_symlinks() {
+		let directory = tempfile::tempdir().unwrap();
+		let package_directory = directory.path().join(PACKAGE_DIRECTORY);
+		std::fs::create_dir_all(&package_directory).unwrap();
+		std::fs::write(directory.path().join("shared.luau"), "return {}").unwrap();
+		std::os::unix::fs::symlink(
+			directory.path().join("shared.luau"),
+			package_directory.join("shared.luau"),
+		)
+		.unwrap();
+
+		let source = "requireM(\"shared\")";
+		let followed = configuration(serde_json::json!({}));
+		assert!(
+			problems(directory.path(), &followed, source, &["shared"])
+				.await
+				.is_empty()
+		);
+
+		let skipped = configuration(serde_json::json!({ "followSymlinks": false }));
+		let found = problems(directory.path(), &skipped, source, &[]).await;
+		assert_eq!(found.len(), 1);
+		assert!(found[0].contains("pkg/shared.luau is a symlink"));
+	}
+
+	#[tokio::test]
+	async fn accepts_uploaded_and_unknown_modules() {
+		let directory = tempfile::tempdir().unwrap();
+		let package_directory = directory.path().join(PACKAGE_DIRECTORY);
+		std::fs::create_dir_all(package_directory.join("ui")).unwrap();
+		std::fs::write(package_directory.join("ui/button.luau"), "return {}").unwrap();
+
+		let found = problems(
+			directory.path(),
+			&configuration(serde_json::json!({})),
+			"requireM(\"ui/button\") requireM(\"only_on_fumosclub\")",
+			&["ui/button"],
+		)
+		.await;
+		assert!(found.is_empty());
+	}
+}
@@ -0,0 +1,165 @@
+use crate::{
+	client::{ensure_reachable, get_user_agent},
+	error::Error,
+	settings,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub const USERS_API_URL: &str = "https://users.roblox.com";
+pub const THUMBNAILS_API_URL: &str = "https://thumbnails.roblox.com";
+/// Most ids the users and thumbnails APIs accept in a single request
+pub const MAX_BATCH_SIZE: usize = 100;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+	pub id: u64,
+	pub name: String,
+	pub display_name: String,
+}
+
+#[derive(Deserialize)]
+struct Users<T> {
+	data: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Thumbnail {
+	target_id: u64,
+	image_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestedUser {
+	requested_username: String,
+	#[serde(flatten)]
+	user: User,
+}
+
+/// Unauthenticated client for the public Roblox users API.
+pub struct RobloxClient {
+	client: reqwest::Client,
+}
+
+impl RobloxClient {
+	/// # Errors
+	/// - [`Error::Offline`] if `FUMO_OFFLINE=1`
+	/// - [`Error::Reqwest`]
+	pub fn new() -> Result<Self, Error> {
+		ensure_reachable(USERS_API_URL)?;
+		ensure_reachable(THUMBNAILS_API_URL)?;
+
+		Ok(Self {
+			client: reqwest::Client::builder()
+				.user_agent(get_user_agent())
+				.https_only(true)
+				.timeout(settings::get().timeout)
+				.build()?,
+		})
+	}
+
+	/// Gets the users with the given ids; ids which don't exist are left out.
+	///
+	/// # Errors
+	/// - [`Error::Reqwest`]
+	/// - [`Error::Serde`]
+	pub async fn get_users(&self, ids: &[u64]) -> Result<Vec<User>, Error> {
+		if ids.is_empty() {
+			return Ok(Vec::new());
+		}
+
+		let users: Users<User> = serde_json::from_slice(
+			&self
+				.client
+				.post(format!("{USERS_API_URL}/v1/users"))
+				.header("Content-Type", "application/json")
+				.body(serde_json::to_string(&json!({
+					"userIds": ids,
+					"excludeBannedUsers": false
+				}))?)
+				.send()
+				.await?
+				.error_for_status()?
+				.bytes()
+				.await?,
+		)?;
+
+		Ok(users.data)
+	}
+
+	/// Gets headshot urls of the users with the given ids, paired with their ids; users without a
+	/// headshot, such as deleted ones, are left out.
+	///
+	/// # Errors
+	/// - [`Error::Reqwest`]
+	/// - [`Error::Serde`]
+	pub async fn get_avatar_urls(&self, ids: &[u64]) -> Result<Vec<(u64, String)>, Error> {
+		if ids.is_empty() {
+			return Ok(Vec::new());
+		}
+
+		let ids: Vec<String> = ids.iter().map(u64::to_string).collect();
+		let thumbnails: Users<Thumbnail> = serde_json::from_slice(
+			&self
+				.client
+				.get(format!("{THUMBNAILS_API_URL}/v1/users/avatar-headshot"))
+				.query(&[
+					("userIds", ids.join(",").as_str()),
+					("size", "150x150"),
+					("format", "Png"),
+				])
+				.send()
+				.await?
+				.error_for_status()?
+				.bytes()
+				.await?,
+		)?;
+
+		Ok(
+			thumbnails
+				.data
+				.into_iter()
+				.filter_map(|thumbnail| Some((thumbnail.target_id, thumbnail.image_url?)))
+				.collect(),
+		)
+	}
+
+	/// Gets the users with the given usernames, paired with the username which was asked for;
+	/// usernames which don't exist are left out.
+	///
+	/// # Errors
+	/// - [`Error::Reqwest`]
+	/// - [`Error::Serde`]
+	pub async fn get_users_by_name(&self, usernames: &[&str]) -> Result<Vec<(String, User)>, Error> {
+		if usernames.is_empty() {
+			return Ok(Vec::new());
+		}
+
+		let users: Users<RequestedUser> = serde_json::from_slice(
+			&self
+				.client
+				.post(format!("{USERS_API_URL}/v1/usernames/users"))
+				.header("Content-Type", "application/json")
+				.body(serde_json::to_string(&json!({
+					"usernames": usernames,
+					"excludeBannedUsers": false
+				}))?)
+				.send()
+				.await?
+				.error_for_status()?
+				.bytes()
+				.await?,
+		)?;
+
+		Ok(
+			users
+				.data
+				.into_iter()
+				.map(|requested| (requested.requested_username, requested.user))
+				.collect(),
+		)
+	}
+}
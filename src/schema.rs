@@ -0,0 +1,117 @@
+use crate::{
+	error::Error,
+	project::{LOCAL_STATE_DIRECTORY, write_file},
+};
+use std::path::{Path, PathBuf};
+
+/// JSON Schema of fumosync.json; fields of [`Configuration`](crate::project::Configuration) must
+/// be added to it as they land
+pub const SCHEMA: &str = include_str!("../assets/fumosync.schema.json");
+/// Copy of the schema next to fumosync.json, committed with the project so clones have it
+pub const SCHEMA_FILE: &str = "fumosync.schema.json";
+/// Name of the copy older versions kept in the local state directory, which isn't committed
+const LEGACY_SCHEMA_FILE: &str = "schema.json";
+
+/// Gets the `$schema` value referencing the copy, relative to the project directory.
+pub fn reference() -> String {
+	format!("./{SCHEMA_FILE}")
+}
+
+/// Whether a `$schema` value references the copy older versions kept in .fumo/.
+pub fn is_legacy_reference(reference: &str) -> bool {
+	reference == format!("./{LOCAL_STATE_DIRECTORY}/{LEGACY_SCHEMA_FILE}")
+}
+
+/// Writes the schema next to the project's configuration.
+pub async fn write(project_directory: &Path) -> Result<PathBuf, Error> {
+	let path = project_directory.join(SCHEMA_FILE);
+	write_file(&path, SCHEMA).await?;
+	Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::project::{Configuration, EditorSetup, Metadata, ProjectType, WatchEvent};
+	use crate::whitelist::WhitelistMerge;
+	use std::collections::{BTreeMap, BTreeSet};
+
+	fn schema() -> serde_json::Value {
+		serde_json::from_str(SCHEMA).unwrap()
+	}
+
+	#[test]
+	fn schema_documents_every_configuration_field() {
+		// no `..Default::default()`, so new fields have to be added here, and to the schema
+		let configuration = Configuration {
+			schema: Some(reference()),
+			script_name: "fumo".to_owned(),
+			script_id: "abc123".to_owned(),
+			whitelist: vec!["friend".to_owned()],
+			is_public: false,
+			project_type: ProjectType::Script,
+			debounce: Some(50),
+			module_aliases: BTreeMap::from([("local".to_owned(), "remote".to_owned())]),
+			editor: Some(EditorSetup::Vscode),
+			follow_symlinks: Some(true),
+			sync_metadata: Some(true),
+			suppress_name_check: Some(false),
+			allow_empty_files: Some(false),
+			asset_extensions: vec!["png".to_owned()],
+			whitelist_merge: Some(WhitelistMerge::Union),
+			arguments: vec![serde_json::json!(1)],
+			inject_arguments: Some(true),
+			watch_events: vec![WatchEvent::Metadata],
+			meta: Some(Metadata {
+				synced_at: chrono::Utc::now(),
+				creator: None,
+				fumo_version: "0.0.0".to_owned(),
+				script_type: ProjectType::Script,
+			}),
+		};
+		let serialized = serde_json::to_value(&configuration).unwrap();
+		let fields: BTreeSet<&str> = serialized
+			.as_object()
+			.unwrap()
+			.keys()
+			.map(String::as_str)
+			.collect();
+
+		let schema = schema();
+		let properties: BTreeSet<&str> = schema["properties"]
+			.as_object()
+			.unwrap()
+			.keys()
+			.map(String::as_str)
+			.collect();
+		assert_eq!(fields, properties);
+	}
+
+	#[test]
+	fn schema_requires_the_fields_without_defaults() {
+		let minimal = serde_json::json!({
+			"scriptName": "fumo",
+			"scriptId": "abc123",
+			"whitelist": [],
+			"isPublic": false,
+		});
+		assert!(serde_json::from_value::<Configuration>(minimal.clone()).is_ok());
+
+		let schema = schema();
+		let required: Vec<&str> = schema["required"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.map(|field| field.as_str().unwrap())
+			.collect();
+		assert_eq!(required.len(), minimal.as_object().unwrap().len());
+		for field in required {
+			let mut missing = minimal.clone();
+			assert!(missing.as_object_mut().unwrap().remove(field).is_some());
+			assert!(
+				serde_json::from_value::<Configuration>(missing).is_err(),
+				"{field} is required by the schema, but not by Configuration"
+			);
+		}
+	}
+}
@@ -0,0 +1,496 @@
+use crate::{
+	client::BASE_URL,
+	error::Error,
+	limits::{
+		DEFAULT_DESCRIPTION_WARN_LENGTH, DEFAULT_MAIN_SCRIPT_WARN_BYTES,
+		DEFAULT_MAIN_SCRIPT_WARN_LINES, DEFAULT_MAX_DESCRIPTION_LENGTH, DEFAULT_MAX_FILE_SIZE,
+		DEFAULT_MAX_LINE_LENGTH, DEFAULT_MAX_TOTAL_SIZE,
+	},
+	login::get_config_directory,
+	output::ColorChoice,
+	project::{Configuration, read_file, write_file},
+};
+use chrono::TimeDelta;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{io::IsTerminal, path::PathBuf, sync::OnceLock, time::Duration};
+use tracing::warn;
+
+pub const GLOBAL_CONFIGURATION_FILE: &str = "config.toml";
+/// Keys understood by this version of fumo; other keys only produce warnings
+pub const GLOBAL_CONFIGURATION_KEYS: &[&str] = &[
+	"autoRefresh",
+	"expiryWarningDays",
+	"clockSkewHours",
+	"quiet",
+	"color",
+	"baseUrl",
+	"timeout",
+	"debounce",
+	"maxFileSize",
+	"maxTotalSize",
+	"descriptionWarnLength",
+	"maxDescriptionLength",
+	"scriptListTtl",
+	"compressUploads",
+	"projectRegistry",
+	"maxLineLength",
+	"mainScriptWarnBytes",
+	"mainScriptWarnLines",
+	"sessionKeepaliveMinutes",
+	"retries",
+	"defaultProfile",
+	"confirmPublicPush",
+	"notifications",
+];
+
+pub const DEFAULT_EXPIRY_WARNING_DAYS: u32 = 7;
+pub const DEFAULT_CLOCK_SKEW_HOURS: u32 = 6;
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+pub const DEFAULT_DEBOUNCE_MILLISECONDS: u64 = 2000;
+pub const DEFAULT_SCRIPT_LIST_TTL_SECONDS: u64 = 60;
+pub const DEFAULT_SESSION_KEEPALIVE_MINUTES: u64 = 30;
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// config.toml
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GlobalConfiguration {
+	pub auto_refresh: Option<bool>,
+	/// Warn about expiring secrets this many days in advance
+	pub expiry_warning_days: Option<u32>,
+	/// Secrets which expired less than this many hours ago are still checked with fumosclub
+	pub clock_skew_hours: Option<u32>,
+	pub quiet: Option<bool>,
+	pub color: Option<ColorChoice>,
+	pub base_url: Option<String>,
+	/// Request timeout in seconds
+	pub timeout: Option<u64>,
+	/// Watcher debounce in milliseconds
+	pub debounce: Option<u64>,
+	/// Largest file, in bytes, which is uploaded
+	pub max_file_size: Option<u64>,
+	/// Largest combined size, in bytes, of the files uploaded by a push
+	pub max_total_size: Option<u64>,
+	/// Descriptions longer than this many characters produce warnings
+	pub description_warn_length: Option<usize>,
+	/// Longest description, in characters, which is uploaded
+	pub max_description_length: Option<usize>,
+	/// Seconds the cached script list is used for before asking fumosclub again
+	pub script_list_ttl: Option<u64>,
+	/// Minutes between session checks of `watch --session-keepalive`
+	pub session_keepalive_minutes: Option<u64>,
+	/// Whether large pushes are gzipped
+	pub compress_uploads: Option<bool>,
+	/// Whether pushes warn about scripts last pushed by another project
+	pub project_registry: Option<bool>,
+	/// Lines longer than this many characters produce warnings
+	pub max_line_length: Option<usize>,
+	/// Main scripts over this many bytes produce warnings
+	pub main_script_warn_bytes: Option<usize>,
+	/// Main scripts over this many lines produce warnings
+	pub main_script_warn_lines: Option<usize>,
+	/// Times requests are retried after connection failures and outages
+	pub retries: Option<u32>,
+	/// Profile whose saved login is used when --profile isn't given
+	pub default_profile: Option<String>,
+	/// Whether pushes of public scripts are confirmed first
+	pub confirm_public_push: Option<bool>,
+	/// Whether the watcher rings the terminal bell when a sync fails
+	pub notifications: Option<bool>,
+}
+
+fn get_global_configuration_path() -> Result<PathBuf, Error> {
+	Ok(get_config_directory()?.join(GLOBAL_CONFIGURATION_FILE))
+}
+
+async fn read_global_configuration_table() -> Result<toml::Table, Error> {
+	let path = get_global_configuration_path()?;
+	if !path.try_exists()? {
+		return Ok(toml::Table::new());
+	}
+
+	toml::from_str(&read_file(&path).await?)
+		.map_err(|error| Error::GlobalConfiguration(path, error.to_string()))
+}
+
+/// Reads the global configuration, returning the defaults if the file doesn't exist.
+///
+/// Unknown keys are returned alongside the configuration so they can be warned about.
+pub async fn read_global_configuration() -> Result<(GlobalConfiguration, Vec<String>), Error> {
+	let table = read_global_configuration_table().await?;
+	let unknown_keys = table
+		.keys()
+		.filter(|key| !GLOBAL_CONFIGURATION_KEYS.contains(&key.as_str()))
+		.cloned()
+		.collect();
+
+	let configuration = toml::Value::Table(table).try_into().map_err(|error| {
+		Error::GlobalConfiguration(
+			get_global_configuration_path().unwrap_or_default(),
+			error.to_string(),
+		)
+	})?;
+
+	Ok((configuration, unknown_keys))
+}
+
+/// Gets a value from the global configuration, if it is set.
+pub async fn get_global_value(key: &str) -> Result<Option<String>, Error> {
+	Ok(
+		read_global_configuration_table()
+			.await?
+			.get(key)
+			.map(|value| match value {
+				toml::Value::String(string) => string.clone(),
+				value => value.to_string(),
+			}),
+	)
+}
+
+/// Sets a value in the global configuration, preserving the rest of the file.
+///
+/// Values which aren't valid TOML (such as `auto`) are stored as strings.
+pub async fn set_global_value(key: &str, value: &str) -> Result<(), Error> {
+	if !GLOBAL_CONFIGURATION_KEYS.contains(&key) {
+		warn!("{key} isn't a configuration key known to this version of fumo");
+	}
+
+	let path = get_global_configuration_path()?;
+	let contents = if path.try_exists()? {
+		read_file(&path).await?
+	} else {
+		String::new()
+	};
+
+	let mut document: toml_edit::DocumentMut =
+		contents.parse().map_err(|error: toml_edit::TomlError| {
+			Error::GlobalConfiguration(path.clone(), error.to_string())
+		})?;
+	document[key] = toml_edit::value(
+		value
+			.parse::<toml_edit::Value>()
+			.unwrap_or_else(|_| value.into()),
+	);
+
+	let contents = document.to_string();
+	// refuse to write a file which can't be read back
+	toml::from_str::<GlobalConfiguration>(&contents)
+		.map_err(|error| Error::GlobalConfiguration(path.clone(), error.to_string()))?;
+
+	write_file(&path, &contents).await
+}
+
+/// Format of data written to stdout.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+	/// Human readable text
+	#[default]
+	Text,
+	/// A single JSON document per invocation; errors are written to stdout as `{"error": {...}}`
+	Json,
+}
+
+/// Values given on the command line or through environment variables; `None` when absent.
+#[derive(Default, Clone, Debug)]
+pub struct Overrides {
+	pub auto_refresh: Option<bool>,
+	pub quiet: Option<bool>,
+	pub color: Option<ColorChoice>,
+	pub format: Option<OutputFormat>,
+	pub base_url: Option<String>,
+	pub timeout: Option<u64>,
+	pub debounce: Option<u64>,
+	pub non_interactive: Option<bool>,
+	pub session: Option<String>,
+	pub session_file: Option<PathBuf>,
+	pub user_agent_suffix: Option<String>,
+	pub allow_config_overlap: Option<bool>,
+	pub session_refresh: Option<bool>,
+	pub retries: Option<u32>,
+	pub profile: Option<String>,
+}
+
+/// Settings for the current invocation.
+#[derive(Clone, Debug)]
+pub struct Settings {
+	/// Whether expired secrets should be refreshed from browser cookies
+	pub auto_refresh: bool,
+	/// How long before expiry secrets start producing warnings
+	pub expiry_warning: TimeDelta,
+	/// How far the local clock may be ahead before expired secrets are refused without asking fumosclub
+	pub clock_skew: TimeDelta,
+	pub format: OutputFormat,
+	/// Only warnings and errors should be logged
+	pub quiet: bool,
+	pub color: ColorChoice,
+	pub base_url: String,
+	pub timeout: Duration,
+	pub debounce: Duration,
+	/// Largest file, in bytes, which is read for uploading
+	pub max_file_size: u64,
+	/// Largest combined size, in bytes, of the files uploaded by a push
+	pub max_total_size: u64,
+	/// Descriptions longer than this many characters produce warnings
+	pub description_warn_length: usize,
+	/// Longest description, in characters, which is uploaded
+	pub max_description_length: usize,
+	/// Lines longer than this many characters produce warnings
+	pub max_line_length: usize,
+	pub main_script_warn_bytes: usize,
+	pub main_script_warn_lines: usize,
+	/// How long the cached script list is used for before asking fumosclub again
+	pub script_list_ttl: Duration,
+	/// Time between session checks of `watch --session-keepalive`
+	pub session_keepalive: Duration,
+	/// Whether large pushes are gzipped
+	pub compress_uploads: bool,
+	/// Whether pushes warn about scripts last pushed by another project
+	pub project_registry: bool,
+	/// Whether prompts may be shown; when false, commands use safe defaults or fail with the flag to pass
+	pub interactive: bool,
+	/// Session token given for this invocation only
+	pub session: Option<String>,
+	/// Secrets file read instead of the saved login; never written to
+	pub session_file: Option<PathBuf>,
+	/// Appended to the user agent, to tag automated traffic
+	pub user_agent_suffix: Option<String>,
+	/// Whether projects may overlap the config directory
+	pub allow_config_overlap: bool,
+	/// Whether sessions rotated by fumosclub are saved into secrets.json
+	pub session_refresh: bool,
+	/// Times requests are retried after connection failures and outages
+	pub retries: u32,
+	/// Profile whose saved login is used, instead of the default one
+	pub profile: Option<String>,
+	/// Whether pushes of public scripts are confirmed first
+	pub confirm_public_push: bool,
+	/// Whether the watcher rings the terminal bell when a sync fails
+	pub notifications: bool,
+}
+
+impl Settings {
+	/// Resolves settings with the precedence: command line > environment variables (both in
+	/// `overrides`) > project fumosync.json > global config.toml > built-in defaults.
+	///
+	/// Interactivity is also disabled when stdin isn't a terminal.
+	pub fn resolve(
+		overrides: &Overrides,
+		project: Option<&Configuration>,
+		global: &GlobalConfiguration,
+	) -> Self {
+		let project_debounce = project.and_then(|configuration| configuration.debounce);
+
+		Self {
+			auto_refresh: overrides
+				.auto_refresh
+				.or(global.auto_refresh)
+				.unwrap_or(false),
+			expiry_warning: TimeDelta::days(
+				global
+					.expiry_warning_days
+					.unwrap_or(DEFAULT_EXPIRY_WARNING_DAYS)
+					.into(),
+			),
+			clock_skew: TimeDelta::hours(
+				global
+					.clock_skew_hours
+					.unwrap_or(DEFAULT_CLOCK_SKEW_HOURS)
+					.into(),
+			),
+			format: overrides.format.unwrap_or_default(),
+			quiet: overrides.quiet.or(global.quiet).unwrap_or(false),
+			color: overrides.color.or(global.color).unwrap_or_default(),
+			base_url: overrides
+				.base_url
+				.clone()
+				.or_else(|| global.base_url.clone())
+				.unwrap_or_else(|| BASE_URL.to_owned()),
+			timeout: Duration::from_secs(
+				overrides
+					.timeout
+					.or(global.timeout)
+					.unwrap_or(DEFAULT_TIMEOUT_SECONDS),
+			),
+			debounce: Duration::from_millis(
+				overrides
+					.debounce
+					.or(project_debounce)
+					.or(global.debounce)
+					.unwrap_or(DEFAULT_DEBOUNCE_MILLISECONDS),
+			),
+			max_file_size: global.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE),
+			max_total_size: global.max_total_size.unwrap_or(DEFAULT_MAX_TOTAL_SIZE),
+			description_warn_length: global
+				.description_warn_length
+				.unwrap_or(DEFAULT_DESCRIPTION_WARN_LENGTH),
+			max_description_length: global
+				.max_description_length
+				.unwrap_or(DEFAULT_MAX_DESCRIPTION_LENGTH),
+			max_line_length: global.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH),
+			main_script_warn_bytes: global
+				.main_script_warn_bytes
+				.unwrap_or(DEFAULT_MAIN_SCRIPT_WARN_BYTES),
+			main_script_warn_lines: global
+				.main_script_warn_lines
+				.unwrap_or(DEFAULT_MAIN_SCRIPT_WARN_LINES),
+			script_list_ttl: Duration::from_secs(
+				global
+					.script_list_ttl
+					.unwrap_or(DEFAULT_SCRIPT_LIST_TTL_SECONDS),
+			),
+			session_keepalive: Duration::from_secs(
+				global
+					.session_keepalive_minutes
+					.unwrap_or(DEFAULT_SESSION_KEEPALIVE_MINUTES)
+					* 60,
+			),
+			compress_uploads: global.compress_uploads.unwrap_or(true),
+			project_registry: global.project_registry.unwrap_or(true),
+			interactive: !overrides.non_interactive.unwrap_or(false) && std::io::stdin().is_terminal(),
+			session: overrides.session.clone(),
+			session_file: overrides.session_file.clone(),
+			user_agent_suffix: overrides.user_agent_suffix.clone(),
+			allow_config_overlap: overrides.allow_config_overlap.unwrap_or(false),
+			session_refresh: overrides.session_refresh.unwrap_or(true),
+			retries: overrides
+				.retries
+				.or(global.retries)
+				.unwrap_or(DEFAULT_RETRIES),
+			profile: overrides
+				.profile
+				.clone()
+				.or_else(|| global.default_profile.clone()),
+			confirm_public_push: global.confirm_public_push.unwrap_or(false),
+			notifications: global.notifications.unwrap_or(false),
+		}
+	}
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self::resolve(&Overrides::default(), None, &GlobalConfiguration::default())
+	}
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Installs the settings for the current invocation; only the first call has any effect.
+pub fn install(settings: Settings) {
+	SETTINGS.get_or_init(|| settings);
+}
+
+/// Gets the settings for the current invocation, falling back to the defaults if none were installed.
+pub fn get() -> &'static Settings {
+	SETTINGS.get_or_init(Settings::default)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn falls_back_to_defaults() {
+		let settings = Settings::default();
+		assert!(!settings.auto_refresh);
+		assert!(!settings.quiet);
+		assert_eq!(settings.base_url, BASE_URL);
+		assert_eq!(
+			settings.timeout,
+			Duration::from_secs(DEFAULT_TIMEOUT_SECONDS)
+		);
+		assert_eq!(
+			settings.debounce,
+			Duration::from_millis(DEFAULT_DEBOUNCE_MILLISECONDS)
+		);
+		assert_eq!(settings.retries, DEFAULT_RETRIES);
+		assert_eq!(settings.profile, None);
+		assert!(settings.session_refresh);
+	}
+
+	#[test]
+	fn global_configuration_beats_defaults() {
+		let global = GlobalConfiguration {
+			timeout: Some(60),
+			debounce: Some(500),
+			retries: Some(0),
+			default_profile: Some("work".to_owned()),
+			confirm_public_push: Some(true),
+			..GlobalConfiguration::default()
+		};
+		let settings = Settings::resolve(&Overrides::default(), None, &global);
+		assert_eq!(settings.timeout, Duration::from_secs(60));
+		assert_eq!(settings.debounce, Duration::from_millis(500));
+		assert_eq!(settings.retries, 0);
+		assert_eq!(settings.profile.as_deref(), Some("work"));
+		assert!(settings.confirm_public_push);
+	}
+
+	#[test]
+	fn project_configuration_beats_global_configuration() {
+		let global = GlobalConfiguration {
+			debounce: Some(500),
+			..GlobalConfiguration::default()
+		};
+		let project = Configuration {
+			debounce: Some(250),
+			..Configuration::default()
+		};
+		let settings = Settings::resolve(&Overrides::default(), Some(&project), &global);
+		assert_eq!(settings.debounce, Duration::from_millis(250));
+	}
+
+	#[test]
+	fn overrides_beat_everything() {
+		let global = GlobalConfiguration {
+			timeout: Some(60),
+			debounce: Some(500),
+			base_url: Some("https://global.example".to_owned()),
+			default_profile: Some("work".to_owned()),
+			..GlobalConfiguration::default()
+		};
+		let project = Configuration {
+			debounce: Some(250),
+			..Configuration::default()
+		};
+		let overrides = Overrides {
+			timeout: Some(5),
+			debounce: Some(100),
+			base_url: Some("http://localhost:8080".to_owned()),
+			profile: Some("personal".to_owned()),
+			..Overrides::default()
+		};
+		let settings = Settings::resolve(&overrides, Some(&project), &global);
+		assert_eq!(settings.timeout, Duration::from_secs(5));
+		assert_eq!(settings.debounce, Duration::from_millis(100));
+		assert_eq!(settings.base_url, "http://localhost:8080");
+		assert_eq!(settings.profile.as_deref(), Some("personal"));
+	}
+
+	#[test]
+	fn overrides_can_turn_off_globally_enabled_flags() {
+		let global = GlobalConfiguration {
+			auto_refresh: Some(true),
+			quiet: Some(true),
+			..GlobalConfiguration::default()
+		};
+		let overrides = Overrides {
+			auto_refresh: Some(false),
+			quiet: Some(false),
+			..Overrides::default()
+		};
+		let settings = Settings::resolve(&overrides, None, &global);
+		assert!(!settings.auto_refresh);
+		assert!(!settings.quiet);
+
+		let settings = Settings::resolve(&Overrides::default(), None, &global);
+		assert!(settings.auto_refresh);
+		assert!(settings.quiet);
+	}
+
+	#[test]
+	fn tolerates_unknown_global_keys() {
+		let global: GlobalConfiguration = toml::from_str("timeout = 5\nfromANewerFumo = true").unwrap();
+		assert_eq!(global.timeout, Some(5));
+	}
+}
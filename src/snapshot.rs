@@ -0,0 +1,213 @@
+use crate::{
+	client::Client,
+	error::Error,
+	login::get_session_secrets,
+	project::{
+		Configuration, DESCRIPTION_FILE, LOCAL_STATE_DIRECTORY, MAIN_SCRIPT_FILE, PACKAGE_DIRECTORY,
+		ProjectType, PushOptions, SYNC_CONFIGURATION_FILE, push_project, read_configuration,
+		serialize_configuration, write_file,
+	},
+	state::{self, StateFile},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+	path::{Component, Path, PathBuf},
+	time::Duration,
+};
+use tracing::{info, warn};
+
+/// Directory inside of the local state directory holding snapshots
+pub const SNAPSHOTS_DIRECTORY: &str = "snapshots";
+/// Names snapshot directories; sorts in the order snapshots were taken
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// How often `watch` snapshots the remote script, and how many snapshots are kept.
+#[derive(Clone, Copy, Debug)]
+pub struct SnapshotSchedule {
+	pub interval: Duration,
+	pub keep: usize,
+}
+
+/// snapshots/schedule.json; lets restarted watchers keep to the snapshot interval
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleState {
+	last_taken_at: Option<DateTime<Utc>>,
+}
+
+impl StateFile for ScheduleState {
+	const PATH: &'static str = "snapshots/schedule.json";
+	const VERSION: u32 = 1;
+}
+
+/// Gets how long until the next scheduled snapshot is due; immediately without a previous one.
+pub async fn next_due(project_directory: &Path, schedule: SnapshotSchedule) -> Duration {
+	let state: ScheduleState = state::read(project_directory).await;
+	state
+		.last_taken_at
+		.and_then(|last_taken_at| (Utc::now() - last_taken_at).to_std().ok())
+		.and_then(|elapsed| schedule.interval.checked_sub(elapsed))
+		.unwrap_or_default()
+}
+
+fn get_snapshots_directory(project_directory: &Path) -> PathBuf {
+	project_directory
+		.join(LOCAL_STATE_DIRECTORY)
+		.join(SNAPSHOTS_DIRECTORY)
+}
+
+async fn create_directory_all(path: &Path) -> Result<(), Error> {
+	tokio::fs::create_dir_all(path)
+		.await
+		.map_err(|io_error| Error::CreateDirectory(path.to_path_buf(), io_error))
+}
+
+/// Writes the remote state of the project's script into a new snapshot, laid out like a pulled
+/// project; returns the snapshot's timestamp.
+pub async fn take(project_directory: &Path, client: &Client) -> Result<String, Error> {
+	let configuration = read_configuration(project_directory).await?;
+	let script_info = client
+		.get_editor(&configuration.linked_script_id()?)
+		.await?
+		.script_info;
+
+	let timestamp = Utc::now().format(TIMESTAMP_FORMAT).to_string();
+	let directory = get_snapshots_directory(project_directory).join(&timestamp);
+	let package_directory = directory.join(PACKAGE_DIRECTORY);
+	create_directory_all(&package_directory).await?;
+
+	let project_type = ProjectType::from(&script_info.script_type);
+	let path = directory.join(SYNC_CONFIGURATION_FILE);
+	write_file(
+		&path,
+		&serialize_configuration(
+			&path,
+			&Configuration {
+				script_name: script_info.name,
+				script_id: configuration.script_id,
+				whitelist: script_info.whitelist,
+				is_public: script_info.is_public,
+				project_type,
+				..Configuration::default()
+			},
+		)?,
+	)
+	.await?;
+	write_file(directory.join(DESCRIPTION_FILE), &script_info.description).await?;
+	if project_type == ProjectType::Script {
+		write_file(directory.join(MAIN_SCRIPT_FILE), &script_info.source.main).await?;
+	}
+
+	for (name, source) in script_info.source.modules {
+		let relative_path = PathBuf::from(format!("{name}.luau"));
+		if !relative_path
+			.components()
+			.all(|component| matches!(component, Component::Normal(..)))
+		{
+			warn!(
+				"leaving module {name:?} out of the snapshot; it can't be written inside of {PACKAGE_DIRECTORY}"
+			);
+			continue;
+		}
+
+		let path = package_directory.join(relative_path);
+		if let Some(parent) = path.parent() {
+			create_directory_all(parent).await?;
+		}
+		write_file(path, &source).await?;
+	}
+
+	Ok(timestamp)
+}
+
+/// Lists the timestamps of the project's snapshots, oldest first.
+pub async fn list(project_directory: &Path) -> Result<Vec<String>, Error> {
+	let directory = get_snapshots_directory(project_directory);
+	if !directory.try_exists()? {
+		return Ok(Vec::new());
+	}
+
+	let mut stream = tokio::fs::read_dir(&directory)
+		.await
+		.map_err(|io_error| Error::ReadDirectory(directory.clone(), io_error))?;
+	let mut timestamps = Vec::new();
+	while let Some(entry) = stream.next_entry().await? {
+		if entry.file_type().await?.is_dir() {
+			timestamps.push(entry.file_name().to_string_lossy().to_string());
+		}
+	}
+
+	timestamps.sort();
+	Ok(timestamps)
+}
+
+/// Removes all but the `keep` most recent snapshots.
+pub async fn prune(project_directory: &Path, keep: usize) -> Result<(), Error> {
+	let timestamps = list(project_directory).await?;
+	let excess = timestamps.len().saturating_sub(keep);
+	for timestamp in &timestamps[..excess] {
+		let path = get_snapshots_directory(project_directory).join(timestamp);
+		tokio::fs::remove_dir_all(&path).await?;
+	}
+
+	Ok(())
+}
+
+/// Takes a snapshot and prunes old ones, logging instead of failing so watching carries on.
+///
+/// The client is built on the first snapshot and kept for the next ones, so a watcher doesn't
+/// read the secrets and connect again every interval.
+pub async fn take_scheduled(
+	project_directory: &Path,
+	client: &mut Option<Client>,
+	schedule: SnapshotSchedule,
+) {
+	let result = async {
+		let client = match client {
+			Some(client) => client,
+			None => client.insert(Client::new(get_session_secrets().await?)?),
+		};
+		let timestamp = take(project_directory, client).await?;
+		prune(project_directory, schedule.keep).await?;
+		state::write(
+			project_directory,
+			&ScheduleState {
+				last_taken_at: Some(Utc::now()),
+			},
+		)
+		.await?;
+		Ok::<_, Error>(timestamp)
+	};
+
+	match result.await {
+		Ok(timestamp) => info!("took snapshot {timestamp}"),
+		Err(error) => warn!("failed taking a snapshot: {error}"),
+	}
+}
+
+/// Pushes a snapshot back to fumosclub.
+///
+/// # Errors
+/// - [`Error::UnknownSnapshot`]
+pub async fn restore(project_directory: &Path, timestamp: &str) -> Result<(), Error> {
+	let timestamps = list(project_directory).await?;
+	if !timestamps.iter().any(|existing| existing == timestamp) {
+		return Err(Error::UnknownSnapshot(timestamp.to_owned(), timestamps));
+	}
+
+	// the snapshot is what the script was, empty files included
+	let client = Client::new(get_session_secrets().await?)?;
+	let options = PushOptions {
+		allow_empty: true,
+		registered_directory: Some(project_directory.to_path_buf()),
+		..PushOptions::default()
+	};
+	push_project(
+		&client,
+		&get_snapshots_directory(project_directory).join(timestamp),
+		options,
+	)
+	.await?;
+	Ok(())
+}
@@ -0,0 +1,193 @@
+use crate::{
+	error::Error,
+	project::{LOCAL_STATE_DIRECTORY, read_file, write_file},
+	snapshot::SNAPSHOTS_DIRECTORY,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Written to .gitignore files which don't ignore the local state directory yet
+const GITIGNORE_ENTRY: &str = ".fumo/";
+
+/// Directory inside of the local state directory holding data which can always be fetched again
+pub const CACHE_DIRECTORY: &str = "cache";
+/// Directory inside of the local state directory holding copies of overwritten files
+pub const BACKUPS_DIRECTORY: &str = "backups";
+
+/// A typed JSON file inside of the local state directory.
+pub trait StateFile: Serialize + DeserializeOwned + Default {
+	/// Path relative to the local state directory
+	const PATH: &'static str;
+	/// Bumped on incompatible changes; files of other versions are regenerated
+	const VERSION: u32;
+}
+
+#[derive(Serialize)]
+struct VersionedRef<'a, T> {
+	v: u32,
+	data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Versioned {
+	v: u32,
+	data: serde_json::Value,
+}
+
+pub fn get_state_directory(project_directory: &Path) -> PathBuf {
+	project_directory.join(LOCAL_STATE_DIRECTORY)
+}
+
+fn parse<T: StateFile>(contents: &str) -> Result<T, String> {
+	let versioned: Versioned = serde_json::from_str(contents).map_err(|error| error.to_string())?;
+	if versioned.v != T::VERSION {
+		return Err(format!("it is version {}, not {}", versioned.v, T::VERSION));
+	}
+
+	serde_json::from_value(versioned.data).map_err(|error| error.to_string())
+}
+
+/// Reads a state file, or its default if it doesn't exist.
+///
+/// State never fails a command: unreadable files are warned about, and moved aside to
+/// `<name>.corrupt` so they are regenerated.
+pub async fn read<T: StateFile>(project_directory: &Path) -> T {
+	let path = get_state_directory(project_directory).join(T::PATH);
+	let contents = match tokio::fs::read_to_string(&path).await {
+		Ok(contents) => contents,
+		Err(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => return T::default(),
+		Err(io_error) => {
+			warn!("ignoring {}: {io_error}", path.display());
+			return T::default();
+		}
+	};
+
+	match parse(&contents) {
+		Ok(state) => state,
+		Err(reason) => {
+			let mut aside = path.clone().into_os_string();
+			aside.push(".corrupt");
+			warn!(
+				"{} is unreadable ({reason}); moving it to {} and starting over",
+				path.display(),
+				Path::new(&aside).display()
+			);
+			if let Err(io_error) = tokio::fs::rename(&path, &aside).await {
+				warn!("failed moving {} aside: {io_error}", path.display());
+			}
+
+			T::default()
+		}
+	}
+}
+
+/// Writes a state file; like every [`write_file`], it is never left half written.
+pub async fn write<T: StateFile>(project_directory: &Path, state: &T) -> Result<(), Error> {
+	let path = get_state_directory(project_directory).join(T::PATH);
+	let parent = path.parent().unwrap_or(project_directory);
+	tokio::fs::create_dir_all(parent)
+		.await
+		.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error))?;
+
+	let contents = serde_json::to_string(&VersionedRef {
+		v: T::VERSION,
+		data: state,
+	})?;
+	write_file(path, &contents).await
+}
+
+/// Adds the local state directory to the project's .gitignore, unless it is already there.
+pub async fn ignore_in_git(project_directory: &Path) -> Result<(), Error> {
+	let path = project_directory.join(".gitignore");
+	let mut contents = if path.try_exists()? {
+		read_file(&path).await?
+	} else {
+		String::new()
+	};
+
+	let ignored = contents.lines().any(|line| {
+		matches!(
+			line.trim().trim_start_matches('/').trim_end_matches('/'),
+			LOCAL_STATE_DIRECTORY
+		)
+	});
+	if ignored {
+		return Ok(());
+	}
+
+	if !contents.is_empty() && !contents.ends_with('\n') {
+		contents.push('\n');
+	}
+	contents.push_str(GITIGNORE_ENTRY);
+	contents.push('\n');
+	write_file(path, &contents).await
+}
+
+/// Parts of the local state directory `fumo clean` deletes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateKind {
+	Cache,
+	Backups,
+	Snapshots,
+}
+
+impl StateKind {
+	pub const ALL: [Self; 3] = [Self::Cache, Self::Backups, Self::Snapshots];
+
+	fn directory(self) -> &'static str {
+		match self {
+			Self::Cache => CACHE_DIRECTORY,
+			Self::Backups => BACKUPS_DIRECTORY,
+			Self::Snapshots => SNAPSHOTS_DIRECTORY,
+		}
+	}
+}
+
+/// A directory deleted by [`clean`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Cleaned {
+	pub directory: PathBuf,
+	/// Total size of the files inside of it
+	pub bytes: u64,
+}
+
+/// Sums the sizes of every file below a directory, without following symlinks.
+async fn directory_size(root: &Path) -> Result<u64, Error> {
+	let mut bytes = 0;
+	let mut directories = vec![root.to_path_buf()];
+
+	while let Some(directory) = directories.pop() {
+		let mut stream = tokio::fs::read_dir(&directory)
+			.await
+			.map_err(|io_error| Error::ReadDirectory(directory.clone(), io_error))?;
+		while let Some(entry) = stream.next_entry().await? {
+			let metadata = entry.metadata().await?;
+			if metadata.is_dir() {
+				directories.push(entry.path());
+			} else {
+				bytes += metadata.len();
+			}
+		}
+	}
+
+	Ok(bytes)
+}
+
+/// Deletes parts of the project's local state directory; missing ones are left out of the result.
+pub async fn clean(project_directory: &Path, kinds: &[StateKind]) -> Result<Vec<Cleaned>, Error> {
+	let mut cleaned = Vec::new();
+	for kind in kinds {
+		let directory = get_state_directory(project_directory).join(kind.directory());
+		if !directory.try_exists()? {
+			continue;
+		}
+
+		let bytes = directory_size(&directory).await?;
+		tokio::fs::remove_dir_all(&directory).await?;
+		cleaned.push(Cleaned { directory, bytes });
+	}
+
+	Ok(cleaned)
+}
@@ -0,0 +1,74 @@
+use crate::error::Error;
+use sha2::{Digest, Sha256};
+use tracing_subscriber::{Layer, Registry};
+
+/// Layer exporting spans, installed below every other layer
+pub type TelemetryLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Names a script in spans without its id, since the id is enough to edit some scripts.
+pub fn hash_id(id: &str) -> String {
+	let digest = format!("{:x}", Sha256::digest(id.as_bytes()));
+	digest[..16].to_owned()
+}
+
+/// Flushes exported spans when dropped.
+#[cfg(feature = "otel")]
+pub struct Telemetry(opentelemetry_sdk::trace::SdkTracerProvider);
+
+#[cfg(feature = "otel")]
+impl Drop for Telemetry {
+	fn drop(&mut self) {
+		if let Err(error) = self.0.shutdown() {
+			eprintln!("failed flushing OpenTelemetry spans: {error}");
+		}
+	}
+}
+
+/// There is nothing to flush without the otel feature, and [`layer`] never succeeds
+#[cfg(not(feature = "otel"))]
+pub type Telemetry = std::convert::Infallible;
+
+/// Builds a layer exporting spans to the OTLP/HTTP collector at `endpoint`.
+///
+/// This is the only place the exporter is set up, and it is only called with `--otel-endpoint`, so
+/// nothing is sent without opting in. Log events aren't exported along with the spans, since
+/// their messages name raw script ids; spans only hold hashed ones.
+#[cfg(feature = "otel")]
+pub fn layer(endpoint: &str) -> Result<(TelemetryLayer, Telemetry), Error> {
+	use opentelemetry::trace::TracerProvider;
+	use opentelemetry_otlp::WithExportConfig;
+	use tracing_subscriber::filter::{FilterExt, LevelFilter, filter_fn};
+
+	let endpoint = endpoint.trim_end_matches('/');
+	let endpoint = if endpoint.ends_with("/v1/traces") {
+		endpoint.to_owned()
+	} else {
+		format!("{endpoint}/v1/traces")
+	};
+
+	let exporter = opentelemetry_otlp::SpanExporter::builder()
+		.with_http()
+		.with_endpoint(endpoint)
+		.build()
+		.map_err(|error| Error::Telemetry(error.to_string()))?;
+	let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+		.with_batch_exporter(exporter)
+		.with_resource(
+			opentelemetry_sdk::Resource::builder()
+				.with_service_name("fumo")
+				.build(),
+		)
+		.build();
+
+	let layer = tracing_opentelemetry::layer()
+		.with_tracer(provider.tracer("fumo"))
+		.with_filter(LevelFilter::INFO.and(filter_fn(|metadata| metadata.is_span())))
+		.boxed();
+	Ok((layer, Telemetry(provider)))
+}
+
+/// Stands in for span exporting when fumo is built without the otel feature.
+#[cfg(not(feature = "otel"))]
+pub fn layer(_endpoint: &str) -> Result<(TelemetryLayer, Telemetry), Error> {
+	Err(Error::FeatureDisabled("otel"))
+}
@@ -0,0 +1,273 @@
+use crate::{
+	error::Error,
+	login::get_config_directory,
+	project::{
+		Configuration, EditorSetup, LOCAL_STATE_DIRECTORY, MAIN_SCRIPT_FILE, PLACEHOLDER_SCRIPT_ID,
+		ProjectType, SYNC_CONFIGURATION_FILE, SYNC_CONFIGURATION_TOML_FILE, find_configuration_file,
+		read_configuration, read_file, serialize_configuration, write_file,
+	},
+	vscode::SETTINGS_DIRECTORY,
+};
+use std::path::{Path, PathBuf};
+
+/// Directory inside of the config directory holding user templates
+pub const TEMPLATES_DIRECTORY: &str = "templates";
+pub const DEFAULT_TEMPLATE: &str = "default";
+/// Replaced with the script name in text files copied from templates
+pub const SCRIPT_NAME_PLACEHOLDER: &str = "{{scriptName}}";
+/// File of a template which new modules start from; it isn't copied into projects
+pub const MODULE_TEMPLATE_FILE: &str = "module.luau";
+/// Replaced with the module name in module templates
+pub const MODULE_NAME_PLACEHOLDER: &str = "{{moduleName}}";
+
+const BUILT_IN_MODULE_TEMPLATE: &str = include_str!("../templates/default/module.luau");
+
+/// Directories which are never saved into templates
+const EXCLUDED_DIRECTORIES: &[&str] = &[LOCAL_STATE_DIRECTORY, ".git"];
+
+/// Files of the built-in templates, relative to the project directory.
+const BUILT_IN_TEMPLATES: &[(&str, &[(&str, &str)])] = &[(
+	DEFAULT_TEMPLATE,
+	&[
+		(
+			".vscode/settings.json",
+			include_str!("../templates/default/.vscode/settings.json"),
+		),
+		(
+			".gitignore",
+			include_str!("../templates/default/.gitignore"),
+		),
+		(
+			MAIN_SCRIPT_FILE,
+			include_str!("../templates/default/init.server.luau"),
+		),
+		("README.md", include_str!("../templates/default/README.md")),
+	],
+)];
+
+/// A project scaffold used by `init`.
+pub enum Template {
+	BuiltIn(&'static [(&'static str, &'static str)]),
+	/// A directory under `<config dir>/templates`
+	User(PathBuf),
+}
+
+fn get_templates_directory() -> Result<PathBuf, Error> {
+	Ok(get_config_directory()?.join(TEMPLATES_DIRECTORY))
+}
+
+/// Lists the names of every template, built-in and user defined.
+pub async fn list_templates() -> Result<Vec<String>, Error> {
+	let mut names: Vec<String> = BUILT_IN_TEMPLATES
+		.iter()
+		.map(|(name, _)| (*name).to_owned())
+		.collect();
+
+	let directory = get_templates_directory()?;
+	if directory.try_exists()? {
+		let mut stream = tokio::fs::read_dir(&directory)
+			.await
+			.map_err(|io_error| Error::ReadDirectory(directory.clone(), io_error))?;
+		while let Some(entry) = stream.next_entry().await? {
+			if entry.file_type().await?.is_dir() {
+				names.push(entry.file_name().to_string_lossy().to_string());
+			}
+		}
+	}
+
+	names.sort();
+	names.dedup();
+	Ok(names)
+}
+
+/// Finds a template by name; user templates take precedence over built-in ones.
+///
+/// # Errors
+/// - [`Error::UnknownTemplate`]
+pub async fn find_template(name: &str) -> Result<Template, Error> {
+	let directory = get_templates_directory()?.join(name);
+	if directory.is_dir() {
+		return Ok(Template::User(directory));
+	}
+
+	match BUILT_IN_TEMPLATES
+		.iter()
+		.find(|(built_in_name, _)| *built_in_name == name)
+	{
+		Some((_, files)) => Ok(Template::BuiltIn(files)),
+		None => Err(Error::UnknownTemplate(
+			name.to_owned(),
+			list_templates().await?,
+		)),
+	}
+}
+
+/// Lists every file below a directory, relative to it, skipping excluded directories at the top level.
+async fn list_files(root: &Path) -> Result<Vec<PathBuf>, Error> {
+	let mut files = Vec::new();
+	let mut directories = vec![PathBuf::new()];
+
+	while let Some(relative_directory) = directories.pop() {
+		let directory = root.join(&relative_directory);
+		let mut stream = tokio::fs::read_dir(&directory)
+			.await
+			.map_err(|io_error| Error::ReadDirectory(directory.clone(), io_error))?;
+
+		while let Some(entry) = stream.next_entry().await? {
+			let path = relative_directory.join(entry.file_name());
+			if entry.file_type().await?.is_dir() {
+				if relative_directory.as_os_str().is_empty()
+					&& EXCLUDED_DIRECTORIES.contains(&entry.file_name().to_string_lossy().as_ref())
+				{
+					continue;
+				}
+
+				directories.push(path);
+			} else {
+				files.push(path);
+			}
+		}
+	}
+
+	Ok(files)
+}
+
+async fn create_parent_directory(path: &Path) -> Result<(), Error> {
+	match path.parent() {
+		Some(parent) => tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|io_error| Error::CreateDirectory(parent.to_path_buf(), io_error)),
+		None => Ok(()),
+	}
+}
+
+impl Template {
+	/// Copies the template into a project directory, substituting the script name in text files.
+	///
+	/// Packages don't get a main script, and editor files are left out without an editor;
+	/// fumosync.json is left to the caller.
+	pub async fn apply(
+		&self,
+		directory: &Path,
+		script_name: &str,
+		project_type: ProjectType,
+		editor: EditorSetup,
+	) -> Result<(), Error> {
+		let wanted = |path: &Path| {
+			path != Path::new(SYNC_CONFIGURATION_FILE)
+				&& path != Path::new(SYNC_CONFIGURATION_TOML_FILE)
+				&& path != Path::new(MODULE_TEMPLATE_FILE)
+				&& !(project_type == ProjectType::Package && path == Path::new(MAIN_SCRIPT_FILE))
+				&& !(editor == EditorSetup::None && path.starts_with(SETTINGS_DIRECTORY))
+		};
+
+		match self {
+			Self::BuiltIn(files) => {
+				for (path, contents) in files.iter().filter(|(path, _)| wanted(Path::new(path))) {
+					let path = directory.join(path);
+					create_parent_directory(&path).await?;
+					write_file(
+						path,
+						&contents.replace(SCRIPT_NAME_PLACEHOLDER, script_name),
+					)
+					.await?;
+				}
+			}
+			Self::User(root) => {
+				for relative_path in list_files(root)
+					.await?
+					.into_iter()
+					.filter(|path| wanted(path))
+				{
+					let source = root.join(&relative_path);
+					let destination = directory.join(&relative_path);
+					create_parent_directory(&destination).await?;
+
+					let bytes = tokio::fs::read(&source)
+						.await
+						.map_err(|io_error| Error::ReadFile(source, io_error))?;
+					// binary files are copied as they are
+					let contents = match String::from_utf8(bytes) {
+						Ok(text) => text
+							.replace(SCRIPT_NAME_PLACEHOLDER, script_name)
+							.into_bytes(),
+						Err(error) => error.into_bytes(),
+					};
+
+					tokio::fs::write(&destination, contents)
+						.await
+						.map_err(|io_error| Error::CreateFile(destination, io_error))?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Gets the source of a new module; user templates without a module.luau use the built-in one.
+	pub async fn module_source(&self, module_name: &str) -> Result<String, Error> {
+		let source = match self {
+			Self::User(root) if root.join(MODULE_TEMPLATE_FILE).is_file() => {
+				read_file(root.join(MODULE_TEMPLATE_FILE)).await?
+			}
+			_ => BUILT_IN_MODULE_TEMPLATE.to_owned(),
+		};
+
+		Ok(source.replace(MODULE_NAME_PLACEHOLDER, module_name))
+	}
+
+	/// Reads the configuration of a user template along with its file name, if it has one.
+	pub async fn configuration(&self) -> Result<Option<(PathBuf, Configuration)>, Error> {
+		match self {
+			Self::BuiltIn(..) => Ok(None),
+			Self::User(root) => {
+				let path = find_configuration_file(root)?;
+				if !path.try_exists()? {
+					return Ok(None);
+				}
+
+				let file_name = PathBuf::from(path.file_name().unwrap_or_default());
+				Ok(Some((file_name, read_configuration(root).await?)))
+			}
+		}
+	}
+}
+
+/// Snapshots a project as a new user template.
+///
+/// The local state and git directories are left out, and fumosync.json is unlinked from its script.
+pub async fn save_template(project_directory: &Path, name: &str) -> Result<PathBuf, Error> {
+	let template_directory = get_templates_directory()?.join(name);
+	if template_directory.exists() {
+		return Err(Error::DirectoryAlreadyExists(template_directory));
+	}
+
+	for relative_path in list_files(project_directory).await? {
+		let source = project_directory.join(&relative_path);
+		let destination = template_directory.join(&relative_path);
+		create_parent_directory(&destination).await?;
+
+		if relative_path == Path::new(SYNC_CONFIGURATION_FILE)
+			|| relative_path == Path::new(SYNC_CONFIGURATION_TOML_FILE)
+		{
+			let configuration = Configuration {
+				script_name: SCRIPT_NAME_PLACEHOLDER.to_owned(),
+				script_id: PLACEHOLDER_SCRIPT_ID.to_owned(),
+				meta: None,
+				..read_configuration(project_directory).await?
+			};
+
+			write_file(
+				&destination,
+				&serialize_configuration(&destination, &configuration)?,
+			)
+			.await?;
+		} else {
+			tokio::fs::copy(&source, &destination)
+				.await
+				.map_err(|io_error| Error::CreateFile(destination, io_error))?;
+		}
+	}
+
+	Ok(template_directory)
+}
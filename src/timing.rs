@@ -0,0 +1,80 @@
+use serde::Serialize;
+use std::{
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+/// Phases timed so far, in the order they first finished
+static TIMINGS: Mutex<Vec<Timing>> = Mutex::new(Vec::new());
+
+/// Time spent in a phase, summed over every time it ran.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Timing {
+	pub phase: &'static str,
+	pub count: u32,
+	pub milliseconds: f64,
+}
+
+/// Records the time until it is dropped under a phase.
+#[must_use = "the section is recorded when dropped"]
+pub struct Section {
+	phase: &'static str,
+	start: Instant,
+}
+
+/// Starts timing a phase.
+pub fn section(phase: &'static str) -> Section {
+	Section {
+		phase,
+		start: Instant::now(),
+	}
+}
+
+impl Drop for Section {
+	fn drop(&mut self) {
+		record(self.phase, self.start.elapsed());
+	}
+}
+
+pub fn record(phase: &'static str, elapsed: Duration) {
+	// timings are best effort, so a poisoned lock only loses them
+	let Ok(mut timings) = TIMINGS.lock() else {
+		return;
+	};
+
+	match timings.iter_mut().find(|timing| timing.phase == phase) {
+		Some(timing) => {
+			timing.count += 1;
+			timing.milliseconds += elapsed.as_secs_f64() * 1000.0;
+		}
+		None => timings.push(Timing {
+			phase,
+			count: 1,
+			milliseconds: elapsed.as_secs_f64() * 1000.0,
+		}),
+	}
+}
+
+/// Takes the timings recorded so far, so the next report starts empty.
+pub fn take() -> Vec<Timing> {
+	TIMINGS
+		.lock()
+		.map(|mut timings| std::mem::take(&mut *timings))
+		.unwrap_or_default()
+}
+
+/// Prints the timings recorded so far to stderr, keeping stdout for command output.
+pub fn print_report() {
+	for timing in take() {
+		eprintln!(
+			"{:>10.2}ms  {}{}",
+			timing.milliseconds,
+			timing.phase,
+			match timing.count {
+				1 => String::new(),
+				count => format!(" ({count} times)"),
+			}
+		);
+	}
+}
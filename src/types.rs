@@ -0,0 +1,107 @@
+use crate::{
+	client::{PROGRAM_VERSION, get_user_agent},
+	error::Error,
+	project::{read_file, write_file},
+	settings,
+};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+pub const TYPES_FILE: &str = "types.d.luau";
+/// Starts the first line of type definitions written by fumo
+const HEADER_PREFIX: &str = "-- written by fumo";
+const TYPE_DEFINITIONS: &str = include_str!("../assets/types.d.luau");
+
+/// SHA-256 of every type definitions body fumo has shipped, without the header or trailing
+/// whitespace; files matching one of these have no local edits
+const KNOWN_TYPE_DEFINITION_HASHES: &[&str] =
+	&["d3fd63af96601d3197955f30204f3cf35856ea3941a38e5e554db5b4167756dd"];
+
+fn with_header(source: &str, definitions: &str) -> String {
+	format!(
+		"{HEADER_PREFIX} {PROGRAM_VERSION}{source}; run `fumo types update` to refresh\n{definitions}"
+	)
+}
+
+/// Gets the type definitions embedded in this version of fumo.
+pub fn current() -> String {
+	with_header("", TYPE_DEFINITIONS)
+}
+
+/// Strips the header fumo writes, leaving the definitions themselves.
+fn body(contents: &str) -> &str {
+	match contents.split_once('\n') {
+		Some((first_line, rest)) if first_line.starts_with(HEADER_PREFIX) => rest,
+		_ => contents,
+	}
+}
+
+fn is_known(contents: &str) -> bool {
+	let hash = format!("{:x}", Sha256::digest(body(contents).trim_end()));
+	KNOWN_TYPE_DEFINITION_HASHES.contains(&hash.as_str())
+}
+
+async fn fetch(url: &str) -> Result<String, Error> {
+	let definitions = reqwest::Client::builder()
+		.user_agent(get_user_agent())
+		.timeout(settings::get().timeout)
+		.build()?
+		.get(url)
+		.send()
+		.await?
+		.error_for_status()?
+		.text()
+		.await?;
+
+	Ok(with_header(&format!(" from {url}"), &definitions))
+}
+
+/// Type definitions which replace a project's once [applied](PendingUpdate::apply).
+pub struct PendingUpdate {
+	path: PathBuf,
+	contents: String,
+	/// Unified diff from the existing file, if it has local edits which the update overwrites
+	pub local_edits: Option<String>,
+}
+
+impl PendingUpdate {
+	pub async fn apply(self) -> Result<(), Error> {
+		write_file(self.path, &self.contents).await
+	}
+}
+
+/// Gets the update of the project's type definitions to the embedded copy, or the file at `url`;
+/// `None` if they are up to date.
+pub async fn prepare_update(
+	project_directory: &Path,
+	url: Option<&str>,
+) -> Result<Option<PendingUpdate>, Error> {
+	let path = project_directory.join(TYPES_FILE);
+	let updated = match url {
+		Some(url) => fetch(url).await?,
+		None => current(),
+	};
+
+	let mut local_edits = None;
+	if path.try_exists()? {
+		let existing = read_file(&path).await?;
+		if body(&existing) == body(&updated) {
+			return Ok(None);
+		}
+
+		if !is_known(&existing) {
+			local_edits = Some(
+				similar::TextDiff::from_lines(existing.as_str(), updated.as_str())
+					.unified_diff()
+					.header(TYPES_FILE, "updated")
+					.to_string(),
+			);
+		}
+	}
+
+	Ok(Some(PendingUpdate {
+		path,
+		contents: updated,
+		local_edits,
+	}))
+}
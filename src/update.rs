@@ -0,0 +1,160 @@
+use crate::{
+	error::Error,
+	progress,
+	version::{BuildInfo, PACKAGE_VERSION},
+};
+use self_update::{ArchiveKind, Compression, Download, Extract, backends::github, update::Release};
+use sha2::{Digest, Sha256};
+use std::{
+	fs::{self, File},
+	path::{Path, PathBuf},
+};
+
+pub const REPOSITORY_OWNER: &str = "techs-sus";
+pub const REPOSITORY_NAME: &str = "fumo";
+const BINARY_NAME: &str = "fumo";
+
+/// Result of comparing the running version against the latest release.
+pub struct UpdateCheck {
+	pub current: BuildInfo,
+	pub latest: String,
+	pub available: bool,
+}
+
+fn latest_release() -> Result<Release, Error> {
+	github::Update::configure()
+		.repo_owner(REPOSITORY_OWNER)
+		.repo_name(REPOSITORY_NAME)
+		.bin_name(BINARY_NAME)
+		.current_version(PACKAGE_VERSION)
+		.build()
+		.and_then(|update| update.get_latest_release())
+		.map_err(|error| Error::SelfUpdate(error.to_string()))
+}
+
+async fn fetch_latest_release() -> Result<Release, Error> {
+	progress::spinner("checking for updates", async {
+		tokio::task::spawn_blocking(latest_release)
+			.await
+			.map_err(|error| Error::SelfUpdate(error.to_string()))?
+	})
+	.await
+}
+
+fn is_newer(latest: &str) -> Result<bool, Error> {
+	self_update::version::bump_is_greater(PACKAGE_VERSION, latest)
+		.map_err(|error| Error::SelfUpdate(error.to_string()))
+}
+
+/// Checks whether a newer release of fumo exists.
+pub async fn check() -> Result<UpdateCheck, Error> {
+	let release = fetch_latest_release().await?;
+
+	Ok(UpdateCheck {
+		current: BuildInfo::current(),
+		available: is_newer(&release.version)?,
+		latest: release.version,
+	})
+}
+
+/// Refuses to update binaries which the current user can't replace, such as package manager installs.
+fn ensure_replaceable(executable: &Path) -> Result<(), Error> {
+	let directory = executable
+		.parent()
+		.ok_or_else(|| Error::UpdateNotPermitted(executable.to_path_buf()))?;
+	// uniquely named and deleted when dropped, so concurrent updates never remove each other's probe
+	let writable = !fs::metadata(executable)?.permissions().readonly()
+		&& tempfile::Builder::new()
+			.prefix(".fumo-update-probe")
+			.tempfile_in(directory)
+			.is_ok();
+
+	if writable {
+		Ok(())
+	} else {
+		Err(Error::UpdateNotPermitted(executable.to_path_buf()))
+	}
+}
+
+fn download(url: &str, writer: impl std::io::Write) -> Result<(), Error> {
+	Download::from_url(url)
+		.set_header(
+			reqwest::header::ACCEPT,
+			"application/octet-stream"
+				.parse()
+				.expect("failed parsing accept header"),
+		)
+		.download_to(writer)
+		.map_err(|error| Error::SelfUpdate(error.to_string()))
+}
+
+/// Downloads, verifies and installs a release; the running executable is only touched by the final
+/// replace, which is atomic.
+fn install(release: &Release, executable: PathBuf) -> Result<PathBuf, Error> {
+	let target = self_update::get_target();
+	let asset = release
+		.asset_for(target, None)
+		.ok_or_else(|| Error::NoReleaseAsset(release.version.clone(), target.to_owned()))?;
+	let checksum_name = format!("{}.sha256", asset.name);
+	let checksum_asset = release
+		.assets
+		.iter()
+		.find(|candidate| candidate.name == checksum_name)
+		.ok_or_else(|| Error::NoReleaseAsset(release.version.clone(), checksum_name.clone()))?;
+
+	let temporary_directory = tempfile::Builder::new().prefix("fumo-update").tempdir()?;
+	let archive_path = temporary_directory.path().join(&asset.name);
+	download(&asset.download_url, File::create(&archive_path)?)?;
+
+	let mut checksum = Vec::new();
+	download(&checksum_asset.download_url, &mut checksum)?;
+	// checksum files are formatted like sha256sum output: "<digest>  <file name>"
+	let expected = String::from_utf8_lossy(&checksum)
+		.split_whitespace()
+		.next()
+		.unwrap_or_default()
+		.to_lowercase();
+	let actual = format!("{:x}", Sha256::digest(fs::read(&archive_path)?));
+	if expected != actual {
+		return Err(Error::ChecksumMismatch(asset.name));
+	}
+
+	let archive = if asset.name.ends_with(".zip") {
+		ArchiveKind::Zip
+	} else {
+		ArchiveKind::Tar(Some(Compression::Gz))
+	};
+	let binary_name = format!("{BINARY_NAME}{}", std::env::consts::EXE_SUFFIX);
+	Extract::from_source(&archive_path)
+		.archive(archive)
+		.extract_file(temporary_directory.path(), &binary_name)
+		.map_err(|error| Error::SelfUpdate(error.to_string()))?;
+
+	self_update::self_replace::self_replace(temporary_directory.path().join(&binary_name))?;
+	Ok(executable)
+}
+
+/// Updates the running executable to the latest release, if it is newer.
+///
+/// Returns the installed version, or `None` if fumo is already up to date.
+pub async fn update() -> Result<Option<String>, Error> {
+	let executable = std::env::current_exe()?;
+	ensure_replaceable(&executable)?;
+
+	let release = fetch_latest_release().await?;
+
+	if !is_newer(&release.version)? {
+		return Ok(None);
+	}
+
+	let version = release.version.clone();
+	let executable = progress::spinner(&format!("installing fumo {version}"), async {
+		tokio::task::spawn_blocking(move || install(&release, executable))
+			.await
+			.map_err(|error| Error::SelfUpdate(error.to_string()))?
+	})
+	.await?;
+
+	tracing::info!("replaced {}", executable.display());
+	Ok(Some(version))
+}
@@ -0,0 +1,155 @@
+use crate::{
+	client::{EditorScriptInfo, EditorUpdate},
+	project::{DESCRIPTION_FILE, MAIN_SCRIPT_FILE},
+};
+use clap::ValueEnum;
+use sha2::{Digest, Sha256};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	time::Duration,
+};
+
+/// What a watcher does about files edited on fumosclub since it last synced them.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RemoteEditPolicy {
+	/// Stop syncing the files until they match fumosclub again
+	#[default]
+	Pause,
+	/// Push the local files over the edits
+	Overwrite,
+	/// Write the edits into the local files
+	Pull,
+}
+
+/// How often `watch --verify-interval` compares the remote script, and what it does about edits.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifySchedule {
+	pub interval: Duration,
+	pub on_remote_edit: RemoteEditPolicy,
+}
+
+/// A file of the remote script.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RemoteFile {
+	Main,
+	Description,
+	/// A module, by its remote name
+	Module(String),
+}
+
+impl std::fmt::Display for RemoteFile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Main => f.write_str(MAIN_SCRIPT_FILE),
+			Self::Description => f.write_str(DESCRIPTION_FILE),
+			Self::Module(name) => write!(f, "module {name:?}"),
+		}
+	}
+}
+
+type Hash = [u8; 32];
+
+fn hash(contents: &str) -> Hash {
+	Sha256::digest(contents.as_bytes()).into()
+}
+
+/// Hashes every file of a remote script.
+fn fingerprint(script_info: &EditorScriptInfo) -> BTreeMap<RemoteFile, Hash> {
+	let mut files = BTreeMap::from([
+		(RemoteFile::Main, hash(&script_info.source.main)),
+		(RemoteFile::Description, hash(&script_info.description)),
+	]);
+	files.extend(
+		script_info
+			.source
+			.modules
+			.iter()
+			.map(|(name, source)| (RemoteFile::Module(name.clone()), hash(source))),
+	);
+
+	files
+}
+
+/// What a watcher last saw on fumosclub or uploaded there, and the files it stopped syncing
+/// because someone else edited them.
+#[derive(Default)]
+pub struct RemoteBaseline {
+	/// `None` until the remote script is first compared
+	files: Option<BTreeMap<RemoteFile, Hash>>,
+	paused: BTreeSet<RemoteFile>,
+}
+
+impl RemoteBaseline {
+	pub fn is_paused(&self, file: &RemoteFile) -> bool {
+		self.paused.contains(file)
+	}
+
+	pub fn pause(&mut self, file: RemoteFile) {
+		self.paused.insert(file);
+	}
+
+	pub fn resume(&mut self, file: &RemoteFile) {
+		self.paused.remove(file);
+	}
+
+	pub fn paused(&self) -> Vec<RemoteFile> {
+		self.paused.iter().cloned().collect()
+	}
+
+	/// Checks whether contents, as they would be uploaded, are what fumosclub last had.
+	pub fn matches(&self, file: &RemoteFile, contents: &str) -> bool {
+		self
+			.files
+			.as_ref()
+			.and_then(|files| files.get(file))
+			.is_some_and(|remote| *remote == hash(contents))
+	}
+
+	/// Records uploaded updates, so they aren't mistaken for edits made on fumosclub.
+	pub fn record(&mut self, updates: &[EditorUpdate<'_>]) {
+		let Some(files) = &mut self.files else {
+			return;
+		};
+
+		for update in updates {
+			match update {
+				EditorUpdate::MainSource(source) => {
+					files.insert(RemoteFile::Main, hash(source));
+				}
+				EditorUpdate::Description(description) => {
+					files.insert(RemoteFile::Description, hash(description));
+				}
+				EditorUpdate::Module { name, source } => {
+					files.insert(RemoteFile::Module(name.to_string()), hash(source));
+				}
+				EditorUpdate::RemoveModule(name) => {
+					files.remove(&RemoteFile::Module((*name).to_owned()));
+				}
+				EditorUpdate::Whitelist(..) | EditorUpdate::Name(..) | EditorUpdate::Publicity(..) => {}
+			}
+		}
+	}
+
+	/// Compares the remote script against the baseline, returning the files added, changed or
+	/// removed on fumosclub, and adopts it as the new baseline; the first comparison only records
+	/// it.
+	pub fn compare(&mut self, script_info: &EditorScriptInfo) -> Vec<RemoteFile> {
+		let current = fingerprint(script_info);
+		let Some(previous) = self.files.replace(current.clone()) else {
+			return Vec::new();
+		};
+
+		let mut edited: Vec<RemoteFile> = current
+			.iter()
+			.filter(|(file, hash)| previous.get(*file) != Some(*hash))
+			.map(|(file, _)| file.clone())
+			.collect();
+		edited.extend(
+			previous
+				.into_keys()
+				.filter(|file| !current.contains_key(file)),
+		);
+		edited.sort();
+		edited
+	}
+}
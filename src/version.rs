@@ -0,0 +1,92 @@
+use crate::client::PROGRAM_VERSION;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Version in Cargo.toml, which releases are tagged with
+pub const PACKAGE_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Target triple fumo was compiled for, set by build.rs
+pub const TARGET: &str = env!("FUMO_TARGET");
+/// Seconds since the unix epoch when build.rs last ran, or `SOURCE_DATE_EPOCH`
+const BUILD_TIMESTAMP: &str = env!("FUMO_BUILD_TIMESTAMP");
+
+/// Optional cargo features, and whether this build has them
+const FEATURES: &[(&str, bool)] = &[
+	("browser-login", cfg!(feature = "browser-login")),
+	("cookie-import", cfg!(feature = "cookie-import")),
+	("git", cfg!(feature = "git")),
+	("otel", cfg!(feature = "otel")),
+	("self-update", cfg!(feature = "self-update")),
+];
+
+/// Which build of fumo is running, printed by `fumo version`; fields are only ever added.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+	/// Git description of the checkout built from; see [`PROGRAM_VERSION`]
+	pub version: &'static str,
+	pub package_version: &'static str,
+	pub target: &'static str,
+	pub built_at: Option<DateTime<Utc>>,
+	/// Enabled optional features, sorted
+	pub features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+	pub fn current() -> Self {
+		Self {
+			version: PROGRAM_VERSION,
+			package_version: PACKAGE_VERSION,
+			target: TARGET,
+			built_at: BUILD_TIMESTAMP
+				.parse()
+				.ok()
+				.and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+			features: FEATURES
+				.iter()
+				.filter(|(_, enabled)| *enabled)
+				.map(|(feature, _)| *feature)
+				.collect(),
+		}
+	}
+}
+
+impl std::fmt::Display for BuildInfo {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "fumo {} ({}", self.package_version, self.version)?;
+		if let Some(built_at) = self.built_at {
+			write!(f, ", built {}", built_at.format("%Y-%m-%d %H:%M UTC"))?;
+		}
+		write!(f, ") for {}", self.target)?;
+		if !self.features.is_empty() {
+			write!(f, " with {}", self.features.join(", "))?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn build_info_has_the_required_fields() {
+		let json = serde_json::to_string(&BuildInfo::current()).unwrap();
+		let info: serde_json::Value = serde_json::from_str(&json).unwrap();
+		for field in ["version", "packageVersion", "target"] {
+			assert!(
+				info[field].as_str().is_some_and(|value| !value.is_empty()),
+				"{field} is missing or empty in {json}"
+			);
+		}
+		assert!(info["builtAt"].is_string(), "{json}");
+		assert!(info["features"].is_array(), "{json}");
+	}
+
+	#[test]
+	fn lists_only_enabled_features() {
+		let features = BuildInfo::current().features;
+		assert_eq!(features.contains(&"otel"), cfg!(feature = "otel"));
+		assert!(features.is_sorted());
+	}
+}
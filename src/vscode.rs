@@ -0,0 +1,159 @@
+use crate::{
+	error::Error,
+	project::{read_file, write_file},
+	types::TYPES_FILE,
+};
+use serde_json::{Map, Value};
+use std::path::Path;
+use tracing::warn;
+
+pub const SETTINGS_DIRECTORY: &str = ".vscode";
+pub const SETTINGS_FILE: &str = "settings.json";
+const SECURITY_LEVEL_KEY: &str = "luau-lsp.types.robloxSecurityLevel";
+const DEFINITION_FILES_KEY: &str = "luau-lsp.types.definitionFiles";
+const DEFAULT_SECURITY_LEVEL: &str = "None";
+
+/// Merges the luau-lsp settings fumo relies on into .vscode/settings.json.
+///
+/// Other settings, and a security level the user picked, are kept; the file is only rewritten if
+/// something was missing. Files serde_json can't read, such as ones with comments, are left alone.
+pub async fn merge_settings(project_directory: &Path) -> Result<(), Error> {
+	let directory = project_directory.join(SETTINGS_DIRECTORY);
+	let path = directory.join(SETTINGS_FILE);
+	let mut settings = if path.try_exists()? {
+		match serde_json::from_str(&read_file(&path).await?) {
+			Ok(Value::Object(settings)) => settings,
+			_ => {
+				warn!(
+					"leaving {} alone; it isn't a plain JSON object",
+					path.display()
+				);
+				return Ok(());
+			}
+		}
+	} else {
+		tokio::fs::create_dir_all(&directory)
+			.await
+			.map_err(|io_error| Error::CreateDirectory(directory, io_error))?;
+		Map::new()
+	};
+
+	if !merge(&mut settings) {
+		return Ok(());
+	}
+
+	write_file(
+		&path,
+		&serde_json::to_string_pretty(&Value::Object(settings))?,
+	)
+	.await
+}
+
+/// Adds the missing settings, returning whether anything changed.
+fn merge(settings: &mut Map<String, Value>) -> bool {
+	let mut changed = false;
+	if !settings.contains_key(SECURITY_LEVEL_KEY) {
+		settings.insert(SECURITY_LEVEL_KEY.to_owned(), DEFAULT_SECURITY_LEVEL.into());
+		changed = true;
+	}
+
+	let types_file = Value::from(TYPES_FILE);
+	match settings.get_mut(DEFINITION_FILES_KEY) {
+		Some(Value::Array(files)) if files.contains(&types_file) => {}
+		Some(Value::Array(files)) => {
+			files.push(types_file);
+			changed = true;
+		}
+		_ => {
+			settings.insert(DEFINITION_FILES_KEY.to_owned(), vec![types_file].into());
+			changed = true;
+		}
+	}
+
+	changed
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn merged(settings: Value) -> (bool, Value) {
+		let Value::Object(mut settings) = settings else {
+			unreachable!()
+		};
+		let changed = merge(&mut settings);
+		(changed, Value::Object(settings))
+	}
+
+	#[test]
+	fn keeps_unrelated_settings() {
+		let (changed, settings) = merged(json!({
+			"editor.tabSize": 4,
+			"files.exclude": { "**/.fumo": true },
+		}));
+		assert!(changed);
+		assert_eq!(
+			settings,
+			json!({
+				"editor.tabSize": 4,
+				"files.exclude": { "**/.fumo": true },
+				SECURITY_LEVEL_KEY: DEFAULT_SECURITY_LEVEL,
+				DEFINITION_FILES_KEY: [TYPES_FILE],
+			})
+		);
+	}
+
+	#[test]
+	fn keeps_the_chosen_security_level_and_definition_files() {
+		let (changed, settings) = merged(json!({
+			SECURITY_LEVEL_KEY: "PluginSecurity",
+			DEFINITION_FILES_KEY: ["globals.d.luau"],
+		}));
+		assert!(changed);
+		assert_eq!(
+			settings,
+			json!({
+				SECURITY_LEVEL_KEY: "PluginSecurity",
+				DEFINITION_FILES_KEY: ["globals.d.luau", TYPES_FILE],
+			})
+		);
+	}
+
+	#[test]
+	fn leaves_complete_settings_unchanged() {
+		let complete = json!({
+			SECURITY_LEVEL_KEY: "None",
+			DEFINITION_FILES_KEY: [TYPES_FILE],
+		});
+		assert_eq!(merged(complete.clone()), (false, complete));
+	}
+
+	#[tokio::test]
+	async fn leaves_files_with_comments_alone() {
+		let directory = tempfile::tempdir().unwrap();
+		let path = directory
+			.path()
+			.join(SETTINGS_DIRECTORY)
+			.join(SETTINGS_FILE);
+		std::fs::create_dir(path.parent().unwrap()).unwrap();
+		let commented = "{\n\t// mine\n\t\"editor.tabSize\": 2\n}\n";
+		std::fs::write(&path, commented).unwrap();
+
+		merge_settings(directory.path()).await.unwrap();
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), commented);
+	}
+
+	#[tokio::test]
+	async fn creates_missing_settings() {
+		let directory = tempfile::tempdir().unwrap();
+		merge_settings(directory.path()).await.unwrap();
+
+		let path = directory
+			.path()
+			.join(SETTINGS_DIRECTORY)
+			.join(SETTINGS_FILE);
+		let settings: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+		assert_eq!(settings[DEFINITION_FILES_KEY], json!([TYPES_FILE]));
+	}
+}
@@ -0,0 +1,347 @@
+use crate::{
+	client::Client,
+	error::Error,
+	login::get_session_secrets,
+	progress,
+	project::{Configuration, read_configuration, write_configuration},
+	roblox::{MAX_BATCH_SIZE, RobloxClient, User},
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// How pushes combine the local whitelist with the remote one, which collaborators may have edited
+/// on the website.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WhitelistMerge {
+	/// Upload the local whitelist as it is
+	#[default]
+	Replace,
+	/// Keep entries which are only in the remote whitelist
+	Union,
+	/// Upload the local whitelist, warning about each remote entry it removes
+	LocalWinsWithWarning,
+}
+
+/// Works out the whitelist a push uploads, without duplicates.
+pub fn merge(policy: WhitelistMerge, local: &[String], remote: &[String]) -> Vec<String> {
+	let mut merged: Vec<String> = Vec::with_capacity(local.len());
+	let entries = match policy {
+		WhitelistMerge::Union => [local, remote].concat(),
+		WhitelistMerge::Replace | WhitelistMerge::LocalWinsWithWarning => local.to_vec(),
+	};
+	for entry in entries {
+		if !merged.contains(&entry) {
+			merged.push(entry);
+		}
+	}
+
+	merged
+}
+
+/// Gets the entries uploading `merged` adds to and removes from the remote whitelist.
+fn changes<'a>(merged: &'a [String], remote: &'a [String]) -> (Vec<&'a str>, Vec<&'a str>) {
+	let added: Vec<&str> = merged
+		.iter()
+		.filter(|entry| !remote.contains(entry))
+		.map(String::as_str)
+		.collect();
+	let mut removed: Vec<&str> = Vec::new();
+	for entry in remote {
+		if !merged.contains(entry) && !removed.contains(&entry.as_str()) {
+			removed.push(entry);
+		}
+	}
+
+	(added, removed)
+}
+
+/// Logs the entries an upload of `merged` adds to and removes from the remote whitelist; only
+/// called once it is being sent, so checks and dry runs don't claim changes.
+pub fn log_changes(policy: WhitelistMerge, merged: &[String], remote: &[String]) {
+	let (added, removed) = changes(merged, remote);
+	if !added.is_empty() {
+		info!("adding {} to the remote whitelist", added.join(", "));
+	}
+	if !removed.is_empty() {
+		if policy == WhitelistMerge::LocalWinsWithWarning {
+			warn!(
+				"removing {} from the remote whitelist; they aren't in fumosync.json",
+				removed.join(", ")
+			);
+		} else {
+			info!("removing {} from the remote whitelist", removed.join(", "));
+		}
+	}
+}
+
+/// How a whitelist entry resolved.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditStatus {
+	Resolved,
+	/// The id isn't a Roblox user, usually because the account was deleted
+	Missing,
+	/// The entry isn't an id, or the Roblox API couldn't be reached
+	Unknown,
+}
+
+/// Which whitelists an entry is in, when comparing the local and remote whitelists.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Presence {
+	Both,
+	LocalOnly,
+	RemoteOnly,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+	pub entry: String,
+	pub status: AuditStatus,
+	pub user: Option<User>,
+	pub avatar_url: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub presence: Option<Presence>,
+}
+
+/// Resolves whitelist entries to Roblox users, in batches.
+///
+/// Failing batches are warned about and leave their entries unknown, rather than failing the
+/// whole audit.
+pub async fn audit(entries: &[String]) -> Vec<AuditEntry> {
+	let ids: Vec<u64> = entries.iter().filter_map(|entry| parse_id(entry)).collect();
+	let mut users: Vec<User> = Vec::with_capacity(ids.len());
+	let mut avatar_urls: Vec<(u64, String)> = Vec::with_capacity(ids.len());
+	let mut unknown: Vec<u64> = Vec::new();
+
+	match RobloxClient::new() {
+		Ok(client) => {
+			for batch in ids.chunks(MAX_BATCH_SIZE) {
+				match client.get_users(batch).await {
+					Ok(batch_users) => users.extend(batch_users),
+					Err(error) => {
+						warn!(
+							"failed resolving {} whitelist entries: {error}",
+							batch.len()
+						);
+						unknown.extend(batch);
+					}
+				}
+
+				// avatars are cosmetic, so they aren't worth marking anything as unknown over
+				match client.get_avatar_urls(batch).await {
+					Ok(batch_urls) => avatar_urls.extend(batch_urls),
+					Err(error) => warn!("failed getting avatars: {error}"),
+				}
+			}
+		}
+		Err(error) => {
+			warn!("failed reaching the Roblox API: {error}");
+			unknown = ids;
+		}
+	}
+
+	entries
+		.iter()
+		.map(|entry| {
+			let id = parse_id(entry);
+			let user = id.and_then(|id| users.iter().find(|user| user.id == id).cloned());
+			let status = match (id, &user) {
+				(_, Some(..)) => AuditStatus::Resolved,
+				(Some(id), None) if !unknown.contains(&id) => AuditStatus::Missing,
+				_ => AuditStatus::Unknown,
+			};
+
+			AuditEntry {
+				entry: entry.clone(),
+				status,
+				user,
+				avatar_url: id.and_then(|id| {
+					avatar_urls
+						.iter()
+						.find(|(avatar_id, _)| *avatar_id == id)
+						.map(|(_, url)| url.clone())
+				}),
+				presence: None,
+			}
+		})
+		.collect()
+}
+
+/// Gets the whitelist of the project's script on fumosclub.
+pub async fn get_remote_whitelist(configuration: &Configuration) -> Result<Vec<String>, Error> {
+	let client = Client::new(get_session_secrets().await?)?;
+	Ok(
+		progress::spinner(
+			"fetching script",
+			client.get_editor(&configuration.linked_script_id()?),
+		)
+		.await?
+		.script_info
+		.whitelist,
+	)
+}
+
+/// Audits the union of the local and remote whitelists, marking which of them each entry is in.
+pub async fn audit_diff(local: &[String], remote: &[String]) -> Vec<AuditEntry> {
+	let mut entries: Vec<String> = local.to_vec();
+	entries.extend(
+		remote
+			.iter()
+			.filter(|entry| !local.contains(entry))
+			.cloned(),
+	);
+
+	let mut audited = audit(&entries).await;
+	for entry in &mut audited {
+		entry.presence = Some(
+			match (local.contains(&entry.entry), remote.contains(&entry.entry)) {
+				(true, true) => Presence::Both,
+				(true, false) => Presence::LocalOnly,
+				_ => Presence::RemoteOnly,
+			},
+		);
+	}
+
+	audited
+}
+
+/// Parses a whitelist entry as a user id, which is the only form fumosclub accepts.
+fn parse_id(entry: &str) -> Option<u64> {
+	if entry.is_empty() || !entry.bytes().all(|byte| byte.is_ascii_digit()) {
+		return None;
+	}
+
+	entry.parse().ok()
+}
+
+/// Resolves the whitelist, along with `entries`, to Roblox users and rewrites it with their ids.
+///
+/// Entries may be usernames or ids, and surrounding whitespace is ignored. Returns the users in
+/// whitelist order, without duplicates.
+///
+/// # Errors
+/// - [`Error::UnresolvedWhitelistEntries`] if an entry isn't a Roblox user; nothing is rewritten
+pub async fn resolve(project_directory: &Path, entries: Vec<String>) -> Result<Vec<User>, Error> {
+	let mut configuration = read_configuration(project_directory).await?;
+	let entries: Vec<String> = configuration
+		.whitelist
+		.iter()
+		.chain(&entries)
+		.map(|entry| entry.trim().to_owned())
+		.collect();
+
+	let ids: Vec<u64> = entries.iter().filter_map(|entry| parse_id(entry)).collect();
+	let usernames: Vec<&str> = entries
+		.iter()
+		.filter(|entry| parse_id(entry).is_none())
+		.map(String::as_str)
+		.collect();
+
+	let client = RobloxClient::new()?;
+	let mut users_by_id = Vec::with_capacity(ids.len());
+	for batch in ids.chunks(MAX_BATCH_SIZE) {
+		users_by_id.extend(client.get_users(batch).await?);
+	}
+	let mut users_by_name = Vec::with_capacity(usernames.len());
+	for batch in usernames.chunks(MAX_BATCH_SIZE) {
+		users_by_name.extend(client.get_users_by_name(batch).await?);
+	}
+
+	let mut users: Vec<User> = Vec::with_capacity(entries.len());
+	let mut unresolved = Vec::new();
+	for entry in entries {
+		let user = match parse_id(&entry) {
+			Some(id) => users_by_id.iter().find(|user| user.id == id),
+			None => users_by_name
+				.iter()
+				.find(|(requested, _)| requested.eq_ignore_ascii_case(&entry))
+				.map(|(_, user)| user),
+		};
+
+		match user {
+			Some(user) if users.iter().any(|existing| existing.id == user.id) => {}
+			Some(user) => users.push(user.clone()),
+			None => unresolved.push(entry),
+		}
+	}
+
+	if !unresolved.is_empty() {
+		return Err(Error::UnresolvedWhitelistEntries(unresolved));
+	}
+
+	configuration.whitelist = users.iter().map(|user| user.id.to_string()).collect();
+	write_configuration(project_directory, &configuration).await?;
+
+	Ok(users)
+}
+
+/// Checks the whitelist before it is pushed.
+///
+/// Only the form of the entries is checked, so pushes don't wait on the Roblox API; `fumo audit`
+/// shows ids which aren't Roblox users.
+///
+/// # Errors
+/// - [`Error::InvalidWhitelistEntries`] for entries which aren't ids, such as usernames
+pub fn check(configuration: &Configuration) -> Result<(), Error> {
+	let invalid: Vec<String> = configuration
+		.whitelist
+		.iter()
+		.filter(|entry| parse_id(entry).is_none())
+		.cloned()
+		.collect();
+	if !invalid.is_empty() {
+		return Err(Error::InvalidWhitelistEntries(invalid));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entries(entries: &[&str]) -> Vec<String> {
+		entries.iter().map(|&entry| entry.to_owned()).collect()
+	}
+
+	#[test]
+	fn replacing_uploads_the_local_whitelist() {
+		let local = entries(&["1", "2", "1"]);
+		let remote = entries(&["2", "3"]);
+		for policy in [
+			WhitelistMerge::Replace,
+			WhitelistMerge::LocalWinsWithWarning,
+		] {
+			let merged = merge(policy, &local, &remote);
+			assert_eq!(merged, entries(&["1", "2"]));
+			assert_eq!(changes(&merged, &remote), (vec!["1"], vec!["3"]));
+		}
+	}
+
+	#[test]
+	fn unions_keep_remote_entries() {
+		let local = entries(&["1", "2"]);
+		let remote = entries(&["3", "2", "3"]);
+		let merged = merge(WhitelistMerge::Union, &local, &remote);
+		assert_eq!(merged, entries(&["1", "2", "3"]));
+		assert_eq!(changes(&merged, &remote), (vec!["1"], Vec::new()));
+	}
+
+	#[test]
+	fn unchanged_whitelists_have_no_changes() {
+		let whitelist = entries(&["1", "2"]);
+		for policy in [
+			WhitelistMerge::Replace,
+			WhitelistMerge::Union,
+			WhitelistMerge::LocalWinsWithWarning,
+		] {
+			let merged = merge(policy, &whitelist, &whitelist);
+			assert_eq!(merged, whitelist);
+			assert_eq!(changes(&merged, &whitelist), (Vec::new(), Vec::new()));
+		}
+	}
+}
@@ -0,0 +1,215 @@
+use crate::{
+	error::Error,
+	project::{
+		Configuration, ConfirmCallback, Metadata, PLACEHOLDER_SCRIPT_ID, ProjectType, PushOptions,
+		compare_remote, find_configuration_file, push, read_configuration, read_file, read_metadata,
+	},
+	report::{FileReport, Report},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	path::{Component, Path, PathBuf},
+};
+use tracing::{error, info, warn};
+
+pub const WORKSPACE_FILE: &str = "fumo-workspace.json";
+
+/// Order members are pushed in.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceOrder {
+	/// Packages are pushed before the scripts which may depend on them
+	#[default]
+	PackagesFirst,
+	/// Members are pushed in the order they are listed
+	Manifest,
+}
+
+/// fumo-workspace.json
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+	/// Project directories, relative to the workspace file
+	pub members: Vec<PathBuf>,
+	#[serde(default)]
+	pub order: WorkspaceOrder,
+}
+
+/// A validated workspace member.
+pub struct Member {
+	/// Path relative to the workspace root, as listed in the manifest
+	pub path: PathBuf,
+	pub directory: PathBuf,
+	pub configuration: Configuration,
+}
+
+/// Whether the directory only holds a workspace, rather than a project.
+pub fn is_workspace_root(directory: &Path) -> Result<bool, Error> {
+	Ok(
+		directory.join(WORKSPACE_FILE).try_exists()?
+			&& !find_configuration_file(directory)?.try_exists()?,
+	)
+}
+
+/// Reads and validates the workspace, returning its members in push order.
+///
+/// # Errors
+/// - [`Error::InvalidWorkspaceMember`] for paths leaving the workspace, and members without a project
+/// - [`Error::DuplicateScriptId`] if several members link to the same script
+pub async fn read_workspace(root: &Path) -> Result<Vec<Member>, Error> {
+	let workspace: Workspace = serde_json::from_str(&read_file(root.join(WORKSPACE_FILE)).await?)?;
+
+	let mut members = Vec::with_capacity(workspace.members.len());
+	for path in workspace.members {
+		if !path
+			.components()
+			.all(|component| matches!(component, Component::Normal(..) | Component::CurDir))
+		{
+			return Err(Error::InvalidWorkspaceMember(
+				path,
+				"members must be relative paths inside of the workspace".to_owned(),
+			));
+		}
+
+		let directory = root.join(&path);
+		let configuration = match read_configuration(&directory).await {
+			Ok(configuration) => configuration,
+			Err(error) => return Err(Error::InvalidWorkspaceMember(path, error.to_string())),
+		};
+
+		members.push(Member {
+			path,
+			directory,
+			configuration,
+		});
+	}
+
+	let mut members_by_script: HashMap<&str, Vec<PathBuf>> = HashMap::new();
+	// members which aren't linked yet all have the placeholder id
+	for member in members
+		.iter()
+		.filter(|member| member.configuration.script_id != PLACEHOLDER_SCRIPT_ID)
+	{
+		members_by_script
+			.entry(member.configuration.script_id.as_str())
+			.or_default()
+			.push(member.path.clone());
+	}
+	if let Some((script_id, paths)) = members_by_script
+		.into_iter()
+		.find(|(_, paths)| paths.len() > 1)
+	{
+		return Err(Error::DuplicateScriptId(script_id.to_owned(), paths));
+	}
+
+	if workspace.order == WorkspaceOrder::PackagesFirst {
+		// stable, so members of the same type keep their manifest order
+		members.sort_by_key(|member| member.configuration.project_type != ProjectType::Package);
+	}
+
+	Ok(members)
+}
+
+/// Pushes every member in order, carrying on past failures and reporting them at the end.
+pub async fn push_workspace(
+	root: &Path,
+	allow_empty: bool,
+	strict: bool,
+	confirm: Option<ConfirmCallback>,
+) -> Result<(), Error> {
+	let members = read_workspace(root).await?;
+	let total = members.len();
+
+	let mut failed = 0;
+	for member in members {
+		info!("pushing {}", member.path.display());
+		let options = PushOptions {
+			allow_empty,
+			strict,
+			confirm: confirm.clone(),
+			..PushOptions::default()
+		};
+		match push(&member.directory, options).await {
+			Ok(_) => info!("pushed {}", member.path.display()),
+			Err(push_error) => {
+				error!("failed pushing {}: {push_error}", member.path.display());
+				failed += 1;
+			}
+		}
+	}
+
+	if failed > 0 {
+		return Err(Error::WorkspaceFailed(failed, total));
+	}
+
+	Ok(())
+}
+
+/// Verification results of a single member.
+#[derive(Serialize)]
+pub struct MemberVerification {
+	pub member: PathBuf,
+	/// `None` if the member couldn't be verified
+	pub files: Option<Vec<FileReport>>,
+	pub error: Option<String>,
+}
+
+/// Verifies every member, carrying on past failures.
+pub async fn verify_workspace(root: &Path) -> Result<Vec<MemberVerification>, Error> {
+	let mut results = Vec::new();
+	for member in read_workspace(root).await? {
+		results.push(match compare_remote(&member.directory, false).await {
+			Ok(report) => MemberVerification {
+				member: member.path,
+				files: Some(report.files),
+				error: None,
+			},
+			Err(verify_error) => {
+				error!("failed verifying {}: {verify_error}", member.path.display());
+				MemberVerification {
+					member: member.path,
+					files: None,
+					error: Some(verify_error.to_string()),
+				}
+			}
+		});
+	}
+
+	Ok(results)
+}
+
+/// Drift of a single member, for `fumo status --workspace`.
+pub struct MemberStatus {
+	pub member: PathBuf,
+	pub configuration: Configuration,
+	pub meta: Option<Metadata>,
+	/// `None` if the member couldn't be compared against fumosclub
+	pub comparison: Option<Report>,
+}
+
+/// Compares every member against fumosclub, carrying on past failures; like `status`, members
+/// which can't be compared are only warned about.
+pub async fn status_workspace(root: &Path) -> Result<Vec<MemberStatus>, Error> {
+	let mut results = Vec::new();
+	for member in read_workspace(root).await? {
+		let comparison = match compare_remote(&member.directory, false).await {
+			Ok(report) => Some(report),
+			Err(status_error) => {
+				warn!(
+					"couldn't compare {} against fumosclub: {status_error}",
+					member.path.display()
+				);
+				None
+			}
+		};
+		results.push(MemberStatus {
+			meta: read_metadata(&member.directory, &member.configuration).await,
+			member: member.path,
+			configuration: member.configuration,
+			comparison,
+		});
+	}
+
+	Ok(results)
+}
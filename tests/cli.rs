@@ -0,0 +1,125 @@
+//! Runs the fumo binary against a mock fumosclub, the way CI runs it.
+// the config directory is only redirected through XDG_CONFIG_HOME on Linux
+#![cfg(target_os = "linux")]
+
+use serde_json::json;
+use std::{path::Path, process::Stdio, time::Duration};
+use tokio::process::Command;
+use wiremock::{
+	Mock, MockServer, ResponseTemplate,
+	matchers::{body_json, method, path},
+};
+
+const SCRIPT_ID: &str = "abc123";
+
+/// How long a run may take before it's taken to be waiting on a prompt
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The binary with its config directory inside of `home`, talking to `server`, with stdin closed.
+fn fumo(home: &Path, server: &MockServer) -> Command {
+	let mut command = Command::new(env!("CARGO_BIN_EXE_fumo"));
+	command
+		.env("HOME", home)
+		.env("XDG_CONFIG_HOME", home.join("config"))
+		.env("FUMO_BASE_URL", server.uri())
+		.env("FUMO_SESSION", "token")
+		.env_remove("FUMO_NON_INTERACTIVE")
+		.env_remove("FUMO_PROFILE")
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.kill_on_drop(true);
+	command
+}
+
+fn project(directory: &Path) {
+	let configuration = json!({
+		"scriptName": "my script",
+		"scriptId": SCRIPT_ID,
+		"whitelist": [],
+		"isPublic": false,
+	});
+	std::fs::write(directory.join("fumosync.json"), configuration.to_string()).unwrap();
+	std::fs::write(directory.join("README.md"), "# listing").unwrap();
+	std::fs::write(directory.join("init.server.luau"), "print(1)").unwrap();
+}
+
+async fn mount_auth(server: &MockServer) {
+	Mock::given(method("GET"))
+		.and(path("/api/auth/auth"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true, "role": 0 })))
+		.mount(server)
+		.await;
+}
+
+#[tokio::test]
+async fn push_with_stdin_closed_fails_naming_the_flag() {
+	let server = MockServer::start().await;
+	mount_auth(&server).await;
+
+	let home = tempfile::tempdir().unwrap();
+	let other = tempfile::tempdir().unwrap();
+	let directory = tempfile::tempdir().unwrap();
+	project(directory.path());
+	// another project last pushed the script, which push would otherwise ask about
+	let config = home.path().join("config/fumosclub-cli");
+	std::fs::create_dir_all(&config).unwrap();
+	let registry = json!({
+		SCRIPT_ID: { "path": other.path(), "pushedAt": "2026-01-01T00:00:00Z" },
+	});
+	std::fs::write(config.join("projects.json"), registry.to_string()).unwrap();
+
+	let output = tokio::time::timeout(
+		TIMEOUT,
+		fumo(home.path(), &server)
+			.arg("push")
+			.arg("--project-directory")
+			.arg(directory.path())
+			.output(),
+	)
+	.await
+	.expect("push waited on a prompt instead of failing")
+	.unwrap();
+
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("--force"), "{stderr}");
+}
+
+#[tokio::test]
+async fn quiet_generate_prints_only_the_key() {
+	let server = MockServer::start().await;
+	mount_auth(&server).await;
+	Mock::given(method("POST"))
+		.and(path("/api/script/generatekey"))
+		.and(body_json(json!({ "scriptId": SCRIPT_ID })))
+		.respond_with(
+			ResponseTemplate::new(200)
+				.set_body_json(json!({ "success": true, "require": "require(123)" })),
+		)
+		.mount(&server)
+		.await;
+
+	let home = tempfile::tempdir().unwrap();
+	let directory = tempfile::tempdir().unwrap();
+	project(directory.path());
+
+	let output = tokio::time::timeout(
+		TIMEOUT,
+		fumo(home.path(), &server)
+			.arg("generate")
+			.arg("-q")
+			.current_dir(directory.path())
+			.output(),
+	)
+	.await
+	.unwrap()
+	.unwrap();
+
+	assert!(
+		output.status.success(),
+		"{}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+	assert_eq!(output.stdout, b"require(123)\n");
+}
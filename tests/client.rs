@@ -0,0 +1,572 @@
+//! Talks to a mock fumosclub, checking what the client sends and how it reads the answers.
+
+use chrono::{TimeDelta, Utc};
+use fumo::{
+	client::{
+		Client, ClientOptions, EditorUpdate, ModuleName, Reachability, ScriptId, probe_reachability,
+		resolve_script_reference,
+	},
+	error::Error,
+	limits,
+	login::Secrets,
+	project::{PullOptions, PushPart, prune_modules, push_part, read_configuration},
+};
+use serde_json::{Value, json};
+use wiremock::{
+	Mock, MockServer, ResponseTemplate,
+	matchers::{body_json, header, method, path, query_param},
+};
+
+const SCRIPT_ID: &str = "abc123";
+
+fn client(server: &MockServer) -> Client {
+	let secrets = Secrets::new("token".to_owned(), Utc::now() + TimeDelta::days(30));
+	let options = ClientOptions {
+		base_url: server.uri(),
+		compress_uploads: false,
+	};
+	Client::with_options(secrets, options).unwrap()
+}
+
+fn script_id() -> ScriptId {
+	ScriptId::new(SCRIPT_ID).unwrap()
+}
+
+fn script_list() -> Value {
+	json!({
+		"success": true,
+		"scripts": [{
+			"id": SCRIPT_ID,
+			"name": "my script",
+			"description": "does things",
+			"type": 0,
+			"creator": "someone",
+			"creatorIcon": "https://example.com/icon.png",
+			"editable": true,
+			"isFavorite": false,
+		}],
+	})
+}
+
+fn editor() -> Value {
+	json!({
+		"success": true,
+		"scriptInfo": {
+			"name": "my script",
+			"type": 0,
+			"description": "# my script",
+			"isPublic": false,
+			"whitelist": ["1", "2"],
+			"source": {
+				"main": "print(requireM(\"util\"))",
+				"modules": { "util": "return 1" },
+			},
+		},
+	})
+}
+
+async fn mount_editor(server: &MockServer) {
+	Mock::given(method("GET"))
+		.and(path("/api/script/editor"))
+		.and(query_param("id", SCRIPT_ID))
+		.respond_with(ResponseTemplate::new(200).set_body_json(editor()))
+		.mount(server)
+		.await;
+}
+
+#[tokio::test]
+async fn lists_scripts() {
+	let server = MockServer::start().await;
+	Mock::given(method("GET"))
+		.and(path("/api/script/home/getscripts"))
+		.and(header("Cookie", "session=token"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(script_list()))
+		.expect(1)
+		.mount(&server)
+		.await;
+
+	let list = client(&server).list_scripts().await.unwrap();
+	assert!(list.success);
+	assert_eq!(list.scripts.len(), 1);
+	assert_eq!(list.scripts[0].id, SCRIPT_ID);
+	assert_eq!(list.scripts[0].creator, "someone");
+	assert!(list.scripts[0].editable);
+}
+
+#[tokio::test]
+async fn views_an_editor() {
+	let server = MockServer::start().await;
+	mount_editor(&server).await;
+
+	let script_info = client(&server)
+		.get_editor(&script_id())
+		.await
+		.unwrap()
+		.script_info;
+	assert_eq!(script_info.name, "my script");
+	assert_eq!(script_info.whitelist, ["1", "2"]);
+	assert_eq!(script_info.source.modules["util"], "return 1");
+}
+
+#[tokio::test]
+async fn patches_only_the_updated_fields() {
+	let server = MockServer::start().await;
+	Mock::given(method("PATCH"))
+		.and(path("/api/script/editor"))
+		.and(header("Content-Type", "application/json"))
+		.and(body_json(json!({
+			"scriptId": SCRIPT_ID,
+			"scriptInfo": {
+				"source": {
+					"main": "print(1)",
+					"modules": { "util": "return 2" },
+				},
+				"whitelist": ["1", "3"],
+			},
+		})))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(1)
+		.mount(&server)
+		.await;
+
+	let name = ModuleName::new("util").unwrap();
+	client(&server)
+		.set_editor(
+			&script_id(),
+			&[
+				EditorUpdate::MainSource("print(1)"),
+				EditorUpdate::Module {
+					name: &name,
+					source: "return 2",
+				},
+				EditorUpdate::Whitelist(vec!["3", "1", "3"]),
+			],
+		)
+		.await
+		.unwrap();
+}
+
+/// Creates a project linked to the mock script, with its README.
+fn linked_project() -> tempfile::TempDir {
+	let directory = tempfile::tempdir().unwrap();
+	let configuration = json!({
+		"scriptName": "renamed",
+		"scriptId": SCRIPT_ID,
+		"whitelist": ["3"],
+		"isPublic": true,
+	});
+	std::fs::write(
+		directory.path().join("fumosync.json"),
+		configuration.to_string(),
+	)
+	.unwrap();
+	std::fs::write(directory.path().join("README.md"), "# new listing").unwrap();
+	std::fs::write(directory.path().join("init.server.luau"), "print(2)").unwrap();
+	directory
+}
+
+#[tokio::test]
+async fn pushes_only_the_description() {
+	let server = MockServer::start().await;
+	Mock::given(method("PATCH"))
+		.and(path("/api/script/editor"))
+		.and(body_json(json!({
+			"scriptId": SCRIPT_ID,
+			"scriptInfo": {
+				"source": {},
+				"description": "# new listing",
+			},
+		})))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(1)
+		.mount(&server)
+		.await;
+
+	let project = linked_project();
+	push_part(
+		&client(&server),
+		project.path(),
+		PushPart::Description,
+		None,
+	)
+	.await
+	.unwrap();
+}
+
+#[tokio::test]
+async fn pushes_only_the_metadata() {
+	let server = MockServer::start().await;
+	mount_editor(&server).await;
+	Mock::given(method("PATCH"))
+		.and(path("/api/script/editor"))
+		.and(body_json(json!({
+			"scriptId": SCRIPT_ID,
+			"scriptInfo": {
+				"source": {},
+				"whitelist": ["3"],
+				"name": "renamed",
+				"isPublic": true,
+			},
+		})))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(1)
+		.mount(&server)
+		.await;
+
+	let project = linked_project();
+	push_part(&client(&server), project.path(), PushPart::Metadata, None)
+		.await
+		.unwrap();
+}
+
+#[tokio::test]
+async fn refuses_pushing_oversized_descriptions() {
+	let server = MockServer::start().await;
+	Mock::given(method("PATCH"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(0)
+		.mount(&server)
+		.await;
+
+	let project = linked_project();
+	let description = "a".repeat(limits::DEFAULT_MAX_DESCRIPTION_LENGTH + 1);
+	let pushed = push_part(
+		&client(&server),
+		project.path(),
+		PushPart::Description,
+		Some(description),
+	)
+	.await;
+	assert!(matches!(pushed, Err(Error::DescriptionTooLong { .. })));
+}
+
+#[tokio::test]
+async fn prunes_modules_with_null_sources() {
+	let server = MockServer::start().await;
+	Mock::given(method("PATCH"))
+		.and(path("/api/script/editor"))
+		.and(body_json(json!({
+			"scriptId": SCRIPT_ID,
+			"scriptInfo": {
+				"source": {
+					"modules": { "old": null, "unused": null },
+				},
+			},
+		})))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(1)
+		.mount(&server)
+		.await;
+	// the editor mock only has "util" left
+	mount_editor(&server).await;
+
+	let project = linked_project();
+	prune_modules(
+		&client(&server),
+		project.path(),
+		&["unused".to_owned(), "old".to_owned()],
+	)
+	.await
+	.unwrap();
+}
+
+#[tokio::test]
+async fn reports_modules_fumosclub_kept() {
+	let server = MockServer::start().await;
+	Mock::given(method("PATCH"))
+		.and(path("/api/script/editor"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.mount(&server)
+		.await;
+	mount_editor(&server).await;
+
+	let project = linked_project();
+	let error = prune_modules(&client(&server), project.path(), &["util".to_owned()])
+		.await
+		.unwrap_err();
+	match error {
+		Error::ModulesNotPruned(remaining) => assert_eq!(remaining, ["util"]),
+		error => panic!("expected the kept modules, got {error}"),
+	}
+}
+
+#[tokio::test]
+async fn pulls_a_project() {
+	let server = MockServer::start().await;
+	Mock::given(method("GET"))
+		.and(path("/api/script/home/getscripts"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(script_list()))
+		.mount(&server)
+		.await;
+	mount_editor(&server).await;
+
+	let directory = tempfile::tempdir().unwrap();
+	let project_directory = directory.path().join("my-script");
+	let report = fumo::project::pull_project(
+		&client(&server),
+		SCRIPT_ID,
+		project_directory.clone(),
+		PullOptions::default(),
+	)
+	.await
+	.unwrap();
+	assert_eq!(report.script_id, SCRIPT_ID);
+	assert_eq!(report.modules, 1);
+
+	let read = |relative: &str| std::fs::read_to_string(project_directory.join(relative)).unwrap();
+	assert_eq!(read("init.server.luau"), "print(requireM(\"util\"))");
+	assert_eq!(read("README.md"), "# my script");
+	assert_eq!(read("pkg/util.luau"), "return 1");
+
+	let configuration = read_configuration(&project_directory).await.unwrap();
+	assert_eq!(configuration.script_id, SCRIPT_ID);
+	assert_eq!(configuration.script_name, "my script");
+	assert_eq!(configuration.whitelist, ["1", "2"]);
+	assert!(!configuration.is_public);
+}
+
+#[tokio::test]
+async fn reports_error_responses() {
+	let server = MockServer::start().await;
+	Mock::given(method("PATCH"))
+		.and(path("/api/script/editor"))
+		.respond_with(
+			ResponseTemplate::new(400)
+				.insert_header("x-vercel-id", "fra1::abcde")
+				.set_body_json(json!({ "success": false, "message": "script not found" })),
+		)
+		.mount(&server)
+		.await;
+
+	let error = client(&server)
+		.set_editor(&script_id(), &[EditorUpdate::Name("renamed")])
+		.await
+		.unwrap_err();
+	match error {
+		Error::ResponseStatus {
+			status,
+			endpoint,
+			request_id,
+			body,
+		} => {
+			assert_eq!(status, 400);
+			assert_eq!(endpoint, "/api/script/editor");
+			assert_eq!(request_id.as_deref(), Some("fra1::abcde"));
+			assert!(body.is_some_and(|body| body.contains("script not found")));
+		}
+		error => panic!("expected a response status error, got {error}"),
+	}
+}
+
+// one test, since a refused compressed body turns compression off for the whole process
+#[tokio::test]
+async fn only_stops_compressing_when_the_encoding_is_refused() {
+	let server = MockServer::start().await;
+	let compressing_client = || {
+		let secrets = Secrets::new("token".to_owned(), Utc::now() + TimeDelta::days(30));
+		let options = ClientOptions {
+			base_url: server.uri(),
+			compress_uploads: true,
+		};
+		Client::with_options(secrets, options).unwrap()
+	};
+	let main = "print(1)\n".repeat(16 * 1024);
+	let updates = [EditorUpdate::MainSource(&main)];
+
+	let invalid = Mock::given(method("PATCH"))
+		.and(header("Content-Encoding", "gzip"))
+		.respond_with(
+			ResponseTemplate::new(400)
+				.set_body_json(json!({ "success": false, "message": "invalid whitelist" })),
+		)
+		.expect(1)
+		.mount_as_scoped(&server)
+		.await;
+	let uncompressed = Mock::given(method("PATCH"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(0)
+		.mount_as_scoped(&server)
+		.await;
+	let error = compressing_client()
+		.set_editor(&script_id(), &updates)
+		.await
+		.unwrap_err();
+	assert!(matches!(error, Error::ResponseStatus { status, .. } if status == 400));
+	drop((invalid, uncompressed));
+
+	Mock::given(method("PATCH"))
+		.and(header("Content-Encoding", "gzip"))
+		.respond_with(ResponseTemplate::new(415))
+		.expect(1)
+		.mount(&server)
+		.await;
+	Mock::given(method("PATCH"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+		.expect(1)
+		.mount(&server)
+		.await;
+	compressing_client()
+		.set_editor(&script_id(), &updates)
+		.await
+		.unwrap();
+}
+
+#[tokio::test]
+async fn reports_html_error_pages_by_their_status() {
+	let server = MockServer::start().await;
+	Mock::given(method("GET"))
+		.and(path("/api/script/home/getscripts"))
+		.respond_with(ResponseTemplate::new(401).set_body_raw("<html>log in</html>", "text/html"))
+		.mount(&server)
+		.await;
+
+	let error = client(&server).list_scripts().await.unwrap_err();
+	match error {
+		Error::ResponseStatus { status, .. } => assert_eq!(status, 401),
+		error => panic!("expected a response status error, got {error}"),
+	}
+}
+
+#[tokio::test]
+async fn probes_reachability() {
+	let server = MockServer::start().await;
+	Mock::given(method("GET"))
+		.and(path("/"))
+		.respond_with(ResponseTemplate::new(200).set_body_raw("<html>fumosclub</html>", "text/html"))
+		.up_to_n_times(1)
+		.mount(&server)
+		.await;
+	Mock::given(method("GET"))
+		.and(path("/"))
+		.respond_with(ResponseTemplate::new(503).set_body_raw("<html>maintenance</html>", "text/html"))
+		.mount(&server)
+		.await;
+
+	let base_url = server.uri();
+	assert_eq!(
+		probe_reachability(&base_url).await.unwrap(),
+		Reachability::Reachable
+	);
+	assert_eq!(
+		probe_reachability(&base_url).await.unwrap(),
+		Reachability::ServiceUnavailable
+	);
+}
+
+#[tokio::test]
+async fn reports_maintenance_pages_as_outages() {
+	let server = MockServer::start().await;
+	Mock::given(method("GET"))
+		.and(path("/api/script/home/getscripts"))
+		.respond_with(
+			ResponseTemplate::new(503)
+				.insert_header("Retry-After", "120")
+				.set_body_raw("<html>down for maintenance</html>", "text/html"),
+		)
+		.mount(&server)
+		.await;
+
+	let error = client(&server).list_scripts().await.unwrap_err();
+	match error {
+		Error::ServiceUnavailable {
+			status,
+			retry_after,
+		} => {
+			assert_eq!(status, 503);
+			assert_eq!(retry_after.map(|delay| delay.as_secs()), Some(120));
+		}
+		error => panic!("expected an outage, got {error}"),
+	}
+}
+
+async fn mount_scripts(server: &MockServer, scripts: &[(&str, &str)]) {
+	let scripts: Vec<Value> = scripts
+		.iter()
+		.map(|(id, name)| {
+			json!({
+				"id": id,
+				"name": name,
+				"description": "",
+				"type": 0,
+				"creator": "someone",
+				"creatorIcon": "https://example.com/icon.png",
+				"editable": true,
+				"isFavorite": false,
+			})
+		})
+		.collect();
+	Mock::given(method("GET"))
+		.and(path("/api/script/home/getscripts"))
+		.respond_with(
+			ResponseTemplate::new(200).set_body_json(json!({ "success": true, "scripts": scripts })),
+		)
+		.mount(server)
+		.await;
+}
+
+const LISTED_SCRIPTS: &[(&str, &str)] = &[
+	("675e3d3e59d07c8ba779b988", "Admin Panel"),
+	("675e9a0c1d2b3e4f5a6b7c8d", "admin tools"),
+	("7b1f00aa00bb00cc00dd00ee", "Fly Script"),
+];
+
+#[tokio::test]
+async fn resolves_unique_prefixes_and_names() {
+	let server = MockServer::start().await;
+	mount_scripts(&server, LISTED_SCRIPTS).await;
+	let client = client(&server);
+
+	let resolved = resolve_script_reference(&client, "7b1f").await.unwrap();
+	assert_eq!(resolved.as_str(), "7b1f00aa00bb00cc00dd00ee");
+	let resolved = resolve_script_reference(&client, "fly").await.unwrap();
+	assert_eq!(resolved.as_str(), "7b1f00aa00bb00cc00dd00ee");
+}
+
+#[tokio::test]
+async fn refuses_ambiguous_references() {
+	let server = MockServer::start().await;
+	mount_scripts(&server, LISTED_SCRIPTS).await;
+
+	let error = resolve_script_reference(&client(&server), "admin")
+		.await
+		.unwrap_err();
+	match error {
+		Error::AmbiguousScriptReference(reference, candidates) => {
+			assert_eq!(reference, "admin");
+			let ids: Vec<&str> = candidates.iter().map(|script| script.id.as_str()).collect();
+			assert_eq!(
+				ids,
+				["675e3d3e59d07c8ba779b988", "675e9a0c1d2b3e4f5a6b7c8d"]
+			);
+		}
+		error => panic!("expected an ambiguous reference, got {error}"),
+	}
+}
+
+#[tokio::test]
+async fn keeps_references_matching_nothing() {
+	let server = MockServer::start().await;
+	mount_scripts(&server, LISTED_SCRIPTS).await;
+
+	let resolved = resolve_script_reference(&client(&server), "unlisted")
+		.await
+		.unwrap();
+	assert_eq!(resolved.as_str(), "unlisted");
+}
+
+#[tokio::test]
+async fn uses_whole_ids_without_listing() {
+	let server = MockServer::start().await;
+	Mock::given(method("GET"))
+		.and(path("/api/script/home/getscripts"))
+		.respond_with(ResponseTemplate::new(500))
+		.expect(0)
+		.mount(&server)
+		.await;
+
+	// a prefix of the listed ids, were it matched against them
+	let resolved = resolve_script_reference(&client(&server), "675e00000000000000000000")
+		.await
+		.unwrap();
+	assert_eq!(resolved.as_str(), "675e00000000000000000000");
+}